@@ -0,0 +1,51 @@
+(set-logic HORN)
+
+; Instances separated by scopes, solved with `--batch on`.
+
+(declare-fun inv ( Int ) Bool)
+
+(assert
+  (forall ( (n Int) )
+    (=> (= n 0) (inv n))
+  )
+)
+(assert
+  (forall ( (n Int) )
+    (=> (and (inv n) (< n 10)) (inv (+ n 1)))
+  )
+)
+
+(push 1)
+; Sat.
+(assert
+  (forall ( (n Int) )
+    (=> (and (inv n) (> n 10)) false)
+  )
+)
+(check-sat)
+(pop 1)
+
+(push 1)
+; Unsat.
+(assert
+  (forall ( (n Int) )
+    (=> (and (inv n) (= n 10)) false)
+  )
+)
+(check-sat)
+(pop 1)
+
+; Sat, the queries above are forgotten.
+(check-sat)
+
+(reset)
+
+; Sat, `inv` can be declared again after a reset.
+(set-logic HORN)
+(declare-fun inv ( Bool ) Bool)
+(assert
+  (forall ( (b Bool) )
+    (=> b (inv b))
+  )
+)
+(check-sat)
@@ -0,0 +1,7 @@
+(set-logic HORN)
+
+(declare-fun p ( Int ) Bool)
+
+(assert (forall ( (x Int) ) (=> (forall ( (y Int) ) (p y)) (p x))))
+
+(check-sat)
@@ -0,0 +1,31 @@
+(set-logic HORN)
+
+(declare-fun p (Int Bool Int) Bool)
+(declare-fun q (Int Int Bool) Bool)
+
+(assert
+  (forall ( (n Int) (m Int) (b Bool) )
+    (=> (and (= n 0) (= m 1)) (p n b m))
+  )
+)
+
+(assert
+  (forall ( (n Int) (m Int) (b Bool) )
+    (=> (and (p n b m) (< n 5)) (p (+ n 1) b (+ m 1)))
+  )
+)
+
+(assert
+  (forall ( (n Int) (m Int) (b Bool) )
+    (=> (p n b m) (q m n b))
+  )
+)
+
+(assert
+  (forall ( (n Int) (m Int) (b Bool) )
+    (=> (and (q n m b) (< n m)) false)
+  )
+)
+
+(check-sat)
+(get-model)
@@ -0,0 +1,20 @@
+(set-logic HORN)
+(set-info :status sat)
+
+; The second argument of `inv` is a fresh variable in all the clauses `inv` is
+; the rhs of: `inv` holds for all its values, argument reduction should drop
+; it even though the last clause constrains it.
+(declare-fun inv (Int Int) Bool)
+
+(assert (forall ((x Int) (z Int))
+  (=> (= x 0) (inv x z))
+))
+(assert (forall ((x Int) (z Int) (y Int) (w Int))
+  (=> (and (inv x z) (= y (+ x 1))) (inv y w))
+))
+(assert (forall ((x Int) (z Int))
+  (=> (and (inv x z) (> z 5)) (>= x 0))
+))
+
+(check-sat)
+(get-model)
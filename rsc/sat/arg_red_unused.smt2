@@ -0,0 +1,19 @@
+(set-logic HORN)
+(set-info :status sat)
+
+; The second argument of `inv` never influences satisfiability: argument
+; reduction should drop it, and the model should still mention it.
+(declare-fun inv (Int Int Bool) Bool)
+
+(assert (forall ((x Int) (junk Int) (b Bool))
+  (=> (= x 0) (inv x junk b))
+))
+(assert (forall ((x Int) (junk Int) (y Int) (b Bool))
+  (=> (and (inv x junk b) (= y (+ x 1))) (inv y junk (not b)))
+))
+(assert (forall ((x Int) (junk Int) (b Bool))
+  (=> (inv x junk b) (>= x 0))
+))
+
+(check-sat)
+(get-model)
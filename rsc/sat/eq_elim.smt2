@@ -0,0 +1,22 @@
+(set-logic HORN)
+(set-info :source |
+    Clauses full of introduced equalities, eliminated by substitution during
+    clause simplification. Some can only be solved for a clause-local variable,
+    some only for a real variable with a non-unit coefficient.
+|)
+(set-info :status sat)
+(declare-fun inv (Int Real) Bool)
+
+(assert (forall ((n Int) (r Real) (tmp Int) (half Real))
+  (=> (and (= tmp 0) (= n tmp) (= (* 2.0 half) 1.0) (= r (* 2.0 half)))
+      (inv n r))))
+(assert (forall ((n Int) (r Real) (n1 Int) (r1 Real) (d Int) (s Real))
+  (=> (and (inv n r) (= d (+ n 1)) (= (+ n1 (* 2 d)) (* 3 d)) (= (* 3.0 s) r) (= r1 (* 3.0 s)))
+      (inv n1 r1))))
+(assert (forall ((n Int) (r Real) (k Int))
+  (=> (and (inv n r) (= k (- n)) (> k 0)) false)))
+(assert (forall ((n Int) (r Real))
+  (=> (and (inv n r) (not (= r 1.0))) false)))
+
+(check-sat)
+(get-model)
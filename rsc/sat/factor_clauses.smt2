@@ -0,0 +1,30 @@
+(set-logic HORN)
+
+(declare-fun inv (Int Int) Bool)
+
+(assert
+  (forall ( (x Int) (y Int) )
+    (=> (and (= x 0) (= y 0)) (inv x y))
+  )
+)
+
+(assert
+  (forall ( (x Int) (y Int) )
+    (=> (and (inv x y) (>= x 0) (< x 10)) (inv (+ x 1) (+ y 1)))
+  )
+)
+
+(assert
+  (forall ( (x Int) (y Int) )
+    (=> (and (inv x y) (>= x 0) (< x 10) (> y 10)) false)
+  )
+)
+
+(assert
+  (forall ( (x Int) (y Int) )
+    (=> (and (inv x y) (>= x 0) (< x 10) (< y 0)) false)
+  )
+)
+
+(check-sat)
+(get-model)
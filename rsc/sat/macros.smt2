@@ -0,0 +1,30 @@
+(set-logic HORN)
+
+; Non-recursive `define-fun`s, nullary ones used as bare symbols. Inlined with
+; `--inline_funs on`.
+
+(define-fun bound () Int 10)
+(define-fun small ( (n Int) ) Bool (< n bound))
+
+(declare-fun inv ( Int ) Bool)
+
+(assert
+  (forall ( (n Int) )
+    (=> (= n 0) (inv n))
+  )
+)
+
+(assert
+  (forall ( (n Int) )
+    (=> (and (inv n) (small n)) (inv (+ n 1)))
+  )
+)
+
+(assert
+  (forall ( (n Int) )
+    (=> (and (inv n) (> n bound)) false)
+  )
+)
+
+(check-sat)
+(get-model)
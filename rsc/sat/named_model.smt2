@@ -0,0 +1,15 @@
+(set-logic HORN)
+(set-info :source |
+    Named assertions, one of which yields two clauses. The model is preceded by
+    the names of the assertions mentioning each predicate.
+|)
+(set-info :status sat)
+(declare-fun inv (Int) Bool)
+(declare-fun pos (Int) Bool)
+
+(assert (! (forall ((n Int)) (=> (= n 0) (inv n))) :named init))
+(assert (! (forall ((n Int)) (=> (inv n) (and (inv (+ n 1)) (pos n)))) :named step))
+(assert (! (forall ((n Int)) (=> (and (pos n) (< n 0)) false)) :named safe))
+
+(check-sat)
+(get-model)
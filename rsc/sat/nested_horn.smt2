@@ -0,0 +1,35 @@
+(set-logic HORN)
+
+(declare-fun inv ( Int Int ) Bool)
+(declare-fun aux ( Int ) Bool)
+
+; Curried implication.
+(assert (forall ( (x Int) ) (=> (>= x 0) (=> (<= x 10) (inv x 0)))))
+
+; Quantifier nested under an implication.
+(assert (forall ( (x Int) )
+  (=> (inv x 0)
+    (forall ( (y Int) ) (=> (= y (+ x 1)) (aux y)))
+  )
+))
+
+; Conjunction of an implication in the head.
+(assert (forall ( (x Int) (z Int) )
+  (=> (inv x z)
+    (and (>= x 0) (=> (aux (+ x 1)) (inv (+ x 1) z)))
+  )
+))
+
+; Existential quantifier in the body.
+(assert (forall ( (x Int) )
+  (=> (and (exists ( (z Int) ) (inv x z)) (< x 0)) false)
+))
+
+; Existential quantifier shadowing a variable of the clause: `y` is the variable of the clause
+; again after the quantifier.
+(assert (forall ( (x Int) (y Int) )
+  (=> (and (inv x y) (exists ( (y Int) ) (= y (+ x 20))) (> y 10)) false)
+))
+
+(check-sat)
+(get-model)
@@ -0,0 +1,14 @@
+(set-logic HORN)
+(set-info :source |
+    Two counters moving in opposite directions, the sum of which is constant.
+    Exercises `--qual_octagon`.
+|)
+(set-info :status sat)
+(declare-fun inv (Int Int) Bool)
+
+(assert (forall ((x Int) (y Int)) (=> (and (= x 0) (= y 10)) (inv x y))))
+(assert (forall ((x Int) (y Int)) (=> (inv x y) (inv (+ x 1) (- y 1)))))
+(assert (forall ((x Int) (y Int)) (=> (and (inv x y) (> (+ x y) 10)) false)))
+
+(check-sat)
+(get-model)
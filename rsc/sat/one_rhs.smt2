@@ -0,0 +1,21 @@
+(set-logic HORN)
+(set-info :source |
+    Helper predicates defined by exactly one clause, and a recursive predicate
+    appearing in exactly one rhs. Exercises `--one_rhs_max_blowup`.
+|)
+(set-info :status sat)
+(declare-fun init (Int) Bool)
+(declare-fun step (Int Int) Bool)
+(declare-fun inv (Int) Bool)
+(declare-fun loop (Int) Bool)
+
+(assert (forall ((n Int)) (=> (and (>= n 0) (<= n 2)) (init n))))
+(assert (forall ((n Int) (m Int)) (=> (and (< n 10) (= m (+ n 1))) (step n m))))
+(assert (forall ((n Int)) (=> (init n) (inv n))))
+(assert (forall ((n Int) (m Int)) (=> (and (inv n) (step n m)) (inv m))))
+(assert (forall ((n Int)) (=> (and (inv n) (> n 10)) false)))
+(assert (forall ((n Int)) (=> (and (loop n) (> n 0)) (loop (+ n 1)))))
+(assert (forall ((n Int)) (=> (and (loop n) (< n 0)) false)))
+
+(check-sat)
+(get-model)
@@ -0,0 +1,13 @@
+(set-logic HORN)
+(set-info :source |
+    Counter incremented by two, never odd. Exercises `--qual_max_modulo`.
+|)
+(set-info :status sat)
+(declare-fun inv (Int) Bool)
+
+(assert (forall ((x Int)) (=> (= x 0) (inv x))))
+(assert (forall ((x Int)) (=> (inv x) (inv (+ x 2)))))
+(assert (forall ((x Int)) (=> (and (inv x) (= x 7)) false)))
+
+(check-sat)
+(get-model)
@@ -0,0 +1,14 @@
+(set-logic HORN)
+(set-info :source |
+    Real counters growing at different rates. The invariant `x = 2 * y` needs
+    a rational coefficient.
+|)
+(set-info :status sat)
+(declare-fun inv (Real Real) Bool)
+
+(assert (forall ((x Real) (y Real)) (=> (and (= x 0.0) (= y 0.0)) (inv x y))))
+(assert (forall ((x Real) (y Real)) (=> (inv x y) (inv (+ x 1.0) (+ y 0.5)))))
+(assert (forall ((x Real) (y Real)) (=> (and (inv x y) (> y x)) false)))
+
+(check-sat)
+(get-model)
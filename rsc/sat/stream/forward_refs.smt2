@@ -0,0 +1,33 @@
+(set-logic HORN)
+
+; Only legal with `--stream_parse on`: `inv` is used before it is declared. The second clause is
+; a tautology, dropped while parsing.
+
+(assert
+  (forall ( (n Int) )
+    (=> (= n 0) (inv n))
+  )
+)
+
+(assert
+  (forall ( (n Int) )
+    (=> (and (inv n) (> n 0) (not (> n 0))) (inv (+ n 7)))
+  )
+)
+
+(declare-fun inv ( Int ) Bool)
+
+(assert
+  (forall ( (n Int) )
+    (=> (and (inv n) (< n 10)) (inv (+ n 1)))
+  )
+)
+
+(assert
+  (forall ( (n Int) )
+    (=> (inv n) (<= n 10))
+  )
+)
+
+(check-sat)
+(get-model)
@@ -0,0 +1,16 @@
+(set-logic HORN)
+(set-info :source |
+    Sum with several base cases. Exercises splitting on positive clauses with
+    `--split_pos on`.
+|)
+(set-info :status sat)
+(declare-fun sum (Int Int) Bool)
+
+(assert (forall ((n Int)) (=> (<= n 0) (sum n 0))))
+(assert (forall ((n Int)) (=> (= n 1) (sum n 1))))
+(assert (forall ((n Int)) (=> (= n 2) (sum n 3))))
+(assert (forall ((n Int) (r Int)) (=> (and (> n 0) (sum (- n 1) r)) (sum n (+ r n)))))
+(assert (forall ((n Int) (r Int)) (=> (and (sum n r) (< r n)) false)))
+
+(check-sat)
+(get-model)
@@ -0,0 +1,25 @@
+(set-logic HORN)
+(set-info :status sat)
+
+(declare-fun uninterpreted_funs_f (Int) Int)
+(declare-fun uninterpreted_funs_c () Int)
+(declare-fun inv (Int Int) Bool)
+
+(assert
+  (forall ( (x Int) )
+    (=> (>= x uninterpreted_funs_c) (inv (- x uninterpreted_funs_c) (uninterpreted_funs_f x)))
+  )
+)
+(assert
+  (forall ( (x Int) (y Int) )
+    (=> (and (inv x y) (> x 0)) (inv (- x 1) (+ y (uninterpreted_funs_f y))))
+  )
+)
+(assert
+  (forall ( (x Int) (y Int) )
+    (=> (and (inv x y) (< x 0)) false)
+  )
+)
+
+(check-sat)
+(get-model)
@@ -0,0 +1,26 @@
+(set-logic HORN)
+
+; Unsat, but the soft clauses with the smallest weight are the only conflicting ones. The last
+; assertion yields two soft clauses, only `(=> (inv n) (< n 5))` needs to be violated.
+
+(declare-fun inv ( Int ) Bool)
+
+(assert
+  (forall ( (n Int) )
+    (=> (= n 0) (inv n))
+  )
+)
+
+(assert
+  (! (forall ( (n Int) )
+    (=> (and (inv n) (< n 10)) (inv (+ n 1)))
+  ) :named step :weight 10)
+)
+
+(assert
+  (! (forall ( (n Int) )
+    (=> (inv n) (and (< n 5) (> n (- 3))))
+  ) :weight 1 :named bound)
+)
+
+(check-sat)
@@ -0,0 +1,27 @@
+(set-option :produce-proofs true)
+
+(set-logic HORN)
+
+(declare-fun p (Int) Bool)
+
+(assert
+  (forall ((n Int))
+    (=> (= n 0) (p n))
+  )
+)
+
+(assert
+  (forall ((n Int))
+    (=> (and (p n) (>= n 0)) (p (+ n 1)))
+  )
+)
+
+; Falsifiable regardless of `p`.
+(assert
+  (forall ((x Int) (y Int))
+    (=> (and (>= x 3) (= y (* 2 x))) false)
+  )
+)
+
+(check-sat)
+(get-proof)
@@ -21,18 +21,20 @@ fn main() {
     if let Err(errs) = ::hoice::work() {
         let errs = match *errs.kind() {
             ErrorKind::Z3SpawnError => format!(
-                "could not spawn z3 using command `{}`\n\
-                 make sure the z3 binary has that name and is in your path,\n\
-                 or specify a different z3 command with option `{}`",
+                "could not spawn {} using command `{}`\n\
+                 make sure the binary has that name and is in your path,\n\
+                 or specify a different command with option `{}` or `{}`",
+                conf.solver.name(),
                 conf.emph(&conf.solver.conf().get_cmd()),
-                conf.emph("--z3")
+                conf.emph("--z3"),
+                conf.emph("--cvc_cmd")
             )
             .into(),
             _ => errs,
         };
         print_err(&errs);
-        ::std::process::exit(2)
+        ::std::process::exit(ExitCodes::ERROR)
     } else {
-        ::std::process::exit(0)
+        ::std::process::exit(::hoice::exit_code())
     }
 }
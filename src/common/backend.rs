@@ -0,0 +1,195 @@
+//! SMT solver backends.
+//!
+//! Everything hoice needs to know about a specific solver goes through the [`Backend`] trait:
+//! how to spawn it, how to give it a timeout, and how to pre-process the values of its models
+//! before hoice parses them. The backend is selected with `--solver`, see
+//! [`BackendKind`].
+//!
+//! [`Backend`]: trait.Backend.html (Backend trait)
+//! [`BackendKind`]: enum.BackendKind.html (BackendKind enum)
+
+use std::borrow::Cow;
+
+use rsmt2::SmtConf as SolverConf;
+
+/// An SMT solver hoice can use.
+pub trait Backend: Sync {
+    /// Name of the solver, for messages.
+    fn name(&self) -> &'static str;
+
+    /// Default command to spawn the solver.
+    fn default_cmd(&self) -> &'static str;
+
+    /// Solver configuration spawning the solver with `cmd` in interactive mode, with models.
+    fn conf(&self, cmd: &str) -> SolverConf;
+
+    /// Command-line option giving a global timeout (in seconds) to the solver.
+    fn timeout_opt(&self, secs: u64) -> String;
+
+    /// Option setting the timeout of each check-sat, in milliseconds.
+    fn check_timeout_opt(&self) -> &'static str;
+
+    /// Model-parsing hook.
+    ///
+    /// Rewrites a value from an answer to `get-model` or `get-value` into a form hoice's parser
+    /// understands. Identity by default.
+    fn model_value<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        Cow::Borrowed(value)
+    }
+}
+
+/// The z3 backend.
+pub struct Z3;
+impl Backend for Z3 {
+    fn name(&self) -> &'static str {
+        "z3"
+    }
+    fn default_cmd(&self) -> &'static str {
+        "z3"
+    }
+    fn conf(&self, cmd: &str) -> SolverConf {
+        let mut conf = SolverConf::default_z3();
+        conf.cmd(cmd.to_string());
+        conf.models();
+        conf
+    }
+    fn timeout_opt(&self, secs: u64) -> String {
+        format!("-T:{}", secs)
+    }
+    fn check_timeout_opt(&self) -> &'static str {
+        ":timeout"
+    }
+}
+
+/// The CVC4 backend.
+pub struct Cvc4;
+impl Backend for Cvc4 {
+    fn name(&self) -> &'static str {
+        "CVC4"
+    }
+    fn default_cmd(&self) -> &'static str {
+        "cvc4"
+    }
+    fn conf(&self, cmd: &str) -> SolverConf {
+        let mut conf = SolverConf::default_cvc4();
+        conf.cmd(cmd.to_string());
+        conf.models();
+        conf
+    }
+    fn timeout_opt(&self, secs: u64) -> String {
+        format!("--tlimit={}", secs * 1000)
+    }
+    fn check_timeout_opt(&self) -> &'static str {
+        ":tlimit-per"
+    }
+}
+
+/// The CVC5 backend.
+///
+/// CVC5 writes negative rationals as `(/ (- n) d)` in its models, which this backend rewrites as
+/// `(- (/ n d))`.
+pub struct Cvc5;
+impl Backend for Cvc5 {
+    fn name(&self) -> &'static str {
+        "CVC5"
+    }
+    fn default_cmd(&self) -> &'static str {
+        "cvc5"
+    }
+    fn conf(&self, cmd: &str) -> SolverConf {
+        // CVC5 takes the same options as CVC4 in interactive mode.
+        let mut conf = SolverConf::default_cvc4();
+        conf.cmd(cmd.to_string());
+        conf.models();
+        conf
+    }
+    fn timeout_opt(&self, secs: u64) -> String {
+        format!("--tlimit={}", secs * 1000)
+    }
+    fn check_timeout_opt(&self) -> &'static str {
+        ":tlimit-per"
+    }
+    fn model_value<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        neg_rat(value)
+    }
+}
+
+/// Rewrites `(/ (- n) d)` as `(- (/ n d))`, leaves anything else untouched.
+///
+/// # Examples
+///
+/// ```rust
+/// use hoice::common::backend::neg_rat;
+/// assert_eq! { neg_rat("(/ (- 1) 2)"), "(- (/ 1 2))" }
+/// assert_eq! { neg_rat("( /  ( - 7 )  3 )"), "(- (/ 7 3))" }
+/// assert_eq! { neg_rat("(/ 1 2)"), "(/ 1 2)" }
+/// assert_eq! { neg_rat("(- 1)"), "(- 1)" }
+/// ```
+pub fn neg_rat(value: &str) -> Cow<str> {
+    let tokens: Vec<&str> = value
+        .split(|c: char| c.is_whitespace() || c == '(' || c == ')')
+        .filter(|token| !token.is_empty())
+        .collect();
+    let parens = value.chars().filter(|c| *c == '(' || *c == ')').count();
+    match tokens.as_slice() {
+        ["/", "-", num, den]
+            if parens == 4
+                && value.trim_start().starts_with('(')
+                && num.chars().all(|c| c.is_numeric() || c == '.')
+                && den.chars().all(|c| c.is_numeric() || c == '.') =>
+        {
+            Cow::Owned(format!("(- (/ {} {}))", num, den))
+        }
+        _ => Cow::Borrowed(value),
+    }
+}
+
+/// Solver backends, see `--solver`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackendKind {
+    /// z3.
+    Z3,
+    /// CVC4.
+    Cvc4,
+    /// CVC5.
+    Cvc5,
+}
+impl BackendKind {
+    /// Backend kind of a string.
+    pub fn of_str(s: &str) -> Option<Self> {
+        match s {
+            "z3" => Some(BackendKind::Z3),
+            "cvc4" => Some(BackendKind::Cvc4),
+            "cvc5" => Some(BackendKind::Cvc5),
+            _ => None,
+        }
+    }
+
+    /// Actual backend.
+    pub fn backend(self) -> &'static dyn Backend {
+        match self {
+            BackendKind::Z3 => &Z3,
+            BackendKind::Cvc4 => &Cvc4,
+            BackendKind::Cvc5 => &Cvc5,
+        }
+    }
+}
+impl ::std::fmt::Display for BackendKind {
+    fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match self {
+            BackendKind::Z3 => write!(fmt, "z3"),
+            BackendKind::Cvc4 => write!(fmt, "cvc4"),
+            BackendKind::Cvc5 => write!(fmt, "cvc5"),
+        }
+    }
+}
+
+/// Validates backend input.
+#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
+pub fn backend_validator(s: String) -> Result<(), String> {
+    if BackendKind::of_str(&s).is_some() {
+        Ok(())
+    } else {
+        Err(format!("expected `z3`, `cvc4` or `cvc5`, got `{}`", s))
+    }
+}
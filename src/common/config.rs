@@ -10,7 +10,14 @@ use clap::{crate_authors, crate_name, Arg};
 use error_chain::bail;
 use rsmt2::SmtConf as SolverConf;
 
-use crate::{common::mk_dir, errors::*, instance::Instance};
+use crate::{
+    common::{
+        backend::{backend_validator, Backend, BackendKind},
+        mk_dir,
+    },
+    errors::*,
+    instance::Instance,
+};
 
 /// Creates a function adding arguments to a `::clap::App`.
 macro_rules! app_fun {
@@ -35,6 +42,9 @@ macro_rules! app_fun {
     (@arg $arg:expr => long $long:expr, $($stuff:tt)*) => (
         app_fun!(@arg $arg.long($long) => $($stuff)*)
     );
+    (@arg $arg:expr => alias $alias:expr, $($stuff:tt)*) => (
+        app_fun!(@arg $arg.alias($alias) => $($stuff)*)
+    );
     (@arg $arg:expr => short $short:expr, $($stuff:tt)*) => (
         app_fun!(@arg $arg.short($short) => $($stuff)*)
     );
@@ -150,6 +160,24 @@ pub trait SubConf {
 make_conf! {
     /// Solver configuration.
     SmtConf {
+        solver, kind: BackendKind {
+            help "Solver backend: `z3`, `cvc4` or `cvc5`.",
+            long_help "\
+                Selects the SMT solver hoice uses. The command spawning z3 is given by `--z3` \
+                and the one spawning CVC4 or CVC5 by `--cvc_cmd`, defaulting to `cvc4` and \
+                `cvc5` respectively.\
+            ",
+            long "--solver",
+            validator backend_validator,
+            val_name "z3|cvc4|cvc5",
+            default "z3",
+            takes_val,
+            val_nb 1,
+        } {
+            |mtch| BackendKind::of_str(mtch).expect(
+                "unreachable(solver): default is provided and input validated"
+            )
+        }
         z3_cmd, conf: SolverConf {
             help "Sets the command used to call z3.",
             long_help "\
@@ -157,6 +185,7 @@ make_conf! {
                 *interactive* mode, so there's no need to specify `-in`.\
             ",
             long "--z3",
+            alias "z3_cmd",
             default "z3",
             takes_val,
             val_nb 1,
@@ -169,6 +198,40 @@ make_conf! {
                 conf
             }
         }
+        cvc_cmd, cvc_cmd: Option<String> {
+            help "Sets the command used to call CVC4 or CVC5.",
+            long_help "\
+                If not empty, hoice spawns CVC4 or CVC5 (see `--solver`) using this command. If \
+                `--solver` is `z3`, setting this switches to CVC4 and `--z3` is ignored. Hoice \
+                automatically launches it in *interactive* mode.\
+            ",
+            long "--cvc_cmd",
+            default "",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| if mtch.is_empty() {
+                None
+            } else {
+                Some(mtch.to_string())
+            }
+        }
+        solver_opts, opts: Vec<String> {
+            help "Additional options passed to the solver, separated by spaces.",
+            long_help "\
+                Whitespace-separated options passed as is to every solver hoice spawns, after \
+                the ones hoice uses. For instance, `--solver_opts \"trace=true\"` activates \
+                z3's tracing.\
+            ",
+            long "--solver_opts",
+            default "",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| mtch.split_whitespace().map(|opt| opt.to_string()).collect()
+        }
         log_smt, log: bool {
             help "(De)activates smt logging to the output directory.",
             long_help "\
@@ -183,6 +246,27 @@ make_conf! {
         } {
             |mtch| bool_of_match(mtch)
         }
+        smt_log, log_dir: Option<String> {
+            help "Directory to write standalone transcripts of all solver interactions to.",
+            long_help "\
+                If not empty, the commands sent to each solver are written to \
+                `<dir>/<role>_<index>.smt2`, where `<role>` is what the solver is used for \
+                (`teacher`, `ice_learner`, `proof_reconstruction`...) and `<index>` counts the \
+                solvers spawned for this role, across splits. Each transcript starts with the \
+                options hoice passes to the solver and can be replayed as is.\
+            ",
+            long "--smt_log",
+            default "",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| if mtch.is_empty() {
+                None
+            } else {
+                Some(mtch.to_string())
+            }
+        }
     }
 
     impl SubConf for SmtConf {
@@ -192,9 +276,75 @@ make_conf! {
     }
 
     impl SmtConf {
+        /// Kind of the solver backend actually used.
+        ///
+        /// This is `--solver`, except that `--cvc_cmd` switches from z3 to CVC4.
+        pub fn backend_kind(&self) -> BackendKind {
+            if self.kind == BackendKind::Z3 && self.cvc_cmd.is_some() {
+                BackendKind::Cvc4
+            } else {
+                self.kind
+            }
+        }
+
+        /// Solver backend actually used.
+        pub fn backend(&self) -> &'static dyn Backend {
+            self.backend_kind().backend()
+        }
+
         /// Actual, `rsmt2` solver configuration.
         pub fn conf(&self) -> SolverConf {
-            self.conf.clone()
+            let mut conf = match self.backend_kind() {
+                BackendKind::Z3 => self.conf.clone(),
+                kind => {
+                    let backend = kind.backend();
+                    backend.conf(
+                        self.cvc_cmd
+                            .as_ref()
+                            .map(|cmd| cmd as &str)
+                            .unwrap_or_else(|| backend.default_cmd()),
+                    )
+                }
+            };
+            for opt in &self.opts {
+                conf.option(opt.clone());
+            }
+            conf
+        }
+
+        /// Name of the solver used, for messages.
+        pub fn name(&self) -> &'static str {
+            self.backend().name()
+        }
+
+        /// Checks the solver can be run by querying its version.
+        ///
+        /// Returns the first line of the version information. The error explains which command
+        /// failed and how to change it.
+        pub fn check_solver(&self) -> Res<String> {
+            use std::process::Command;
+            let cmd = self.conf().get_cmd().to_string();
+            let failure = |reason: String| -> Res<String> {
+                bail!(
+                    "could not query the version of {} using command `{}`: {}\n\
+                     make sure the binary has that name and is in your path,\n\
+                     or specify a different command with `--z3` or `--cvc_cmd`",
+                    self.name(),
+                    crate::common::conf.emph(&cmd),
+                    reason
+                )
+            };
+            match Command::new(&cmd).arg("--version").output() {
+                Ok(ref output) if output.status.success() => {
+                    Ok(String::from_utf8_lossy(&output.stdout)
+                        .lines()
+                        .next()
+                        .unwrap_or("unknown version")
+                        .to_string())
+                }
+                Ok(output) => failure(format!("command exited with {}", output.status)),
+                Err(e) => failure(e.to_string()),
+            }
         }
 
         /// Spawns a solver.
@@ -212,14 +362,29 @@ make_conf! {
         where
             I: AsRef<Instance>,
         {
-            let mut smt_conf = self.conf.clone();
+            let mut smt_conf = self.conf();
+            // Options hoice adds to the default ones of the backend.
+            let mut opts = self.opts.clone();
             if let Some(timeout) = crate::common::conf.until_timeout() {
-                smt_conf.option(format!("-T:{}", timeout.as_secs() + 1));
+                let opt = self.backend().timeout_opt(timeout.as_secs() + 1);
+                smt_conf.option(opt.clone());
+                opts.push(opt)
             }
 
+            // Spawn through a wrapper retrieving the exit status and stderr of the solver, if
+            // possible.
+            #[cfg(target_os = "linux")]
+            let smt_conf = {
+                let mut wrapped = smt_conf.clone();
+                match crate::smt::wrap_solver_cmd(name, &mut wrapped) {
+                    Ok(()) => wrapped,
+                    Err(_) => smt_conf,
+                }
+            };
+
             let mut solver = ::rsmt2::Solver::new(smt_conf, parser)?;
             if let Some(log) = self
-                .log_file(name, instance.as_ref())
+                .log_file(name, &opts, instance.as_ref())
                 .chain_err(|| format!("While opening log file for {}", crate::common::conf.emph(name)))?
             {
                 solver.tee(log)?
@@ -267,6 +432,24 @@ make_conf! {
             self.internal_spawn(name, parser, instance, true)
         }
 
+        /// Path of the transcript of a new solver for `name`, if `--smt_log` is active.
+        fn transcript_path(&self, name: &'static str) -> Res<Option<PathBuf>> {
+            if let Some(dir) = self.log_dir.as_ref() {
+                mk_dir(dir)?;
+                let mut logs = smt_logs
+                    .lock()
+                    .map_err(|_| "[bug] lock on smt transcripts is corrupted...")?;
+                let index = logs.get(name).map(|(index, _)| index + 1).unwrap_or(0);
+                let mut path = PathBuf::from(dir);
+                path.push(format!("{}_{}", name, index));
+                path.set_extension("smt2");
+                logs.insert(name, (index, path.clone()));
+                Ok(Some(path))
+            } else {
+                Ok(None)
+            }
+        }
+
         /// Smt log dir, if any.
         fn log_dir(&self, instance: &Instance) -> Res<Option<PathBuf>> {
             if self.log {
@@ -279,15 +462,60 @@ make_conf! {
             }
         }
 
+        /// Path of the smt log file of a solver, if logging is active.
+        ///
+        /// With `--smt_log`, this is the transcript of the last solver spawned for `name`.
+        pub fn log_path(&self, name: &str, instance: &Instance) -> Option<PathBuf> {
+            if self.log_dir.is_some() {
+                smt_logs
+                    .lock()
+                    .ok()
+                    .and_then(|logs| logs.get(name).map(|(_, path)| path.clone()))
+            } else if self.log {
+                let mut path = crate::common::conf.out_dir(instance);
+                path.push("solvers");
+                path.push(name);
+                path.set_extension("smt2");
+                Some(path)
+            } else {
+                None
+            }
+        }
+
         /// Smt log file, if any.
-        fn log_file<S: AsRef<str>>(
+        ///
+        /// The header of a `--smt_log` transcript records the command-line of hoice, and the
+        /// command spawning the solver with the options hoice passes it, `opts`.
+        fn log_file(
             &self,
-            name: S,
+            name: &'static str,
+            opts: &[String],
             instance: &Instance,
         ) -> Res<Option<::std::fs::File>> {
             use std::fs::OpenOptions;
-            if let Some(mut path) = self.log_dir(instance)? {
-                path.push(name.as_ref());
+            use std::io::Write;
+            if let Some(path) = self.transcript_path(name)? {
+                let mut file = OpenOptions::new()
+                    .write(true)
+                    .truncate(true)
+                    .create(true)
+                    .open(&path)
+                    .chain_err(
+                        || format!("while creating smt transcript {}", path.to_string_lossy())
+                    )?;
+                // The options of the configuration are sent before the file is tee-ed.
+                let args: Vec<String> = ::std::env::args().collect();
+                writeln!(file, "; hoice invoked as `{}`.", args.join(" "))?;
+                let mut cmd = self.conf().get_cmd().to_string();
+                for opt in opts {
+                    cmd.push(' ');
+                    cmd.push_str(opt)
+                }
+                writeln!(file, "; Solver `{}`, spawned with `{}`.", name, cmd)?;
+                writeln!(file, "(set-option :produce-models true)")?;
+                Ok(Some(file))
+            } else if let Some(mut path) = self.log_dir(instance)? {
+                path.push(name);
                 path.set_extension("smt2");
                 let file = OpenOptions::new()
                     .write(true)
@@ -305,6 +533,13 @@ make_conf! {
     }
 }
 
+lazy_static::lazy_static! {
+    /// Number of solvers spawned so far and last transcript for each role, for `--smt_log`.
+    static ref smt_logs: ::std::sync::Mutex<
+        ::std::collections::BTreeMap<&'static str, (usize, PathBuf)>
+    > = ::std::sync::Mutex::new(::std::collections::BTreeMap::new());
+}
+
 /// Instance and factory configuration.
 ///
 /// Currently, these options are static. They cannot be changed through clap.
@@ -375,6 +610,107 @@ make_conf! {
             |val| bool_of_match(val)
         }
 
+        dump_preproc, dump_preproc: Option<String> {
+            help "File to write the pre-processed instance to.",
+            long_help "\
+                Specifies a file to write the instance to once top pre-processing is over, in \
+                SMT-LIB 2. The file can be given back to hoice. Inactive by default.\
+            ",
+            long "--dump_preproc",
+            val_name "FILE",
+            default "",
+            takes_val,
+            val_nb 1,
+        } {
+            |mtch| if mtch.is_empty() {
+                None
+            } else {
+                Some(mtch.to_string())
+            }
+        }
+
+        preproc_stats, preproc_stats: bool {
+            help "(De)activates the per-pass pre-processing statistics.",
+            long_help "\
+                If active, a table is printed at the end of each pre-processing run giving, for \
+                each pass, how many times it ran and changed the instance, the reduction it \
+                reported, and the variation of the number of clauses, predicates, predicate \
+                arguments and lhs terms it caused (terms simplified away count negatively). The \
+                variations sum to the overall size reduction.\
+            ",
+            long "--preproc_stats",
+            takes_val,
+            val_name bool_format,
+            val_nb 1,
+            validator bool_validator,
+            default "no",
+        } {
+            |val| bool_of_match(val)
+        }
+
+        no_preproc, no_preproc: ::std::collections::BTreeSet<String> {
+            help "Pre-processing passes to deactivate.",
+            long_help "\
+                Comma-separated list of the names of the pre-processing passes to deactivate, or \
+                `none`. Deactivated passes are skipped wherever pre-processing would run them. \
+                The passes are `arg_order`, `arg_reduce`, `biased_unroll`, `cfg_red`, \
+                `dead_preds`, `disj_split`, `equal_preds`, `fact_prop`, `factor`, `fun_preds`, \
+                `lin_accel`, `one_lhs`, `one_rhs`, `runroll`, `simplify`, `strict_neg` and \
+                `trivial_clauses`.\
+            ",
+            long "--no_preproc",
+            validator pass_list_validator,
+            val_name "<pass>,...",
+            default "none",
+            takes_val,
+            val_nb 1,
+        } {
+            |mtch| pass_list_of_str(mtch).expect(
+                "unreachable(no_preproc): default is provided and input validated"
+            )
+        }
+
+        dump_dot, dump_dot: Option<String> {
+            help "File to write the predicate dependency graph to, in DOT.",
+            long_help "\
+                Specifies a file to write the dependency graph of the instance to once top \
+                pre-processing is over, as a Graphviz DOT graph (`dot -Tpng <FILE>`). Nodes are \
+                predicates, edges are clauses going from their lhs predicates to their rhs \
+                predicate. Positive clauses start from a `TRUE` node and negative clauses go to a \
+                `FALSE` node. Edges are labeled with the index of the clause. Inactive by \
+                default.\
+            ",
+            long "--dump_dot",
+            val_name "FILE",
+            default "",
+            takes_val,
+            val_nb 1,
+        } {
+            |mtch| if mtch.is_empty() {
+                None
+            } else {
+                Some(mtch.to_string())
+            }
+        }
+
+        compact, compact: bool {
+            help "(De)activates clause renumbering at the end of pre-processing.",
+            long_help "\
+                If active, the clauses are renumbered once pre-processing is over, in the order \
+                of the original clauses they come from. Clause indices in logs and transcripts \
+                then follow the order of the input rather than the order in which pre-processing \
+                happened to delete clauses.\
+            ",
+            long "--compact",
+            takes_val,
+            val_name bool_format,
+            val_nb 1,
+            validator bool_validator,
+            default "off",
+        } {
+            |val| bool_of_match(val)
+        }
+
         prune_terms, prune_terms: bool {
             help "(De)activates expensive clause term pruning when simplifying clauses.",
             long_help "\
@@ -410,6 +746,24 @@ make_conf! {
             |val| bool_of_match(val)
         }
 
+        one_rhs_max_blowup, one_rhs_max_blowup: usize {
+            help "Maximal size blowup allowed by one rhs reduction, 0 for no limit.",
+            long_help "\
+                Predicates are only unfolded by one rhs reduction if the size of the instance, \
+                in number of atoms, stays below this factor times its size when the \
+                pre-processor started. Zero deactivates the limit.\
+            ",
+            long "--one_rhs_max_blowup",
+            validator int_validator,
+            val_name "int",
+            default "0",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| int_of_match(mtch)
+        }
+
         one_lhs, one_lhs: bool {
             help "(De)activates reduction of predicate appearing in exactly one clause lhs.",
             long_help "\
@@ -428,6 +782,27 @@ make_conf! {
             |val| bool_of_match(val)
         }
 
+        max_rounds, max_rounds: Option<usize> {
+            help "Maximal number of preprocessing rounds, `0` for no limit.",
+            long_help "\
+                Preprocessing runs its passes in rounds until nothing changes, or until the \
+                instance is the same as at the beginning of a previous round. This bounds the \
+                number of rounds. Zero deactivates the limit.\
+            ",
+            long "--max_rounds",
+            validator int_validator,
+            val_name "int",
+            default "0",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| match int_of_match(mtch) {
+                0 => None,
+                n => Some(n),
+            }
+        }
+
         arg_red, arg_red: bool {
             help "(De)activates argument reduction.",
             long_help "\
@@ -446,48 +821,51 @@ make_conf! {
             |val| bool_of_match(val)
         }
 
-        cfg_red, cfg_red: bool {
-            help "(De)activates control flow graph reduction.",
+        arg_order, arg_order: bool {
+            help "(De)activates the canonical ordering of the predicate arguments.",
             long_help "\
-                If active, analyzes the *control flow graph* of the predicates and inlines \
-                predicates that are not involved in loops. This pre-processor might introduce \
-                quantifiers in the predicates' definitions.\
+                If active, the arguments of the predicates are reordered by type, then so that \
+                they follow the arguments of the predicates they are passed to or received from. \
+                This helps the learner reuse qualifiers across predicates when the input passes \
+                corresponding arguments in different positions. Models are given on the original \
+                signatures.\
             ",
-            long "--cfg_red",
+            long "--arg_order",
             takes_val,
             val_name bool_format,
             val_nb 1,
             validator bool_validator,
-            default "on",
+            default "off",
             hidden,
         } {
             |val| bool_of_match(val)
         }
 
-        log_pred_dep, log_pred_dep: bool {
-            help "(De)activates predicate dependency dumps (cfg_red).",
+        dead_preds, dead_preds: bool {
+            help "(De)activates dead predicate elimination.",
             long_help "\
-                If active, dumps the dependencies between the predicates as a `dot` graph. If \
-                `dot` is available on your computer, the pdf will be generated automatically. In \
-                addition to the original dependency graph, a new graph is dumped each time a \
-                pre-processor changed the relations between the predicates.\
+                If active, predicates that cannot reach a negative clause through the clauses \
+                are forced to true, and the clauses mentioning them are dropped.\
             ",
-            long "--log_pred_dep",
+            long "--dead_preds",
             takes_val,
             val_name bool_format,
             val_nb 1,
             validator bool_validator,
-            default "no",
+            default "off",
+            hidden,
         } {
             |val| bool_of_match(val)
         }
 
-        runroll, runroll: bool {
-            help "(De)activates reverse unrolling.",
+        disj_split, disj_split: bool {
+            help "(De)activates disjunction splitting.",
             long_help "\
-                If active, predicates appearing in negative clauses will be unrolled in reverse.\
+                If active, the top-level disjunctions in the lhs of the clauses are distributed, \
+                so that the guards of the clauses are conjunctions. A clause is left as is if \
+                splitting it would create more than `--disj_split_max` clauses.\
             ",
-            long "--runroll",
+            long "--disj_split",
             takes_val,
             val_name bool_format,
             val_nb 1,
@@ -498,85 +876,85 @@ make_conf! {
             |val| bool_of_match(val)
         }
 
-        pos_unroll, pos_unroll: bool {
-            help "(De)activates positive unrolling.",
+        disj_split_max, disj_split_max: usize {
+            help "Maximal number of clauses splitting a clause can create.",
             long_help "\
-                If active, predicates appearing in positive clauses will be unrolled if unrolling \
-                them creates positive clauses for predicates that did not appear in positive \
-                clauses yet.\
+                When splitting disjunctions, a clause is replaced by one clause per combination \
+                of the disjuncts of its lhs. Clauses with more combinations than this are not \
+                split.\
             ",
-            long "--pos_unroll",
+            long "--disj_split_max",
+            validator int_validator,
+            val_name "int",
+            default "8",
             takes_val,
-            val_name bool_format,
             val_nb 1,
-            validator bool_validator,
-            default "on",
             hidden,
         } {
-            |val| bool_of_match(val)
+            |mtch| int_of_match(mtch)
         }
 
-        neg_unroll, neg_unroll: bool {
-            help "(De)activates negative unrolling.",
+        equal_preds, equal_preds: bool {
+            help "(De)activates the merging of interchangeable predicates.",
             long_help "\
-                If active, predicates appearing in negative clauses will be unrolled if unrolling \
-                them creates negative clauses for predicates that did not appear in negative \
-                clauses yet.\
+                If active, looks for pairs of predicates such that swapping them leaves the \
+                clauses unchanged. One of them is then replaced by the other everywhere, and \
+                the model gives them the same definition.\
             ",
-            long "--neg_unroll",
+            long "--equal_preds",
             takes_val,
             val_name bool_format,
             val_nb 1,
             validator bool_validator,
-            default "on",
+            default "off",
             hidden,
         } {
             |val| bool_of_match(val)
         }
 
-        split_strengthen, split_strengthen: bool {
-            help "(De)activates strengthening when splitting is active.",
+        fact_prop, fact_prop: bool {
+            help "(De)activates ground fact propagation.",
             long_help "\
-                If active, negative clauses ignored while splitting will be injected in \
-                implication clauses. This strengthens a given split using information from \
-                (currently) ignored negative clauses.\
+                If active, predicates only defined by ground facts such as `(p 0)` are replaced \
+                by the disjunction of these facts in the clauses they appear in. This usually \
+                turns other clauses into ground facts, and cascades.\
             ",
-            long "--split_strengthen",
+            long "--fact_prop",
             takes_val,
             val_name bool_format,
             val_nb 1,
             validator bool_validator,
-            default "on",
+            default "off",
+            hidden,
         } {
             |val| bool_of_match(val)
         }
 
-        split_sort, split_sort: bool {
-            help "(De)activates clause sorting when splitting is active.",
+        fact_prop_max, fact_prop_max: usize {
+            help "Maximal number of facts a predicate can have for fact propagation.",
             long_help "\
-                If active, hoice will sort the negative clauses before splitting on them. This \
-                is a heuristic and does not give any guarantees as to whether it really improves \
-                solving.\
+                Predicates with more ground facts than this are not propagated by fact \
+                propagation, since each fact duplicates the clauses the predicate appears in.\
             ",
-            long "--split_sort",
+            long "--fact_prop_max",
+            validator int_validator,
+            val_name "int",
+            default "5",
             takes_val,
-            val_name bool_format,
             val_nb 1,
-            validator bool_validator,
-            default "on",
+            hidden,
         } {
-            |val| bool_of_match(val)
+            |mtch| int_of_match(mtch)
         }
 
-        strict_neg, strict_neg: bool {
-            help "(De)activates strengthening by strict negative clauses.",
+        cfg_red, cfg_red: bool {
+            help "(De)activates control flow graph reduction.",
             long_help "\
-                If active, hoice will use strict negative clauses (clauses that have only one \
-                predicate application) to infer partial definitions for the predicates. If `pred` \
-                appears in `tail => (pred _)`, then `pred` will be constructed as
-                `tail /\\ <def>` where `<def>` needs to be inferred by hoice.\
+                If active, analyzes the *control flow graph* of the predicates and inlines \
+                predicates that are not involved in loops. This pre-processor might introduce \
+                quantifiers in the predicates' definitions.\
             ",
-            long "--strict_neg",
+            long "--cfg_red",
             takes_val,
             val_name bool_format,
             val_nb 1,
@@ -587,34 +965,314 @@ make_conf! {
             |val| bool_of_match(val)
         }
 
-        fun_preds, fun_preds: bool {
-            help "(De)activates predicate-to-function reduction.",
+        cfg_red_max_qvars, cfg_red_max_qvars: Option<usize> {
+            help "Maximal number of quantified variables cfg_red introduces, `0` for no limit.",
             long_help "\
-                If active, hoice will attempt to reconstruct some of the predicates as functions. \
-                Only works on horn clauses over datatypes.\
+                Control flow graph reduction does not inline a predicate if one of the \
+                disjuncts of its definition existentially quantifies more than this many \
+                variables. Zero deactivates the limit.\
             ",
-            long "--fun_preds",
+            long "--cfg_red_max_qvars",
+            validator int_validator,
+            val_name "int",
+            default "0",
             takes_val,
-            val_name bool_format,
             val_nb 1,
-            validator bool_validator,
-            default "on",
             hidden,
         } {
-            |val| bool_of_match(val)
-        }
-    }
-
-    impl SubConf for PreprocConf {
-        fn need_out_dir(&self) -> bool {
-            self.log_preproc && self.active || self.log_pred_dep
+            |mtch| match int_of_match(mtch) {
+                0 => None,
+                n => Some(n),
+            }
         }
-    }
 
-    impl PreprocConf {
-        /// Instance dump dir.
-        fn log_dir<Path>(&self, sub: Path, instance: &Instance) -> Res<PathBuf>
-        where
+        log_pred_dep, log_pred_dep: bool {
+            help "(De)activates predicate dependency dumps (cfg_red).",
+            long_help "\
+                If active, dumps the dependencies between the predicates as a `dot` graph. If \
+                `dot` is available on your computer, the pdf will be generated automatically. In \
+                addition to the original dependency graph, a new graph is dumped each time a \
+                pre-processor changed the relations between the predicates.\
+            ",
+            long "--log_pred_dep",
+            takes_val,
+            val_name bool_format,
+            val_nb 1,
+            validator bool_validator,
+            default "no",
+        } {
+            |val| bool_of_match(val)
+        }
+
+        runroll, runroll: bool {
+            help "(De)activates reverse unrolling.",
+            long_help "\
+                If active, predicates appearing in negative clauses will be unrolled in reverse.\
+            ",
+            long "--runroll",
+            takes_val,
+            val_name bool_format,
+            val_nb 1,
+            validator bool_validator,
+            default "off",
+            hidden,
+        } {
+            |val| bool_of_match(val)
+        }
+
+        runroll_depths, runroll_depths: ::std::collections::BTreeMap<String, usize> {
+            help "Per-predicate reverse unrolling depths.",
+            long_help "\
+                Comma-separated list of `<pred>=<int>` bindings specifying how many times \
+                reverse unrolling can unroll each predicate. Predicates not mentioned are \
+                unrolled at most once. Only relevant when reverse unrolling is active.\
+            ",
+            long "--runroll_depths",
+            validator depth_map_validator,
+            val_name "<pred>=<int>,...",
+            default "none",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| depth_map_of_str(mtch).expect(
+                "unreachable(runroll_depths): default is provided and input validated"
+            )
+        }
+
+        pos_unroll, pos_unroll: bool {
+            help "(De)activates positive unrolling.",
+            long_help "\
+                If active, predicates appearing in positive clauses will be unrolled if unrolling \
+                them creates positive clauses for predicates that did not appear in positive \
+                clauses yet.\
+            ",
+            long "--pos_unroll",
+            takes_val,
+            val_name bool_format,
+            val_nb 1,
+            validator bool_validator,
+            default "on",
+            hidden,
+        } {
+            |val| bool_of_match(val)
+        }
+
+        neg_unroll, neg_unroll: bool {
+            help "(De)activates negative unrolling.",
+            long_help "\
+                If active, predicates appearing in negative clauses will be unrolled if unrolling \
+                them creates negative clauses for predicates that did not appear in negative \
+                clauses yet.\
+            ",
+            long "--neg_unroll",
+            takes_val,
+            val_name bool_format,
+            val_nb 1,
+            validator bool_validator,
+            default "on",
+            hidden,
+        } {
+            |val| bool_of_match(val)
+        }
+
+        bias_unroll_radius, bias_unroll_radius: Option<usize> {
+            help "Maximal distance to a negative clause for biased unrolling, `0` for none.",
+            long_help "\
+                Biased unrolling only generates clauses for predicates at most this many \
+                clauses away from a negative clause in the dependency graph. Predicates \
+                appearing in negative clauses are one clause away. Zero deactivates the limit.\
+            ",
+            long "--bias_unroll_radius",
+            validator int_validator,
+            val_name "int",
+            default "0",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| match int_of_match(mtch) {
+                0 => None,
+                n => Some(n),
+            }
+        }
+
+        split_strengthen, split_strengthen: bool {
+            help "(De)activates strengthening when splitting is active.",
+            long_help "\
+                If active, negative clauses ignored while splitting will be injected in \
+                implication clauses. This strengthens a given split using information from \
+                (currently) ignored negative clauses.\
+            ",
+            long "--split_strengthen",
+            takes_val,
+            val_name bool_format,
+            val_nb 1,
+            validator bool_validator,
+            default "on",
+        } {
+            |val| bool_of_match(val)
+        }
+
+        split_sort, split_sort: bool {
+            help "(De)activates clause sorting when splitting is active.",
+            long_help "\
+                If active, hoice will sort the negative clauses before splitting on them. This \
+                is a heuristic and does not give any guarantees as to whether it really improves \
+                solving.\
+            ",
+            long "--split_sort",
+            takes_val,
+            val_name bool_format,
+            val_nb 1,
+            validator bool_validator,
+            default "on",
+        } {
+            |val| bool_of_match(val)
+        }
+
+        split_hash_sort, split_hash_sort: bool {
+            help "Orders splits using a hash of the content of the negative clauses.",
+            long_help "\
+                If active, hoice will order the negative clauses to split on by a hash of their \
+                content (terms and predicate applications) instead of their index. The resulting \
+                order does not depend on pre-processing. If `split_sort` is also active, the hash \
+                is only used to break ties.\
+            ",
+            long "--split_hash_sort",
+            takes_val,
+            val_name bool_format,
+            val_nb 1,
+            validator bool_validator,
+            default "off",
+        } {
+            |val| bool_of_match(val)
+        }
+
+        strict_neg, strict_neg: bool {
+            help "(De)activates strengthening by strict negative clauses.",
+            long_help "\
+                If active, hoice will use strict negative clauses (clauses that have only one \
+                predicate application) to infer partial definitions for the predicates. If `pred` \
+                appears in `tail => (pred _)`, then `pred` will be constructed as
+                `tail /\\ <def>` where `<def>` needs to be inferred by hoice.\
+            ",
+            long "--strict_neg",
+            takes_val,
+            val_name bool_format,
+            val_nb 1,
+            validator bool_validator,
+            default "on",
+            hidden,
+        } {
+            |val| bool_of_match(val)
+        }
+
+        trivial_clauses, trivial_clauses: bool {
+            help "(De)activates the early detection of trivial clauses.",
+            long_help "\
+                If active, top pre-processing starts by removing the clauses the lhs of which is \
+                unsatisfiable, and by looking for a clause without predicate applications the lhs \
+                of which is satisfiable. Such a clause makes the instance unsat right away. \
+                Syntactic checks run first, the solver is only used on the clauses without \
+                predicate applications.\
+            ",
+            long "--trivial_clauses",
+            takes_val,
+            val_name bool_format,
+            val_nb 1,
+            validator bool_validator,
+            default "on",
+            hidden,
+        } {
+            |val| bool_of_match(val)
+        }
+
+        lin_accel, lin_accel: bool {
+            help "(De)activates hint inference for linearly recursive predicates.",
+            long_help "\
+                If active, hoice looks for predicates that only depend on themselves, with at \
+                most one recursive application per clause. For the integer arguments that \
+                change by a constant at each step, it infers bounds from the initial values \
+                and the guards of the steps, and relations between the counters. These \
+                candidates are given to the teacher as hints (see `--hint`), which drops the \
+                ones that are not inductive before learning starts.\
+            ",
+            long "--lin_accel",
+            takes_val,
+            val_name bool_format,
+            val_nb 1,
+            validator bool_validator,
+            default "off",
+            hidden,
+        } {
+            |val| bool_of_match(val)
+        }
+
+        factor_clauses, factor_clauses: bool {
+            help "(De)activates the factoring of the lhs terms shared by several clauses.",
+            long_help "\
+                If active, at the end of pre-processing hoice looks for groups of clauses the \
+                lhs of which share the same terms and predicate applications, at least two of \
+                them. The shared part is replaced by an application of a new auxiliary \
+                predicate, defined by a new clause. Auxiliary predicates are inlined away in \
+                the final model. Ignored when producing proofs, and when the instance may be \
+                split.\
+            ",
+            long "--factor_clauses",
+            takes_val,
+            val_name bool_format,
+            val_nb 1,
+            validator bool_validator,
+            default "off",
+            hidden,
+        } {
+            |val| bool_of_match(val)
+        }
+
+        fun_preds, fun_preds: bool {
+            help "(De)activates predicate-to-function reduction.",
+            long_help "\
+                If active, hoice will attempt to reconstruct some of the predicates as functions. \
+                Only works on horn clauses over datatypes.\
+            ",
+            long "--fun_preds",
+            takes_val,
+            val_name bool_format,
+            val_nb 1,
+            validator bool_validator,
+            default "on",
+            hidden,
+        } {
+            |val| bool_of_match(val)
+        }
+    }
+
+    impl SubConf for PreprocConf {
+        fn need_out_dir(&self) -> bool {
+            self.log_preproc && self.active || self.log_pred_dep
+        }
+    }
+
+    impl PreprocConf {
+        /// Maximal number of times reverse unrolling can unroll a predicate.
+        pub fn runroll_depth(&self, pred: &str) -> usize {
+            self.runroll_depths.get(pred).cloned().unwrap_or(1)
+        }
+
+        /// True if a pre-processing pass is not deactivated by `--no_preproc`.
+        pub fn pass_enabled(&self, name: &str) -> bool {
+            !self.no_preproc.contains(name)
+        }
+
+        /// Number of reverse unrolling rounds needed to honor all depths.
+        pub fn runroll_rounds(&self) -> usize {
+            self.runroll_depths.values().cloned().fold(1, ::std::cmp::max)
+        }
+
+        /// Instance dump dir.
+        fn log_dir<Path>(&self, sub: Path, instance: &Instance) -> Res<PathBuf>
+        where
             Path: AsRef<::std::path::Path>,
         {
             let mut path = crate::common::conf.out_dir(instance);
@@ -757,6 +1415,26 @@ make_conf! {
             |mtch| bool_of_match(mtch)
         }
 
+        dt_tiebreak, dt_tiebreak: DtTiebreak {
+            help "Tie-breaking between the qualifiers with the same gain.",
+            long_help "\
+                Decides which qualifier the decision tree uses when several qualifiers have the \
+                best gain. `first` keeps the first one considered (and, for synthesized \
+                qualifiers, the one mentioning the most variables). `simplest` keeps the one \
+                with the lowest syntactic complexity, so qualifiers with fewer and smaller \
+                coefficients are preferred. `random` picks one at random, the choice only \
+                depends on `--seed`.\
+            ",
+            long "--dt_tiebreak",
+            validator dt_tiebreak_validator,
+            val_name "first|simplest|random",
+            default "first",
+            takes_val,
+            val_nb 1,
+        } {
+            |mtch| DtTiebreak::of_str(mtch).expect("failed to retrieve tie-breaking argument")
+        }
+
         complete, complete: bool {
             help "Generates complete transformations for qualifiers.",
             long_help "\
@@ -764,7 +1442,147 @@ make_conf! {
                 This might cause an explosion in the number of qualifiers. Probably best to \
                 avoid it on big problems.\
             ",
-            long "--qual_complete",
+            long "--qual_complete",
+            validator bool_validator,
+            val_name bool_format,
+            default "no",
+            takes_val,
+            val_nb 1,
+        } {
+            |mtch| bool_of_match(mtch)
+        }
+
+        qual_bias, qual_bias: bool {
+            help "Predicate bias for qualifier section.",
+            long_help "\
+                If active, the qualifiers that are generated for a specific predicate will not be \
+                generalized to other predicates. \
+                This might cause an explosion in the number of qualifiers. Probably best to \
+                avoid it on big problems.\
+            ",
+            long "--qual_bias",
+            validator bool_validator,
+            val_name bool_format,
+            default "on",
+            takes_val,
+            val_nb 1,
+        } {
+            |mtch| bool_of_match(mtch)
+        }
+
+        qual_blacklist, qual_blacklist: Option<String> {
+            help "File of qualifier patterns the learner should ignore.",
+            long_help "\
+                Specifies a file listing some qualifier patterns, one per line. Lines starting \
+                with `;` are ignored. Patterns are SMT-LIB 2 terms over the variables `v_0`, \
+                `v_1`, ... of the predicate signatures, and are matched up to variable \
+                renaming: `(<= v_0 0)` blacklists `(<= v_i 0)` for each integer variable `v_i` \
+                of each predicate. Inactive by default.\
+            ",
+            long "--qual_blacklist",
+            val_name "FILE",
+            default "",
+            takes_val,
+            val_nb 1,
+        } {
+            |mtch| if mtch.is_empty() {
+                None
+            } else {
+                Some(mtch.to_string())
+            }
+        }
+
+        qual_max_modulo, qual_max_modulo: usize {
+            help "Maximal modulus of the divisibility qualifiers synthesized, `0` or `1` for none.",
+            long_help "\
+                Specifies the maximal `k` for which the qualifiers `(= (mod x k) r)` are \
+                synthesized from the integer values of the samples. The candidate moduli are \
+                `2` and, for each pair of integer values of a sample, their gcd and the absolute \
+                value of their difference. `2` only synthesizes parity qualifiers. Values lower \
+                than `2` deactivate divisibility synthesis.\
+            ",
+            long "--qual_max_modulo",
+            validator int_validator,
+            val_name "int",
+            default "0",
+            takes_val,
+            val_nb 1,
+        } {
+            |mtch| int_of_match(mtch)
+        }
+
+        qual_max_degree, qual_max_degree: usize {
+            help "Maximal degree of the synthesized qualifiers.",
+            long_help "\
+                Specifies the maximal degree of the qualifiers synthesized from samples, seen as \
+                polynomials: `1` only allows linear qualifiers, `2` also allows `x*y` and `x*x` \
+                but not `x*y*z`, etc. Multiplications by a constant do not increase the degree, \
+                and `div`, `mod` and `rem` have the degree of their arguments. Does not apply to \
+                the qualifiers mined from the clauses.\
+            ",
+            long "--qual_max_degree",
+            validator int_validator,
+            val_name "int",
+            default "1",
+            takes_val,
+            val_nb 1,
+        } {
+            |mtch| int_of_match(mtch)
+        }
+
+        qual_templates, qual_templates: Option<String> {
+            help "File of qualifier templates the learner should instantiate.",
+            long_help "\
+                Specifies a file listing some qualifier templates, one per line. Lines starting \
+                with `;` are ignored. A template is a list of sorted variables followed by a \
+                boolean term over them, for instance \
+                `((x Int) (y Int) (?a Int) (?c Int)) (<= (+ x (* ?a y)) ?c)`. Variables the \
+                name of which starts with `?` are holes. The other variables are bound to the \
+                arguments of the predicates of the same type. If the template is an arithmetic \
+                comparison, its last hole gets the value making the comparison tight on each \
+                sample. The other holes range over the integers between \
+                `-qual_template_coef` and `qual_template_coef`. Inactive by default.\
+            ",
+            long "--qual_templates",
+            val_name "FILE",
+            default "",
+            takes_val,
+            val_nb 1,
+        } {
+            |mtch| if mtch.is_empty() {
+                None
+            } else {
+                Some(mtch.to_string())
+            }
+        }
+
+        qual_template_coef, qual_template_coef: usize {
+            help "Bound on the coefficient holes of the qualifier templates.",
+            long_help "\
+                The coefficient holes of the qualifier templates range over the integers \
+                between minus this value and this value, see `--qual_templates`.\
+            ",
+            long "--qual_template_coef",
+            validator int_validator,
+            val_name "int",
+            default "2",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| int_of_match(mtch)
+        }
+
+        qual_octagon, qual_octagon: bool {
+            help "(De)activates octagon qualifier synthesis.",
+            long_help "\
+                If active, synthesizes the octagon qualifiers `(<= (+ (* s x) (* t y)) c)` for \
+                all signs `s` and `t` in `{-1, 1}` and all pairs of integer variables `x` and \
+                `y`, where `c` is the value of the left-hand side on the sample. On top of the \
+                other qualifier families. Only applies to the samples with at most \
+                `--qual_octagon_max_arity` integer variables.\
+            ",
+            long "--qual_octagon",
             validator bool_validator,
             val_name bool_format,
             default "no",
@@ -774,22 +1592,22 @@ make_conf! {
             |mtch| bool_of_match(mtch)
         }
 
-        qual_bias, qual_bias: bool {
-            help "Predicate bias for qualifier section.",
+        qual_octagon_max_arity, qual_octagon_max_arity: usize {
+            help "Maximal number of integer variables for octagon synthesis.",
             long_help "\
-                If active, the qualifiers that are generated for a specific predicate will not be \
-                generalized to other predicates. \
-                This might cause an explosion in the number of qualifiers. Probably best to \
-                avoid it on big problems.\
+                Octagon synthesis generates a number of qualifiers quadratic in the number of \
+                integer variables of the samples. It is skipped on the samples with more than \
+                this many integer variables, see `--qual_octagon`.\
             ",
-            long "--qual_bias",
-            validator bool_validator,
-            val_name bool_format,
-            default "on",
+            long "--qual_octagon_max_arity",
+            validator int_validator,
+            val_name "int",
+            default "8",
             takes_val,
             val_nb 1,
+            hidden,
         } {
-            |mtch| bool_of_match(mtch)
+            |mtch| int_of_match(mtch)
         }
 
         qual_print, qual_print: bool {
@@ -808,6 +1626,27 @@ make_conf! {
             |mtch| bool_of_match(mtch)
         }
 
+        dump_quals, dump_quals: Option<String> {
+            help "File to write the qualifiers used by the learned model to.",
+            long_help "\
+                Specifies a file to write the qualifiers appearing in the model to, along with \
+                the number of samples each qualifier separated when it was selected. Qualifiers \
+                are written as `define-fun`s over the original signature of their predicate, so \
+                that the file can be used as a hint file (see `--hint`). Inactive by default.\
+            ",
+            long "--dump_quals",
+            val_name "FILE",
+            default "",
+            takes_val,
+            val_nb 1,
+        } {
+            |mtch| if mtch.is_empty() {
+                None
+            } else {
+                Some(mtch.to_string())
+            }
+        }
+
         gain_pivot, gain_pivot: f64 {
             help "Gain in percent above which hoice considers a qualifier satisfactory.",
             long_help "\
@@ -1020,88 +1859,452 @@ make_conf! {
             |mtch| bool_of_match(mtch)
         }
 
-        assistant, assistant: bool {
-            help "(De)activates breaking implication constraints.",
+        assistant, assistant: bool {
+            help "(De)activates breaking implication constraints.",
+            long_help "\
+                If active the assistant will run: it receives (new) implications constraints \
+                from the teacher and attempts to break them. Almost always useful, but might \
+                slow progress down slightly.\
+            ",
+            long "--assistant",
+            validator bool_validator,
+            val_name bool_format,
+            default "on",
+            takes_val,
+            val_nb 1,
+        } {
+            |mtch| bool_of_match(mtch)
+        }
+
+        bias_cexs, bias_cexs: bool {
+            help "(De)activates biased implication constraints.",
+            long_help "\
+                If active, when the teacher checks implication constraint, it will look for \
+                counterexamples that have at least one positive/negative sample.\
+            ",
+            long "--bias_cexs",
+            validator bool_validator,
+            val_name bool_format,
+            default "on",
+            takes_val,
+            val_nb 1,
+        } {
+            |mtch| bool_of_match(mtch)
+        }
+
+        max_bias, max_bias: bool {
+            help "(De)activates a more extreme version of `--bias_cexs`.",
+            long_help "\
+                If active, only considers counterexamples that have no unclassified data when \
+                possible.\
+            ",
+            long "--max_bias",
+            validator bool_validator,
+            val_name bool_format,
+            default "off",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| bool_of_match(mtch)
+        }
+
+        partial, partial: bool {
+            help "(De)activates partial samples.",
+            long_help "\
+                If active, the teacher is allowed to generate *partial samples*. These are \
+                samples that have unspecified, *don't care* values.\
+            ",
+            long "--partial",
+            validator bool_validator,
+            val_name bool_format,
+            default "on",
+            takes_val,
+            val_nb 1,
+        } {
+            |mtch| bool_of_match(mtch)
+        }
+
+        restart_on_cex, restart_on_cex: bool {
+            help "Restarts the teacher's solver for each counterexample query.",
+            long_help "\
+                If active, the teacher will always restart before checking the learner's \
+                candidates for counterexamples. Note that if the clauses mention datatypes \
+                the solver is always restarted.\
+            ",
+            long "--restart_on_cex",
+            validator bool_validator,
+            val_name bool_format,
+            default "off",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| bool_of_match(mtch)
+        }
+
+        pipeline, pipeline: Option<usize> {
+            help "Pipelines the teacher and the learners, `0` for sequential.",
+            long_help "\
+                If not zero, the teacher sends the counterexamples of the positive and strict \
+                negative clauses as soon as it has them, and the learners start working on them \
+                while the teacher checks the other clauses. A learner receiving new data while \
+                learning drops its current work and starts over on the new data, and the teacher \
+                ignores candidates learned from outdated data. The value bounds the number of \
+                data messages waiting for each learner, the teacher blocks when a learner lags \
+                behind that much.\
+            ",
+            long "--pipeline",
+            validator int_validator,
+            val_name "int",
+            default "0",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| match int_of_match(mtch) {
+                0 => None,
+                n => Some(n),
+            }
+        }
+
+        teacher_restart, restart: Option<usize> {
+            help "Rounds without progress before the learners restart, `0` for never.",
+            long_help "\
+                If the number of clauses the candidates falsify does not decrease for this many \
+                rounds, the teacher asks the learners to restart: they forget their qualifiers \
+                and re-seed the random generators ordering them, but keep the learning data. \
+                Zero deactivates teacher-driven restarts.\
+            ",
+            long "--teacher_restart",
+            validator int_validator,
+            val_name "int",
+            default "0",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| match int_of_match(mtch) {
+                0 => None,
+                n => Some(n),
+            }
+        }
+
+        teacher_restart_seed, restart_seed: usize {
+            help "Seed of the random generator used for teacher-driven restarts.",
+            long_help "\
+                Seeds the random generator the teacher uses to draw the seeds the learners \
+                restart with (see `--teacher_restart`). Runs with the same seed are \
+                reproducible.\
+            ",
+            long "--teacher_restart_seed",
+            validator int_validator,
+            val_name "int",
+            default "0",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| int_of_match(mtch)
+        }
+
+        bounded_fallback, bounded_fallback: Option<usize> {
+            help "Rounds without progress before bounded model-finding, `0` for never.",
+            long_help "\
+                If the number of clauses the candidates falsify does not decrease for this many \
+                rounds, the teacher enumerates the truth tables of the predicates the arguments \
+                of which all have a finite domain (booleans, datatypes with no constructor \
+                arguments). The other predicates keep their current candidates. The teacher \
+                stops as soon as a table has no counterexample. Only engages if there are at \
+                most `--bounded_fallback_max` tables, once the values of the samples are fixed. \
+                Zero deactivates bounded model-finding.\
+            ",
+            long "--bounded_fallback",
+            validator int_validator,
+            val_name "int",
+            default "0",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| match int_of_match(mtch) {
+                0 => None,
+                n => Some(n),
+            }
+        }
+
+        bounded_fallback_max, bounded_fallback_max: usize {
+            help "Maximum number of truth tables bounded model-finding enumerates.",
+            long_help "\
+                Bounded model-finding (see `--bounded_fallback`) does not engage if the finite \
+                predicates have more than this many truth tables, or if one of them has more \
+                than this many tuples of argument values.\
+            ",
+            long "--bounded_fallback_max",
+            validator int_validator,
+            val_name "int",
+            default "256",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| int_of_match(mtch)
+        }
+
+        compact_data, compact_data: Option<usize> {
+            help "Compacts the learning data every `n` learning rounds, `0` for never.",
+            long_help "\
+                If not zero, the teacher compacts the learning data every `n` learning rounds: \
+                constraints subsumed by other constraints or by known samples are removed, and \
+                constraints whose antecedents are all positive become positive samples. This \
+                does not change the learning data, only its size.\
+            ",
+            long "--compact_data",
+            validator int_validator,
+            val_name "int",
+            default "0",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| match int_of_match(mtch) {
+                0 => None,
+                n => Some(n),
+            }
+        }
+
+        incremental, incremental: bool {
+            help "(De)activates the incremental teacher.",
+            long_help "\
+                If active, the teacher keeps the definitions of the candidates in its solver \
+                between checks, and only defines the predicates whose candidate changed. \
+                Otherwise, the candidates are defined and then popped for each check, which \
+                is more robust if the solver has trouble with many definitions. Inactive if \
+                the solver is restarted for each counterexample query.\
+            ",
+            long "--incremental_teacher",
+            validator bool_validator,
+            val_name bool_format,
+            default "off",
+            takes_val,
+            val_nb 1,
+        } {
+            |mtch| bool_of_match(mtch)
+        }
+
+        batch_cexs, batch_cexs: bool {
+            help "Checks similar clauses together before looking for counterexamples.",
+            long_help "\
+                If active, the clauses mentioning the same predicates over the same variables \
+                are first checked together, with a single check-sat. When none of them is \
+                falsifiable, this saves one check-sat for each clause but one. Otherwise, the \
+                clauses are checked one by one as usual, so that the counterexamples are the \
+                same as without batching. Inactive if the solver is restarted for each \
+                counterexample query.\
+            ",
+            long "--batch_cexs",
+            validator bool_validator,
+            val_name bool_format,
+            default "off",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| bool_of_match(mtch)
+        }
+
+        memo_checks, memo_checks: bool {
+            help "Skips clauses that were valid for the same candidates of their predicates.",
+            long_help "\
+                If active, the teacher remembers the candidates of the predicates of each clause \
+                the last time it had no counterexample. The clause is not checked again as long \
+                as the candidates of these predicates do not change.\
+            ",
+            long "--memo_checks",
+            validator bool_validator,
+            val_name bool_format,
+            default "off",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| bool_of_match(mtch)
+        }
+
+        clause_cores, clause_cores: bool {
+            help "(De)activates clause relevance extraction in batch checks.",
+            long_help "\
+                If active, when a group of similar clauses is falsifiable during batch checking \
+                (see `--batch_cexs`), the teacher extracts an unsat core of the group: a subset \
+                of clauses such that whenever a clause of the group is falsified, one of the \
+                core is too. Clauses that appear in such cores are then checked first.\
+            ",
+            long "--clause_cores",
+            validator bool_validator,
+            val_name bool_format,
+            default "off",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| bool_of_match(mtch)
+        }
+
+        cex_yield, cex_yield: bool {
+            help "(De)activates clause scheduling based on counterexample yield.",
+            long_help "\
+                If active, the teacher maintains a score for each clause that increases when \
+                the clause yields counterexamples and decays exponentially otherwise. Within \
+                each class of clauses (positive, negative, implication), clauses with a higher \
+                score are checked first. Only changes the order in which counterexamples are \
+                discovered. If inactive, clauses are checked in their usual order.\
+            ",
+            long "--cex_yield",
+            validator bool_validator,
+            val_name bool_format,
+            default "off",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| bool_of_match(mtch)
+        }
+
+        cex_yield_decay, cex_yield_decay: f64 {
+            help "Decay in percent of the counterexample yield scores, see `--cex_yield`.",
+            long_help "\
+                With `--cex_yield`, the score of each clause is multiplied by this value after \
+                each check, before the scores of the clauses that yielded counterexamples are \
+                bumped. Lower values make the scheduling favor recent counterexamples.\n
+                \n\
+                The value is in percent, between 0 and 100.\
+            ",
+            long "--cex_yield_decay",
+            validator int_validator,
+            val_name "int",
+            default "80",
+            takes_val,
+            val_nb 1,
+            hidden,
+        } {
+            |mtch| {
+                let value = int_of_match(mtch) as f64 / 100.0;
+                if 1.0 < value {
+                    1.0
+                } else {
+                    value
+                }
+            }
+        }
+
+        clause_timeout, clause_timeout: Option<usize> {
+            help "Timeout of the teacher's check-sats in milliseconds, `0` for none.",
             long_help "\
-                If active the assistant will run: it receives (new) implications constraints \
-                from the teacher and attempts to break them. Almost always useful, but might \
-                slow progress down slightly.\
+                Gives each check-sat of the teacher on a clause this many milliseconds. A clause \
+                the check of which times out is considered to have no counterexample for the \
+                current candidates; if no other clause has one, the teacher checks the clauses \
+                again. A clause timing out `--clause_timeout_tries` times in a row makes hoice \
+                answer `unknown`, naming the clause. Zero deactivates the timeout. Not used when \
+                the clauses mention recursive functions.\
             ",
-            long "--assistant",
-            validator bool_validator,
-            val_name bool_format,
-            default "on",
+            long "--clause_timeout",
+            validator int_validator,
+            val_name "int",
+            default "0",
             takes_val,
             val_nb 1,
+            hidden,
         } {
-            |mtch| bool_of_match(mtch)
+            |mtch| match int_of_match(mtch) {
+                0 => None,
+                n => Some(n),
+            }
         }
 
-        bias_cexs, bias_cexs: bool {
-            help "(De)activates biased implication constraints.",
+        clause_timeout_tries, clause_timeout_tries: usize {
+            help "Number of consecutive timeouts on a clause before answering `unknown`.",
             long_help "\
-                If active, when the teacher checks implication constraint, it will look for \
-                counterexamples that have at least one positive/negative sample.\
+                With `--clause_timeout`, hoice gives up and answers `unknown` when the check of \
+                a clause times out this many times in a row.\
             ",
-            long "--bias_cexs",
-            validator bool_validator,
-            val_name bool_format,
-            default "on",
+            long "--clause_timeout_tries",
+            validator int_validator,
+            val_name "int",
+            default "3",
             takes_val,
             val_nb 1,
+            hidden,
         } {
-            |mtch| bool_of_match(mtch)
+            |mtch| int_of_match(mtch)
         }
 
-        max_bias, max_bias: bool {
-            help "(De)activates a more extreme version of `--bias_cexs`.",
+        cex_strategy, cex_strategy: CexStrategy {
+            help "Strategy used to select counterexamples.",
             long_help "\
-                If active, only considers counterexamples that have no unclassified data when \
-                possible.\
+                Decides which counterexample the teacher sends when an implication constraint \
+                is falsifiable. `first` keeps the first model found by the solver. `smallest` \
+                looks for a model minimizing the sum of the absolute values of the arithmetic \
+                variables, and falls back to `first` if it cannot improve on it. `diverse` \
+                looks at a few models and keeps the one furthest away (L1 distance) from the \
+                samples the learner already knows. Inactive on biased counterexamples and when \
+                the clauses mention recursive functions.\
             ",
-            long "--max_bias",
-            validator bool_validator,
-            val_name bool_format,
-            default "off",
+            long "--cex_strategy",
+            validator cex_strategy_validator,
+            val_name "first|smallest|diverse",
+            default "first",
             takes_val,
             val_nb 1,
-            hidden,
         } {
-            |mtch| bool_of_match(mtch)
+            |mtch| CexStrategy::of_str(mtch).expect("failed to retrieve cex strategy argument")
         }
 
-        partial, partial: bool {
-            help "(De)activates partial samples.",
+        learn_only, learn_only: Option<String> {
+            help "File to write the learning data of the trivial candidates to, without learning.",
             long_help "\
-                If active, the teacher is allowed to generate *partial samples*. These are \
-                samples that have unspecified, *don't care* values.\
+                If given, no learner runs. Instead, the teacher checks the candidates where all \
+                predicates are `true`, and then the ones where they are all `false`, turns the \
+                counterexamples into learning data, writes the data to the file and stops with \
+                `unknown`. The data is written as JSON lines: positive and negative samples, and \
+                implication constraints, with the names of the predicates and the values of \
+                their arguments. Inactive by default.\
             ",
-            long "--partial",
-            validator bool_validator,
-            val_name bool_format,
-            default "on",
+            long "--learn_only",
+            val_name "FILE",
+            default "",
             takes_val,
             val_nb 1,
         } {
-            |mtch| bool_of_match(mtch)
+            |mtch| if mtch.is_empty() {
+                None
+            } else {
+                Some(mtch.to_string())
+            }
         }
 
-        restart_on_cex, restart_on_cex: bool {
-            help "Restarts the teacher's solver for each counterexample query.",
+        replay, replay: Option<String> {
+            help "File of learning data to give to the learner, without checking its candidates.",
             long_help "\
-                If active, the teacher will always restart before checking the learner's \
-                candidates for counterexamples. Note that if the clauses mention datatypes \
-                the solver is always restarted.\
+                If given, the learning data in the file, in the format written by \
+                `--learn_only`, is given to the learner, and the teacher never checks the \
+                candidates. The first candidates the learner produces are printed as comments \
+                and hoice stops with `unknown`, or with `unsat` if the data is contradictory. \
+                The predicates of the data must be the ones of the instance after \
+                pre-processing, so the options must be the same as when the data was written. \
+                Useful to debug or benchmark the learner on a fixed sample set. Incompatible \
+                with `--learn_only`. Inactive by default.\
             ",
-            long "--restart_on_cex",
-            validator bool_validator,
-            val_name bool_format,
-            default "off",
+            long "--replay",
+            val_name "FILE",
+            default "",
             takes_val,
             val_nb 1,
-            hidden,
         } {
-            |mtch| bool_of_match(mtch)
+            |mtch| if mtch.is_empty() {
+                None
+            } else {
+                Some(mtch.to_string())
+            }
         }
     }
 
@@ -1121,14 +2324,36 @@ pub struct Config {
     pub verb: usize,
     /// Statistics flag.
     pub stats: bool,
+    /// File to write the statistics to as JSON, one object per line.
+    pub stats_json: Option<String>,
     /// Inference flag.
     pub infer: bool,
     /// Reason on each negative clause separately.
     pub split: bool,
     /// Pause between negative clauses when in split mode.
     pub split_step: bool,
+    /// Solve splits in parallel.
+    pub split_par: bool,
+    /// Number of workers for parallel splitting.
+    pub split_workers: usize,
+    /// Reuse the solutions of previous splits.
+    pub split_reuse: bool,
+    /// Split on positive clauses instead of negative ones.
+    pub split_pos: bool,
+    /// Timeout for each split in seconds, `0` for none.
+    pub split_timeout: usize,
+    /// Print progress and ETA in split mode.
+    pub split_eta: bool,
+    /// Solve each split in a child process.
+    pub split_subprocess: bool,
+    /// Checkpoint file for split mode.
+    split_resume: Option<String>,
+    /// Cache file for split mode, survives changes to the instance.
+    split_cache: Option<String>,
     /// Instant at which we'll timeout.
     timeout: Option<Instant>,
+    /// Memory limit in MB.
+    pub mem_limit: Option<usize>,
     /// Output directory.
     out_dir: String,
     /// Styles, for coloring.
@@ -1142,6 +2367,46 @@ pub struct Config {
     pub check_simpl: bool,
     /// Level of term simplification.
     pub term_simpl: usize,
+    /// Maximum depth of the entry point reconstruction.
+    pub proof_recon_depth: usize,
+    /// Minimize the entry points after reconstruction.
+    pub proof_minimize: bool,
+    /// Record unknown sample dependencies as unresolved entry points instead of failing.
+    pub proof_lenient: bool,
+    /// Writes a resolution certificate for unsat proofs.
+    pub proof_certificate: bool,
+    /// Writes a derivation trace for unsat proofs.
+    pub proof_trace: bool,
+    /// Candidate invariants file.
+    hint: Option<String>,
+    /// Format of the models.
+    pub model_format: ModelFormat,
+    /// Exit codes of the process.
+    pub exit_codes: ExitCodes,
+    /// Share common subterms of the models with let-bindings.
+    pub model_let: bool,
+    /// Subterms are let-bound if they appear more than this many times.
+    pub model_let_min: usize,
+    /// Factors the guards shared by the disjuncts of the models.
+    pub factor_model: bool,
+    /// Checks the models against the original instance.
+    pub check_model: bool,
+    /// Streaming parse mode for large inputs.
+    pub stream_parse: bool,
+    /// Inline non-recursive `define-fun`s during parsing.
+    pub inline_funs: bool,
+    /// Reads the input as a Prolog-style Horn program.
+    pub horn: bool,
+    /// Solves each instance of the input separately, see `(reset)`, `(push)` and `(pop)`.
+    pub batch: bool,
+    /// Prints the last candidates as an unverified model on timeout.
+    pub best_effort: bool,
+    /// Relaxes the soft clauses (`:weight`) greedily when the instance is unsat.
+    pub soft_clauses: bool,
+    /// Prints the model of each split as soon as it is solved.
+    pub anytime: bool,
+    /// Seed of all the random generators.
+    pub seed: u64,
 
     /// Instance and factory configuration.
     pub instance: InstanceConf,
@@ -1180,34 +2445,105 @@ impl Config {
     pub fn check_file(&self) -> Option<&String> {
         self.check.as_ref()
     }
+    /// Checkpoint file for split mode.
+    #[inline]
+    pub fn split_resume_file(&self) -> Option<&String> {
+        self.split_resume.as_ref()
+    }
+    /// Cache file for split mode.
+    #[inline]
+    pub fn split_cache_file(&self) -> Option<&String> {
+        self.split_cache.as_ref()
+    }
+    /// Candidate invariants file.
+    #[inline]
+    pub fn hint_file(&self) -> Option<&String> {
+        self.hint.as_ref()
+    }
 
     /// Checks if we're out of time.
+    ///
+    /// Takes the local deadline of the current thread into account, see [`with_deadline`].
+    ///
+    /// [`with_deadline`]: ../fn.with_deadline.html (with_deadline function)
     #[inline]
     pub fn check_timeout(&self) -> Res<()> {
-        if let Some(max) = self.timeout.as_ref() {
-            if Instant::now() > *max {
+        if let Some(max) = self.deadline() {
+            if Instant::now() > max {
                 bail!(ErrorKind::Timeout)
             }
         }
         Ok(())
     }
+    /// Checks if we're over the memory limit, if any.
+    ///
+    /// The `phase` is what hoice is doing, for the error message.
+    pub fn check_mem(&self, phase: &'static str) -> Res<()> {
+        if let Some(max) = self.mem_limit {
+            if let Some(used) = crate::common::resident_mb() {
+                if used > max {
+                    bail!(ErrorKind::OutOfMemory(phase, used, max))
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Time until timeout.
     #[inline]
     pub fn until_timeout(&self) -> Option<Duration> {
-        if let Some(timeout) = self.timeout.as_ref() {
+        if let Some(timeout) = self.deadline() {
             let now = Instant::now();
-            if now > *timeout {
+            if now > timeout {
                 Some(Duration::new(0, 0))
             } else {
-                Some(*timeout - now)
+                Some(timeout - now)
             }
         } else {
             None
         }
     }
 
+    /// Earliest of the global timeout and the local deadline of the current thread.
+    fn deadline(&self) -> Option<Instant> {
+        match (self.timeout, crate::common::current_deadline()) {
+            (Some(global), Some(local)) => Some(::std::cmp::min(global, local)),
+            (global, None) => global,
+            (None, local) => local,
+        }
+    }
+
     /// Parses command-line arguments and generates the configuration.
     pub fn clap() -> Self {
+        Self::of_matches(Self::app().get_matches())
+    }
+
+    /// Generates a configuration from some arguments, without the name of the binary.
+    ///
+    /// Used to run hoice with several configurations in the same process, see [`with_conf`]. The
+    /// timeout, if any, starts when the configuration is created.
+    ///
+    /// [`with_conf`]: ../fn.with_conf.html (with_conf function)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::common::Config;
+    /// let config = Config::of_args(&["--seed", "7", "--split", "off"]).unwrap();
+    /// assert_eq! { config.seed, 7 }
+    /// assert! { !config.split }
+    /// assert! { Config::of_args(&["--split", "maybe"]).is_err() }
+    /// ```
+    pub fn of_args(args: &[&str]) -> Res<Self> {
+        let args = Some(crate_name!()).into_iter().chain(args.iter().cloned());
+        match Self::app().get_matches_from_safe(args) {
+            Ok(matches) => Ok(Self::of_matches(matches)),
+            Err(e) => bail!("illegal arguments: {}", e.message),
+        }
+    }
+
+    /// Clap application, with all the arguments.
+    fn app() -> App {
         let mut app = App::new(crate_name!());
         // let mut order = 0;
         app = Self::add_args(app, 0);
@@ -1217,9 +2553,11 @@ impl Config {
         app = IceConf::add_args(app, &mut 400);
         app = TeacherConf::add_args(app, &mut 500);
         app = Self::add_check_args(app, 600);
+        app
+    }
 
-        let matches = app.get_matches();
-
+    /// Generates the configuration from clap matches.
+    fn of_matches(matches: Matches) -> Self {
         // Input file.
         let file = matches.value_of("input file").map(|s| s.to_string());
 
@@ -1246,6 +2584,7 @@ impl Config {
 
         // Profiling.
         let stats = bool_of_matches(&matches, "stats");
+        let stats_json = matches.value_of("stats_json").map(|s| s.to_string());
 
         // Inference flag.
         let infer = bool_of_matches(&matches, "infer");
@@ -1258,8 +2597,21 @@ impl Config {
             0 => None,
             n => Some(Instant::now() + Duration::new(n as u64, 0)),
         };
+        let mem_limit = match int_of_matches(&matches, "mem_limit") {
+            0 => None,
+            n => Some(n),
+        };
 
         let split = bool_of_matches(&matches, "split");
+        let split_par = bool_of_matches(&matches, "split_par");
+        let split_workers = int_of_matches(&matches, "split_workers");
+        let split_reuse = bool_of_matches(&matches, "split_reuse");
+        let split_pos = bool_of_matches(&matches, "split_pos");
+        let split_timeout = int_of_matches(&matches, "split_timeout");
+        let split_eta = bool_of_matches(&matches, "split_eta");
+        let split_subprocess = bool_of_matches(&matches, "split_subprocess");
+        let split_resume = matches.value_of("split_resume").map(|s| s.to_string());
+        let split_cache = matches.value_of("split_cache").map(|s| s.to_string());
 
         // Result checking.
         let check = matches.value_of("check").map(|s| s.to_string());
@@ -1269,6 +2621,38 @@ impl Config {
         // Timeout.
         let term_simpl = int_of_matches(&matches, "term_simpl");
 
+        // Proof reconstruction.
+        let proof_recon_depth = int_of_matches(&matches, "proof_recon_depth");
+        let proof_minimize = bool_of_matches(&matches, "proof_minimize");
+        let proof_lenient = bool_of_matches(&matches, "proof_lenient");
+        let proof_certificate = bool_of_matches(&matches, "proof_certificate");
+        let proof_trace = bool_of_matches(&matches, "proof_trace");
+
+        // Candidate invariants.
+        let hint = matches.value_of("hint").map(|s| s.to_string());
+
+        // Model format.
+        let model_format = matches
+            .value_of("model_format")
+            .and_then(ModelFormat::of_str)
+            .expect("unreachable(model_format): default is provided and input validated");
+        let exit_codes = matches
+            .value_of("exit_codes")
+            .and_then(ExitCodes::of_str)
+            .expect("unreachable(exit_codes): default is provided and input validated");
+        let model_let = bool_of_matches(&matches, "model_let");
+        let model_let_min = int_of_matches(&matches, "model_let_min");
+        let factor_model = bool_of_matches(&matches, "factor_model");
+        let check_model = bool_of_matches(&matches, "check_model");
+        let stream_parse = bool_of_matches(&matches, "stream_parse");
+        let inline_funs = bool_of_matches(&matches, "inline_funs");
+        let horn = bool_of_matches(&matches, "horn");
+        let batch = bool_of_matches(&matches, "batch");
+        let best_effort = bool_of_matches(&matches, "best_effort");
+        let soft_clauses = bool_of_matches(&matches, "soft_clauses");
+        let anytime = bool_of_matches(&matches, "anytime");
+        let seed = int_of_matches(&matches, "seed") as u64;
+
         let instance = InstanceConf::new(&matches);
         let preproc = PreprocConf::new(&matches);
         let solver = SmtConf::new(&matches);
@@ -1279,16 +2663,47 @@ impl Config {
             file,
             verb,
             stats,
+            stats_json,
             infer,
             split,
             split_step,
+            split_par,
+            split_workers,
+            split_reuse,
+            split_pos,
+            split_timeout,
+            split_eta,
+            split_subprocess,
+            split_resume,
+            split_cache,
             timeout,
+            mem_limit,
             out_dir,
             styles,
             check,
             check_eld,
             check_simpl,
             term_simpl,
+            proof_recon_depth,
+            proof_minimize,
+            proof_lenient,
+            proof_certificate,
+            proof_trace,
+            hint,
+            model_format,
+            exit_codes,
+            model_let,
+            model_let_min,
+            factor_model,
+            check_model,
+            stream_parse,
+            inline_funs,
+            horn,
+            batch,
+            best_effort,
+            soft_clauses,
+            anytime,
+            seed,
             instance,
             preproc,
             solver,
@@ -1314,49 +2729,326 @@ impl Config {
                     .display_order(order()),
             )
             .arg(
-                Arg::with_name("verb")
-                    .short("-v")
-                    .help("increases verbosity")
-                    .takes_value(false)
-                    .multiple(true)
+                Arg::with_name("verb")
+                    .short("-v")
+                    .help("increases verbosity")
+                    .takes_value(false)
+                    .multiple(true)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("quiet")
+                    .short("-q")
+                    .help("decreases verbosity")
+                    .takes_value(false)
+                    .multiple(true)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("color")
+                    .long("--color")
+                    .short("-c")
+                    .help("(de)activates coloring (off if output is not a tty)")
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("on")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("out_dir")
+                    .long("--out_dir")
+                    .short("-o")
+                    .help("sets the output directory")
+                    .value_name("DIR")
+                    .default_value("hoice_out")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("stats")
+                    .long("--stats")
+                    .short("-s")
+                    .help("reports some statistics at the end of the run")
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("no")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("stats_json")
+                    .long("--stats_json")
+                    .help(
+                        "writes the statistics to a file as JSON, one object per profiler with \
+                         the wall-clock time, thread CPU time and number of runs of each phase \
+                         (the CPU time of the SMT solvers is not included)",
+                    )
+                    .value_name("FILE")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("infer")
+                    .long("--infer")
+                    .short("-i")
+                    .help("if `off`, ignore `check-sat` and `get-model` queries")
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("on")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("timeout")
+                    .long("--timeout")
+                    .short("-t")
+                    .help(
+                        "sets a timeout in seconds, `0` for none; counts from startup and \
+                         covers parsing, pre-processing, solving and proof reconstruction",
+                    )
+                    .validator(int_validator)
+                    .value_name("int")
+                    .default_value("0")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("mem_limit")
+                    .long("--mem_limit")
+                    .help(
+                        "sets a limit on the resident memory of hoice in MB, `0` for none; \
+                         hoice answers `unknown` when it exceeds it (linux only)",
+                    )
+                    .validator(int_validator)
+                    .value_name("int")
+                    .default_value("0")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("split")
+                    .long("--split")
+                    .help("reason on each negative clause separately")
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("off")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("split_step")
+                    .long("--split_step")
+                    .help("pause between negative clauses in split mode")
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("off")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("split_par")
+                    .long("--split_par")
+                    .help("solve the sub-instances of split mode in parallel")
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("off")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("split_workers")
+                    .long("--split_workers")
+                    .help("number of workers used when `split_par` is active")
+                    .validator(|s| bounded_int_validator(s, 1, ::std::usize::MAX))
+                    .value_name("int")
+                    .default_value("2")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("split_reuse")
+                    .long("--split_reuse")
+                    .help(
+                        "in split mode, do not strengthen sub-instances with \
+                         the negative clauses solved by previous splits",
+                    )
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("on")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("split_pos")
+                    .long("--split_pos")
+                    .help("reason on each positive clause separately (linear instances only)")
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("off")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("split_timeout")
+                    .long("--split_timeout")
+                    .help(
+                        "sets a timeout in seconds for each split, `0` for none; \
+                         if a split times out the result is `unknown`",
+                    )
+                    .validator(int_validator)
+                    .value_name("int")
+                    .default_value("0")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("split_eta")
+                    .long("--split_eta")
+                    .help("print progress and estimated time remaining in split mode")
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("off")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("split_subprocess")
+                    .long("--split_subprocess")
+                    .help(
+                        "solve each split in a fresh hoice process, a split that crashes is \
+                         ignored like a split that times out",
+                    )
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("off")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("split_resume")
+                    .long("--split_resume")
+                    .help(
+                        "checkpoint file for split mode: splits already solved in this file are \
+                         skipped, and new solved splits are added to it",
+                    )
+                    .value_name("FILE")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("split_cache")
+                    .long("--split_cache")
+                    .help(
+                        "cache file for split mode: like `--split_resume`, but a split is only \
+                         skipped if its clause and the clauses it shares with the other splits \
+                         did not change, so the file can be reused after editing the instance",
+                    )
+                    .value_name("FILE")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .conflicts_with("split_resume")
                     .display_order(order()),
             )
             .arg(
-                Arg::with_name("quiet")
-                    .short("-q")
-                    .help("decreases verbosity")
-                    .takes_value(false)
-                    .multiple(true)
+                Arg::with_name("term_simpl")
+                    .long("--term_simpl")
+                    .help("level of term simplification between 0 and 3")
+                    .validator(int_validator)
+                    .value_name("int")
+                    .default_value("1")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order())
+                    .hidden(true),
+            )
+            .arg(
+                Arg::with_name("check_simpl")
+                    .long("--check_simpl")
+                    .help("if true, check all simplifications")
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("no")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order())
+                    .hidden(true),
+            )
+            .arg(
+                Arg::with_name("proof_recon_depth")
+                    .long("--proof_recon_depth")
+                    .help("maximum depth of the unsat proof reconstruction")
+                    .validator(int_validator)
+                    .value_name("int")
+                    .default_value("1000")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order())
+                    .hidden(true),
+            )
+            .arg(
+                Arg::with_name("proof_minimize")
+                    .long("--proof_minimize")
+                    .help("(de)activates the minimization of the entry points of unsat proofs")
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("no")
+                    .takes_value(true)
+                    .number_of_values(1)
                     .display_order(order()),
             )
             .arg(
-                Arg::with_name("color")
-                    .long("--color")
-                    .short("-c")
-                    .help("(de)activates coloring (off if output is not a tty)")
+                Arg::with_name("proof_lenient")
+                    .long("--proof_lenient")
+                    .help(
+                        "in unsat proofs, record dependencies to unknown positive samples as \
+                         unresolved entry points instead of failing, the proof is then partial",
+                    )
                     .validator(bool_validator)
                     .value_name(bool_format)
-                    .default_value("on")
+                    .default_value("no")
                     .takes_value(true)
                     .number_of_values(1)
                     .display_order(order()),
             )
             .arg(
-                Arg::with_name("out_dir")
-                    .long("--out_dir")
-                    .short("-o")
-                    .help("sets the output directory")
-                    .value_name("DIR")
-                    .default_value("hoice_out")
+                Arg::with_name("proof_certificate")
+                    .long("--proof_certificate")
+                    .help(
+                        "(de)activates writing unsat proofs as a sequence of ground resolution \
+                         steps from the entry points to the empty clause",
+                    )
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("no")
                     .takes_value(true)
                     .number_of_values(1)
                     .display_order(order()),
             )
             .arg(
-                Arg::with_name("stats")
-                    .long("--stats")
-                    .short("-s")
-                    .help("reports some statistics at the end of the run")
+                Arg::with_name("proof_trace")
+                    .long("--proof_trace")
+                    .help(
+                        "(de)activates writing, in comments before unsat proofs, the clause \
+                         applications leading from the entry points to the contradiction",
+                    )
                     .validator(bool_validator)
                     .value_name(bool_format)
                     .default_value("no")
@@ -1365,33 +3057,94 @@ impl Config {
                     .display_order(order()),
             )
             .arg(
-                Arg::with_name("infer")
-                    .long("--infer")
-                    .short("-i")
-                    .help("if `off`, ignore `check-sat` and `get-model` queries")
+                Arg::with_name("hint")
+                    .long("--hint")
+                    .help(
+                        "file containing candidate invariants for some of the predicates, \
+                         as `define-fun`s, checked before learning starts",
+                    )
+                    .value_name("FILE")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("model_format")
+                    .long("--model_format")
+                    .help(
+                        "format of the models: `define-fun`s, or Prolog/Datalog rules \
+                         (quantifier-free linear definitions only)",
+                    )
+                    .validator(model_format_validator)
+                    .value_name("smt2|datalog")
+                    .default_value("smt2")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("exit_codes")
+                    .long("--exit_codes")
+                    .help(
+                        "exit codes of the process, for the last `check-sat`: \
+                         `simple` exits with 0 whatever the result, \
+                         `smtcomp` exits with 10 on sat, 20 on unsat and 0 on unknown; \
+                         errors exit with 2 in both cases",
+                    )
+                    .validator(exit_codes_validator)
+                    .value_name("simple|smtcomp")
+                    .default_value("simple")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("model_let")
+                    .long("--model_let")
+                    .help("(de)activates sharing common subterms in models with let-bindings")
                     .validator(bool_validator)
                     .value_name(bool_format)
-                    .default_value("on")
+                    .default_value("off")
                     .takes_value(true)
                     .number_of_values(1)
                     .display_order(order()),
             )
             .arg(
-                Arg::with_name("timeout")
-                    .long("--timeout")
-                    .short("-t")
-                    .help("sets a timeout in seconds, `0` for none")
+                Arg::with_name("model_let_min")
+                    .long("--model_let_min")
+                    .help(
+                        "with `--model_let`, subterms are let-bound if they appear more than \
+                         this many times",
+                    )
                     .validator(int_validator)
                     .value_name("int")
-                    .default_value("0")
+                    .default_value("2")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order())
+                    .hidden(true),
+            )
+            .arg(
+                Arg::with_name("factor_model")
+                    .long("--factor_model")
+                    .help(
+                        "(de)activates factoring the conjuncts shared by the disjuncts of the \
+                         models, and absorption",
+                    )
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("off")
                     .takes_value(true)
                     .number_of_values(1)
                     .display_order(order()),
             )
             .arg(
-                Arg::with_name("split")
-                    .long("--split")
-                    .help("reason on each negative clause separately")
+                Arg::with_name("check_model")
+                    .long("--check_model")
+                    .help(
+                        "(de)activates checking the models against all the clauses of the \
+                         original instance, before preprocessing",
+                    )
                     .validator(bool_validator)
                     .value_name(bool_format)
                     .default_value("off")
@@ -1400,9 +3153,14 @@ impl Config {
                     .display_order(order()),
             )
             .arg(
-                Arg::with_name("split_step")
-                    .long("--split_step")
-                    .help("pause between negative clauses in split mode")
+                Arg::with_name("stream_parse")
+                    .long("--stream_parse")
+                    .help(
+                        "(de)activates streaming parsing: trivial clauses are dropped as \
+                         they are parsed, input buffers are released eagerly, and items \
+                         referring to identifiers declared later are delayed until the \
+                         declaration, useful on very large inputs",
+                    )
                     .validator(bool_validator)
                     .value_name(bool_format)
                     .default_value("off")
@@ -1411,28 +3169,107 @@ impl Config {
                     .display_order(order()),
             )
             .arg(
-                Arg::with_name("term_simpl")
-                    .long("--term_simpl")
-                    .help("level of term simplification between 0 and 3")
-                    .validator(int_validator)
-                    .value_name("int")
-                    .default_value("1")
+                Arg::with_name("inline_funs")
+                    .long("--inline_funs")
+                    .help(
+                        "(de)activates the inlining of non-recursive `define-fun`s \
+                         during parsing, instead of keeping them as functions",
+                    )
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("off")
                     .takes_value(true)
                     .number_of_values(1)
-                    .display_order(order())
-                    .hidden(true),
+                    .display_order(order()),
             )
             .arg(
-                Arg::with_name("check_simpl")
-                    .long("--check_simpl")
-                    .help("if true, check all simplifications")
+                Arg::with_name("horn")
+                    .long("--horn")
+                    .help(
+                        "(de)activates the Prolog-style input dialect: rules \
+                         `head :- body.` and goals `?- body.`, solved as if followed by \
+                         `(check-sat)` and `(get-model)`",
+                    )
                     .validator(bool_validator)
                     .value_name(bool_format)
-                    .default_value("no")
+                    .default_value("off")
                     .takes_value(true)
                     .number_of_values(1)
-                    .display_order(order())
-                    .hidden(true),
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("batch")
+                    .long("--batch")
+                    .help(
+                        "(de)activates batch mode: the input is a sequence of instances \
+                         separated by `(reset)`, `(push)` or `(pop)`, each instance is solved \
+                         separately with the declarations and assertions of the scopes it is \
+                         in",
+                    )
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("off")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("best_effort")
+                    .long("--best_effort")
+                    .help(
+                        "(de)activates printing the last candidates of the learners as an \
+                         unverified model, in comments, on timeout",
+                    )
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("off")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("soft_clauses")
+                    .long("--soft_clauses")
+                    .help(
+                        "when the instance is unsat, greedily drop the lightest soft clauses \
+                         (`(! <clause> :weight <n>)`) until it is sat, and print the model and \
+                         the violated clauses in comments",
+                    )
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("off")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("anytime")
+                    .long("--anytime")
+                    .help(
+                        "(de)activates printing the model of each split, in comments, as soon as \
+                         it is solved",
+                    )
+                    .validator(bool_validator)
+                    .value_name(bool_format)
+                    .default_value("off")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
+            )
+            .arg(
+                Arg::with_name("seed")
+                    .long("--seed")
+                    .help(
+                        "seed of all the random generators, two runs with the same seed make \
+                         the same decisions as long as a single learner runs (no \
+                         `--pure_synth`) and `--split_par` is off",
+                    )
+                    .validator(int_validator)
+                    .value_name("int")
+                    .default_value("0")
+                    .takes_value(true)
+                    .number_of_values(1)
+                    .display_order(order()),
             )
     }
 
@@ -1617,6 +3454,277 @@ pub fn bounded_int_validator(s: String, lo: usize, hi: usize) -> Result<(), Stri
     }
 }
 
+/// Counterexample selection strategies, see `--cex_strategy`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CexStrategy {
+    /// First model found by the solver.
+    First,
+    /// Model minimizing the sum of the absolute values of the arithmetic variables.
+    Smallest,
+    /// Model furthest away from the known samples.
+    Diverse,
+}
+impl CexStrategy {
+    /// Cex strategy of a string.
+    pub fn of_str(s: &str) -> Option<Self> {
+        match s {
+            "first" => Some(CexStrategy::First),
+            "smallest" => Some(CexStrategy::Smallest),
+            "diverse" => Some(CexStrategy::Diverse),
+            _ => None,
+        }
+    }
+}
+impl ::std::fmt::Display for CexStrategy {
+    fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match self {
+            CexStrategy::First => write!(fmt, "first"),
+            CexStrategy::Smallest => write!(fmt, "smallest"),
+            CexStrategy::Diverse => write!(fmt, "diverse"),
+        }
+    }
+}
+
+/// Tie-breaking strategies between qualifiers with the same gain, see `--dt_tiebreak`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DtTiebreak {
+    /// First qualifier considered.
+    First,
+    /// Qualifier with the lowest syntactic complexity.
+    Simplest,
+    /// Random qualifier.
+    Random,
+}
+impl DtTiebreak {
+    /// Tie-breaking strategy of a string.
+    pub fn of_str(s: &str) -> Option<Self> {
+        match s {
+            "first" => Some(DtTiebreak::First),
+            "simplest" => Some(DtTiebreak::Simplest),
+            "random" => Some(DtTiebreak::Random),
+            _ => None,
+        }
+    }
+}
+impl ::std::fmt::Display for DtTiebreak {
+    fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match self {
+            DtTiebreak::First => write!(fmt, "first"),
+            DtTiebreak::Simplest => write!(fmt, "simplest"),
+            DtTiebreak::Random => write!(fmt, "random"),
+        }
+    }
+}
+
+/// Model formats, see `--model_format`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModelFormat {
+    /// SMT-LIB 2 `define-fun`s.
+    Smt2,
+    /// Prolog/Datalog rules.
+    Datalog,
+}
+impl ModelFormat {
+    /// Model format of a string.
+    pub fn of_str(s: &str) -> Option<Self> {
+        match s {
+            "smt2" => Some(ModelFormat::Smt2),
+            "datalog" => Some(ModelFormat::Datalog),
+            _ => None,
+        }
+    }
+}
+impl ::std::fmt::Display for ModelFormat {
+    fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match self {
+            ModelFormat::Smt2 => write!(fmt, "smt2"),
+            ModelFormat::Datalog => write!(fmt, "datalog"),
+        }
+    }
+}
+
+/// Exit code conventions, see `--exit_codes`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExitCodes {
+    /// Exits with `0` whatever the result.
+    Simple,
+    /// Exits with `10` on sat, `20` on unsat and `0` on unknown.
+    SmtComp,
+}
+impl ExitCodes {
+    /// Exit codes of a string.
+    pub fn of_str(s: &str) -> Option<Self> {
+        match s {
+            "simple" => Some(ExitCodes::Simple),
+            "smtcomp" => Some(ExitCodes::SmtComp),
+            _ => None,
+        }
+    }
+
+    /// Exit code of a result: `Some(true)` for sat, `Some(false)` for unsat, `None` for unknown.
+    ///
+    /// Errors always exit with [`ExitCodes::ERROR`].
+    ///
+    /// [`ExitCodes::ERROR`]: #associatedconstant.ERROR (ERROR constant)
+    ///
+    /// ```
+    /// # use hoice::common::config::ExitCodes;
+    /// assert_eq! { ExitCodes::SmtComp.code(Some(true)), 10 }
+    /// assert_eq! { ExitCodes::SmtComp.code(Some(false)), 20 }
+    /// assert_eq! { ExitCodes::SmtComp.code(None), 0 }
+    /// assert_eq! { ExitCodes::Simple.code(Some(false)), 0 }
+    /// ```
+    pub fn code(self, result: Option<bool>) -> i32 {
+        match (self, result) {
+            (ExitCodes::Simple, _) => 0,
+            (ExitCodes::SmtComp, Some(true)) => 10,
+            (ExitCodes::SmtComp, Some(false)) => 20,
+            (ExitCodes::SmtComp, None) => 0,
+        }
+    }
+
+    /// Exit code on errors.
+    pub const ERROR: i32 = 2;
+}
+impl ::std::fmt::Display for ExitCodes {
+    fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match self {
+            ExitCodes::Simple => write!(fmt, "simple"),
+            ExitCodes::SmtComp => write!(fmt, "smtcomp"),
+        }
+    }
+}
+
+/// Validates exit codes input.
+#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
+pub fn exit_codes_validator(s: String) -> Result<(), String> {
+    if ExitCodes::of_str(&s).is_some() {
+        Ok(())
+    } else {
+        Err(format!("expected `simple` or `smtcomp`, got `{}`", s))
+    }
+}
+
+/// Validates model format input.
+#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
+pub fn model_format_validator(s: String) -> Result<(), String> {
+    if ModelFormat::of_str(&s).is_some() {
+        Ok(())
+    } else {
+        Err(format!("expected `smt2` or `datalog`, got `{}`", s))
+    }
+}
+
+/// Validates cex strategy input.
+#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
+pub fn cex_strategy_validator(s: String) -> Result<(), String> {
+    if CexStrategy::of_str(&s).is_some() {
+        Ok(())
+    } else {
+        Err(format!(
+            "expected `first`, `smallest` or `diverse`, got `{}`",
+            s
+        ))
+    }
+}
+
+/// Validates tie-breaking strategy input.
+#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
+pub fn dt_tiebreak_validator(s: String) -> Result<(), String> {
+    if DtTiebreak::of_str(&s).is_some() {
+        Ok(())
+    } else {
+        Err(format!(
+            "expected `first`, `simplest` or `random`, got `{}`",
+            s
+        ))
+    }
+}
+
+/// Parses a map from predicate names to depths: `<pred>=<int>,...` or `none`.
+///
+/// ```
+/// # use hoice::common::config::depth_map_of_str;
+/// let map = depth_map_of_str("inv=3, aux=0").unwrap();
+/// assert_eq! { map.get("inv"), Some(&3) }
+/// assert_eq! { map.get("aux"), Some(&0) }
+/// assert! { depth_map_of_str("none").unwrap().is_empty() }
+/// assert! { depth_map_of_str("inv").is_none() }
+/// ```
+pub fn depth_map_of_str(s: &str) -> Option<::std::collections::BTreeMap<String, usize>> {
+    let mut map = ::std::collections::BTreeMap::new();
+    if s.trim() == "none" {
+        return Some(map);
+    }
+    for binding in s.split(',') {
+        let mut split = binding.splitn(2, '=');
+        let pred = split.next()?.trim();
+        let depth = split.next()?.trim().parse::<usize>().ok()?;
+        if pred.is_empty() {
+            return None;
+        }
+        map.insert(pred.to_string(), depth);
+    }
+    Some(map)
+}
+
+/// Validates depth map input.
+#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
+pub fn depth_map_validator(s: String) -> Result<(), String> {
+    if depth_map_of_str(&s).is_some() {
+        Ok(())
+    } else {
+        Err(format!(
+            "expected `none` or a list `<pred>=<int>,...`, got `{}`",
+            s
+        ))
+    }
+}
+
+/// Parses a list of pre-processing pass names: `<pass>,...` or `none`.
+///
+/// Fails on names that are not in [`PASS_NAMES`].
+///
+/// [`PASS_NAMES`]: ../../preproc/constant.PASS_NAMES.html (PASS_NAMES constant)
+///
+/// ```
+/// # use hoice::common::config::pass_list_of_str;
+/// let passes = pass_list_of_str("one_rhs, cfg_red").unwrap();
+/// assert! { passes.contains("one_rhs") }
+/// assert! { passes.contains("cfg_red") }
+/// assert_eq! { passes.len(), 2 }
+/// assert! { pass_list_of_str("none").unwrap().is_empty() }
+/// assert! { pass_list_of_str("inlining").is_none() }
+/// ```
+pub fn pass_list_of_str(s: &str) -> Option<::std::collections::BTreeSet<String>> {
+    let mut set = ::std::collections::BTreeSet::new();
+    if s.trim() == "none" {
+        return Some(set);
+    }
+    for pass in s.split(',') {
+        let pass = pass.trim();
+        if !crate::preproc::PASS_NAMES.contains(&pass) {
+            return None;
+        }
+        set.insert(pass.to_string());
+    }
+    Some(set)
+}
+
+/// Validates pre-processing pass list input.
+#[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
+pub fn pass_list_validator(s: String) -> Result<(), String> {
+    if pass_list_of_str(&s).is_some() {
+        Ok(())
+    } else {
+        Err(format!(
+            "expected `none` or a list of pass names among {}, got `{}`",
+            crate::preproc::PASS_NAMES.join(", "),
+            s
+        ))
+    }
+}
+
 /// Validates boolean input.
 #[cfg_attr(feature = "cargo-clippy", allow(needless_pass_by_value))]
 pub fn bool_validator(s: String) -> Result<(), String> {
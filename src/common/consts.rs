@@ -17,6 +17,8 @@ pub mod err {
     pub static timeout_desc: &'static str = "timeout";
     /// Description for exit error(s).
     pub static exit_desc: &'static str = "exit";
+    /// Description for preemption error(s).
+    pub static preempted_desc: &'static str = "preempted";
 }
 
 /// Use this macro to declare keywords.
@@ -184,6 +186,8 @@ pub mod keywords {
                 get_proof ("get-proof", doc = "Get-proof keyword.")
 
                 reset ("reset", doc = "Reset keyword.")
+                push  ("push", doc = "Push keyword.")
+                pop   ("pop", doc = "Pop keyword.")
                 exit  ("exit", doc = "Exit keyword.")
             }
         }
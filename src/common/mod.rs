@@ -31,7 +31,7 @@ pub use crate::{
     errors::*,
     fun,
     fun::Fun,
-    instance::{Clause, Instance},
+    instance::{Clause, DepGraph, Instance, InstanceStats, InstanceView},
     term,
     term::{typ, Op, Quant, RTerm, TTerm, TTermSet, TTerms, Term, Typ},
     val,
@@ -44,6 +44,7 @@ mod wrappers;
 
 #[macro_use]
 pub mod macros;
+pub mod backend;
 pub mod config;
 
 #[macro_use]
@@ -57,13 +58,137 @@ pub use self::profiling::{CanPrint, Profiler};
 pub use self::wrappers::*;
 
 lazy_static! {
-    /// Configuration from clap.
-    pub static ref conf: Config = Config::clap();
+    /// Configuration from clap, the configuration of the threads [`with_conf`] does not override.
+    ///
+    /// [`with_conf`]: fn.with_conf.html (with_conf function)
+    static ref clap_conf: Config = Config::clap();
     static ref version_string: String = crate_version!().into();
     /// Version with revision info.
     pub static ref version: & 'static str = & version_string;
 }
 
+thread_local! {
+    /// Configuration overriding the one from clap in the current thread, see [`with_conf`].
+    ///
+    /// [`with_conf`]: fn.with_conf.html (with_conf function)
+    static thread_conf: ::std::cell::Cell<Option<&'static Config>> = ::std::cell::Cell::new(None);
+    /// Local deadline of the current thread, see [`with_deadline`].
+    ///
+    /// [`with_deadline`]: fn.with_deadline.html (with_deadline function)
+    static thread_deadline: ::std::cell::Cell<Option<::std::time::Instant>> =
+        ::std::cell::Cell::new(None);
+}
+
+/// The configuration, dereferences to [`current_conf`].
+///
+/// [`current_conf`]: fn.current_conf.html (current_conf function)
+pub struct Conf {
+    _private: (),
+}
+impl ::std::ops::Deref for Conf {
+    type Target = Config;
+    fn deref(&self) -> &Config {
+        current_conf()
+    }
+}
+
+/// The configuration of the current thread.
+///
+/// This is the configuration from clap, unless the thread runs under [`with_conf`].
+///
+/// [`with_conf`]: fn.with_conf.html (with_conf function)
+#[allow(non_upper_case_globals)]
+pub static conf: Conf = Conf { _private: () };
+
+/// The configuration of the current thread, see [`conf`].
+///
+/// [`conf`]: static.conf.html (conf static)
+pub fn current_conf() -> &'static Config {
+    thread_conf
+        .with(|current| current.get())
+        .unwrap_or_else(|| &*clap_conf)
+}
+
+/// Runs something with a given configuration instead of the one from clap.
+///
+/// The configuration applies to the current thread, and to the threads hoice spawns from it (the
+/// learners and the `--split_par` workers). It does not apply to the sub-processes of
+/// `--split_subprocess`, which parse their own command line. Global state other than the
+/// configuration, like the declared functions and datatypes, is still shared by all threads.
+///
+/// The configuration must live forever: `conf` hands out `&'static` references that hoice stores
+/// (in the solver wrappers, the learners, ...) and the threads it spawns outlive this call. A
+/// configuration leaked with `Box::leak` is **never freed**, so build each configuration once (in
+/// a `lazy_static` for instance) and reuse it across resolutions instead of leaking a new one per
+/// call.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hoice::common::*;
+/// let fast: &'static Config = Box::leak(Box::new(
+///     Config::of_args(&["--split", "off", "--seed", "7"]).unwrap()
+/// ));
+/// assert! { with_conf(fast, || !conf.split && conf.seed == 7) }
+/// assert! { with_conf(fast, || ::std::thread::spawn(|| conf.seed).join().unwrap()) != 7 }
+/// ```
+pub fn with_conf<T, F: FnOnce() -> T>(config: &'static Config, f: F) -> T {
+    /// Restores the previous configuration, even on panics.
+    struct Restore(Option<&'static Config>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0;
+            thread_conf.with(|current| current.set(previous))
+        }
+    }
+    let _restore = Restore(thread_conf.with(|current| current.replace(Some(config))));
+    f()
+}
+
+/// Runs something with a local deadline on top of the global timeout.
+///
+/// The deadline applies to the current thread and to the learners the teacher spawns from it:
+/// `conf.check_timeout()` fails and `conf.until_timeout()` is bounded accordingly. When deadlines
+/// are nested, the earliest one applies. Used for the sub-instances of `--split_timeout`.
+///
+/// # Examples
+///
+/// ```rust
+/// # use hoice::common::*;
+/// use std::time::{Duration, Instant};
+/// let past = Instant::now();
+/// ::std::thread::sleep(Duration::from_millis(1));
+/// assert! { with_deadline(Some(past), || conf.check_timeout().is_err()) }
+/// assert! { with_deadline(Some(past), || conf.until_timeout()) == Some(Duration::new(0, 0)) }
+/// assert! { with_deadline(None, || current_deadline()).is_none() }
+/// assert! { current_deadline().is_none() }
+/// ```
+pub fn with_deadline<T, F: FnOnce() -> T>(deadline: Option<::std::time::Instant>, f: F) -> T {
+    /// Restores the previous deadline, even on panics.
+    struct Restore(Option<::std::time::Instant>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            let previous = self.0;
+            thread_deadline.with(|current| current.set(previous))
+        }
+    }
+    let previous = current_deadline();
+    let deadline = match (previous, deadline) {
+        (Some(previous), Some(deadline)) => Some(::std::cmp::min(previous, deadline)),
+        (previous, None) => previous,
+        (None, deadline) => deadline,
+    };
+    let _restore = Restore(thread_deadline.with(|current| current.replace(deadline)));
+    f()
+}
+
+/// Local deadline of the current thread, see [`with_deadline`].
+///
+/// [`with_deadline`]: fn.with_deadline.html (with_deadline function)
+pub fn current_deadline() -> Option<::std::time::Instant> {
+    thread_deadline.with(|current| current.get())
+}
+
 // |===| Helpers.
 
 /// Provides a discard function that drops anything.
@@ -79,11 +204,18 @@ pub fn print_stats(_: &'static str, _: Profiler) {}
 /// Prints the stats if asked. Does nothing in bench mode.
 #[cfg(not(feature = "bench"))]
 pub fn print_stats(name: &str, profiler: Profiler) {
-    if conf.stats {
+    if conf.stats || conf.stats_json.is_some() {
         let others = profiler.drain_others();
-        println!();
-        profiler.print(name, "", &["data"]);
-        println!();
+        if let Some(file) = conf.stats_json.as_ref() {
+            if let Err(e) = profiling::write_json(file, name, &profiler) {
+                warn!("could not write statistics to `{}`: {}", file, e)
+            }
+        }
+        if conf.stats {
+            println!();
+            profiler.print(name, "", &["data"]);
+            println!();
+        }
         for (name, other) in others {
             print_stats(&name, other)
         }
@@ -126,6 +258,45 @@ pub fn mk_dir<P: AsRef<::std::path::Path>>(path: P) -> Res<()> {
     Ok(())
 }
 
+/// Resident memory of the process in MB, if available.
+///
+/// Reads `/proc/self/statm`, so only available on linux.
+pub fn resident_mb() -> Option<usize> {
+    let mut statm = String::new();
+    ::std::fs::File::open("/proc/self/statm")
+        .and_then(|mut file| file.read_to_string(&mut statm))
+        .ok()?;
+    let pages: usize = statm.split_whitespace().nth(1)?.parse().ok()?;
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    if page_size <= 0 {
+        return None;
+    }
+    Some(pages * page_size as usize / (1024 * 1024))
+}
+
+/// Creates a random generator from a salt and a seed.
+///
+/// Different salts yield independent generators for the same seed. All the random generators of
+/// hoice are created this way, from `conf.seed`, so that runs are reproducible.
+///
+/// # Examples
+///
+/// ```rust
+/// use rand::Rng;
+/// use hoice::common::seeded_rng;
+/// let (mut rng_1, mut rng_2) = (seeded_rng(42, 7), seeded_rng(42, 7));
+/// let (n_1, n_2): (u64, u64) = (rng_1.gen(), rng_2.gen());
+/// assert_eq! { n_1, n_2 }
+/// ```
+pub fn seeded_rng(salt: u8, seed: u64) -> Rng {
+    use rand::SeedableRng;
+    let mut bytes = [salt; 16];
+    for (byte, seed_byte) in bytes.iter_mut().zip(seed.to_le_bytes().iter()) {
+        *byte ^= *seed_byte
+    }
+    Rng::from_seed(bytes)
+}
+
 /// Compares two data metrics.
 ///
 /// Takes the amount of classified and unknown data from two data collections
@@ -278,6 +449,108 @@ pub type ConjModel = Vec<Vec<(PrdIdx, Vec<TTerms>)>>;
 /// A model of conjunctions, reference version.
 pub type ConjModelRef<'a> = &'a [Vec<(PrdIdx, Vec<TTerms>)>];
 
+/// Model alias type extension, evaluates the definitions of a model.
+///
+/// Definitions can mention the predicates defined before them, or the predicates they are
+/// mutually recursive with. Evaluation follows predicate applications up to a depth of
+/// `1000`.
+///
+/// # Examples
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate hoice;
+/// use hoice::{ common::*, var_vals };
+///
+/// # fn main() {
+/// let (p, q): (PrdIdx, PrdIdx) = (0.into(), 1.into());
+/// let v_0 = term::int_var(0);
+/// let mut conj = TTermSet::of_term(term::ge(v_0.clone(), term::int(0)));
+/// let succ = term::add(vec![v_0, term::int(1)]);
+/// conj.insert_pred_app(p, var_to::terms::new(vec![succ].into()));
+/// let model: Model = vec![
+///     (p, TTerms::of_term(None, term::le(term::int_var(0), term::int(7)))),
+///     (q, TTerms::conj(None, conj)),
+/// ];
+///
+/// assert_eq! { model.eval(q, &var_vals!( (int 6) )).unwrap(), val::bool(true) }
+/// assert_eq! { model.eval(q, &var_vals!( (int 7) )).unwrap(), val::bool(false) }
+/// assert_eq! { model.eval(q, &var_vals!( (int (-1)) )).unwrap(), val::bool(false) }
+/// assert! { model.eval(2.into(), &var_vals!( (int 0) )).is_err() }
+/// # }
+/// ```
+pub trait ModelExt {
+    /// Evaluates the definition of a predicate on some arguments.
+    ///
+    /// The result is a boolean value, unknown if some of the arguments the definition depends on
+    /// are. Fails if the predicate has no definition or if its definition is quantified.
+    fn eval(&self, pred: PrdIdx, args: &VarVals) -> Res<Val>;
+}
+impl ModelExt for Model {
+    fn eval(&self, pred: PrdIdx, args: &VarVals) -> Res<Val> {
+        let defs = |pred: PrdIdx| {
+            self.iter()
+                .find(|(p, _)| *p == pred)
+                .map(|(_, def)| ::std::slice::from_ref(def))
+        };
+        eval_pred_def(&defs, pred, args, 0).map(bool_val)
+    }
+}
+impl ModelExt for ConjModel {
+    fn eval(&self, pred: PrdIdx, args: &VarVals) -> Res<Val> {
+        let defs = |pred: PrdIdx| {
+            self.iter()
+                .flat_map(|defs| defs.iter())
+                .find(|(p, _)| *p == pred)
+                .map(|(_, def)| &def[..])
+        };
+        eval_pred_def(&defs, pred, args, 0).map(bool_val)
+    }
+}
+
+/// Boolean value, unknown if `None`.
+fn bool_val(b: Option<bool>) -> Val {
+    if let Some(b) = b {
+        val::bool(b)
+    } else {
+        val::none(typ::bool())
+    }
+}
+
+/// Evaluates the conjunction of the definitions of a predicate, see [`ModelExt`].
+///
+/// [`ModelExt`]: trait.ModelExt.html (ModelExt trait)
+fn eval_pred_def<'a, Defs>(
+    defs: &Defs,
+    pred: PrdIdx,
+    args: &VarVals,
+    depth: usize,
+) -> Res<Option<bool>>
+where
+    Defs: Fn(PrdIdx) -> Option<&'a [TTerms]>,
+{
+    if depth > 1000 {
+        bail!("predicate applications nested too deeply in the model")
+    }
+    let tterms = if let Some(tterms) = defs(pred) {
+        tterms
+    } else {
+        bail!("no definition for predicate #{} in the model", pred)
+    };
+    let mut res = Some(true);
+    for tterms in tterms {
+        let val = tterms.eval(args, &mut |pred, args| {
+            eval_pred_def(defs, pred, args, depth + 1)
+        })?;
+        match val {
+            Some(true) => (),
+            Some(false) => return Ok(Some(false)),
+            None => res = None,
+        }
+    }
+    Ok(res)
+}
+
 /// A model or unsat.
 #[derive(Clone, PartialEq, Eq)]
 pub enum MaybeModel<M: Clone + PartialEq + Eq> {
@@ -1,7 +1,7 @@
 //! Messages used in the framework.
 
-use std::cell::RefCell;
-use std::sync::mpsc::channel;
+use std::cell::{Cell, RefCell};
+use std::sync::mpsc::{channel, sync_channel, SendError, SyncSender};
 
 use crate::{
     common::{profiling::Profiler, *},
@@ -45,10 +45,17 @@ impl_fmt! {
   }
 }
 
+/// Qualifiers appearing in some candidates, with the number of samples they separated.
+pub type QualStats = PrdHMap<Vec<(Term, usize)>>;
+
 /// Kind of messages the teacher can receive.
 pub enum MsgKind {
     /// Some candidates, from learners.
-    Cands(Candidates),
+    ///
+    /// Also carries the number of data messages the learner received before learning them.
+    Cands(Candidates, usize),
+    /// Qualifiers of the next candidates, from learners. Only sent with `--dump_quals`.
+    Quals(QualStats),
     /// Some samples from the assistant.
     Samples(Box<AssData>),
     /// A message.
@@ -65,7 +72,7 @@ impl MsgKind {
     /// True if the message is a candidates message.
     pub fn is_candidates(&self) -> bool {
         match *self {
-            MsgKind::Cands(_) => true,
+            MsgKind::Cands(_, _) => true,
             _ => false,
         }
     }
@@ -79,11 +86,6 @@ impl MsgKind {
     }
 }
 
-impl From<Candidates> for MsgKind {
-    fn from(cands: Candidates) -> MsgKind {
-        MsgKind::Cands(cands)
-    }
-}
 impl From<AssData> for MsgKind {
     fn from(data: AssData) -> MsgKind {
         MsgKind::Samples(Box::new(data))
@@ -126,11 +128,19 @@ impl Msg {
     }
 
     /// Creates a candidates message.
-    pub fn cands(id: Id, cands: Candidates) -> Self {
+    pub fn cands(id: Id, cands: Candidates, round: usize) -> Self {
+        debug_assert! { id.is_learner() }
+        Msg {
+            id,
+            msg: MsgKind::Cands(cands, round),
+        }
+    }
+    /// Creates a qualifiers message.
+    pub fn quals(id: Id, quals: QualStats) -> Self {
         debug_assert! { id.is_learner() }
         Msg {
             id,
-            msg: MsgKind::Cands(cands),
+            msg: MsgKind::Quals(quals),
         }
     }
     /// Creates a samples message.
@@ -154,12 +164,44 @@ pub enum FromTeacher {
     Exit,
     /// Learning data.
     Data(Box<LrnData>),
+    /// Learning data, and a request to restart with some seed.
+    ///
+    /// The learner forgets its qualifiers and re-seeds its random generators, but keeps the data.
+    Restart(Box<LrnData>, u64),
 }
 impl FromTeacher {
     /// Channel from the teacher.
     pub fn channel() -> Channel<FromTeacher> {
         channel()
     }
+
+    /// Channel from the teacher to a learner, bounded with `--pipeline`.
+    pub fn learner_channel() -> (ToLearner, Receiver<FromTeacher>) {
+        if let Some(bound) = conf.teacher.pipeline {
+            let (sender, recver) = sync_channel(bound);
+            (ToLearner::Bounded(sender), recver)
+        } else {
+            let (sender, recver) = channel();
+            (ToLearner::Unbounded(sender), recver)
+        }
+    }
+}
+
+/// Sender from the teacher to a learner.
+pub enum ToLearner {
+    /// Sequential mode.
+    Unbounded(Sender<FromTeacher>),
+    /// Pipelined mode, sending blocks when the learner lags behind.
+    Bounded(SyncSender<FromTeacher>),
+}
+impl ToLearner {
+    /// Sends something to the learner.
+    pub fn send(&self, msg: FromTeacher) -> Result<(), SendError<FromTeacher>> {
+        match self {
+            ToLearner::Unbounded(sender) => sender.send(msg),
+            ToLearner::Bounded(sender) => sender.send(msg),
+        }
+    }
 }
 
 /// Bails saying `"disconnected from teacher"`.
@@ -179,6 +221,10 @@ pub struct MsgCore {
     sender: Sender<Msg>,
     /// Receives stuff from the teacher.
     recver: Receiver<FromTeacher>,
+    /// Number of data messages received.
+    rounds: Cell<usize>,
+    /// Data received while learning, with `--pipeline`.
+    pending: RefCell<Option<(LrnData, Option<u64>)>>,
     /// Profiler.
     pub _profiler: Profiler,
     /// Some profilers whoever is above the core can use.
@@ -192,6 +238,8 @@ impl MsgCore {
             id: Id::Learner(id),
             sender,
             recver,
+            rounds: Cell::new(0),
+            pending: RefCell::new(None),
             _profiler: Profiler::new(),
             _subs: RefCell::new(HashMap::new()),
         }
@@ -203,6 +251,8 @@ impl MsgCore {
             id: Id::Assistant,
             sender,
             recver,
+            rounds: Cell::new(0),
+            pending: RefCell::new(None),
             _profiler: Profiler::new(),
             _subs: RefCell::new(HashMap::new()),
         }
@@ -228,7 +278,20 @@ impl MsgCore {
 
     /// Sends some candidates.
     pub fn send_candidates(&self, candidates: Candidates) -> Res<()> {
-        if self.sender.send(Msg::cands(self.id, candidates)).is_ok() {
+        if self
+            .sender
+            .send(Msg::cands(self.id, candidates, self.rounds.get()))
+            .is_ok()
+        {
+            Ok(())
+        } else {
+            deco!()
+        }
+    }
+
+    /// Sends the qualifiers of the next candidates.
+    pub fn send_quals(&self, quals: QualStats) -> Res<()> {
+        if self.sender.send(Msg::quals(self.id, quals)).is_ok() {
             Ok(())
         } else {
             deco!()
@@ -299,24 +362,63 @@ impl MsgCore {
     }
 
     /// Exit if we have received an exit message.
+    ///
+    /// With `--pipeline`, new data preempts the learner: the data is kept for the next call to
+    /// [`recv`] and this function fails with [`ErrorKind::Preempted`].
+    ///
+    /// [`recv`]: #method.recv (recv method)
+    /// [`ErrorKind::Preempted`]: ../../errors/enum.ErrorKind.html#variant.Preempted
+    /// (Preempted variant of ErrorKind)
     #[inline]
     pub fn check_exit(&self) -> Res<()> {
         use std::sync::mpsc::TryRecvError::*;
-        match self.recver.try_recv() {
-            Ok(FromTeacher::Exit) => bail!(ErrorKind::Exit),
-            Ok(_) => bail!("received data while checking for exit, logic error"),
-            Err(Empty) => Ok(()),
-            Err(Disconnected) => deco!(),
+        let mut preempted = false;
+        loop {
+            match self.recver.try_recv() {
+                Ok(FromTeacher::Exit) => bail!(ErrorKind::Exit),
+                Ok(_) if conf.teacher.pipeline.is_none() => {
+                    bail!("received data while checking for exit, logic error")
+                }
+                Ok(FromTeacher::Data(data)) => {
+                    self.pend(*data, None);
+                    preempted = true
+                }
+                Ok(FromTeacher::Restart(data, seed)) => {
+                    self.pend(*data, Some(seed));
+                    preempted = true
+                }
+                Err(Empty) if preempted => bail!(ErrorKind::Preempted),
+                Err(Empty) => return Ok(()),
+                Err(Disconnected) => deco!(),
+            }
         }
     }
 
+    /// Stores some data received while learning.
+    ///
+    /// Only the latest data is kept, but a pending restart request is not forgotten.
+    fn pend(&self, data: LrnData, seed: Option<u64>) {
+        self.rounds.set(self.rounds.get() + 1);
+        let mut pending = self.pending.borrow_mut();
+        let seed = seed.or_else(|| pending.as_ref().and_then(|(_, seed)| *seed));
+        *pending = Some((data, seed))
+    }
+
     /// Receives some data from the teacher.
-    pub fn recv(&self) -> Res<LrnData> {
-        match self.recver.recv() {
+    ///
+    /// Also returns the seed to restart with, if the teacher asked for a restart.
+    pub fn recv(&self) -> Res<(LrnData, Option<u64>)> {
+        if let Some(pending) = self.pending.borrow_mut().take() {
+            return Ok(pending);
+        }
+        let res = match self.recver.recv() {
             Ok(FromTeacher::Exit) => bail!(ErrorKind::Exit),
-            Ok(FromTeacher::Data(data)) => Ok(*data),
+            Ok(FromTeacher::Data(data)) => (*data, None),
+            Ok(FromTeacher::Restart(data, seed)) => (*data, Some(seed)),
             Err(_) => deco!(),
-        }
+        };
+        self.rounds.set(self.rounds.get() + 1);
+        Ok(res)
     }
 }
 
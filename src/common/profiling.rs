@@ -224,6 +224,31 @@ impl CanPrint for Stats {
 /// - a duration representing the total runtime of this scope.
 pub type InstantMap = BTreeMap<Vec<&'static str>, (Option<Instant>, Duration)>;
 
+/// Maps scopes to
+///
+/// - the CPU time of the thread when the scope was last ticked, if it is currently active and
+///   `--stats_json` is on,
+/// - the total CPU time spent in this scope, and
+/// - the number of times the scope was ticked.
+pub type CpuMap = BTreeMap<Vec<&'static str>, (Option<Duration>, Duration, usize)>;
+
+/// CPU time of the current thread.
+///
+/// Does not include the time spent by the SMT solvers, which run in separate processes.
+#[cfg(not(feature = "bench"))]
+fn thread_cpu_time() -> Duration {
+    let mut time = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    let res = unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut time) };
+    if res == 0 {
+        Duration::new(time.tv_sec as u64, time.tv_nsec as u32)
+    } else {
+        Duration::from_secs(0)
+    }
+}
+
 // The following import is not used in bench mode.
 #[allow(unused_imports)]
 use std::cell::RefCell;
@@ -239,6 +264,8 @@ use std::cell::RefCell;
 pub struct Profiler {
     /// String-indexed durations.
     map: RefCell<InstantMap>,
+    /// String-indexed CPU times and tick counts.
+    cpu: RefCell<CpuMap>,
     /// Starting tick, for total time.
     start: Instant,
     /// Other statistics.
@@ -262,6 +289,7 @@ impl Profiler {
     pub fn new() -> Self {
         Profiler {
             map: RefCell::new(InstantMap::new()),
+            cpu: RefCell::new(CpuMap::new()),
             start: Instant::now(),
             stats: RefCell::new(Stats::new()),
             subs: RefCell::new(Vec::new()),
@@ -280,6 +308,7 @@ impl Profiler {
     #[cfg(not(feature = "bench"))]
     pub fn merge(&mut self, other: Self) {
         let map = other.map.into_inner();
+        let cpu = other.cpu.into_inner();
         let stats = other.stats.into_inner();
         let subs = other.subs.into_inner();
         for sub in subs {
@@ -292,6 +321,15 @@ impl Profiler {
                 .or_insert_with(|| (None, Duration::new(0, 0)))
                 .1 += duration
         }
+        for (scope, (_, duration, count)) in cpu {
+            let entry = self
+                .cpu
+                .get_mut()
+                .entry(scope)
+                .or_insert_with(|| (None, Duration::new(0, 0), 0));
+            entry.1 += duration;
+            entry.2 += count
+        }
         for (scope, val) in stats {
             *self.stats.get_mut().entry(scope).or_insert_with(|| 0) += val
         }
@@ -304,6 +342,7 @@ impl Profiler {
     #[cfg(not(feature = "bench"))]
     pub fn merge_set(&mut self, other: Self) {
         let map = other.map.into_inner();
+        let cpu = other.cpu.into_inner();
         let stats = other.stats.into_inner();
         let subs = other.subs.into_inner();
         for sub in subs {
@@ -316,6 +355,15 @@ impl Profiler {
                 .or_insert_with(|| (None, Duration::new(0, 0)))
                 .1 += duration
         }
+        for (scope, (_, duration, count)) in cpu {
+            let entry = self
+                .cpu
+                .get_mut()
+                .entry(scope)
+                .or_insert_with(|| (None, Duration::new(0, 0), 0));
+            entry.1 += duration;
+            entry.2 += count
+        }
         for (scope, val) in stats {
             *self.stats.get_mut().entry(scope).or_insert_with(|| 0) = val
         }
@@ -341,6 +389,15 @@ impl Profiler {
         if scope.is_empty() {
             panic!("Profile: can't use scope `total`")
         }
+        let mut cpu = self.cpu.borrow_mut();
+        let cpu_time = cpu
+            .entry(scope.clone())
+            .or_insert_with(|| (None, Duration::from_secs(0), 0));
+        // CPU times only appear in the JSON statistics, don't query the clock otherwise.
+        if conf.stats_json.is_some() {
+            cpu_time.0 = Some(thread_cpu_time())
+        }
+        cpu_time.2 += 1;
         let mut map = self.map.borrow_mut();
         let time = map
             .entry(scope)
@@ -357,6 +414,11 @@ impl Profiler {
         if scope.is_empty() {
             panic!("Profile: can't use scope `total`")
         }
+        if let Some(&mut (ref mut tick, ref mut sum, _)) = self.cpu.borrow_mut().get_mut(&scope) {
+            if let Some(tick) = tick.take() {
+                *sum += thread_cpu_time().checked_sub(tick).unwrap_or_default()
+            }
+        }
         let mut map = self.map.borrow_mut();
         if let Some(&mut (ref mut tick, ref mut sum)) = map.get_mut(&scope) {
             let mut instant = None;
@@ -422,6 +484,62 @@ impl Profiler {
         res
     }
 
+    /// Writes a profiler as a single-line JSON object.
+    ///
+    /// The object has the name of the profiler, its total wall-clock time, its phases (scopes)
+    /// with their wall-clock time, thread CPU time and number of runs, its metrics, and its
+    /// sub-profilers. Times are in seconds.
+    #[cfg(not(feature = "bench"))]
+    pub fn json<W: Write>(&self, w: &mut W, name: &str) -> IoRes<()> {
+        write!(w, "{{\"name\":")?;
+        json_string(w, name)?;
+        write!(
+            w,
+            ",\"wall\":{},\"phases\":[",
+            Instant::now().duration_since(self.start).to_str()
+        )?;
+        let cpu = self.cpu.borrow();
+        for (idx, (scope, &(_, wall))) in self.map.borrow().iter().enumerate() {
+            if idx > 0 {
+                write!(w, ",")?
+            }
+            write!(w, "{{\"scope\":[")?;
+            for (idx, segment) in scope.iter().enumerate() {
+                if idx > 0 {
+                    write!(w, ",")?
+                }
+                json_string(w, segment)?
+            }
+            let (cpu_time, count) = cpu
+                .get(scope)
+                .map(|&(_, cpu_time, count)| (cpu_time, count))
+                .unwrap_or_else(|| (Duration::from_secs(0), 0));
+            write!(
+                w,
+                "],\"wall\":{},\"cpu\":{},\"count\":{}}}",
+                wall.to_str(),
+                cpu_time.to_str(),
+                count
+            )?
+        }
+        write!(w, "],\"stats\":{{")?;
+        for (idx, (stat, count)) in self.stats.borrow().iter().enumerate() {
+            if idx > 0 {
+                write!(w, ",")?
+            }
+            json_string(w, stat)?;
+            write!(w, ":{}", count)?
+        }
+        write!(w, "}},\"subs\":[")?;
+        for (idx, (sub_name, sub)) in self.subs.borrow().iter().enumerate() {
+            if idx > 0 {
+                write!(w, ",")?
+            }
+            sub.json(w, sub_name)?
+        }
+        write!(w, "]}}")
+    }
+
     /// Consumes and prints a profiler.
     ///
     /// - `set_sum` is a slice of scopes which have no duration and will be set
@@ -456,3 +574,37 @@ impl Profiler {
         println!("; {}{}", pref, conf.emph("}"))
     }
 }
+
+/// Writes a string as a JSON string literal.
+#[cfg(not(feature = "bench"))]
+fn json_string<W: Write>(w: &mut W, s: &str) -> IoRes<()> {
+    write!(w, "{}", crate::data::json::escape(s))
+}
+
+#[cfg(not(feature = "bench"))]
+lazy_static! {
+    /// File the JSON statistics are written to, created on first use.
+    static ref json_file: ::std::sync::Mutex<Option<::std::fs::File>> =
+        ::std::sync::Mutex::new(None);
+}
+
+/// Appends the JSON statistics of a profiler to a file, see [`Profiler::json`].
+///
+/// The file is created (or truncated) the first time this function is called.
+///
+/// [`Profiler::json`]: struct.Profiler.html#method.json (json method on Profiler)
+#[cfg(not(feature = "bench"))]
+pub fn write_json(file: &str, name: &str, profiler: &Profiler) -> Res<()> {
+    let mut json = json_file.lock().map_err(corrupted_err)?;
+    if json.is_none() {
+        *json = Some(
+            ::std::fs::File::create(file)
+                .chain_err(|| format!("while creating `{}`", conf.emph(file)))?,
+        )
+    }
+    if let Some(json) = json.as_mut() {
+        profiler.json(json, name)?;
+        writeln!(json)?
+    }
+    Ok(())
+}
@@ -61,6 +61,250 @@ pub fn preproc_reset<P>(solver: &mut Solver<P>) -> Res<()> {
     preproc_init(solver)
 }
 
+/// Number of logged commands, and of lines of stderr, reported when a solver fails.
+const FAILURE_LOG_TAIL: usize = 10;
+
+lazy_static! {
+    /// Diagnostic files of the last solver spawned for each role, see [`wrap_solver_cmd`].
+    ///
+    /// [`wrap_solver_cmd`]: fn.wrap_solver_cmd.html (wrap_solver_cmd function)
+    static ref solver_diags: ::std::sync::Mutex<
+        BTreeMap<&'static str, ::std::path::PathBuf>
+    > = ::std::sync::Mutex::new(BTreeMap::new());
+    /// Number of solvers wrapped so far.
+    static ref solver_count: ::std::sync::atomic::AtomicUsize =
+        ::std::sync::atomic::AtomicUsize::new(0);
+    /// True if scripts can run from the diagnostic directory, which might be `noexec`.
+    static ref can_wrap: bool = probe_wrapper();
+}
+
+/// Directory of the diagnostic files of the solvers of this process.
+fn diag_dir() -> ::std::path::PathBuf {
+    let mut dir = ::std::env::temp_dir();
+    dir.push(format!("hoice_{}_solvers", ::std::process::id()));
+    dir
+}
+
+/// Writes an executable shell script.
+#[cfg(target_os = "linux")]
+fn write_script(path: &::std::path::Path, lines: &[String]) -> Res<()> {
+    use std::os::unix::fs::PermissionsExt;
+    {
+        let mut file = ::std::fs::File::create(path)
+            .chain_err(|| format!("while creating script `{}`", path.display()))?;
+        writeln!(file, "#!/bin/sh")?;
+        for line in lines {
+            writeln!(file, "{}", line)?
+        }
+    }
+    ::std::fs::set_permissions(path, ::std::fs::Permissions::from_mode(0o700))
+        .chain_err(|| format!("while making script `{}` executable", path.display()))?;
+    Ok(())
+}
+
+/// Checks that scripts can run from the diagnostic directory.
+#[cfg(target_os = "linux")]
+fn probe_wrapper() -> bool {
+    let mut path = diag_dir();
+    if mk_dir(&path).is_err() {
+        return false;
+    }
+    path.push("probe.sh");
+    let ok = write_script(&path, &["exit 0".into()]).is_ok()
+        && ::std::process::Command::new(&path)
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false);
+    let _ = ::std::fs::remove_file(&path);
+    ok
+}
+#[cfg(not(target_os = "linux"))]
+fn probe_wrapper() -> bool {
+    false
+}
+
+/// Makes a solver configuration spawn the solver through a wrapper script.
+///
+/// rsmt2 does not give access to the solver process. The wrapper writes its pid to a file and
+/// `exec`s the solver with the same arguments and its stderr redirected to another file, so that
+/// [`solver_failure`] can retrieve the exit status and the stderr of a solver that died. Fails
+/// if scripts cannot run from the temporary directory.
+///
+/// [`solver_failure`]: fn.solver_failure.html (solver_failure function)
+#[cfg(target_os = "linux")]
+pub fn wrap_solver_cmd(name: &'static str, smt_conf: &mut rsmt2::SmtConf) -> Res<()> {
+    use std::sync::atomic::Ordering;
+
+    if !*can_wrap {
+        bail!("cannot run solver wrappers from `{}`", diag_dir().display())
+    }
+
+    let quote =
+        |path: &::std::path::Path| format!("'{}'", path.to_string_lossy().replace('\'', "'\\''"));
+
+    let dir = diag_dir();
+    mk_dir(&dir)?;
+    let mut diag = dir;
+    diag.push(format!(
+        "{}_{}",
+        name,
+        solver_count.fetch_add(1, Ordering::SeqCst)
+    ));
+    let (script, pid, err) = (
+        diag.with_extension("sh"),
+        diag.with_extension("pid"),
+        diag.with_extension("err"),
+    );
+    let cmd = ::std::path::PathBuf::from(smt_conf.get_cmd().to_string());
+
+    write_script(
+        &script,
+        &[
+            "rm -f \"$0\"".into(),
+            format!("echo $$ > {}", quote(&pid)),
+            format!("exec {} \"$@\" 2> {}", quote(&cmd), quote(&err)),
+        ],
+    )?;
+
+    smt_conf.cmd(script.to_string_lossy().to_string());
+    solver_diags
+        .lock()
+        .map_err(|_| "[bug] lock on solver diagnostics is corrupted...")?
+        .insert(name, diag);
+    Ok(())
+}
+
+/// Removes the diagnostic files of the solvers of this process.
+pub fn clean_solver_diags() {
+    let _ = ::std::fs::remove_dir_all(diag_dir());
+}
+
+/// Exit status and stderr tail of the last solver spawned for a role, if it has exited.
+///
+/// Does not reap the solver process, which rsmt2 still owns.
+#[cfg(target_os = "linux")]
+fn solver_diag(name: &str) -> Option<(String, Vec<String>)> {
+    let diag = solver_diags.lock().ok()?.get(name).cloned()?;
+    let pid: libc::pid_t = ::std::fs::read_to_string(diag.with_extension("pid"))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()?;
+
+    let mut status = None;
+    // The solver might not be done exiting yet.
+    for _ in 0..10 {
+        let mut info: libc::siginfo_t = unsafe { ::std::mem::zeroed() };
+        let res = unsafe {
+            libc::waitid(
+                libc::P_PID,
+                pid as libc::id_t,
+                &mut info,
+                libc::WEXITED | libc::WNOHANG | libc::WNOWAIT,
+            )
+        };
+        if res != 0 {
+            break;
+        }
+        if unsafe { info.si_pid() } == pid {
+            let code = unsafe { info.si_status() };
+            status = Some(if info.si_code == libc::CLD_EXITED {
+                format!("exit code {}", code)
+            } else {
+                format!("killed by signal {}", code)
+            });
+            break;
+        }
+        ::std::thread::sleep(::std::time::Duration::from_millis(10))
+    }
+    let status = status?;
+
+    let stderr = ::std::fs::read_to_string(diag.with_extension("err"))
+        .map(|err| {
+            let lines: Vec<_> = err
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| line.to_string())
+                .collect();
+            let start = lines.len().saturating_sub(FAILURE_LOG_TAIL);
+            lines[start..].to_vec()
+        })
+        .unwrap_or_else(|_| Vec::new());
+    Some((status, stderr))
+}
+#[cfg(not(target_os = "linux"))]
+fn solver_diag(_: &str) -> Option<(String, Vec<String>)> {
+    None
+}
+
+/// Classifies an error coming from a solver as a crash, a timeout or a malformed answer.
+///
+/// Errors that do not come from the solver, and `unknown` results, are returned as is. A solver
+/// crashed if its process exited, or if hoice could not communicate with it. The resulting error
+/// mentions the clause the solver was working on, the exit status and the end of the stderr of
+/// the solver if it exited, and the last commands sent to the solver if they were logged with
+/// `--log_smt`.
+///
+/// The solver is the last one spawned for the role `solver`, which might not be the right one
+/// when several solvers with the same role run in parallel (`--split_par`).
+pub fn solver_failure(
+    err: Error,
+    solver: &'static str,
+    clause: Option<ClsIdx>,
+    instance: &Instance,
+) -> Error {
+    let is_solver_err = match err.kind() {
+        ErrorKind::SmtError(_) | ErrorKind::Io(_) => true,
+        _ => false,
+    };
+    if !is_solver_err || err.is_unknown() {
+        return err;
+    }
+
+    let msg = err.to_string();
+    let (status, stderr) = match solver_diag(solver) {
+        Some((status, stderr)) => (Some(status), stderr),
+        None => (None, vec![]),
+    };
+    let crashed = status.is_some()
+        || match err.kind() {
+            ErrorKind::Io(_) => true,
+            _ => false,
+        };
+
+    let last_commands = conf
+        .solver
+        .log_path(solver, instance)
+        .and_then(|path| ::std::fs::read_to_string(path).ok())
+        .map(|log| {
+            let lines: Vec<_> = log
+                .lines()
+                .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with(';'))
+                .map(|line| line.to_string())
+                .collect();
+            let start = lines.len().saturating_sub(FAILURE_LOG_TAIL);
+            lines[start..].to_vec()
+        })
+        .unwrap_or_else(Vec::new);
+
+    let info = SolverFailure {
+        solver,
+        clause,
+        msg,
+        status,
+        stderr,
+        last_commands,
+    };
+    let kind = if err.is_timeout() {
+        ErrorKind::SolverTimeout(info)
+    } else if crashed {
+        ErrorKind::SolverCrash(info)
+    } else {
+        ErrorKind::SolverOutput(info)
+    };
+    Error::with_chain(err, kind)
+}
+
 /// Performs a check-sat.
 pub fn tmo_multi_try_check_sat<P, F>(
     solver: &mut Solver<P>,
@@ -71,7 +315,8 @@ pub fn tmo_multi_try_check_sat<P, F>(
 where
     F: FnOnce(&mut Solver<P>) -> Res<()>,
 {
-    solver.set_option(":timeout", &format!("{}000", tmo.as_secs()))?;
+    let timeout_opt = conf.solver.backend().check_timeout_opt();
+    solver.set_option(timeout_opt, &format!("{}000", tmo.as_secs()))?;
     if let Some(res) = multi_try_check_sat_or_unk(solver)? {
         return Ok(res);
     }
@@ -82,7 +327,7 @@ where
         if let Some(res) = multi_try_check_sat_or_unk(solver)? {
             return Ok(res);
         }
-        solver.set_option(":timeout", "1000000000")?;
+        solver.set_option(timeout_opt, "1000000000")?;
         multi_try_check_sat(solver)
     }
 }
@@ -233,6 +478,40 @@ impl<'a> Expr2Smt<(&'a PrdSet, &'a PrdSet, &'a Preds)> for NegQClause<'a> {
     }
 }
 
+/// Smt-prints the disjunction of the negation of some clauses.
+///
+/// The clauses are not quantified, their variables must be declared beforehand. Used to check
+/// several clauses with a single check-sat: the disjunction is unsatisfiable iff none of the
+/// clauses is falsifiable. Predicate applications are written with the names of the predicates
+/// given in the info.
+pub struct NegClauses<'a> {
+    /// The instance.
+    pub instance: &'a Instance,
+    /// The clauses.
+    pub clauses: &'a [ClsIdx],
+}
+impl<'a> NegClauses<'a> {
+    /// Constructor.
+    pub fn new(instance: &'a Instance, clauses: &'a [ClsIdx]) -> Self {
+        NegClauses { instance, clauses }
+    }
+}
+impl<'a> Expr2Smt<(&'a PrdSet, &'a PrdSet, &'a Preds)> for NegClauses<'a> {
+    fn expr_to_smt2<Writer: Write>(
+        &self,
+        w: &mut Writer,
+        (true_preds, false_preds, preds): (&'a PrdSet, &'a PrdSet, &'a Preds),
+    ) -> SmtRes<()> {
+        write!(w, "(or")?;
+        for clause in self.clauses {
+            write!(w, "\n  ")?;
+            self.instance[*clause].expr_to_smt2(w, &(false, true_preds, false_preds, preds))?
+        }
+        write!(w, "\n)")?;
+        Ok(())
+    }
+}
+
 /// Smt-prints a clause that has no predicate application.
 pub struct SmtSideClause<'a> {
     /// The clause.
@@ -880,6 +1159,8 @@ impl<'a> ModelParser<FPVar, Typ, FPVal, &'a str> for FullParser {
         _params: &[(FPVar, Typ)],
         _out: &Typ,
     ) -> SmtRes<FPVal> {
+        let value = conf.solver.backend().model_value(input);
+        let input: &str = &value;
         let mut cxt = crate::parse::ParserCxt::new();
         let dummy_profiler = Profiler::new();
         let mut parser = cxt.parser(input, 0, &dummy_profiler);
@@ -24,6 +24,8 @@ pub struct Constraint {
     lhs: Option<PrdHMap<VarValsSet>>,
     /// Right-hand side.
     rhs: Option<Sample>,
+    /// Clause the constraint comes from.
+    clause: ClsIdx,
 }
 
 impl Constraint {
@@ -32,10 +34,11 @@ impl Constraint {
     /// None if the constraint is a tautology:
     ///
     /// - `lhs.is_empty` and `rhs.is_empty()`
-    pub fn new(lhs: PrdHMap<VarValsSet>, rhs: Option<Sample>) -> Constraint {
+    pub fn new(clause: ClsIdx, lhs: PrdHMap<VarValsSet>, rhs: Option<Sample>) -> Constraint {
         Constraint {
             lhs: Some(lhs),
             rhs,
+            clause,
         }
     }
 
@@ -71,7 +74,7 @@ impl Constraint {
                 bail!("partial arguments in constraint ({})", args)
             }
         }
-        if let Some(Sample { pred, ref args }) = self.rhs {
+        if let Some(Sample { pred, ref args, .. }) = self.rhs {
             if let Some(argss) = self.lhs.as_ref().and_then(|map| map.get(&pred)) {
                 if args.set_subsumed(argss) {
                     bail!("rhs is subsumed by lhs")
@@ -101,6 +104,10 @@ impl Constraint {
     pub fn rhs(&self) -> Option<&Sample> {
         self.rhs.as_ref()
     }
+    /// Clause the constraint comes from.
+    pub fn clause(&self) -> ClsIdx {
+        self.clause
+    }
 
     /// Removes samples subsumed by a sample from the lhs.
     ///
@@ -140,7 +147,7 @@ impl Constraint {
         F: FnMut(PrdIdx, VarVals) -> Res<()>,
     {
         let rhs = ::std::mem::replace(&mut self.rhs, None);
-        if let Some(Sample { pred, args }) = rhs {
+        if let Some(Sample { pred, args, .. }) = rhs {
             f(pred, args)?
         }
 
@@ -250,6 +257,7 @@ impl Constraint {
         let is_in_rhs = if let Some(Sample {
             pred: rhs_pred,
             args: ref mut rhs_args,
+            ..
         }) = self.rhs
         {
             rhs_pred == pred && args.subsumes(rhs_args)
@@ -354,7 +362,7 @@ impl Constraint {
             // Only reachable if there's one pred app in lhs.
             let (pred, argss) = old_lhs.unwrap().into_iter().next().unwrap();
             let args = argss.into_iter().next().unwrap();
-            Either::Left((Sample { pred, args }, false))
+            Either::Left((Sample::new(pred, args), false))
         } else {
             Either::Right(false)
         }
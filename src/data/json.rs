@@ -0,0 +1,225 @@
+//! Minimal JSON support for learning data files, see `--learn_only` and `--replay`.
+
+use crate::common::*;
+
+/// A JSON value.
+///
+/// Numbers and booleans are not supported, learning data files do not use them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Json {
+    /// Null.
+    Null,
+    /// A string.
+    Str(String),
+    /// A list.
+    List(Vec<Json>),
+    /// An object, fields are in the order they appear in.
+    Obj(Vec<(String, Json)>),
+}
+
+/// Escapes a string for JSON, quotes included.
+///
+/// Control characters other than newlines, tabulations and carriage returns are written as
+/// `\uXXXX` escapes.
+///
+/// # Examples
+///
+/// ```
+/// use hoice::data::json::{escape, Json};
+/// let s = "a \"quoted\"\tstring\\\r\n\u{1}";
+/// assert_eq! { escape(s), r#""a \"quoted\"\tstring\\\r\n\u0001""# }
+/// assert_eq! { Json::parse(&escape(s)).unwrap(), Json::Str(s.into()) }
+/// ```
+pub fn escape(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\t' => res.push_str("\\t"),
+            '\r' => res.push_str("\\r"),
+            c if c.is_control() => res.push_str(&format!("\\u{:04x}", c as u32)),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+impl Json {
+    /// Parses a JSON value.
+    ///
+    /// Fails if there is anything but whitespace after the value.
+    pub fn parse(s: &str) -> Res<Self> {
+        let mut parser = JsonParser {
+            chars: s.chars().collect(),
+            pos: 0,
+        };
+        let res = parser.value()?;
+        parser.ws();
+        if parser.pos < parser.chars.len() {
+            bail!("unexpected text after JSON value at column {}", parser.pos)
+        }
+        Ok(res)
+    }
+
+    /// Value of a field of an object.
+    pub fn field(&self, name: &str) -> Res<&Json> {
+        if let Json::Obj(fields) = self {
+            for (field, value) in fields {
+                if field == name {
+                    return Ok(value);
+                }
+            }
+            bail!("missing field `{}`", name)
+        } else {
+            bail!("expected JSON object")
+        }
+    }
+
+    /// String value.
+    pub fn as_str(&self) -> Res<&str> {
+        if let Json::Str(s) = self {
+            Ok(s)
+        } else {
+            bail!("expected JSON string")
+        }
+    }
+
+    /// List value.
+    pub fn as_list(&self) -> Res<&[Json]> {
+        if let Json::List(elems) = self {
+            Ok(elems)
+        } else {
+            bail!("expected JSON list")
+        }
+    }
+}
+
+/// Parser for JSON values.
+struct JsonParser {
+    /// Characters of the input.
+    chars: Vec<char>,
+    /// Current position.
+    pos: usize,
+}
+
+impl JsonParser {
+    /// Skips whitespace.
+    fn ws(&mut self) {
+        while self.pos < self.chars.len() && self.chars[self.pos].is_whitespace() {
+            self.pos += 1
+        }
+    }
+
+    /// Next character, skips whitespace first.
+    fn peek(&mut self) -> Option<char> {
+        self.ws();
+        self.chars.get(self.pos).cloned()
+    }
+
+    /// Parses a character, skips whitespace first.
+    fn tag(&mut self, c: char) -> Res<()> {
+        match self.peek() {
+            Some(next) if next == c => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(next) => bail!("expected `{}`, found `{}` at column {}", c, next, self.pos),
+            None => bail!("expected `{}`, found end of line", c),
+        }
+    }
+
+    /// Parses a value.
+    fn value(&mut self) -> Res<Json> {
+        match self.peek() {
+            Some('"') => self.string().map(Json::Str),
+            Some('[') => {
+                self.pos += 1;
+                let mut elems = vec![];
+                if self.peek() == Some(']') {
+                    self.pos += 1;
+                    return Ok(Json::List(elems));
+                }
+                loop {
+                    elems.push(self.value()?);
+                    if self.peek() == Some(',') {
+                        self.pos += 1
+                    } else {
+                        self.tag(']')?;
+                        return Ok(Json::List(elems));
+                    }
+                }
+            }
+            Some('{') => {
+                self.pos += 1;
+                let mut fields = vec![];
+                if self.peek() == Some('}') {
+                    self.pos += 1;
+                    return Ok(Json::Obj(fields));
+                }
+                loop {
+                    let field = self.string()?;
+                    self.tag(':')?;
+                    fields.push((field, self.value()?));
+                    if self.peek() == Some(',') {
+                        self.pos += 1
+                    } else {
+                        self.tag('}')?;
+                        return Ok(Json::Obj(fields));
+                    }
+                }
+            }
+            Some('n') if self.chars[self.pos..].starts_with(&['n', 'u', 'l', 'l']) => {
+                self.pos += 4;
+                Ok(Json::Null)
+            }
+            Some(c) => bail!("unexpected character `{}` at column {}", c, self.pos),
+            None => bail!("unexpected end of line"),
+        }
+    }
+
+    /// Parses a string.
+    fn string(&mut self) -> Res<String> {
+        self.tag('"')?;
+        let mut res = String::new();
+        loop {
+            let c = if let Some(c) = self.chars.get(self.pos).cloned() {
+                c
+            } else {
+                bail!("unterminated JSON string")
+            };
+            self.pos += 1;
+            match c {
+                '"' => return Ok(res),
+                '\\' => {
+                    let escaped = self.chars.get(self.pos).cloned();
+                    self.pos += 1;
+                    match escaped {
+                        Some('n') => res.push('\n'),
+                        Some('t') => res.push('\t'),
+                        Some('r') => res.push('\r'),
+                        Some('b') => res.push('\u{8}'),
+                        Some('f') => res.push('\u{c}'),
+                        Some('u') => {
+                            let code: String = self.chars.iter().skip(self.pos).take(4).collect();
+                            self.pos += 4;
+                            match u32::from_str_radix(&code, 16)
+                                .ok()
+                                .and_then(::std::char::from_u32)
+                            {
+                                Some(c) => res.push(c),
+                                None => bail!("illegal unicode escape `\\u{}`", code),
+                            }
+                        }
+                        Some(c) => res.push(c),
+                        None => bail!("unterminated JSON string"),
+                    }
+                }
+                c => res.push(c),
+            }
+        }
+    }
+}
@@ -15,8 +15,12 @@ use crate::{
     learning::ice::data::CData,
 };
 
+#[cfg(not(feature = "bench"))]
+use self::sample::Provenance;
+
 pub mod constraint;
 mod info;
+pub mod json;
 pub mod sample;
 
 pub use self::constraint::Constraint;
@@ -309,7 +313,7 @@ impl LrnData {
                 } else {
 
                     match data.constraints[constraint].try_trivial() {
-                        Either::Left((Sample { pred, args }, pos)) => {
+                        Either::Left((Sample { pred, args, .. }, pos)) => {
                             // Constraint is trivial: unlink and forget.
                             if let Some(set) = map[pred].get_mut(& args) {
                                 let was_there = set.remove(& constraint) ;
@@ -592,6 +596,9 @@ pub struct Data {
     _profiler: Profiler,
     /// Entry point tracker.
     entry_points: Option<crate::unsat_core::entry_points::EntryPoints>,
+    /// Provenance of the positive and negative samples, for debugging.
+    #[cfg(not(feature = "bench"))]
+    provenance: PrdMap<VarValsMap<Provenance>>,
 }
 
 impl Clone for Data {
@@ -611,6 +618,8 @@ impl Clone for Data {
             // graph: None,
             _profiler: Profiler::new(),
             entry_points: None,
+            #[cfg(not(feature = "bench"))]
+            provenance: self.provenance.clone(),
         }
     }
 }
@@ -639,6 +648,8 @@ impl Data {
             PrdMap::with_capacity(pred_count),
             PrdMap::with_capacity(pred_count),
         );
+        #[cfg(not(feature = "bench"))]
+        let mut provenance = PrdMap::with_capacity(pred_count);
 
         for _ in instance.preds() {
             map.push(VarValsMap::with_capacity(103));
@@ -646,6 +657,8 @@ impl Data {
             neg.push(VarValsSet::with_capacity(103));
             pos_single.push(VarValsSet::with_capacity(13));
             neg_single.push(VarValsSet::with_capacity(13));
+            #[cfg(not(feature = "bench"))]
+            provenance.push(VarValsMap::with_capacity(103));
         }
         // let track_samples = instance.track_samples() ;
 
@@ -668,9 +681,114 @@ impl Data {
             neg_single,
             _profiler: Profiler::new(),
             entry_points,
+            #[cfg(not(feature = "bench"))]
+            provenance,
         }
     }
 
+    /// Sample for some predicate and arguments, with its provenance if known.
+    ///
+    /// The provenance is only tracked for positive and negative samples, and not at all in bench
+    /// mode. See [`Sample::explain`].
+    ///
+    /// [`Sample::explain`]: sample/struct.Sample.html#method.explain (explain function)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[macro_use]
+    /// extern crate hoice;
+    /// use hoice::{ common::*, data::Data, var_vals };
+    /// fn main() {
+    ///     let instance = ::hoice::parse::mc_91();
+    ///     let p_0: PrdIdx = 0.into();
+    ///     let mut data = Data::new(Arc::new(instance.clone()));
+    ///     data.add_data(
+    ///         0.into(), vec![], Some((p_0, r_var_vals!((int 101) (int 91))))
+    ///     ).expect("while adding positive data");
+    ///     data.propagate().expect("during propagation");
+    ///     let sample = data.sample(p_0, &var_vals!((int 101) (int 91)));
+    ///     assert_eq! {
+    ///         sample.explain(&instance), "(mc91 101 91) from positive clause #0"
+    ///     }
+    /// }
+    /// ```
+    pub fn sample(&self, pred: PrdIdx, args: &VarVals) -> Sample {
+        #[cfg_attr(feature = "bench", allow(unused_mut))]
+        let mut sample = Sample::new(pred, args.clone());
+        if_not_bench! {
+            sample.provenance = self.provenance[pred].get(args).cloned()
+        }
+        sample
+    }
+
+    /// Explains where a sample comes from, see [`Sample::explain`].
+    ///
+    /// The provenance of the antecedents of implied samples is looked up in the data, so that the
+    /// whole derivation of the sample is explained.
+    ///
+    /// [`Sample::explain`]: sample/struct.Sample.html#method.explain (explain function)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[macro_use]
+    /// extern crate hoice;
+    /// use hoice::{ common::*, data::Data, var_vals };
+    /// fn main() {
+    ///     let instance = ::hoice::parse::mc_91();
+    ///     let p_0: PrdIdx = 0.into();
+    ///     let mut data = Data::new(Arc::new(instance.clone()));
+    ///     data.add_data(
+    ///         0.into(), vec![], Some((p_0, r_var_vals!((int 101) (int 91))))
+    ///     ).expect("while adding positive data");
+    ///     data.add_data(
+    ///         1.into(),
+    ///         vec![(p_0, r_var_vals!((int 101) (int 91)))],
+    ///         Some((p_0, r_var_vals!((int 90) (int 91)))),
+    ///     ).expect("while adding implication data");
+    ///     data.add_data(
+    ///         1.into(),
+    ///         vec![(p_0, r_var_vals!((int 90) (int 91)))],
+    ///         Some((p_0, r_var_vals!((int 80) (int 91)))),
+    ///     ).expect("while adding implication data");
+    ///     data.propagate().expect("during propagation");
+    ///     assert_eq! {
+    ///         data.explain(p_0, &var_vals!((int 80) (int 91))), "\
+    /// (mc91 80 91) implied by clause #1 from
+    ///   (mc91 90 91) implied by clause #1 from
+    ///     (mc91 101 91) from positive clause #0\
+    ///         "
+    ///     }
+    /// }
+    /// ```
+    pub fn explain(&self, pred: PrdIdx, args: &VarVals) -> String {
+        let sample = self.sample(pred, args);
+        if_not_bench! {
+            then {
+                sample.explain_with(&self.instance, |sample| {
+                    self.provenance[sample.pred].get(&sample.args).cloned()
+                })
+            } else {
+                sample.explain(&self.instance)
+            }
+        }
+    }
+
+    /// Registers the provenance of a sample, if it has one and none was known.
+    #[cfg(not(feature = "bench"))]
+    fn register_provenance(&mut self, sample: &Sample) {
+        if let Some(provenance) = sample.provenance.as_ref() {
+            self.provenance[sample.pred]
+                .entry(sample.args.clone())
+                .or_insert_with(|| provenance.clone());
+        }
+    }
+    /// Registers the provenance of a sample, if it has one and none was known.
+    #[cfg(feature = "bench")]
+    #[inline]
+    fn register_provenance(&mut self, _: &Sample) {}
+
     /// Accessor for the profiler.
     pub fn profiler(&self) -> &Profiler {
         &self._profiler
@@ -873,7 +991,7 @@ impl Data {
         scoped! {
           let constraint = & self.constraints[index] ;
           let similar = if let Some(
-            & Sample { pred, ref args }
+            & Sample { pred, ref args, .. }
           ) = constraint.rhs() {
             if let Some(similar) = self.map[pred].get(args) {
               similar
@@ -976,11 +1094,16 @@ impl Data {
 
     /// Adds a negative example.
     ///
-    /// The `clause` input is necessary for unsat core extraction.
+    /// The `clause` input is only used to track the provenance of the sample.
     ///
     /// Does not propagate.
-    fn add_raw_neg(&mut self, pred: PrdIdx, args: RVarVals) -> bool {
+    fn add_raw_neg(&mut self, _clause: ClsIdx, pred: PrdIdx, args: RVarVals) -> bool {
         let args = var_to::vals::new(args);
+        if_not_bench! {
+            self.register_provenance(
+                &Sample::new(pred, args.clone()).with_provenance(Provenance::Cex(_clause))
+            )
+        }
         self.add_neg(pred, args.clone())
     }
 
@@ -995,6 +1118,17 @@ impl Data {
             if let Some(e) = self.entry_points.as_mut() {
                 e.register(Sample::new(pred, args.clone()))
             }
+            if_not_bench! {
+                self.register_provenance(
+                    &Sample::new(pred, args.clone()).with_provenance(Provenance::Positive(clause))
+                )
+            }
+        } else {
+            if_not_bench! {
+                self.register_provenance(
+                    &Sample::new(pred, args.clone()).with_provenance(Provenance::Cex(clause))
+                )
+            }
         }
         self.add_pos_untracked(pred, args)
     }
@@ -1032,6 +1166,251 @@ impl Data {
         (pos, neg)
     }
 
+    /// Writes the data as JSON lines, see `--learn_only`.
+    ///
+    /// One JSON object per line: positive samples `{ "kind": "pos", "pred": <name>, "args":
+    /// [...] }`, negative samples (same with `"neg"`), and implication constraints `{ "kind":
+    /// "impl", "clause": <index>, "lhs": [<sample>, ...], "rhs": <sample> }` where `clause` is
+    /// the index of the clause the constraint comes from and `rhs` is `null` for negative
+    /// constraints. Arguments are values in SMT-LIB 2 format, `null` when any value works.
+    /// Tautological constraints are ignored.
+    pub fn write_json_lines<W: Write>(&self, w: &mut W) -> Res<()> {
+        use self::json::escape;
+
+        /// JSON representation of some arguments.
+        fn args_to_json(args: &VarVals) -> String {
+            let args: Vec<_> = args
+                .iter()
+                .map(|val| {
+                    if val.is_known() {
+                        escape(&val.to_string())
+                    } else {
+                        "null".into()
+                    }
+                })
+                .collect();
+            format!("[{}]", args.join(", "))
+        }
+
+        let sample_to_json = |pred: PrdIdx, args: &VarVals| {
+            format!(
+                "{{ \"pred\": {}, \"args\": {} }}",
+                escape(&self.instance[pred].name),
+                args_to_json(args)
+            )
+        };
+
+        for (kind, samples) in &[("pos", &self.pos), ("neg", &self.neg)] {
+            for (pred, argss) in samples.index_iter() {
+                for args in argss {
+                    writeln!(
+                        w,
+                        "{{ \"kind\": \"{}\", \"pred\": {}, \"args\": {} }}",
+                        kind,
+                        escape(&self.instance[pred].name),
+                        args_to_json(args)
+                    )?
+                }
+            }
+        }
+
+        for constraint in &self.constraints {
+            let lhs = if let Some(lhs) = constraint.lhs() {
+                lhs
+            } else {
+                continue;
+            };
+            let mut preds: Vec<_> = lhs.keys().cloned().collect();
+            preds.sort();
+            let mut lhs_json = vec![];
+            for pred in preds {
+                for args in &lhs[&pred] {
+                    lhs_json.push(sample_to_json(pred, args))
+                }
+            }
+            let rhs = constraint
+                .rhs()
+                .map(|sample| sample_to_json(sample.pred, &sample.args))
+                .unwrap_or_else(|| "null".into());
+            writeln!(
+                w,
+                "{{ \"kind\": \"impl\", \"clause\": \"{}\", \"lhs\": [{}], \"rhs\": {} }}",
+                constraint.clause(),
+                lhs_json.join(", "),
+                rhs
+            )?
+        }
+
+        Ok(())
+    }
+
+    /// Reads some learning data written by [`write_json_lines`], see `--replay`.
+    ///
+    /// The predicates and their signatures must be the ones of the instance of the data: samples
+    /// mentioning unknown predicates, or with the wrong number or type of arguments, are errors.
+    /// Propagates the data once everything is added.
+    ///
+    /// [`write_json_lines`]: #method.write_json_lines (write_json_lines function)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::{ common::*, data::Data, parse };
+    /// let instance = parse::instance("
+    ///   (declare-fun p ( Int Bool ) Bool)
+    ///   (assert (forall ( (n Int) ) (=> (> n 0) (p n true))))
+    ///   (assert (forall ( (n Int) (b Bool) ) (=> (and (p n b) (p (+ n 1) b)) false)))
+    /// ");
+    /// let mut data = Data::new(Arc::new(instance));
+    /// data.read_json_lines(r#"
+    /// { "kind": "pos", "pred": "p", "args": ["1", "true"] }
+    /// { "kind": "neg", "pred": "p", "args": ["(- 3)", null] }
+    /// { "kind": "impl", "lhs": [{ "pred": "p", "args": ["7", "false"] }], "rhs": null }
+    /// { "kind": "impl", "clause": "1", "lhs": [
+    ///     { "pred": "p", "args": ["8", "false"] }, { "pred": "p", "args": ["9", "false"] }
+    /// ], "rhs": null }
+    /// "#).unwrap();
+    /// // The first implication is a negative sample.
+    /// assert_eq! { data.pos_neg_count(), (1, 2) }
+    /// assert_eq! { data.constraints.len(), 1 }
+    ///
+    /// let mut buf: Vec<u8> = vec![];
+    /// data.write_json_lines(&mut buf).unwrap();
+    /// let written = String::from_utf8(buf).unwrap();
+    /// assert_eq! { written.lines().count(), 4 }
+    /// assert! { written.contains(r#"{ "kind": "neg", "pred": "p", "args": ["(- 3)", null] }"#) }
+    /// assert! { written.contains(r#"{ "kind": "impl", "clause": "1", "lhs": [{ "pred": "p""#) }
+    ///
+    /// let mut replayed = Data::new(data.instance.clone());
+    /// replayed.read_json_lines(&written).unwrap();
+    /// assert_eq! { replayed.pos_neg_count(), (1, 2) }
+    /// assert_eq! { replayed.constraints.len(), 1 }
+    ///
+    /// let mut data = Data::new(data.instance.clone());
+    /// assert! { data.read_json_lines(r#"{ "kind": "pos", "pred": "q", "args": [] }"#).is_err() }
+    /// assert! { data.read_json_lines(r#"{ "kind": "pos", "pred": "p", "args": ["1"] }"#).is_err() }
+    /// assert! {
+    ///     data.read_json_lines(r#"{ "kind": "pos", "pred": "p", "args": ["1", "2"] }"#).is_err()
+    /// }
+    /// assert! {
+    ///     data.read_json_lines(
+    ///         r#"{ "kind": "impl", "clause": "7", "lhs": [{ "pred": "p", "args": ["1", "true"] }], "rhs": null }"#
+    ///     ).is_err()
+    /// }
+    /// ```
+    pub fn read_json_lines(&mut self, content: &str) -> Res<()> {
+        use self::json::Json;
+
+        // Clause the implication constraints without a clause index are attributed to.
+        let default_clause = self
+            .instance
+            .clauses()
+            .index_iter()
+            .find(|(_, clause)| !clause.lhs_preds().is_empty())
+            .map(|(idx, _)| idx);
+
+        for (line_idx, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let res: Res<()> = Json::parse(line).and_then(|json| {
+                match json.field("kind")?.as_str()? {
+                    "pos" => {
+                        let (pred, args) = self.sample_of_json(&json)?;
+                        let _ = self.add_pos_untracked(pred, var_to::vals::new(args));
+                    }
+                    "neg" => {
+                        let (pred, args) = self.sample_of_json(&json)?;
+                        let _ = self.add_neg_untracked(pred, var_to::vals::new(args));
+                    }
+                    "impl" => {
+                        let mut lhs = vec![];
+                        for sample in json.field("lhs")?.as_list()? {
+                            lhs.push(self.sample_of_json(sample)?)
+                        }
+                        let rhs = match json.field("rhs")? {
+                            Json::Null => None,
+                            sample => Some(self.sample_of_json(sample)?),
+                        };
+                        let clause = match json.field("clause") {
+                            Ok(clause) => {
+                                let clause = clause.as_str()?;
+                                match clause.parse::<usize>() {
+                                    Ok(idx) if idx < self.instance.clauses().len() => idx.into(),
+                                    _ => bail!("illegal clause index `{}`", clause),
+                                }
+                            }
+                            Err(_) => {
+                                if let Some(clause) = default_clause {
+                                    clause
+                                } else {
+                                    bail!("the instance has no implication or negative clauses")
+                                }
+                            }
+                        };
+                        let _ = self.add_cstr(clause, lhs, rhs)?;
+                    }
+                    kind => bail!("unknown kind `{}`, expected `pos`, `neg` or `impl`", kind),
+                }
+                Ok(())
+            });
+            res.chain_err(|| format!("on line {}", line_idx + 1))?
+        }
+
+        let _ = self.propagate()?;
+        Ok(())
+    }
+
+    /// Sample of a JSON object `{ "pred": <name>, "args": [...] }`, checked against the instance.
+    fn sample_of_json(&self, json: &self::json::Json) -> Res<(PrdIdx, RVarVals)> {
+        use self::json::Json;
+
+        let name = json.field("pred")?.as_str()?;
+        let pred = if let Some(pred) = self.instance.preds().iter().find(|pred| pred.name == name) {
+            pred
+        } else {
+            bail!("unknown predicate `{}`", name)
+        };
+        if pred.is_defined() {
+            bail!("predicate `{}` is defined in this instance", name)
+        }
+
+        let args = json.field("args")?.as_list()?;
+        if args.len() != pred.sig.len() {
+            bail!(
+                "predicate `{}` has {} argument(s), found {}",
+                name,
+                pred.sig.len(),
+                args.len()
+            )
+        }
+
+        let mut sample = VarMap::with_capacity(args.len());
+        for ((var, typ), arg) in pred.sig.index_iter().zip(args.iter()) {
+            let val = match arg {
+                Json::Null => val::none(typ.clone()),
+                arg => {
+                    let s = arg.as_str()?;
+                    let val = crate::parse::term_opt(s, &VarInfos::new(), &self.instance)?
+                        .and_then(|term| term.val());
+                    match val {
+                        Some(val) if val.typ() == *typ => val,
+                        _ => bail!(
+                            "illegal value `{}` for argument {} of `{}`, expected a value of type {}",
+                            s,
+                            var.default_str(),
+                            name,
+                            typ
+                        ),
+                    }
+                }
+            };
+            sample.push(val)
+        }
+        Ok((pred.idx, sample))
+    }
+
     /// Shrinks the list of constraints.
     ///
     /// - pops all trailing empty constraints from [`self.constraints`][cstrs].
@@ -1250,10 +1629,6 @@ impl Data {
 
         let (mut pos_cnt, mut neg_cnt) = (0, 0);
 
-        // This is used to remember new constraints from this propagation phase, to
-        // check for useless constraints after propagation is over.
-        let mut modded_constraints = CstrSet::new();
-
         'propagate: while let Some((pred, mut argss, pos)) = self.staged.pop() {
             macro_rules! single_target_set {
                 () => {
@@ -1323,69 +1698,7 @@ impl Data {
                 }
 
                 if let Some(constraints) = self.remove_subs(pred, &args) {
-                    profile! { self tick "propagate", "cstr update" }
-                    for constraint_idx in constraints {
-                        macro_rules! constraint {
-                            () => {
-                                self.constraints[constraint_idx]
-                            };
-                        }
-
-                        let tautology = {
-                            let map = &mut self.map;
-                            let constraint = &mut constraint!();
-                            constraint
-                                .force_sample(pred, &args, pos, |pred, args| {
-                                    Self::tauto_fun(map, constraint_idx, pred, &args)
-                                })
-                                .chain_err(|| "in propagate")?
-                        };
-
-                        if tautology {
-                            // Tautology, discard.
-                            self.cstr_info.forget(constraint_idx)
-                        } else {
-                            if pos {
-                                self.register_lhs_constraint_simpl(constraint_idx, pred, &args)?
-                            }
-
-                            match constraint!().try_trivial() {
-                                Either::Left((Sample { pred, args }, pos)) => {
-                                    // Constraint is trivial: unlink and forget.
-                                    if let Some(set) = self.map[pred].get_mut(&args) {
-                                        let was_there = set.remove(&constraint_idx);
-                                        debug_assert! { was_there }
-                                    }
-                                    self.cstr_info.forget(constraint_idx);
-                                    // Stage the consequence of the triviality.
-                                    self.staged.add(pred, args, pos);
-                                }
-                                Either::Right(false) => {
-                                    // Otherwise, the constraint was modified and we're keeping
-                                    // it.
-                                    self.cstr_info
-                                        .register_modded(constraint_idx, &constraint!())?;
-                                    modded_constraints.insert(constraint_idx);
-                                }
-                                Either::Right(true) => {
-                                    self.cstr_info.forget(constraint_idx);
-                                    debug_assert! { pos }
-                                    let is_new = self.add_neg(pred, args);
-                                    debug_assert! { is_new }
-                                    unsat!("by `true => false` in constraint (data, propagate)")
-                                }
-                            }
-                        }
-                    }
-                    profile! { self mark "propagate", "cstr update" }
-
-                    for constraint in modded_constraints.drain() {
-                        if !self.constraints[constraint].is_tautology()
-                            && !self.cstr_useful(constraint).chain_err(|| "in propagate")?
-                        {
-                            self.tautologize(constraint)?
-                        }
-                    }
+                    self.force_in_constraints(pred, &args, pos, constraints)?
                 }
             }
         }
@@ -1401,6 +1714,181 @@ impl Data {
         Ok((pos_cnt, neg_cnt))
     }
 
+    /// Updates the constraints mentioning a sample that is now known to be positive/negative.
+    ///
+    /// The sample must have been removed from [`self.map`][map] already, `constraints` are the
+    /// constraints it was linked to. Trivial constraints are forgotten and their consequence is
+    /// staged, modified constraints go through the usefulness check.
+    ///
+    /// [map]: #structfield.map (map field)
+    fn force_in_constraints(
+        &mut self,
+        pred: PrdIdx,
+        args: &VarVals,
+        pos: bool,
+        constraints: CstrSet,
+    ) -> Res<()> {
+        // This is used to remember the constraints modified, to check for useless constraints
+        // after the update is over.
+        let mut modded_constraints = CstrSet::new();
+
+        profile! { self tick "propagate", "cstr update" }
+        for constraint_idx in constraints {
+            macro_rules! constraint {
+                () => {
+                    self.constraints[constraint_idx]
+                };
+            }
+
+            let tautology = {
+                let map = &mut self.map;
+                let constraint = &mut constraint!();
+                constraint
+                    .force_sample(pred, args, pos, |pred, args| {
+                        Self::tauto_fun(map, constraint_idx, pred, &args)
+                    })
+                    .chain_err(|| "in force_in_constraints")?
+            };
+
+            if tautology {
+                // Tautology, discard.
+                self.cstr_info.forget(constraint_idx)
+            } else {
+                if pos {
+                    self.register_lhs_constraint_simpl(constraint_idx, pred, args)?
+                }
+
+                match constraint!().try_trivial() {
+                    Either::Left((sample, pos)) => {
+                        self.register_provenance(&sample);
+                        let Sample { pred, args, .. } = sample;
+                        // Constraint is trivial: unlink and forget.
+                        if let Some(set) = self.map[pred].get_mut(&args) {
+                            let was_there = set.remove(&constraint_idx);
+                            debug_assert! { was_there }
+                        }
+                        self.cstr_info.forget(constraint_idx);
+                        // Stage the consequence of the triviality.
+                        self.staged.add(pred, args, pos);
+                    }
+                    Either::Right(false) => {
+                        // Otherwise, the constraint was modified and we're keeping
+                        // it.
+                        self.cstr_info
+                            .register_modded(constraint_idx, &constraint!())?;
+                        modded_constraints.insert(constraint_idx);
+                    }
+                    Either::Right(true) => {
+                        self.cstr_info.forget(constraint_idx);
+                        debug_assert! { pos }
+                        let is_new = self.add_neg(pred, args.clone());
+                        debug_assert! { is_new }
+                        unsat!("by `true => false` in constraint (data, force_in_constraints)")
+                    }
+                }
+            }
+        }
+        profile! { self mark "propagate", "cstr update" }
+
+        for constraint in modded_constraints.drain() {
+            if !self.constraints[constraint].is_tautology()
+                && !self
+                    .cstr_useful(constraint)
+                    .chain_err(|| "in force_in_constraints")?
+            {
+                self.tautologize(constraint)?
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Compacts the learning data.
+    ///
+    /// Propagation keeps the data mostly compact, this is a more thorough (and more expensive)
+    /// pass meant to run periodically. It
+    ///
+    /// - propagates all staged samples, which turns constraints with only positive samples in
+    ///   their lhs into positive samples;
+    /// - forces the samples of the constraints that are subsumed by a positive or negative
+    ///   sample, in case propagation missed them;
+    /// - drops the constraints subsumed by another constraint.
+    ///
+    /// The data is equivalent before and after compaction, only smaller. Returns the number of
+    /// constraints removed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[macro_use]
+    /// extern crate hoice;
+    /// use hoice::{ common::*, data::Data, var_vals };
+    /// fn main() {
+    ///     let instance = ::hoice::parse::mc_91();
+    ///     let p_0: PrdIdx = 0.into();
+    ///     let mut data = Data::new(Arc::new(instance));
+    ///     data.add_data(
+    ///         1.into(), vec![(p_0, r_var_vals!((int 1) (int 101)))],
+    ///         Some((p_0, r_var_vals!((int 7) (int 3))))
+    ///     ).expect("while adding constraint");
+    ///     data.add_data(
+    ///         0.into(), vec![], Some((p_0, r_var_vals!((int 1) (int 101))))
+    ///     ).expect("while adding positive data");
+    ///
+    ///     // The only constraint becomes a positive sample.
+    ///     let removed = data.compact().expect("during compaction");
+    ///     assert_eq! { removed, 1 }
+    ///     assert! { data.pos[p_0].contains(&var_vals!((int 7) (int 3))) }
+    ///     assert_eq! { data.metrics(), (2, 0, 0) }
+    /// }
+    /// ```
+    pub fn compact(&mut self) -> Res<usize> {
+        profile! { self tick "compaction" }
+        let live = |data: &Self| {
+            data.constraints
+                .iter()
+                .filter(|constraint| !constraint.is_tautology())
+                .count()
+        };
+        let before = live(self);
+
+        self.propagate()?;
+
+        let mut forced = vec![];
+        for (pred, map) in self.map.index_iter() {
+            for args in map.keys() {
+                if args.set_subsumed(&self.pos[pred]) {
+                    forced.push((pred, args.clone(), true))
+                } else if args.set_subsumed(&self.neg[pred]) {
+                    forced.push((pred, args.clone(), false))
+                }
+            }
+        }
+        for (pred, args, pos) in forced {
+            // Might have been removed while forcing a previous sample.
+            if let Some(constraints) = self.map[pred].remove(&args) {
+                self.force_in_constraints(pred, &args, pos, constraints)?
+            }
+        }
+        self.propagate()?;
+
+        for constraint in 0..self.constraints.len() {
+            let constraint: CstrIdx = constraint.into();
+            if !self.constraints[constraint].is_tautology()
+                && !self.cstr_useful(constraint).chain_err(|| "in compact")?
+            {
+                self.tautologize(constraint)?
+            }
+        }
+        self.shrink_constraints();
+        self.check("after compaction")?;
+
+        let removed = before - live(self);
+        profile! { self "constraints removed by compaction" => add removed }
+        profile! { self mark "compaction" }
+        Ok(removed)
+    }
+
     /// Length of positive/negative samples and constraints.
     pub fn metrics(&self) -> (usize, usize, usize) {
         (
@@ -1432,7 +1920,7 @@ impl Data {
                 }
             }
         }
-        if let Some(&Sample { pred, ref args }) = constraint.rhs() {
+        if let Some(&Sample { pred, ref args, .. }) = constraint.rhs() {
             let is_new = self.map[pred]
                 .entry(args.clone())
                 .or_insert_with(|| CstrSet::with_capacity(17))
@@ -1532,7 +2020,7 @@ impl Data {
                     false
                 };
                 if add_as_neg {
-                    self.add_raw_neg(pred, sample.clone());
+                    self.add_raw_neg(clause, pred, sample.clone());
                 }
                 if lhs.is_empty() {
                     // Positive sample.
@@ -1549,7 +2037,7 @@ impl Data {
                     // Negative sample.
                     let (pred, sample) = lhs.pop().expect("failed pop on vector of length 1");
                     debug_assert_eq! { lhs.len(), 0 }
-                    let new = self.add_raw_neg(pred, sample);
+                    let new = self.add_raw_neg(clause, pred, sample);
                     return Ok(new);
                 } else {
                     // Constraint.
@@ -1604,16 +2092,22 @@ impl Data {
                 Some(args)
             };
 
-            args.map(|args| Sample { pred, args })
+            args.map(|args| Sample::new(pred, args))
         } else {
             None
         };
 
         let mut nu_lhs = PrdHMap::with_capacity(lhs.len());
+        // Samples for the lhs of the clause, provenance of the rhs.
+        #[cfg(not(feature = "bench"))]
+        let mut ante = Vec::with_capacity(lhs.len());
 
         // Look at the lhs and remove stuff we know is true.
         'lhs_iter: while let Some((pred, args)) = lhs.pop() {
             let (args, is_new) = var_to::vals::new_is_new(args);
+            if_not_bench! {
+                ante.push(Sample::new(pred, args.clone()))
+            }
 
             // If no partial examples and sample is new, no need to check anything.
             if conf.teacher.partial || !is_new {
@@ -1643,8 +2137,21 @@ impl Data {
             ()
         }
 
-        if let Some(Sample { pred, args }) = nu_rhs.as_ref() {
+        let nu_rhs = nu_rhs.map(|rhs| {
+            if_not_bench! {
+                then {
+                    rhs.with_provenance(Provenance::Implied { from: clause, ante })
+                } else {
+                    rhs
+                }
+            }
+        });
+
+        if let Some(rhs) = nu_rhs.as_ref() {
+            let Sample { pred, args, .. } = rhs;
             if nu_lhs.is_empty() {
+                // Register the provenance first, `add_pos` would consider this a cex.
+                self.register_provenance(rhs);
                 self.add_pos(clause, *pred, args.clone());
             } else if let Some(argss) = nu_lhs.get(pred) {
                 // Subsumed by lhs?
@@ -1709,7 +2216,7 @@ impl Data {
         let (pos, neg) = self.propagate()?;
         let nu_stuff = pos != 0 || neg != 0;
 
-        let mut constraint = Constraint::new(nu_lhs, nu_rhs);
+        let mut constraint = Constraint::new(clause, nu_lhs, nu_rhs);
         constraint.check().chain_err(|| {
             format!(
                 "while checking {}",
@@ -1721,7 +2228,9 @@ impl Data {
         profile! { self mark "add cstr", "pre-checks" }
 
         match constraint.try_trivial() {
-            Either::Left((Sample { pred, args }, pos)) => {
+            Either::Left((sample, pos)) => {
+                self.register_provenance(&sample);
+                let Sample { pred, args, .. } = sample;
                 let is_new = self.staged.add(pred, args, pos);
                 Ok(nu_stuff || is_new)
             }
@@ -1787,6 +2296,7 @@ impl Data {
                 if let Some(&Sample {
                     pred: $pred,
                     args: ref $sample,
+                    ..
                 }) = $cstr.rhs()
                 {
                     $body
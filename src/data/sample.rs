@@ -1,19 +1,184 @@
 use crate::common::{var_to::vals::VarValsSet, *};
 
+/// Provenance of a sample: the clause (and samples) that introduced it.
+///
+/// Used for debugging only, not tracked in bench mode.
+#[derive(Clone, Debug)]
+pub enum Provenance {
+    /// Sample comes from a positive clause.
+    Positive(ClsIdx),
+    /// Sample is implied by a clause, given some antecedent samples.
+    Implied {
+        /// Clause the sample is implied by.
+        from: ClsIdx,
+        /// Samples for the predicate applications in the LHS of the clause.
+        ante: Vec<Sample>,
+    },
+    /// Sample comes from a counterexample for a clause.
+    Cex(ClsIdx),
+}
+
 /// A sample is some values for a predicate.
-#[derive(Clone, PartialEq, Eq, Hash, Debug, PartialOrd, Ord)]
+///
+/// Equality, comparison and hashing only consider the predicate and the arguments, the
+/// [`Provenance`] of the sample (if any) is ignored.
+///
+/// [`Provenance`]: enum.Provenance.html (Provenance enum)
+#[derive(Clone, Debug)]
 pub struct Sample {
     /// Predicate the sample is for.
     pub pred: PrdIdx,
     /// Arguments.
     pub args: VarVals,
+    /// Provenance of the sample, if known.
+    #[cfg(not(feature = "bench"))]
+    pub provenance: Option<Provenance>,
+}
+impl PartialEq for Sample {
+    fn eq(&self, other: &Self) -> bool {
+        self.pred == other.pred && self.args == other.args
+    }
+}
+impl Eq for Sample {}
+impl ::std::hash::Hash for Sample {
+    fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
+        self.pred.hash(state);
+        self.args.hash(state)
+    }
+}
+impl PartialOrd for Sample {
+    fn partial_cmp(&self, other: &Self) -> Option<::std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Sample {
+    fn cmp(&self, other: &Self) -> ::std::cmp::Ordering {
+        self.pred
+            .cmp(&other.pred)
+            .then_with(|| self.args.cmp(&other.args))
+    }
 }
 impl Sample {
     /// Constructor.
+    #[cfg(not(feature = "bench"))]
+    pub fn new(pred: PrdIdx, args: VarVals) -> Self {
+        Sample {
+            pred,
+            args,
+            provenance: None,
+        }
+    }
+    /// Constructor.
+    #[cfg(feature = "bench")]
     pub fn new(pred: PrdIdx, args: VarVals) -> Self {
         Sample { pred, args }
     }
 
+    /// Sets the provenance of a sample.
+    #[cfg(not(feature = "bench"))]
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    /// Explains where a sample comes from.
+    ///
+    /// Antecedents of implied samples are explained recursively, as far as they carry their
+    /// provenance. Use [`Data::explain`] to explain a sample of some data, the antecedents of
+    /// which usually do not. In bench mode, provenance is not tracked and this function only
+    /// prints the sample, followed by ` (provenance is not tracked in bench mode)`.
+    ///
+    /// [`Data::explain`]: ../struct.Data.html#method.explain (explain method on Data)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[macro_use]
+    /// extern crate hoice;
+    /// use hoice::{ common::*, data::sample::{Provenance, Sample}, var_vals };
+    ///
+    /// # fn main() {
+    /// let instance = ::hoice::parse::mc_91();
+    /// let p_0: PrdIdx = 0.into();
+    /// let pos = Sample::new(p_0, var_vals!( (int 101) (int 91) ))
+    ///     .with_provenance(Provenance::Positive(0.into()));
+    /// let implied = Sample::new(p_0, var_vals!( (int 7) (int 91) ))
+    ///     .with_provenance(Provenance::Implied { from: 1.into(), ante: vec![pos] });
+    /// assert_eq! {
+    ///     implied.explain(&instance), "\
+    /// (mc91 7 91) implied by clause #1 from
+    ///   (mc91 101 91) from positive clause #0\
+    ///     "
+    /// }
+    /// # }
+    /// ```
+    pub fn explain(&self, instance: &Instance) -> String {
+        self.explain_with(instance, |_| None)
+    }
+
+    /// Explains where a sample comes from, looking up the provenance of samples lacking one.
+    ///
+    /// `provenance_of` is used for the samples (the sample itself and the antecedents of implied
+    /// samples) that do not carry their provenance, so that whole derivations are explained. A
+    /// sample appearing twice is only explained the first time. See also [`Data::explain`].
+    ///
+    /// [`Data::explain`]: ../struct.Data.html#method.explain (explain method on Data)
+    pub fn explain_with<F>(&self, instance: &Instance, provenance_of: F) -> String
+    where
+        F: Fn(&Sample) -> Option<Provenance>,
+    {
+        let mut s = String::new();
+        let mut seen = ::std::collections::BTreeSet::new();
+        self.write_explanation(instance, "", &provenance_of, &mut seen, &mut s);
+        s
+    }
+
+    /// Writes the explanation for a sample with some indentation.
+    fn write_explanation<F>(
+        &self,
+        instance: &Instance,
+        pref: &str,
+        provenance_of: &F,
+        seen: &mut ::std::collections::BTreeSet<Sample>,
+        s: &mut String,
+    ) where
+        F: Fn(&Sample) -> Option<Provenance>,
+    {
+        *s += &format!("{}({} {})", pref, instance[self.pred], self.args);
+        if !seen.insert(self.clone()) {
+            *s += " (see above)";
+            return;
+        }
+        if_not_bench! {
+            then {
+                let provenance = self.provenance.clone().or_else(|| provenance_of(self));
+                match provenance {
+                    Some(Provenance::Positive(clause)) => {
+                        *s += &format!(" from positive clause #{}", clause)
+                    }
+                    Some(Provenance::Cex(clause)) => {
+                        *s += &format!(" from a counterexample for clause #{}", clause)
+                    }
+                    Some(Provenance::Implied { from, ref ante }) => {
+                        *s += &format!(" implied by clause #{}", from);
+                        if !ante.is_empty() {
+                            *s += " from";
+                            let pref = format!("{}  ", pref);
+                            for sample in ante {
+                                *s += "\n";
+                                sample.write_explanation(instance, &pref, provenance_of, seen, s)
+                            }
+                        }
+                    }
+                    None => *s += " (unknown provenance)",
+                }
+            } else {
+                let _ = provenance_of;
+                *s += " (provenance is not tracked in bench mode)"
+            }
+        }
+    }
+
     /// Tests if a sample is about some predicate and its arguments is subsumed
     /// by one of the elements of a set.
     pub fn set_subsumed(&self, pred: PrdIdx, samples: &VarValsSet) -> bool {
@@ -545,6 +545,22 @@ pub fn create_list_dtyp() {
     ()
 }
 
+/// Forgets all the datatypes, constructors and selectors, see `--batch`.
+pub fn clear() {
+    factory
+        .write()
+        .expect("failed to access dtyp factory (write)")
+        .clear();
+    constructor_map
+        .write()
+        .expect("failed to access constructor map (write)")
+        .clear();
+    selector_set
+        .write()
+        .expect("failed to access selector set (write)")
+        .clear()
+}
+
 /// True if there is at least one datatype declared.
 pub fn one_or_more() -> Res<bool> {
     if let Ok(f) = factory.read() {
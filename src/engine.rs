@@ -0,0 +1,310 @@
+//! Incremental and one-shot solving through the library API.
+//!
+//! An [`Engine`] stores an instance and the last model found for it. Clauses can be added to the
+//! instance after it was solved, and solving it again seeds the teacher with the previous model
+//! as hints (see `--hint`). The hints for the predicates appearing in the new clauses are dropped,
+//! since there is no reason for them to be relevant anymore.
+//!
+//! ```rust,no_run
+//! use hoice::engine::Engine;
+//! let mut engine = Engine::new();
+//! engine.add_clauses("\
+//!     (declare-fun inv (Int) Bool)
+//!     (assert (forall ((n Int)) (=> (= n 0) (inv n))))
+//!     (assert (forall ((n Int)) (=> (and (inv n) (< n 10)) (inv (+ n 1)))))
+//! ").expect("while parsing the base instance");
+//! assert_eq! { engine.resolve().expect("while solving the base instance"), Some(true) }
+//!
+//! engine.add_clauses("\
+//!     (assert (forall ((n Int)) (=> (and (inv n) (< n 0)) false)))
+//! ").expect("while parsing the new clauses");
+//! assert_eq! { engine.resolve().expect("while solving again"), Some(true) }
+//! ```
+//!
+//! One-shot resolutions go through [`solve`] (or [`solve_str`]), which returns a [`SolveOutcome`]
+//! and prints nothing unless the configuration asks for it (`--stats`, `-v`, ...). The
+//! configuration is the one from the command line, [`with_conf`] runs a resolution with another
+//! one.
+//!
+//! ```rust,no_run
+//! use hoice::engine::{solve_str, SolveOutcome};
+//! let outcome = solve_str("\
+//!     (declare-fun inv (Int) Bool)
+//!     (assert (forall ((n Int)) (=> (= n 0) (inv n))))
+//!     (assert (forall ((n Int)) (=> (inv n) (inv (+ n 2)))))
+//!     (assert (forall ((n Int)) (=> (and (inv n) (= n 1)) false)))
+//! ").expect("while solving");
+//! if let SolveOutcome::Sat(model, solved) = outcome {
+//!     let mut buf: Vec<u8> = vec![];
+//!     solved.write_model(&model, &mut buf).expect("while writing the model");
+//!     assert! { String::from_utf8_lossy(&buf).contains("define-fun inv") }
+//! } else {
+//!     panic!("expected sat")
+//! }
+//! ```
+//!
+//! [`Engine`]: struct.Engine.html (Engine struct)
+//! [`solve`]: fn.solve.html (solve function)
+//! [`solve_str`]: fn.solve_str.html (solve_str function)
+//! [`SolveOutcome`]: enum.SolveOutcome.html (SolveOutcome enum)
+//! [`with_conf`]: ../common/fn.with_conf.html (with_conf function)
+
+use crate::{
+    common::*,
+    parse::{Parsed, ParserCxt},
+    preproc::PreprocPass,
+    unsat_core::UnsatRes,
+};
+
+/// Result of a resolution, see [`solve`].
+///
+/// [`solve`]: fn.solve.html (solve function)
+pub enum SolveOutcome {
+    /// Sat, with a model expressed on the original signature of the predicates, and the
+    /// pre-processed instance to write it with (see [`Instance::write_model`]).
+    ///
+    /// [`Instance::write_model`]: ../common/struct.Instance.html#method.write_model
+    /// (write_model method)
+    Sat(ConjModel, Instance),
+    /// Unsat, with a proof if the instance asked for one.
+    Unsat(UnsatRes),
+    /// Unknown.
+    Unknown,
+}
+
+/// Pre-processes and solves an instance.
+///
+/// Fails on errors and timeouts. The resolution uses the configuration of the current thread,
+/// which is the one from the command line unless the call runs under [`with_conf`]:
+///
+/// ```rust,no_run
+/// use hoice::{ common::*, engine::{solve_str, SolveOutcome} };
+/// let config: &'static Config = Box::leak(Box::new(
+///     Config::of_args(&["--timeout", "10"]).expect("while building the configuration")
+/// ));
+/// let outcome = with_conf(config, || solve_str("\
+///     (declare-fun inv (Int) Bool)
+///     (assert (forall ((n Int)) (=> (= n 0) (inv n))))
+///     (assert (forall ((n Int)) (=> (and (inv n) (= n 1)) false)))
+/// ")).expect("while solving");
+/// if let SolveOutcome::Sat(_, _) = outcome { () } else { panic!("expected sat") }
+/// ```
+///
+/// [`with_conf`]: ../common/fn.with_conf.html (with_conf function)
+pub fn solve(instance: Instance) -> Res<SolveOutcome> {
+    let profiler = Profiler::new();
+    match solve_with(instance, &mut vec![], &profiler) {
+        Ok(Some(Either::Left((model, solved)))) => Ok(SolveOutcome::Sat(model, solved)),
+        Ok(Some(Either::Right(unsat))) => Ok(SolveOutcome::Unsat(unsat)),
+        Ok(None) => Ok(SolveOutcome::Unknown),
+        Err(ref e) if e.is_unsat() => Ok(SolveOutcome::Unsat(UnsatRes::None)),
+        Err(ref e) if e.is_unknown() => Ok(SolveOutcome::Unknown),
+        Err(e) => Err(e),
+    }
+}
+
+/// Parses and solves an instance, see [`solve`].
+///
+/// The input is a list of declarations and clauses, everything after the first `check-sat` is
+/// ignored. Fails on any other query. Like [`solve`], uses the configuration of the current
+/// thread, see [`with_conf`].
+///
+/// [`solve`]: fn.solve.html (solve function)
+/// [`with_conf`]: ../common/fn.with_conf.html (with_conf function)
+pub fn solve_str(input: &str) -> Res<SolveOutcome> {
+    let mut instance = Instance::new();
+    let res = ParserCxt::new()
+        .parser(input, 0, &Profiler::new())
+        .parse(&mut instance)?;
+    match res {
+        Parsed::Items | Parsed::Eof | Parsed::CheckSat => (),
+        res => bail!("expected declarations and clauses, found {}", res),
+    }
+    solve(instance)
+}
+
+/// Pre-processes and solves an instance, with some additional pre-processing passes.
+///
+/// On sat, also returns the pre-processed instance. Uses the configuration of the current thread.
+fn solve_with(
+    mut instance: Instance,
+    passes: &mut Vec<Box<dyn PreprocPass>>,
+    profiler: &Profiler,
+) -> Res<Option<Either<(ConjModel, Instance), UnsatRes>>> {
+    let preproc_profiler = Profiler::new();
+    let res = crate::preproc::work_with(&mut instance, &preproc_profiler, passes);
+    profiler.add_sub("preproc", preproc_profiler);
+    match res {
+        Ok(()) => (),
+        Err(ref e) if e.is_unsat() => {
+            return Ok(Some(Either::Right(UnsatRes::of_error(e, &instance))))
+        }
+        Err(e) => bail!(e),
+    }
+
+    if let Some(maybe_model) = instance.is_trivial_conj()? {
+        return Ok(match maybe_model {
+            MaybeModel::Model(model) => Some(Either::Left((model, instance))),
+            MaybeModel::Unsat => Some(Either::Right(UnsatRes::None)),
+        });
+    }
+
+    let arc_instance = Arc::new(instance);
+    let res = crate::split::work(&arc_instance, profiler);
+    let instance =
+        crate::unwrap_arc(arc_instance).chain_err(|| "while trying to recover instance")?;
+    match res? {
+        Some(Either::Left(candidates)) => {
+            let model = instance.extend_model(candidates)?;
+            Ok(Some(Either::Left((model, instance))))
+        }
+        Some(Either::Right(res)) => Ok(Some(Either::Right(res))),
+        None => Ok(None),
+    }
+}
+
+/// Solves an instance incrementally.
+pub struct Engine {
+    /// The instance, as parsed.
+    instance: Instance,
+    /// Parser context, remembers the declaration of the instance.
+    parser_cxt: ParserCxt,
+    /// Last model found, expressed on the original signature of the predicates.
+    model: Option<ConjModel>,
+    /// Pre-processed instance the last model was found on.
+    solved: Option<Instance>,
+    /// Hints for the next resolution, from the last model.
+    hints: PrdHMap<Vec<Term>>,
+    /// Additional pre-processing passes.
+    passes: Vec<Box<dyn PreprocPass>>,
+    /// Profiler.
+    profiler: Profiler,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Engine {
+    /// Constructor, for an empty instance.
+    pub fn new() -> Self {
+        Engine {
+            instance: Instance::new(),
+            parser_cxt: ParserCxt::new(),
+            model: None,
+            solved: None,
+            hints: PrdHMap::new(),
+            passes: vec![],
+            profiler: Profiler::new(),
+        }
+    }
+
+    /// Adds a pre-processing pass, see [`preproc::work_with`].
+    ///
+    /// The pass runs during the top pre-processing of all subsequent resolutions, not on the
+    /// sub-instances created by `--split`.
+    ///
+    /// [`preproc::work_with`]: ../preproc/fn.work_with.html (work_with function)
+    pub fn add_pass(&mut self, pass: Box<dyn PreprocPass>) {
+        self.passes.push(pass)
+    }
+
+    /// Last model found, if any.
+    ///
+    /// Invalidated by [`add_clauses`](#method.add_clauses).
+    pub fn model(&self) -> Option<&ConjModel> {
+        self.model.as_ref()
+    }
+
+    /// Writes the last model found, if any, in SMT-LIB 2.
+    pub fn write_model<W: Write>(&self, w: &mut W) -> Res<()> {
+        if let (Some(model), Some(solved)) = (self.model.as_ref(), self.solved.as_ref()) {
+            solved.write_model(model, w)
+        } else {
+            bail!("no model available")
+        }
+    }
+
+    /// Adds some items to the instance: declarations and clauses.
+    ///
+    /// Fails on queries (`check-sat`, `get-model`, ...), use [`resolve`](#method.resolve) instead.
+    /// Drops the hints for all the predicates appearing in the new clauses.
+    pub fn add_clauses(&mut self, input: &str) -> Res<()> {
+        let old_len = self.instance.clauses().len();
+
+        let res = self
+            .parser_cxt
+            .parser(input, 0, &self.profiler)
+            .parse(&mut self.instance)?;
+        match res {
+            Parsed::Items | Parsed::Eof => (),
+            res => bail!("expected declarations and clauses, found {}", res),
+        }
+
+        for clause in self.instance.clauses().iter().skip(old_len) {
+            for pred in clause.lhs_preds().keys() {
+                let _ = self.hints.remove(pred);
+            }
+            if let Some((pred, _)) = clause.rhs() {
+                let _ = self.hints.remove(&pred);
+            }
+        }
+        if self.instance.clauses().len() != old_len {
+            self.model = None;
+            self.solved = None
+        }
+
+        Ok(())
+    }
+
+    /// Solves the instance, using the last model as hints.
+    ///
+    /// Returns `Some(true)` if sat, `Some(false)` if unsat, and `None` if unknown. On sat, the model
+    /// is available with [`model`](#method.model). Uses the configuration of the current thread,
+    /// run it under [`with_conf`] to use another one.
+    ///
+    /// [`with_conf`]: ../common/fn.with_conf.html (with_conf function)
+    pub fn resolve(&mut self) -> Res<Option<bool>> {
+        self.model = None;
+        self.solved = None;
+
+        // The last model reaches the teacher as hints of the instance.
+        let mut instance = self.instance.clone();
+        for (pred, hints) in &self.hints {
+            instance.add_hints(*pred, hints.clone())
+        }
+        let res = solve_with(instance, &mut self.passes, &self.profiler);
+
+        let model = match res {
+            Ok(Some(Either::Left((model, solved)))) => {
+                self.solved = Some(solved);
+                model
+            }
+            Ok(Some(Either::Right(_))) => return Ok(Some(false)),
+            Ok(None) => return Ok(None),
+            Err(ref e) if e.is_unsat() => return Ok(Some(false)),
+            Err(ref e) if e.is_unknown() => return Ok(None),
+            Err(e) => bail!(e),
+        };
+
+        self.hints.clear();
+        for (pred, tterms_vec) in model.iter().flat_map(|preds| preds.iter()) {
+            let hints = self.hints.entry(*pred).or_insert_with(Vec::new);
+            for tterms in tterms_vec {
+                if let Some(term) = tterms.to_term() {
+                    if let Some(kids) = term.conj_inspect() {
+                        hints.extend(kids.iter().cloned())
+                    } else if term.bool() != Some(true) {
+                        hints.push(term)
+                    }
+                }
+            }
+        }
+        self.hints.retain(|_, hints| !hints.is_empty());
+
+        self.model = Some(model);
+        Ok(Some(true))
+    }
+}
@@ -121,6 +121,52 @@ mylib::impl_fmt! {
   }
 }
 
+/// Information about a solver failure.
+///
+/// Created by [`smt::solver_failure`].
+///
+/// [`smt::solver_failure`]: ../common/smt/fn.solver_failure.html (solver_failure function)
+#[derive(Debug)]
+pub struct SolverFailure {
+    /// Name of the solver that failed, for instance `teacher`.
+    pub solver: &'static str,
+    /// Clause the solver was working on, if any.
+    pub clause: Option<ClsIdx>,
+    /// Error message.
+    pub msg: String,
+    /// Exit status of the solver, if it exited.
+    pub status: Option<String>,
+    /// Last lines the solver wrote on its stderr, if it exited.
+    pub stderr: Vec<String>,
+    /// Last commands sent to the solver, if they were logged (`--log_smt`).
+    pub last_commands: Vec<String>,
+}
+mylib::impl_fmt! {
+    SolverFailure(self, fmt) {
+        write!(fmt, "solver `{}`", self.solver)?;
+        if let Some(clause) = self.clause {
+            write!(fmt, " on clause #{}", clause)?
+        }
+        write!(fmt, ": {}", self.msg)?;
+        if let Some(status) = self.status.as_ref() {
+            write!(fmt, " (solver {})", status)?
+        }
+        if !self.stderr.is_empty() {
+            write!(fmt, "\nend of the stderr of the solver:")?;
+            for line in &self.stderr {
+                write!(fmt, "\n  {}", line)?
+            }
+        }
+        if !self.last_commands.is_empty() {
+            write!(fmt, "\nlast commands sent to the solver:")?;
+            for command in &self.last_commands {
+                write!(fmt, "\n  {}", command)?
+            }
+        }
+        Ok(())
+    }
+}
+
 error_chain::error_chain! {
     types {
         Error, ErrorKind, ResultExt, Res ;
@@ -147,11 +193,43 @@ error_chain::error_chain! {
             description("could not spawn z3")
             display("could not spawn z3")
         }
+        #[doc = "The solver process died, typically because it ran out of memory."]
+        SolverCrash(info: SolverFailure) {
+            description("solver crash")
+            display("crash of {}", info)
+        }
+        #[doc = "The solver was killed because it reached its timeout."]
+        SolverTimeout(info: SolverFailure) {
+            description(consts::err::timeout_desc)
+            display("timeout of {}", info)
+        }
+        #[doc = "The solver produced an answer hoice could not parse."]
+        SolverOutput(info: SolverFailure) {
+            description("malformed solver output")
+            display("malformed output from {}", info)
+        }
+        #[doc = "The child process solving a split failed, see `--split_subprocess`."]
+        SplitCrash(clause: ClsIdx, msg: String) {
+            description("split process crash")
+            display("process solving the split on clause #{} failed: {}", clause, msg)
+        }
         #[doc = "Not really an error, unknown early return."]
         Unknown {
             description(consts::err::unknown_desc)
             display("unknown")
         }
+        #[doc = "Not really an error, the memory limit was exceeded, see `--mem_limit`."]
+        OutOfMemory(phase: &'static str, used: usize, max: usize) {
+            description(consts::err::unknown_desc)
+            display(
+                "out of memory budget during {}: {}MB used, limit is {}MB", phase, used, max
+            )
+        }
+        #[doc = "Not really an error, some clauses kept timing out, see `--clause_timeout`."]
+        ClauseTimeout(clauses: String) {
+            description(consts::err::unknown_desc)
+            display("check of clause(s) {} repeatedly timed out", clauses)
+        }
         #[doc = "Not really an error, unsat early return."]
         Unsat {
             description(consts::err::unsat_desc)
@@ -162,6 +240,11 @@ error_chain::error_chain! {
             description(consts::err::unsat_desc)
             display("unsat by #{}", clause)
         }
+        #[doc = "Not really an error, a learner received new data while learning."]
+        Preempted {
+            description(consts::err::preempted_desc)
+            display("preempted")
+        }
         #[doc = "Not really an error, exit early return."]
         Exit {
             description(consts::err::exit_desc)
@@ -204,6 +287,22 @@ impl Error {
         false
     }
 
+    /// Reason for an unknown result, if it is not a plain [`ErrorKind::Unknown`][unknown].
+    ///
+    /// [unknown]: enum.ErrorKind.html#variant.Unknown
+    /// (ErrorKind's Unknown variant)
+    pub fn unknown_reason(&self) -> Option<String> {
+        for err in self.iter() {
+            if err.description() == consts::err::unknown_desc {
+                let reason = err.to_string();
+                if reason != consts::err::unknown_desc {
+                    return Some(reason);
+                }
+            }
+        }
+        None
+    }
+
     /// Returns the clause explaining an unsat result if any.
     pub fn unsat_cause(&self) -> Option<ClsIdx> {
         match self.kind() {
@@ -228,6 +327,19 @@ impl Error {
         false
     }
 
+    /// True if the kind of the error is [`ErrorKind::Preempted`][preempted].
+    ///
+    /// [preempted]: enum.ErrorKind.html#variant.Preempted
+    /// (ErrorKind's Preempted variant)
+    pub fn is_preempted(&self) -> bool {
+        for err in self.iter() {
+            if err.description() == consts::err::preempted_desc {
+                return true;
+            }
+        }
+        false
+    }
+
     /// True if the kind of the error is [`ErrorKind::Exit`][exit].
     ///
     /// [exit]: enum.ErrorKind.html#variant.Exit (ErrorKind's Exit variant)
@@ -4,6 +4,10 @@
 //! function create by hoice, typically when working with datatypes. Function can be evaluated and
 //! can be used as qualifiers (for datatypes, typically).
 //!
+//! Uninterpreted functions, declared with `declare-fun` with a non-`Bool` range, are also
+//! registered here, see [`RFun::uninterpreted`]. They have no definition: they are declared to the
+//! solvers, evaluate to unknown values and are never used as qualifiers.
+//!
 //! # Usage
 //!
 //! Creating (mutually) recursive is tricky: when constructing the body of the function(s), the
@@ -104,6 +108,7 @@
 //! [`retrieve_sig`]: fn.retrieve_sig.html (retrieve_sig function)
 //! [`Fun`]: type.Fun.html (Fun type)
 //! [`new`]: fn.new.html (new function)
+//! [`RFun::uninterpreted`]: struct.RFun.html#method.uninterpreted (uninterpreted constructor)
 //! [`test`]: test/index.hmtl (function test module)
 
 use std::sync::{RwLockReadGuard, RwLockWriteGuard};
@@ -127,6 +132,15 @@ lazy_static! {
     ) ;
 }
 
+/// Forgets all the functions and function signatures, see `--batch`.
+pub fn clear() {
+    write_factory().clear();
+    fun_sigs
+        .write()
+        .expect("failed to access function signatures (write)")
+        .clear()
+}
+
 /// Registers a function signature.
 ///
 /// Used to create (mutually) recursive function(s), see [module-level documentation].
@@ -290,7 +304,12 @@ pub fn new(fun: RFun) -> Res<Fun> {
 /// Returns a list of functions classes. A function class is a list of function that depend on each
 /// other, meaning that they must be defined together at SMT-level.
 fn ordered() -> Res<Vec<Vec<Fun>>> {
-    let mut all: Vec<_> = read_factory().values().cloned().collect();
+    // Uninterpreted functions are only declared, see `write_decs`.
+    let mut all: Vec<_> = read_factory()
+        .values()
+        .filter(|fun| !fun.is_uninterpreted())
+        .cloned()
+        .collect();
 
     let mut groups = vec![];
 
@@ -324,7 +343,10 @@ where
     all.push(fun);
     for dep in &fun.deps {
         if let Some(dep) = f.get(dep) {
-            all.push(dep)
+            // Uninterpreted functions are declared separately.
+            if !dep.is_uninterpreted() {
+                all.push(dep)
+            }
         } else {
             bail!(
                 "function `{}` depends on unknown function `{}`",
@@ -400,10 +422,55 @@ pub fn write_for_model<W: Write>(w: &mut W, pref: &str, model: ConjModelRef) ->
 }
 
 /// Defines all the functions in SMT-LIB.
+///
+/// Uninterpreted functions are declared first.
 pub fn write_all<W: Write>(w: &mut W, pref: &str, invariants: bool) -> Res<()> {
+    write_decs(w, pref)?;
     write_groups(w, pref, invariants, ordered()?)
 }
 
+/// Declares all the uninterpreted functions in SMT-LIB.
+///
+/// # Examples
+///
+/// ```rust
+/// use hoice::{ common::*, fun, info::VarInfo };
+/// let sig: VarInfos = vec![ VarInfo::new("v_0", typ::int(), 0.into()) ].into();
+/// let uf = fun::RFun::uninterpreted("fun_write_decs_test_uf", sig, typ::int());
+/// fun::new(uf).expect("during function registration");
+/// let mut buf: Vec<u8> = vec![];
+/// fun::write_decs(&mut buf, "").expect("during declaration");
+/// assert_eq! {
+///     String::from_utf8_lossy(&buf),
+///     "(declare-fun fun_write_decs_test_uf ( Int ) Int)\n\n"
+/// }
+/// ```
+pub fn write_decs<W: Write>(w: &mut W, pref: &str) -> Res<()> {
+    let f = factory!(read);
+    let mut one_or_more = false;
+    for fun in f.values() {
+        if !fun.is_uninterpreted() {
+            continue;
+        }
+        one_or_more = true;
+        write!(
+            w,
+            "{}({} {} (",
+            pref,
+            consts::keywords::cmd::dec_fun,
+            fun.name()
+        )?;
+        for info in fun.sig() {
+            write!(w, " {}", info.typ)?
+        }
+        writeln!(w, " ) {})", fun.typ())?
+    }
+    if one_or_more {
+        writeln!(w)?
+    }
+    Ok(())
+}
+
 /// Defines a bunch of functions in SMT-LIB.
 fn write_groups<W: Write>(
     w: &mut W,
@@ -574,6 +641,7 @@ impl FunSig {
             synthetic: None,
             invariants: TermSet::new(),
             recursive,
+            uninterpreted: false,
         }
     }
 }
@@ -597,6 +665,8 @@ pub struct RFun {
     pub invariants: TermSet,
     /// True if the function is recursive.
     recursive: bool,
+    /// True if the function is uninterpreted.
+    uninterpreted: bool,
 }
 
 impl ::std::ops::Deref for RFun {
@@ -644,9 +714,20 @@ impl RFun {
             synthetic: None,
             invariants: TermSet::new(),
             recursive: false,
+            uninterpreted: false,
         }
     }
 
+    /// Constructor for uninterpreted functions.
+    ///
+    /// The definition is irrelevant and set to `true`. Applications of uninterpreted functions
+    /// are passed as is to the solvers, and evaluate to unknown values.
+    pub fn uninterpreted<S: Into<String>>(name: S, sig: VarInfos, typ: Typ) -> Self {
+        let mut fun = Self::new(name, sig, typ);
+        fun.uninterpreted = true;
+        fun
+    }
+
     /// Name of the function.
     #[inline]
     pub fn name(&self) -> &String {
@@ -674,6 +755,11 @@ impl RFun {
         self.recursive
     }
 
+    /// True if the function is uninterpreted.
+    pub fn is_uninterpreted(&self) -> bool {
+        self.uninterpreted
+    }
+
     /// Checks the function is legal.
     pub fn check(&self) -> Res<()> {
         for dep in &self.deps {
@@ -768,6 +854,10 @@ impl Functions {
         let mut from_to_typ = vec![];
 
         'find_funs: for fun in f.values() {
+            // Cannot be evaluated, useless for synthesis.
+            if fun.is_uninterpreted() {
+                continue 'find_funs;
+            }
             let mut sig = fun.sig.iter();
 
             let ftyp = match sig.next() {
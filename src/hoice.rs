@@ -33,6 +33,7 @@ pub mod common;
 pub mod check;
 pub mod data;
 pub mod dtyp;
+pub mod engine;
 pub mod errors;
 pub mod fun;
 pub mod info;
@@ -40,6 +41,7 @@ mod instance;
 pub mod learning;
 pub mod parse;
 pub mod preproc;
+pub mod soft;
 pub mod split;
 pub mod teacher;
 pub mod term;
@@ -52,6 +54,18 @@ use crate::instance::Instance;
 
 /// Parses command-line arguments and works.
 pub fn work() -> Res<()> {
+    let res = work_on_input();
+    smt::clean_solver_diags();
+    res
+}
+
+/// Reads the input and solves it, see [`work`].
+///
+/// [`work`]: fn.work.html (work function)
+fn work_on_input() -> Res<()> {
+    let version = conf.solver.check_solver()?;
+    log! { @verb "using {} ({})", conf.solver.name(), version }
+
     // Reading from file?
     if let Some(file_path) = conf.in_file() {
         use std::fs::OpenOptions;
@@ -67,6 +81,9 @@ pub fn work() -> Res<()> {
             .open(file_path)
             .chain_err(|| format!("while opening input file `{}`", conf.emph(file_path)))?;
 
+        if conf.batch {
+            return read_and_work_batch(file).map(|_| ());
+        }
         read_and_work(file, true, false, false)?;
         Ok(())
     } else {
@@ -74,11 +91,337 @@ pub fn work() -> Res<()> {
 
         let stdin = ::std::io::stdin();
 
+        if conf.batch {
+            return read_and_work_batch(stdin).map(|_| ());
+        }
         read_and_work(stdin, false, false, false)?;
         Ok(())
     }
 }
 
+/// Reads a sequence of instances from a `Read`er and solves them one by one, see `--batch`.
+///
+/// The instances are separated by `(reset)`, `(push [n])` or `(pop [n])` items. Each instance is
+/// solved by [`read_and_work`], and its results are followed by a `; end of instance #<n>`
+/// comment. An instance starts with the declarations, definitions, assertions and options of the
+/// scopes it is in: `(push n)` opens `n` scopes, `(pop n)` closes the last `n` ones and forgets
+/// what was declared and asserted in them, and `(reset)` closes all the scopes. An error or a
+/// timeout in an instance is reported, and does not prevent solving the following ones. Note that
+/// `(exit)` only ends the current instance, and that the timeout is global.
+///
+/// Instances that are only declarations, definitions, assertions or options are not solved, they
+/// just add to the current scope. Returns the result of the last `check-sat` of each instance
+/// solved: `Some(true)` for sat, `Some(false)` for unsat, `None` for unknown or on errors.
+///
+/// [`read_and_work`]: fn.read_and_work.html (read_and_work function)
+pub fn read_and_work_batch<R: ::std::io::Read>(reader: R) -> Res<Vec<Option<bool>>> {
+    use crate::parse::{batch_item, BatchItem, ItemRead};
+
+    let mut reader = ::std::io::BufReader::new(reader);
+    let mut buf = String::with_capacity(STREAM_BUF_CAPACITY);
+    // Declarations, definitions, assertions and options of each scope, outermost first, that are
+    // not in the current instance.
+    let mut scopes = vec![String::new()];
+    // Text of the current instance, and the line it starts at.
+    let mut chunk = String::new();
+    let mut chunk_line = 0;
+    // Items of the current instance that belong to the current scope.
+    let mut scoped = String::new();
+    // True if the current instance has at least one command.
+    let mut has_commands = false;
+    // Line offset of the current item.
+    let mut line_off = 0;
+    // Results of the instances solved so far.
+    let mut results = vec![];
+
+    loop {
+        buf.clear();
+        let lines_parsed = reader
+            .read_item(&mut buf)
+            .chain_err(|| "while reading input")?;
+        let eof = lines_parsed == 0;
+        let item = if eof {
+            BatchItem::Reset
+        } else {
+            batch_item(&buf)
+        };
+
+        match item {
+            BatchItem::Command | BatchItem::Scoped => {
+                if chunk.is_empty() {
+                    chunk_line = line_off
+                }
+                chunk.push_str(&buf);
+                if item == BatchItem::Scoped {
+                    scoped.push_str(&buf)
+                } else {
+                    has_commands = has_commands || buf.contains('(')
+                }
+            }
+            BatchItem::Reset | BatchItem::Push(_) | BatchItem::Pop(_) => (),
+        }
+        line_off += lines_parsed;
+
+        if item == BatchItem::Command || item == BatchItem::Scoped {
+            continue;
+        }
+
+        if has_commands {
+            record_result(None);
+            let mut text: String = scopes.concat();
+            // Keep line numbers right in the errors of the instance.
+            let prefix_lines = text.lines().count();
+            for _ in prefix_lines..chunk_line {
+                text.push('\n')
+            }
+            text.push_str(&chunk);
+            let result = match read_and_work(text.as_bytes(), true, false, false) {
+                Ok(_) => last_result.lock().ok().and_then(|last| *last),
+                Err(e) => {
+                    print_err(&e);
+                    None
+                }
+            };
+            results.push(result);
+            println!("; end of instance #{}", results.len());
+            fun::clear();
+            dtyp::clear();
+        }
+        if let Some(scope) = scopes.last_mut() {
+            scope.push_str(&scoped)
+        }
+        chunk.clear();
+        scoped.clear();
+        has_commands = false;
+
+        match item {
+            BatchItem::Reset => scopes = vec![String::new()],
+            BatchItem::Push(n) => {
+                for _ in 0..n {
+                    scopes.push(String::new())
+                }
+            }
+            BatchItem::Pop(n) => {
+                if n >= scopes.len() {
+                    let e: Error =
+                        format!("cannot pop {} scope(s), only {} open", n, scopes.len() - 1).into();
+                    print_err(&e);
+                    scopes = vec![String::new()]
+                } else {
+                    let len = scopes.len() - n;
+                    scopes.truncate(len)
+                }
+            }
+            BatchItem::Command | BatchItem::Scoped => (),
+        }
+
+        if eof {
+            break;
+        }
+    }
+
+    Ok(results)
+}
+
+/// Writes the names of the original clauses mentioning each predicate, as comments.
+///
+/// Does nothing if no clause is named.
+fn write_clause_names<W: Write>(
+    instance: &Instance,
+    clause_names: &PrdHMap<Vec<String>>,
+    w: &mut W,
+) -> Res<()> {
+    if clause_names.is_empty() {
+        return Ok(());
+    }
+    writeln!(w, "; named assertions mentioning each predicate")?;
+    for pred in instance.preds() {
+        if let Some(names) = clause_names.get(&pred.idx) {
+            writeln!(w, ";   {}: {}", pred.name, names.join(", "))?
+        }
+    }
+    Ok(())
+}
+
+/// Prints the last candidates of the learners as an unverified model, in comments.
+///
+/// Does nothing unless `--best_effort` is active and the learners produced at least one
+/// candidate.
+fn print_best_effort(instance: &Instance) -> Res<()> {
+    if !conf.best_effort {
+        return Ok(());
+    }
+    if let Some(model) = crate::teacher::best_effort_model() {
+        let model: ConjModel = model
+            .into_iter()
+            .map(|(pred, tterms)| vec![(pred, vec![tterms])])
+            .collect();
+        let mut buf: Vec<u8> = vec![];
+        instance.write_model(&model, &mut buf)?;
+        println!("; best effort model, UNVERIFIED: the learners' last candidates");
+        for line in String::from_utf8_lossy(&buf).lines() {
+            println!("; {}", line)
+        }
+    }
+    Ok(())
+}
+
+/// Checks that a model verifies all the clauses of the original instance.
+///
+/// - `original`: the instance before preprocessing
+/// - `instance`: the instance the model was produced for
+///
+/// Checks each clause of `original` separately. Prints the clauses the model falsifies, if any,
+/// along with a falsifying assignment, and fails. Prints `model verified` otherwise.
+pub fn check_model(original: &Instance, instance: &Instance, model: ConjModelRef) -> Res<()> {
+    use crate::common::smt::FullParser as Parser;
+
+    let mut solver = conf.solver.spawn("check_model", Parser, original)?;
+
+    let mut defined = PrdSet::new();
+    for defs in model {
+        for (pred, _) in defs {
+            defined.insert(*pred);
+        }
+    }
+    for pred in original.preds() {
+        if !defined.contains(&pred.idx) {
+            warn!(
+                "predicate {} is not defined in the model",
+                conf.emph(&pred.name)
+            );
+            let sig: Vec<_> = pred.original_sig().iter().map(|typ| typ.get()).collect();
+            solver.declare_fun(&pred.name, &sig, "Bool")?
+        }
+    }
+    instance.write_pred_definitions(&mut solver, "", model)?;
+
+    let (tru, fls) = (PrdSet::new(), PrdSet::new());
+    let mut falsified = 0;
+    for (idx, clause) in original.clauses().index_iter() {
+        solver.push(1)?;
+        clause.declare(&mut solver)?;
+        solver.assert_with(clause, &(false, &tru, &fls, original.preds()))?;
+
+        match solver.check_sat_or_unk()? {
+            Some(true) => {
+                falsified += 1;
+                let cex = solver.get_model()?;
+                let cex = Parser.fix_model(cex)?;
+                let cex = var_to::vals::RVarVals::of_model(clause.vars(), cex, false)?;
+                println!("({} \"", conf.bad("error"));
+                println!("  clause {} is falsified by the model", idx);
+                println!("  {}", clause.to_string_info(original.preds())?);
+                println!("  with {{");
+                for var in clause.vars() {
+                    if var.active {
+                        println!("    {}: {},", var.name, cex[var.idx])
+                    }
+                }
+                println!("  }}");
+                println!("\")");
+            }
+            Some(false) => (),
+            None => warn!(
+                "got unknown while checking clause {} against the model",
+                idx
+            ),
+        }
+
+        solver.pop(1)?
+    }
+
+    solver.kill().chain_err(|| "while killing solver")?;
+
+    if falsified > 0 {
+        bail!(
+            "the model falsifies {} clause(s) of the original instance",
+            falsified
+        )
+    }
+    println!("; model verified");
+    Ok(())
+}
+
+/// Answers `unknown` after a timeout, naming the phase hoice was in.
+///
+/// The timeout is global: it counts from startup and covers parsing, pre-processing, solving and
+/// proof reconstruction.
+fn print_timeout(phase: &str) {
+    record_result(None);
+    println!("unknown");
+    println!("; timeout during {}", phase)
+}
+
+lazy_static! {
+    /// Result of the last `check-sat`, see [`exit_code`](fn.exit_code.html).
+    static ref last_result: ::std::sync::Mutex<Option<bool>> = ::std::sync::Mutex::new(None);
+}
+
+/// Records the result of a `check-sat`: `Some(true)` for sat, `Some(false)` for unsat, `None`
+/// for unknown.
+fn record_result(result: Option<bool>) {
+    if let Ok(mut last) = last_result.lock() {
+        *last = result
+    }
+}
+
+/// Exit code of the process for the result of the last `check-sat`, see `--exit_codes`.
+///
+/// Unknown if there was no `check-sat`.
+pub fn exit_code() -> i32 {
+    let result = last_result.lock().ok().and_then(|last| *last);
+    conf.exit_codes.code(result)
+}
+
+/// Capacity of the input buffer.
+///
+/// In streaming mode, the buffer is shrunk back to this capacity after big items.
+const STREAM_BUF_CAPACITY: usize = 2000;
+
+/// An item delayed because it refers to an undeclared identifier, see `--stream_parse`.
+///
+/// Identifier, text of the item, and line offset of the item.
+type ForwardRef = (String, String, usize);
+
+/// Parses again the items delayed because of forward references, see `--stream_parse`.
+///
+/// Only retries the items the identifier of which is now known, unless `all`. Items still
+/// referring to unknown identifiers are kept, fails on any other error.
+fn parse_forward_refs(
+    forward_refs: &mut Vec<ForwardRef>,
+    parser_cxt: &mut crate::parse::ParserCxt,
+    instance: &mut Instance,
+    profiler: &Profiler,
+    all: bool,
+) -> Res<()> {
+    let mut progress = true;
+    while progress {
+        progress = false;
+        let mut index = 0;
+        while index < forward_refs.len() {
+            if !all && !parser_cxt.knows(&forward_refs[index].0, instance) {
+                index += 1;
+                continue;
+            }
+            let (ident, item, line_off) = forward_refs.remove(index);
+            log! { @verb "parsing the item delayed because of `{}`", ident }
+            match parser_cxt.parser(&item, line_off, profiler).parse(instance) {
+                Ok(_) => progress = true,
+                Err(e) => {
+                    if let Some(ident) = parser_cxt.take_forward_ref() {
+                        forward_refs.insert(index, (ident, item, line_off));
+                        index += 1
+                    } else {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Reads a script from a `Read`er and works.
 ///
 /// Arguments:
@@ -90,23 +433,27 @@ pub fn work() -> Res<()> {
 ///   complete. Only used in tests.
 ///
 /// - `stop_on_err`: forces to stop at the first error. Only used in tests.
+///
+/// Stops reading after a timeout, once `unknown` is printed.
 pub fn read_and_work<R: ::std::io::Read>(
     reader: R,
     file_input: bool,
     stop_on_check: bool,
     stop_on_err: bool,
 ) -> Res<(Option<ConjModel>, Instance)> {
-    use crate::parse::{ItemRead, ParserCxt};
+    use crate::parse::{ItemRead, Parsed, ParserCxt};
 
     let profiler = Profiler::new();
 
     let mut reader = ::std::io::BufReader::new(reader);
     // String buffer.
-    let buf = &mut String::with_capacity(2000);
+    let buf = &mut String::with_capacity(STREAM_BUF_CAPACITY);
     // Parser context.
     let mut parser_cxt = ParserCxt::new();
     // Line offset of the parser.
     let mut line_off = 0;
+    // Items referring to undeclared identifiers, in streaming mode.
+    let mut forward_refs: Vec<ForwardRef> = vec![];
     // Instance.
     let mut instance = Instance::new();
     // Current model.
@@ -122,42 +469,119 @@ pub fn read_and_work<R: ::std::io::Read>(
     // Original instance.
     let mut original_instance = None;
 
-    'parse_work: loop {
-        use crate::parse::Parsed;
+    // Names of the original clauses mentioning each predicate, printed with the model.
+    let mut clause_names = PrdHMap::new();
 
+    // Commands of the horn front-end, in reverse order, see `--horn`.
+    let mut horn_cmds = vec![];
+    if conf.horn {
         profile! { |profiler| tick "parsing" }
-
-        buf.clear();
-        let lines_parsed = reader.read_item(buf).chain_err(|| "while reading input")?;
-
-        if lines_parsed == 0 && file_input {
-            profile! { |profiler| mark "parsing" }
-            break 'parse_work;
+        let mut text = String::new();
+        ::std::io::Read::read_to_string(&mut reader, &mut text)
+            .chain_err(|| "while reading input")?;
+        match crate::parse::horn::parse(&text, &mut instance) {
+            Ok(()) => (),
+            Err(ref e) if e.is_timeout() => {
+                print_timeout("parsing");
+                print_stats("top", profiler);
+                return Ok((model, instance));
+            }
+            Err(e) => bail!(e),
         }
-        let parse_res = parser_cxt
-            .parser(&buf, line_off, &profiler)
-            .parse(&mut instance);
+        profile! { |profiler| mark "parsing" }
+        horn_cmds = vec![Parsed::Exit, Parsed::GetModel, Parsed::CheckSat]
+    }
 
-        line_off += lines_parsed;
+    'parse_work: loop {
+        let parse_res = if conf.horn {
+            horn_cmds.pop().unwrap_or(Parsed::Exit)
+        } else {
+            profile! { |profiler| tick "parsing" }
+
+            if conf.stream_parse && buf.capacity() > STREAM_BUF_CAPACITY {
+                // Do not keep the memory of the biggest item around.
+                *buf = String::with_capacity(STREAM_BUF_CAPACITY)
+            } else {
+                buf.clear()
+            }
+            let lines_parsed = reader.read_item(buf).chain_err(|| "while reading input")?;
 
-        let parse_res = match parse_res {
-            Ok(res) => res,
-            Err(e) => {
-                if stop_on_err {
-                    return Err(e);
-                }
-                // error = true ;
-                print_err(&e);
+            if lines_parsed == 0 && file_input {
                 profile! { |profiler| mark "parsing" }
-                continue 'parse_work;
+                break 'parse_work;
             }
-        };
+            let parse_res = parser_cxt
+                .parser(&buf, line_off, &profiler)
+                .parse(&mut instance);
+
+            line_off += lines_parsed;
+
+            let parse_res = match parse_res {
+                Ok(Parsed::Items) if conf.stream_parse && !forward_refs.is_empty() => {
+                    parse_forward_refs(
+                        &mut forward_refs,
+                        &mut parser_cxt,
+                        &mut instance,
+                        &profiler,
+                        false,
+                    )
+                    .map(|()| Parsed::Items)
+                }
+                Ok(Parsed::CheckSat) if conf.stream_parse && !forward_refs.is_empty() => {
+                    parse_forward_refs(
+                        &mut forward_refs,
+                        &mut parser_cxt,
+                        &mut instance,
+                        &profiler,
+                        true,
+                    )
+                    .and_then(|()| match forward_refs.drain(..).next() {
+                        Some((ident, _, item_line_off)) => bail!(
+                            "item at line {} refers to unknown identifier `{}`",
+                            item_line_off + 1,
+                            conf.bad(&ident)
+                        ),
+                        None => Ok(Parsed::CheckSat),
+                    })
+                }
+                parse_res => parse_res,
+            };
+
+            let parse_res = match parse_res {
+                Ok(res) => res,
+                Err(e) => {
+                    if e.is_timeout() {
+                        print_timeout("parsing");
+                        print_stats("top", profiler);
+                        return Ok((model, instance));
+                    }
+                    if conf.stream_parse {
+                        if let Some(ident) = parser_cxt.take_forward_ref() {
+                            // Forward reference, parse the item again once `ident` is declared.
+                            log! { @verb "delaying item referring to `{}`", ident }
+                            forward_refs.push((ident, buf.clone(), line_off - lines_parsed));
+                            profile! { |profiler| mark "parsing" }
+                            continue 'parse_work;
+                        }
+                    }
+                    if stop_on_err {
+                        return Err(e);
+                    }
+                    // error = true ;
+                    print_err(&e);
+                    profile! { |profiler| mark "parsing" }
+                    continue 'parse_work;
+                }
+            };
 
-        profile! { |profiler| mark "parsing" }
+            profile! { |profiler| mark "parsing" }
+            parse_res
+        };
 
         match parse_res {
             // Check-sat on unsat instance?
             Parsed::CheckSat if unsat.is_some() => {
+                record_result(Some(false));
                 println!("unsat");
 
                 if stop_on_check {
@@ -167,12 +591,26 @@ pub fn read_and_work<R: ::std::io::Read>(
 
             // Check-sat, start class.
             Parsed::CheckSat => {
-                if instance.proofs() {
+                clause_names = instance.clause_names_of_preds();
+                if instance.proofs() || conf.check_model {
                     let mut old = instance.clone();
                     old.finalize()
                         .chain_err(|| "while finalizing original instance")?;
                     original_instance = Some(old)
                 }
+                let soft_instance = if conf.soft_clauses && instance.has_soft_clauses() {
+                    Some(instance.clone())
+                } else {
+                    None
+                };
+                if conf.stats {
+                    println!();
+                    for line in instance.stats().to_string().lines() {
+                        println!("; {}", line)
+                    }
+                    println!();
+                }
+
                 log! { @info "Running top pre-processing" }
 
                 let preproc_profiler = Profiler::new();
@@ -184,14 +622,21 @@ pub fn read_and_work<R: ::std::io::Read>(
                     Ok(()) => (),
                     Err(e) => {
                         if e.is_timeout() {
-                            println!("timeout");
+                            print_timeout("pre-processing");
                             print_stats("top", profiler);
-                            ::std::process::exit(0)
+                            return Ok((model, instance));
                         } else if e.is_unknown() {
+                            record_result(None);
                             println!("unknown");
+                            if let Some(reason) = e.unknown_reason() {
+                                println!("; {}", reason)
+                            }
                             continue;
                         } else if e.is_unsat() {
-                            unsat = Some(unsat_core::UnsatRes::None)
+                            if let Some(clause) = e.unsat_cause() {
+                                log! { @info "unsat by clause #{}", clause }
+                            }
+                            unsat = Some(unsat_core::UnsatRes::of_error(&e, &instance))
                         } else {
                             bail!(e)
                         }
@@ -199,6 +644,24 @@ pub fn read_and_work<R: ::std::io::Read>(
                 }
                 print_stats("top preproc", preproc_profiler);
 
+                if let Some(file) = conf.preproc.dump_preproc.as_ref() {
+                    let mut file = ::std::fs::File::create(file).chain_err(|| {
+                        format!("while creating pre-processing dump file `{}`", file)
+                    })?;
+                    instance
+                        .dump_as_smt2(&mut file, "Instance after pre-processing.")
+                        .chain_err(|| "while dumping pre-processed instance")?
+                }
+
+                if let Some(file) = conf.preproc.dump_dot.as_ref() {
+                    let mut file = ::std::fs::File::create(file).chain_err(|| {
+                        format!("while creating dependency graph dump file `{}`", file)
+                    })?;
+                    DepGraph::new(&instance)
+                        .dot_write(&mut file, &instance)
+                        .chain_err(|| "while dumping dependency graph")?
+                }
+
                 model = if let Some(maybe_model) = instance.is_trivial_conj()? {
                     // Pre-processing already decided satisfiability.
                     log! { @info "solved by pre-processing" }
@@ -245,12 +708,16 @@ pub fn read_and_work<R: ::std::io::Read>(
                             None
                         }
                         Err(ref e) if e.is_timeout() => {
-                            println!("timeout");
+                            print_timeout("solving");
+                            print_best_effort(&instance)?;
                             print_stats("top", profiler);
-                            ::std::process::exit(0)
+                            return Ok((model, instance));
                         }
                         Err(ref e) if e.is_unknown() => {
                             println!("unknown");
+                            if let Some(reason) = e.unknown_reason() {
+                                println!("; {}", reason)
+                            }
                             None
                         }
                         Err(e) => {
@@ -260,6 +727,40 @@ pub fn read_and_work<R: ::std::io::Read>(
                     }
                 };
 
+                let result = if unsat.is_some() {
+                    Some(false)
+                } else if model.is_some() {
+                    Some(true)
+                } else {
+                    None
+                };
+                record_result(result);
+                if let (Some(result), Some(status)) = (result, instance.status()) {
+                    if result != status {
+                        let show = |b| if b { "sat" } else { "unsat" };
+                        warn!(
+                            "result `{}` contradicts the `:status {}` of the input",
+                            show(result),
+                            show(status)
+                        )
+                    }
+                }
+
+                if let (Some(soft_instance), true) = (soft_instance, unsat.is_some()) {
+                    if let Err(e) = soft::work(&soft_instance, &profiler) {
+                        warn!("could not relax the soft clauses: {}", e)
+                    }
+                }
+
+                if conf.check_model {
+                    if let (Some(model), Some(original)) =
+                        (model.as_ref(), original_instance.as_ref())
+                    {
+                        check_model(original, &instance, model)
+                            .chain_err(|| "while checking the model")?
+                    }
+                }
+
                 if stop_on_check {
                     return Ok((model, instance));
                 }
@@ -284,7 +785,11 @@ pub fn read_and_work<R: ::std::io::Read>(
                                 .chain_err(|| "while writing unsat proof")
                         })
                     {
-                        print_err(&e)
+                        if e.is_timeout() {
+                            println!("; timeout during proof reconstruction")
+                        } else {
+                            print_err(&e)
+                        }
                     }
                 } else {
                     print_err(&"no unsat proof available".into())
@@ -296,8 +801,17 @@ pub fn read_and_work<R: ::std::io::Read>(
                 if let Some(model) = model.as_mut() {
                     // Simplify model before writing it.
                     // instance.simplify_pred_defs(model) ? ;
+                    if conf.factor_model {
+                        instance.factor_model(model)
+                    }
                     let stdout = &mut stdout();
-                    instance.write_model(&model, stdout)?
+                    match conf.model_format {
+                        ModelFormat::Smt2 => {
+                            write_clause_names(&instance, &clause_names, stdout)?;
+                            instance.write_model(&model, stdout)?
+                        }
+                        ModelFormat::Datalog => instance.write_datalog_model(&model, stdout)?,
+                    }
                 } else {
                     bail!("no model available")
                 }
@@ -311,8 +825,14 @@ pub fn read_and_work<R: ::std::io::Read>(
 
             Parsed::Reset => {
                 parser_cxt.reset();
+                forward_refs.clear();
+                fun::clear();
+                dtyp::clear();
                 instance = Instance::new();
-                model = None
+                model = None;
+                unsat = None;
+                original_instance = None;
+                clause_names.clear()
             }
 
             Parsed::Eof => {
@@ -333,7 +853,7 @@ pub fn read_and_work<R: ::std::io::Read>(
 }
 
 /// Waits until an `Arc` is unwrap-able.
-fn unwrap_arc<T>(arc: Arc<T>) -> Res<T> {
+pub(crate) fn unwrap_arc<T>(arc: Arc<T>) -> Res<T> {
     while Arc::strong_count(&arc) != 1 {}
     if let Ok(res) = Arc::try_unwrap(arc) {
         Ok(res)
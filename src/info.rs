@@ -94,6 +94,9 @@ pub struct Pred {
     /// Companion functions. Function that were created specifically for this predicate, and must
     /// be given to the user before giving the definition for this predicate.
     funs: Vec<Fun>,
+    /// True if the predicate was introduced by pre-processing. Auxiliary predicates are inlined
+    /// away when building the final model.
+    aux: bool,
 }
 
 impl Pred {
@@ -112,6 +115,7 @@ impl Pred {
             def: None,
             strength: None,
             funs: vec![],
+            aux: false,
         }
     }
 
@@ -252,6 +256,22 @@ impl Pred {
         &self.funs
     }
 
+    /// True if the predicate is auxiliary, *i.e.* was introduced by pre-processing.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, info::Pred };
+    /// let sig: VarMap<_> = vec![ typ::int(), typ::bool() ].into();
+    /// let mut pred = Pred::new("pred", 0.into(), sig.clone());
+    /// assert! { !pred.is_aux() }
+    /// pred.set_aux();
+    /// assert! { pred.is_aux() }
+    /// ```
+    pub fn is_aux(&self) -> bool {
+        self.aux
+    }
+
     /// A variable that does not appear in the **original** signature of the predicate.
     ///
     /// # Examples
@@ -448,6 +468,11 @@ impl Pred {
         self.funs.push(fun)
     }
 
+    /// Marks the predicate as auxiliary.
+    pub fn set_aux(&mut self) {
+        self.aux = true
+    }
+
     /// Finalizes the predicate information.
     ///
     /// After finalization, calls to [`original_sig_term_map`] will always succeed.
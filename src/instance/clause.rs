@@ -358,6 +358,38 @@ impl Clause {
         }
     }
 
+    /// Clones a clause but replaces some top terms of its lhs by a predicate application.
+    ///
+    /// Fails if one of the top terms is not in the lhs.
+    pub fn clone_factoring(&self, tterms: &[TTerm], app: PredApp, info: &'static str) -> Res<Self> {
+        let mut clause = self.clone();
+        for tterm in tterms {
+            let was_there = match tterm {
+                TTerm::T(term) => clause.rm_term(term),
+                TTerm::P { pred, args } => {
+                    let (was_there, is_empty) = if let Some(argss) = clause.lhs_preds.get_mut(pred)
+                    {
+                        (argss.remove(args), argss.is_empty())
+                    } else {
+                        (false, false)
+                    };
+                    if is_empty {
+                        clause.lhs_preds.remove(pred);
+                    }
+                    clause.preds_changed = clause.preds_changed || was_there;
+                    was_there
+                }
+            };
+            if !was_there {
+                bail!("cannot factor top terms that are not in the lhs of the clause")
+            }
+        }
+        let (pred, args) = app;
+        clause.insert_pred_app(pred, args);
+        clause.info = info;
+        Ok(clause)
+    }
+
     /// Removes all redundant terms from `lhs_terms`.
     fn prune(&mut self) {
         use crate::term::simplify::SimplRes::*;
@@ -548,6 +580,64 @@ impl Clause {
         }
     }
 
+    /// Replaces the predicate arguments mentioning uninterpreted functions by fresh variables.
+    ///
+    /// For each such argument `arg`, a fresh variable `v` replaces `arg` and `(= v arg)` is added
+    /// to the lhs. Applications of uninterpreted functions evaluate to unknown values, so
+    /// evaluating `arg` on a counterexample would yield a partial sample, which is not what the
+    /// clause means. The value of `v` is given by the solver instead.
+    ///
+    /// Returns true if the clause changed.
+    pub fn abstract_uf_args(&mut self) -> bool {
+        let mut eqs = vec![];
+
+        {
+            let vars = &mut self.vars;
+            let mut abstract_args = |args: &VarTerms| {
+                if !args.iter().any(|arg| arg.has_uf_apps()) {
+                    return None;
+                }
+                let mut nu_args = VarMap::with_capacity(args.len());
+                for arg in args.iter() {
+                    if arg.has_uf_apps() {
+                        let fresh = vars.next_index();
+                        let fresh_name = format!("hoice_fresh_var@{}", fresh);
+                        vars.push(VarInfo::new(fresh_name, arg.typ(), fresh));
+                        let fresh = term::var(fresh, arg.typ());
+                        eqs.push(term::eq(fresh.clone(), arg.clone()));
+                        nu_args.push(fresh)
+                    } else {
+                        nu_args.push(arg.clone())
+                    }
+                }
+                Some(var_to::terms::new(nu_args))
+            };
+
+            for argss in self.lhs_preds.values_mut() {
+                let mut nu_argss = VarTermsSet::with_capacity(argss.len());
+                for args in argss.iter() {
+                    nu_argss.insert(abstract_args(args).unwrap_or_else(|| args.clone()));
+                }
+                ::std::mem::swap(&mut nu_argss, argss)
+            }
+
+            if let Some((_, ref mut args)) = self.rhs {
+                if let Some(nu_args) = abstract_args(args) {
+                    *args = nu_args
+                }
+            }
+        }
+
+        if eqs.is_empty() {
+            return false;
+        }
+        self.preds_changed = true;
+        for eq in eqs {
+            self.insert_term(eq);
+        }
+        true
+    }
+
     /// Deactivates a variable.
     pub fn deactivate(&mut self, var: VarIdx) -> Res<()> {
         debug_assert!(self.vars[var].active);
@@ -0,0 +1,418 @@
+//! Writes models as Prolog/Datalog rules, see `--model_format`.
+//!
+//! Each predicate definition is put in DNF, and each disjunct yields a rule whose body is the
+//! conjunction of literals of the disjunct. Arithmetic is written with Prolog's evaluating
+//! comparison operators (`=:=`, `=\=`, `<`, `=<`, ...). Booleans are encoded as `0`/`1`.
+//!
+//! Only quantifier-free, linear definitions over booleans, integers and reals are supported.
+
+use crate::{common::*, info::Pred};
+
+/// A literal in the body of a rule.
+#[derive(Clone)]
+enum Lit {
+    /// An arithmetic or boolean atom, potentially negated.
+    Term(Term),
+    /// A predicate application, positive if the flag is `true`.
+    App(bool, PrdIdx, VarTerms),
+}
+
+/// A disjunction of conjunctions of literals.
+type Dnf = Vec<Vec<Lit>>;
+
+/// Writes a model as rules.
+pub fn write_model<W: Write>(instance: &Instance, model: ConjModelRef, w: &mut W) -> Res<()> {
+    for defs in model {
+        for (pred, tterms_vec) in defs {
+            let dnf = pred_dnf(tterms_vec).and_then(|dnf| {
+                let mut s: Vec<u8> = vec![];
+                for conj in &dnf {
+                    write_rule(instance, &mut s, *pred, conj)?
+                }
+                Ok(s)
+            });
+            let s = dnf.chain_err(|| {
+                format!(
+                    "while writing the definition of `{}` in datalog format",
+                    conf.bad(&instance[*pred].name)
+                )
+            })?;
+            w.write_all(&s)?
+        }
+    }
+    Ok(())
+}
+
+/// DNF of a conjunction of top terms.
+fn pred_dnf(tterms_vec: &[TTerms]) -> Res<Dnf> {
+    let mut res = vec![vec![]];
+    for tterms in tterms_vec {
+        res = product(res, &tterms_dnf(tterms)?)
+    }
+    Ok(res)
+}
+
+/// Conjunction of two DNFs.
+fn product(lft: Dnf, rgt: &Dnf) -> Dnf {
+    let mut res = Vec::with_capacity(lft.len() * rgt.len());
+    for l_conj in &lft {
+        for r_conj in rgt {
+            let mut conj = l_conj.clone();
+            conj.extend(r_conj.iter().cloned());
+            res.push(conj)
+        }
+    }
+    res
+}
+
+/// Fails on quantifiers.
+fn no_quant(quant: &Option<Quant>) -> Res<()> {
+    if quant.is_some() {
+        bail!("quantified definitions are not supported")
+    }
+    Ok(())
+}
+
+/// DNF of some top terms.
+fn tterms_dnf(tterms: &TTerms) -> Res<Dnf> {
+    match *tterms {
+        TTerms::True => Ok(vec![vec![]]),
+        TTerms::False => Ok(vec![]),
+
+        TTerms::Conj {
+            ref quant,
+            ref tterms,
+        } => {
+            no_quant(quant)?;
+            tterm_set_dnf(tterms)
+        }
+
+        TTerms::Disj {
+            ref quant,
+            ref tterms,
+            ref neg_preds,
+        } => {
+            no_quant(quant)?;
+            let mut res = vec![];
+            for term in tterms.terms() {
+                res.extend(term_dnf(term, true)?)
+            }
+            for (pred, argss) in tterms.preds() {
+                for args in argss {
+                    res.push(vec![Lit::App(true, *pred, args.clone())])
+                }
+            }
+            for (pred, argss) in neg_preds {
+                for args in argss {
+                    res.push(vec![Lit::App(false, *pred, args.clone())])
+                }
+            }
+            Ok(res)
+        }
+
+        TTerms::Dnf { ref disj } => {
+            let mut res = vec![];
+            for (quant, tterms) in disj {
+                no_quant(quant)?;
+                res.extend(tterm_set_dnf(tterms)?)
+            }
+            Ok(res)
+        }
+    }
+}
+
+/// DNF of a conjunction of top terms.
+fn tterm_set_dnf(tterms: &TTermSet) -> Res<Dnf> {
+    let mut res = vec![vec![]];
+    for term in tterms.terms() {
+        res = product(res, &term_dnf(term, true)?)
+    }
+    for (pred, argss) in tterms.preds() {
+        for args in argss {
+            for conj in &mut res {
+                conj.push(Lit::App(true, *pred, args.clone()))
+            }
+        }
+    }
+    Ok(res)
+}
+
+/// DNF of a boolean term, negated if `pos` is false.
+fn term_dnf(term: &Term, pos: bool) -> Res<Dnf> {
+    if let Some(b) = term.bool() {
+        return Ok(if b == pos { vec![vec![]] } else { vec![] });
+    }
+
+    if let Some((op, args)) = term.app_inspect() {
+        match op {
+            Op::Not => return term_dnf(&args[0], !pos),
+
+            Op::And | Op::Or => {
+                let mut res = vec![];
+                if (op == Op::And) == pos {
+                    res.push(vec![]);
+                    for arg in args {
+                        res = product(res, &term_dnf(arg, pos)?)
+                    }
+                } else {
+                    for arg in args {
+                        res.extend(term_dnf(arg, pos)?)
+                    }
+                }
+                return Ok(res);
+            }
+
+            Op::Impl if args.len() == 2 => {
+                // `(=> lhs rhs)` is `(or (not lhs) rhs)`.
+                return if pos {
+                    let mut res = term_dnf(&args[0], false)?;
+                    res.extend(term_dnf(&args[1], true)?);
+                    Ok(res)
+                } else {
+                    Ok(product(
+                        term_dnf(&args[0], true)?,
+                        &term_dnf(&args[1], false)?,
+                    ))
+                };
+            }
+
+            Op::Ite if args[1].typ().is_bool() => {
+                let mut res = product(term_dnf(&args[0], true)?, &term_dnf(&args[1], pos)?);
+                res.extend(product(
+                    term_dnf(&args[0], false)?,
+                    &term_dnf(&args[2], pos)?,
+                ));
+                return Ok(res);
+            }
+
+            Op::Eql if args.len() == 2 && args[0].typ().is_bool() => {
+                let mut res = product(term_dnf(&args[0], true)?, &term_dnf(&args[1], pos)?);
+                res.extend(product(
+                    term_dnf(&args[0], false)?,
+                    &term_dnf(&args[1], !pos)?,
+                ));
+                return Ok(res);
+            }
+
+            _ => (),
+        }
+    }
+
+    if term.degree() > 1 {
+        bail!("non-linear term `{}` is not supported", term)
+    }
+
+    let lit = if pos {
+        term.clone()
+    } else {
+        term::not(term.clone())
+    };
+    Ok(vec![vec![Lit::Term(lit)]])
+}
+
+/// Writes a predicate name, quoting it if needed.
+fn write_pred_name<W: Write>(w: &mut W, pred: &Pred) -> Res<()> {
+    let name = pred.name.trim_matches('|');
+    let is_atom = name
+        .chars()
+        .next()
+        .map(|c| c.is_ascii_lowercase())
+        .unwrap_or(false)
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if is_atom {
+        write!(w, "{}", name)?
+    } else {
+        write!(w, "'")?;
+        for c in name.chars() {
+            if c == '\'' || c == '\\' {
+                write!(w, "\\")?
+            }
+            write!(w, "{}", c)?
+        }
+        write!(w, "'")?
+    }
+    Ok(())
+}
+
+/// Writes a variable.
+fn write_var<W: Write>(w: &mut W, var: VarIdx) -> Res<()> {
+    write!(w, "V_{}", var)?;
+    Ok(())
+}
+
+/// Writes a value.
+fn write_val<W: Write>(w: &mut W, val: &Val) -> Res<()> {
+    let typ = val.typ();
+    if typ.is_bool() {
+        if let Some(b) = val.to_bool()? {
+            write!(w, "{}", if b { 1 } else { 0 })?;
+            return Ok(());
+        }
+    } else if typ.is_int() {
+        if let Some(i) = val.to_int()? {
+            if i.is_negative() {
+                write!(w, "({})", i)?
+            } else {
+                write!(w, "{}", i)?
+            }
+            return Ok(());
+        }
+    } else if typ.is_real() {
+        if let Some(r) = val.to_real()? {
+            if r.denom().is_one() && !r.numer().is_negative() {
+                write!(w, "{}", r.numer())?
+            } else if r.denom().is_one() {
+                write!(w, "({})", r.numer())?
+            } else {
+                write!(w, "({} / {})", r.numer(), r.denom())?
+            }
+            return Ok(());
+        }
+    }
+    bail!("unsupported value `{}`", val)
+}
+
+/// Writes an arithmetic term.
+fn write_term<W: Write>(w: &mut W, term: &Term) -> Res<()> {
+    if let Some(var) = term.var_idx() {
+        return write_var(w, var);
+    }
+    if let Some(val) = term.val() {
+        return write_val(w, &val);
+    }
+
+    if let Some((op, args)) = term.app_inspect() {
+        let sep = match op {
+            Op::Add => " + ",
+            Op::Sub if args.len() == 1 => {
+                write!(w, "-(")?;
+                write_term(w, &args[0])?;
+                write!(w, ")")?;
+                return Ok(());
+            }
+            Op::Sub => " - ",
+            Op::Mul | Op::CMul => " * ",
+            Op::IDiv => " // ",
+            Op::Div => " / ",
+            Op::Mod => " mod ",
+            Op::Rem => " rem ",
+            Op::ToReal => return write_term(w, &args[0]),
+            _ => bail!("unsupported term `{}`", term),
+        };
+
+        match op {
+            Op::IDiv | Op::Div | Op::Mod | Op::Rem => {
+                if args[1..].iter().any(|arg| arg.val().is_none()) {
+                    bail!("non-linear term `{}` is not supported", term)
+                }
+            }
+            _ => (),
+        }
+
+        write!(w, "(")?;
+        for (idx, arg) in args.iter().enumerate() {
+            if idx > 0 {
+                write!(w, "{}", sep)?
+            }
+            write_term(w, arg)?
+        }
+        write!(w, ")")?;
+        return Ok(());
+    }
+
+    bail!("unsupported term `{}`", term)
+}
+
+/// Writes an atom, negated if `pos` is false.
+fn write_atom<W: Write>(w: &mut W, term: &Term, pos: bool) -> Res<()> {
+    if let Some(var) = term.var_idx() {
+        if term.typ().is_bool() {
+            write_var(w, var)?;
+            write!(w, " =:= {}", if pos { 1 } else { 0 })?;
+            return Ok(());
+        }
+    }
+
+    if let Some((op, args)) = term.app_inspect() {
+        if op == Op::Not {
+            return write_atom(w, &args[0], !pos);
+        }
+
+        let op = match (op, pos) {
+            (Op::Eql, true) | (Op::Distinct, false) => "=:=",
+            (Op::Eql, false) | (Op::Distinct, true) => "=\\=",
+            (Op::Ge, true) | (Op::Lt, false) => ">=",
+            (Op::Gt, true) | (Op::Le, false) => ">",
+            (Op::Le, true) | (Op::Gt, false) => "=<",
+            (Op::Lt, true) | (Op::Ge, false) => "<",
+            _ => bail!("unsupported atom `{}`", term),
+        };
+        if args.len() != 2 {
+            bail!("unsupported atom `{}`", term)
+        }
+
+        write_term(w, &args[0])?;
+        write!(w, " {} ", op)?;
+        write_term(w, &args[1])?;
+        return Ok(());
+    }
+
+    bail!("unsupported atom `{}`", term)
+}
+
+/// Writes a predicate application.
+///
+/// The arguments are expressed in the current signature of the predicate, the arguments
+/// removed by preprocessing are given their default value.
+fn write_app<W: Write>(w: &mut W, pred: &Pred, args: &VarTerms) -> Res<()> {
+    write_pred_name(w, pred)?;
+    write!(w, "(")?;
+    // The map to the original signature is not necessarily increasing.
+    let mut original_args: VarMap<Option<&Term>> = vec![None; pred.original_sig().len()].into();
+    for (var, arg) in args.index_iter() {
+        original_args[pred.original_sig_map()[var]] = Some(arg)
+    }
+    let mut sep = "";
+    for (var, arg) in original_args.index_iter() {
+        write!(w, "{}", sep)?;
+        if let Some(arg) = arg {
+            write_term(w, arg)?
+        } else {
+            write_val(w, &pred.original_sig()[var].default_val())?
+        }
+        sep = ", "
+    }
+    write!(w, ")")?;
+    Ok(())
+}
+
+/// Writes a rule for a predicate.
+fn write_rule<W: Write>(instance: &Instance, w: &mut W, pred: PrdIdx, body: &[Lit]) -> Res<()> {
+    write_pred_name(w, &instance[pred])?;
+    write!(w, "(")?;
+    let mut sep = "";
+    for (var, typ) in instance[pred].original_sig().index_iter() {
+        if !typ.is_bool() && !typ.is_arith() {
+            bail!("arguments of sort {} are not supported", typ)
+        }
+        write!(w, "{}", sep)?;
+        write_var(w, var)?;
+        sep = ", "
+    }
+    write!(w, ")")?;
+
+    for (idx, lit) in body.iter().enumerate() {
+        write!(w, "{}", if idx == 0 { " :-\n    " } else { ",\n    " })?;
+        match *lit {
+            Lit::Term(ref term) => write_atom(w, term, true)?,
+            Lit::App(pos, app_pred, ref args) => {
+                if !pos {
+                    write!(w, "\\+ ")?
+                }
+                write_app(w, &instance[app_pred], args)?
+            }
+        }
+    }
+    writeln!(w, ".")?;
+    Ok(())
+}
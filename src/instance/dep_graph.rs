@@ -0,0 +1,319 @@
+//! Predicate dependency graph.
+
+use crate::common::*;
+
+/// Predicate dependency graph of an instance.
+///
+/// There is an edge from `p` to `q` if some clause mentions `p` in its lhs and has `q` as its rhs.
+/// The graph is a snapshot: it does not follow the modifications of the instance it was built
+/// from.
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::{ common::*, parse };
+/// let instance = parse::instance("
+///   (declare-fun p ( Int ) Bool)
+///   (declare-fun q ( Int ) Bool)
+///   (declare-fun r ( Int ) Bool)
+///   (assert (forall ( (n Int) ) (=> (= n 0) (p n))))
+///   (assert (forall ( (n Int) ) (=> (q n) (p (+ n 1)))))
+///   (assert (forall ( (n Int) ) (=> (p n) (q (+ n 1)))))
+///   (assert (forall ( (n Int) ) (=> (p n) (r n))))
+///   (assert (forall ( (n Int) ) (=> (q n) (>= n 0))))
+/// ");
+/// let (p, q, r): (PrdIdx, PrdIdx, PrdIdx) = (0.into(), 1.into(), 2.into());
+///
+/// let graph = DepGraph::new(&instance);
+/// assert! { graph.rhs_preds_of(p).contains(&q) }
+/// assert! { graph.lhs_preds_of(p).contains(&q) }
+/// assert! { graph.lhs_preds_of(r).contains(&p) }
+///
+/// // `p` and `q` are mutually recursive, `r` is on its own.
+/// assert_eq! { graph.scc_of(p), graph.scc_of(q) }
+/// assert_ne! { graph.scc_of(p), graph.scc_of(r) }
+/// assert! { graph.is_recursive(p) }
+/// assert! { !graph.is_recursive(r) }
+///
+/// // `r` cannot reach the negative clause.
+/// let relevant = graph.neg_reachable(None);
+/// assert! { relevant.contains(&p) && relevant.contains(&q) }
+/// assert! { !relevant.contains(&r) }
+/// let close = graph.neg_reachable(Some(1));
+/// assert! { close.contains(&q) && !close.contains(&p) }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DepGraph {
+    /// Maps predicates to the rhs predicates of the clauses they appear in the lhs of.
+    rhs_preds: PrdMap<PrdSet>,
+    /// Maps predicates to the lhs predicates of the clauses they are the rhs of.
+    lhs_preds: PrdMap<PrdSet>,
+    /// Predicates appearing in the lhs of a negative clause.
+    neg_preds: PrdSet,
+    /// Predicates appearing in the rhs of a positive clause.
+    pos_preds: PrdSet,
+    /// Strongly connected components, in reverse topological order.
+    sccs: Vec<Vec<PrdIdx>>,
+    /// Maps predicates to the index of their SCC in `sccs`.
+    scc_of: PrdMap<usize>,
+}
+
+impl DepGraph {
+    /// Builds the dependency graph of an instance.
+    pub fn new(instance: &Instance) -> Self {
+        let pred_count = instance.preds().len();
+        let mut rhs_preds: PrdMap<PrdSet> = vec![PrdSet::new(); pred_count].into();
+        let mut lhs_preds: PrdMap<PrdSet> = vec![PrdSet::new(); pred_count].into();
+        let mut neg_preds = PrdSet::new();
+        let mut pos_preds = PrdSet::new();
+
+        for clause in instance.clauses() {
+            if let Some((rhs, _)) = clause.rhs() {
+                if clause.lhs_preds().is_empty() {
+                    pos_preds.insert(rhs);
+                }
+                for lhs in clause.lhs_preds().keys() {
+                    rhs_preds[*lhs].insert(rhs);
+                    lhs_preds[rhs].insert(*lhs);
+                }
+            } else {
+                neg_preds.extend(clause.lhs_preds().keys().cloned())
+            }
+        }
+
+        let (sccs, scc_of) = Self::tarjan(&rhs_preds);
+
+        DepGraph {
+            rhs_preds,
+            lhs_preds,
+            neg_preds,
+            pos_preds,
+            sccs,
+            scc_of,
+        }
+    }
+
+    /// Tarjan's algorithm, iterative version.
+    ///
+    /// Yields the SCCs in reverse topological order: an SCC comes after all the SCCs it has edges
+    /// to.
+    fn tarjan(succs: &PrdMap<PrdSet>) -> (Vec<Vec<PrdIdx>>, PrdMap<usize>) {
+        let pred_count = succs.len();
+        let mut index: PrdMap<Option<usize>> = vec![None; pred_count].into();
+        let mut low: PrdMap<usize> = vec![0; pred_count].into();
+        let mut on_stack: PrdMap<bool> = vec![false; pred_count].into();
+        let mut scc_of: PrdMap<usize> = vec![0; pred_count].into();
+        let mut stack = vec![];
+        let mut sccs = vec![];
+        let mut next_index = 0;
+
+        for (root, _) in succs.index_iter() {
+            if index[root].is_some() {
+                continue;
+            }
+            // Call stack: predicate and its successors left to visit.
+            let mut calls: Vec<(PrdIdx, Vec<PrdIdx>)> = vec![];
+            index[root] = Some(next_index);
+            low[root] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root] = true;
+            calls.push((root, succs[root].iter().cloned().collect()));
+
+            while let Some((pred, mut to_visit)) = calls.pop() {
+                if let Some(succ) = to_visit.pop() {
+                    calls.push((pred, to_visit));
+                    if let Some(succ_index) = index[succ] {
+                        if on_stack[succ] && succ_index < low[pred] {
+                            low[pred] = succ_index
+                        }
+                    } else {
+                        index[succ] = Some(next_index);
+                        low[succ] = next_index;
+                        next_index += 1;
+                        stack.push(succ);
+                        on_stack[succ] = true;
+                        calls.push((succ, succs[succ].iter().cloned().collect()))
+                    }
+                    continue;
+                }
+
+                // Done with `pred`.
+                if Some(low[pred]) == index[pred] {
+                    let mut scc = vec![];
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        scc_of[member] = sccs.len();
+                        scc.push(member);
+                        if member == pred {
+                            break;
+                        }
+                    }
+                    scc.sort();
+                    sccs.push(scc)
+                }
+                if let Some((caller, _)) = calls.last() {
+                    let caller = *caller;
+                    if low[pred] < low[caller] {
+                        low[caller] = low[pred]
+                    }
+                }
+            }
+        }
+
+        (sccs, scc_of)
+    }
+
+    /// Predicates appearing in the rhs of the clauses `pred` appears in the lhs of.
+    pub fn rhs_preds_of(&self, pred: PrdIdx) -> &PrdSet {
+        &self.rhs_preds[pred]
+    }
+
+    /// Predicates appearing in the lhs of the clauses `pred` is the rhs of.
+    pub fn lhs_preds_of(&self, pred: PrdIdx) -> &PrdSet {
+        &self.lhs_preds[pred]
+    }
+
+    /// Predicates appearing in the lhs of a negative clause.
+    pub fn neg_preds(&self) -> &PrdSet {
+        &self.neg_preds
+    }
+
+    /// Predicates appearing in the rhs of a positive clause.
+    pub fn pos_preds(&self) -> &PrdSet {
+        &self.pos_preds
+    }
+
+    /// Strongly connected components, in reverse topological order.
+    ///
+    /// An SCC comes after all the SCCs it depends on, *i.e.* the SCCs of the predicates in the
+    /// rhs of the clauses it appears in the lhs of, come first.
+    pub fn sccs(&self) -> &[Vec<PrdIdx>] {
+        &self.sccs
+    }
+
+    /// Index of the SCC of a predicate in [`sccs`](#method.sccs).
+    pub fn scc_of(&self, pred: PrdIdx) -> usize {
+        self.scc_of[pred]
+    }
+
+    /// True if a predicate depends on itself.
+    pub fn is_recursive(&self, pred: PrdIdx) -> bool {
+        self.sccs[self.scc_of[pred]].len() > 1 || self.rhs_preds[pred].contains(&pred)
+    }
+
+    /// Predicates from which a negative clause is reachable in at most `radius` clauses.
+    ///
+    /// Predicates appearing in a negative clause are at distance `1`, and the predicates in the
+    /// lhs of a clause whose rhs is at distance `d` are at distance `d + 1`. No bound if `radius`
+    /// is `None`.
+    pub fn neg_reachable(&self, radius: Option<usize>) -> PrdSet {
+        let mut reachable = PrdSet::new();
+        let mut frontier = self.neg_preds.clone();
+
+        let mut distance = 1;
+        while !frontier.is_empty() && radius.map(|radius| distance <= radius).unwrap_or(true) {
+            let mut next = PrdSet::new();
+            for pred in frontier {
+                if !reachable.insert(pred) {
+                    continue;
+                }
+                for lhs_pred in &self.lhs_preds[pred] {
+                    if !reachable.contains(lhs_pred) {
+                        next.insert(*lhs_pred);
+                    }
+                }
+            }
+            frontier = next;
+            distance += 1
+        }
+
+        reachable
+    }
+
+    /// Writes the graph in Graphviz DOT format.
+    ///
+    /// The DOT graph has one edge per clause and lhs predicate, going to the rhs predicate and
+    /// labeled with the index of the clause (`unrolled` marks clauses created by unrolling).
+    /// Positive clauses start from a `TRUE` node, negative clauses go to a `FALSE` node. Recursive
+    /// predicates have a double border. `instance` must be the instance the graph was built from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hoice::{ common::*, parse };
+    /// let instance = parse::instance("
+    ///   (declare-fun p ( Int ) Bool)
+    ///   (assert (forall ( (n Int) ) (=> (= n 0) (p n))))
+    ///   (assert (forall ( (n Int) ) (=> (p n) (p (+ n 1)))))
+    ///   (assert (forall ( (n Int) ) (=> (p n) (>= n 0))))
+    /// ");
+    /// let mut dot: Vec<u8> = vec![];
+    /// DepGraph::new(&instance).dot_write(&mut dot, &instance).unwrap();
+    /// let dot = String::from_utf8(dot).unwrap();
+    /// assert! { dot.starts_with("digraph") }
+    /// assert! { dot.contains("p_0 [label = \"p\", peripheries = 2] ;") }
+    /// assert! { dot.contains("TRUE -> p_0 [label = \"#0\", color = darkolivegreen3] ;") }
+    /// assert! { dot.contains("p_0 -> p_0 [label = \"#1\"] ;") }
+    /// assert! { dot.contains("p_0 -> FALSE [label = \"#2\", color = indianred1] ;") }
+    /// ```
+    pub fn dot_write<W: Write>(&self, w: &mut W, instance: &Instance) -> Res<()> {
+        writeln!(w, "digraph dependencies {{")?;
+        writeln!(w, "  rankdir = LR ;")?;
+        writeln!(
+            w,
+            "  TRUE [label = \"TRUE\", shape = box, style = filled, color = darkolivegreen3] ;"
+        )?;
+        writeln!(
+            w,
+            "  FALSE [label = \"FALSE\", shape = box, style = filled, color = indianred1] ;"
+        )?;
+
+        for pred in instance.preds() {
+            if pred.is_defined() {
+                continue;
+            }
+            let peripheries = if self.is_recursive(pred.idx) { 2 } else { 1 };
+            writeln!(
+                w,
+                "  p_{} [label = \"{}\", peripheries = {}] ;",
+                pred.idx,
+                dot_escape(&pred.name),
+                peripheries
+            )?
+        }
+
+        for (idx, clause) in instance.clauses().index_iter() {
+            let label = if clause.from_unrolling {
+                format!("#{} unrolled", idx)
+            } else {
+                format!("#{}", idx)
+            };
+            let (tgt, color) = match clause.rhs() {
+                Some((pred, _)) if clause.lhs_preds().is_empty() => {
+                    (format!("p_{}", pred), ", color = darkolivegreen3")
+                }
+                Some((pred, _)) => (format!("p_{}", pred), ""),
+                None => ("FALSE".into(), ", color = indianred1"),
+            };
+            let mut srcs: Vec<PrdIdx> = clause.lhs_preds().keys().cloned().collect();
+            srcs.sort();
+            let srcs: Vec<String> = if srcs.is_empty() {
+                vec!["TRUE".into()]
+            } else {
+                srcs.into_iter().map(|pred| format!("p_{}", pred)).collect()
+            };
+            for src in srcs {
+                writeln!(w, "  {} -> {} [label = \"{}\"{}] ;", src, tgt, label, color)?
+            }
+        }
+
+        writeln!(w, "}}")?;
+        Ok(())
+    }
+}
+
+/// Escapes a string so that it can appear in a DOT string literal.
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -3,10 +3,17 @@
 use crate::{common::*, data::Data, info::*, var_to::terms::VarTermsSet};
 
 mod clause;
+mod datalog;
+mod dep_graph;
 mod pre_instance;
+mod stats;
+mod view;
 
 pub use self::clause::Clause;
+pub use self::dep_graph::DepGraph;
 pub use self::pre_instance::PreInstance;
+pub use self::stats::InstanceStats;
+pub use self::view::InstanceView;
 
 /// Stores the instance: the clauses, the factory and so on.
 ///
@@ -125,6 +132,16 @@ pub struct Instance {
 
     /// Maps **original** clause indexes to their optional name.
     old_names: ClsHMap<String>,
+    /// Maps **original** clause indexes to their weight, for soft clauses (`:weight`).
+    old_weights: ClsHMap<usize>,
+
+    /// Set-infos parsed, in order: keys (without the colon) and verbatim values.
+    infos: Vec<(String, String)>,
+
+    /// Hints inferred by pre-processing, see `--lin_accel`.
+    ///
+    /// Expressed in terms of the original signature of the predicates.
+    hints: PrdHMap<Vec<Term>>,
 
     /// Print success.
     ///
@@ -170,6 +187,9 @@ impl Instance {
             split: None,
             define_funs: BTreeMap::new(),
             old_names: ClsHMap::with_capacity(clause_capa),
+            old_weights: ClsHMap::new(),
+            infos: vec![],
+            hints: PrdHMap::new(),
             print_success: false,
             unsat_cores: false,
             proofs: false,
@@ -184,7 +204,9 @@ impl Instance {
     /// Fails (in debug) if `clause` is not a negative clause of `self` or if
     /// `self` is not finalized.
     pub fn clone_with_clauses(&self, clause: ClsIdx) -> Self {
-        debug_assert! { self.neg_clauses.contains(& clause) }
+        debug_assert! {
+            self.neg_clauses.contains(& clause) || self.pos_clauses.contains(& clause)
+        }
         debug_assert! { self.is_finalized }
 
         Instance {
@@ -205,6 +227,9 @@ impl Instance {
             split: Some(clause),
             define_funs: self.define_funs.clone(),
             old_names: self.old_names.clone(),
+            old_weights: self.old_weights.clone(),
+            infos: self.infos.clone(),
+            hints: self.hints.clone(),
             print_success: false,
             unsat_cores: false,
             proofs: false,
@@ -337,16 +362,49 @@ impl Instance {
         Ok(model)
     }
 
+    /// Merges the unquantified conjunctions of a conjunction of top terms.
+    ///
+    /// The merged conjunction is simplified, which normalizes its arithmetic bounds.
+    fn merge_conj(conj: Vec<TTerms>) -> Vec<TTerms> {
+        let mut merged = TTermSet::new();
+        let mut res = Vec::with_capacity(conj.len());
+        for tterms in conj {
+            match tterms {
+                TTerms::Conj {
+                    quant: None,
+                    tterms,
+                } => {
+                    let (terms, preds) = tterms.destroy();
+                    merged.insert_terms(terms);
+                    for (pred, argss) in preds {
+                        merged.insert_pred_apps(pred, argss)
+                    }
+                }
+                tterms => res.push(tterms),
+            }
+        }
+        match TTerms::conj(None, merged) {
+            TTerms::True if !res.is_empty() => (),
+            TTerms::False => return vec![TTerms::fls()],
+            tterms => res.insert(0, tterms),
+        }
+        res
+    }
+
     /// Returns the model corresponding to the input predicates and the forced
     /// predicates.
     ///
-    /// The model is sorted in topological order.
+    /// The model is sorted in topological order. The auxiliary predicates introduced by
+    /// pre-processing are inlined away, see [`inline_aux_preds`].
+    ///
+    /// [`inline_aux_preds`]: #method.inline_aux_preds (inline_aux_preds function)
     pub fn extend_model(&self, candidates: ConjCandidates) -> Res<ConjModel> {
         let mut model = ConjModel::with_capacity(self.preds.len());
         let mut known_preds = PrdSet::new();
         let mut tmp: Vec<_> = candidates
             .into_iter()
             .map(|(pred, conj)| {
+                let conj = Self::merge_conj(conj);
                 let mut preds = PrdSet::new();
                 for tterms in &conj {
                     preds.extend(tterms.preds())
@@ -387,9 +445,133 @@ impl Instance {
                 bail!("inconsistency in sorted forced predicates")
             }
         }
+        self.inline_aux_preds(&mut model)?;
         Ok(model)
     }
 
+    /// Inlines the definitions of the auxiliary predicates of a model, see [`Pred::is_aux`].
+    ///
+    /// A definition is inlined if it is a plain term that does not mention the predicate it
+    /// defines. Inlined definitions are removed from the model. Fails if an auxiliary predicate
+    /// has a definition that cannot be inlined, since the model would mention a predicate the
+    /// original instance does not have.
+    ///
+    /// [`Pred::is_aux`]: ../info/struct.Pred.html#method.is_aux (is_aux function on Pred)
+    pub fn inline_aux_preds(&self, model: &mut ConjModel) -> Res<()> {
+        if self.preds.iter().all(|pred| !pred.is_aux()) {
+            return Ok(());
+        }
+
+        let mut defs: PrdHMap<Term> = PrdHMap::new();
+        let capa = model.len();
+        let old_model = ::std::mem::replace(model, ConjModel::with_capacity(capa));
+        for mut group in old_model {
+            for (_, conj) in group.iter_mut() {
+                for tterms in conj.iter_mut() {
+                    self.inline_aux_in(tterms, &defs)?
+                }
+            }
+
+            if group.len() == 1 && self[group[0].0].is_aux() {
+                let (pred, ref conj) = group[0];
+                let def: Option<Vec<Term>> = conj.iter().map(|tterms| tterms.to_term()).collect();
+                if let Some(def) = def {
+                    let prev = defs.insert(pred, term::and(def));
+                    debug_assert! { prev.is_none() }
+                    continue;
+                }
+            }
+            if let Some((pred, _)) = group.iter().find(|(pred, _)| self[*pred].is_aux()) {
+                bail!(
+                    "cannot inline the definition of auxiliary predicate {}",
+                    conf.bad(&self[*pred].name)
+                )
+            }
+            model.push(group)
+        }
+
+        Ok(())
+    }
+
+    /// Inlines some definitions of auxiliary predicates in some top terms.
+    fn inline_aux_in(&self, tterms: &mut TTerms, defs: &PrdHMap<Term>) -> Res<()> {
+        match tterms {
+            TTerms::True | TTerms::False => Ok(()),
+            TTerms::Conj { tterms, .. } => self.inline_aux_in_set(tterms, defs),
+            TTerms::Disj {
+                tterms, neg_preds, ..
+            } => {
+                self.inline_aux_in_set(tterms, defs)?;
+                for (pred, def) in defs {
+                    if let Some(argss) = neg_preds.remove(pred) {
+                        for args in argss {
+                            let term = self.aux_app_def(*pred, def, &args)?;
+                            tterms.insert_term(term::not(term));
+                        }
+                    }
+                }
+                Ok(())
+            }
+            TTerms::Dnf { disj } => {
+                for (_, tterms) in disj {
+                    self.inline_aux_in_set(tterms, defs)?
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Inlines some definitions of auxiliary predicates in a set of top terms.
+    ///
+    /// Works for conjunctions and disjunctions alike.
+    fn inline_aux_in_set(&self, tterms: &mut TTermSet, defs: &PrdHMap<Term>) -> Res<()> {
+        for (pred, def) in defs {
+            if let Some(argss) = tterms.preds_mut().remove(pred) {
+                for args in argss {
+                    let term = self.aux_app_def(*pred, def, &args)?;
+                    tterms.insert_term(term);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Definition of an application of an auxiliary predicate.
+    ///
+    /// The definition is over the original signature of the predicate, the arguments are over
+    /// its current signature.
+    fn aux_app_def(&self, pred: PrdIdx, def: &Term, args: &VarTerms) -> Res<Term> {
+        let mut map = VarHMap::new();
+        for (var, arg) in args.index_iter() {
+            map.insert(self[pred].original_sig_map()[var], arg.clone());
+        }
+        if let Some((term, _)) = def.subst_total(&map) {
+            Ok(term)
+        } else {
+            bail!(
+                "could not inline the definition of auxiliary predicate {}",
+                conf.bad(&self[pred].name)
+            )
+        }
+    }
+
+    /// Factors the guards shared by the disjuncts of the definitions of a model.
+    ///
+    /// Only rewrites the definitions that are plain terms, see [`term::simplify::factor`].
+    ///
+    /// [`term::simplify::factor`]: ../term/simplify/fn.factor.html (factor function)
+    pub fn factor_model(&self, model: &mut ConjModel) {
+        for defs in model {
+            for (_, conj) in defs {
+                for tterms in conj {
+                    if let Some(term) = tterms.to_term() {
+                        *tterms = TTerms::of_term(None, term::simplify::factor(&term))
+                    }
+                }
+            }
+        }
+    }
+
     /// True if the instance is sat, false if unsat.
     fn is_trivial(&self) -> Option<bool> {
         if self.is_unsat {
@@ -439,27 +621,39 @@ impl Instance {
     /// Meaning variables are printed with default printing: `<var_idx>` is printed as
     /// `v_<var_idx>`.
     pub fn print_tterms_as_model<W: Write>(&self, w: &mut W, tterms: &TTerms) -> IoRes<()> {
-        tterms.write(
+        self.print_tterms_as_model_with(w, tterms, None)
+    }
+
+    /// Prints some top terms as a model, with optional let-bindings.
+    pub fn print_tterms_as_model_with<W: Write>(
+        &self,
+        w: &mut W,
+        tterms: &TTerms,
+        bindings: Option<&term::Bindings>,
+    ) -> IoRes<()> {
+        tterms.write_with(
             w,
             |w, var| var.default_write(w),
             |w, pred, args| {
                 let pred = &self[pred];
                 write!(w, "({}", pred)?;
-                let mut prev: VarIdx = 0.into();
+                // The map to the original signature is not necessarily increasing.
+                let mut original_args: VarMap<Option<&Term>> =
+                    vec![None; pred.original_sig().len()].into();
                 for (var, arg) in args.index_iter() {
-                    let old_var = pred.original_sig_map()[var];
-                    for var in VarRange::new(prev, old_var) {
-                        write!(w, " {}", pred.original_sig()[var].default_val())?
-                    }
-                    prev = old_var;
-                    prev.inc();
-                    write!(w, " {}", arg)?
+                    original_args[pred.original_sig_map()[var]] = Some(arg)
                 }
-                for var in VarRange::new(prev, pred.original_sig().next_index()) {
-                    write!(w, " {}", pred.original_sig()[var].default_val())?
+                for (var, arg) in original_args.index_iter() {
+                    write!(w, " ")?;
+                    if let Some(arg) = arg {
+                        arg.write_with(w, |w, var| var.default_write(w), bindings)?
+                    } else {
+                        write!(w, "{}", pred.original_sig()[var].default_val())?
+                    }
                 }
                 write!(w, ")")
             },
+            bindings,
         )
     }
 
@@ -484,6 +678,9 @@ impl Instance {
         let mut tmp: Vec<(PrdIdx, PrdSet)> = Vec::with_capacity(self.preds.len());
 
         for (idx, clause) in self.clauses.index_iter_mut() {
+            // Counterexamples cannot evaluate applications of uninterpreted functions.
+            clause.abstract_uf_args();
+
             if clause.rhs().is_none() {
                 if clause.lhs_pred_apps_len() == 1 {
                     let is_new = self.strict_neg_clauses.insert(idx);
@@ -662,6 +859,109 @@ impl Instance {
         &self.clauses
     }
 
+    /// Summary of the shape of the instance, see [`InstanceStats`].
+    ///
+    /// [`InstanceStats`]: struct.InstanceStats.html (InstanceStats struct)
+    pub fn stats(&self) -> InstanceStats {
+        InstanceStats::new(self)
+    }
+
+    /// Read-only view of the clause structure, see [`InstanceView`].
+    ///
+    /// [`InstanceView`]: struct.InstanceView.html (InstanceView struct)
+    pub fn view(&self) -> InstanceView {
+        InstanceView::new(self)
+    }
+
+    /// Hash of the current shape of the instance.
+    ///
+    /// Depends on the signature and definition of the predicates, and on the terms and predicate
+    /// applications of the clauses. It does not depend on the order of the clauses, or of the
+    /// elements of their lhs. Two instances with the same shape hash are (very likely)
+    /// syntactically equal. Terms are hashed by their hashconsing identifier, so the hash is only
+    /// meaningful within a run.
+    pub fn shape_hash(&self) -> u64 {
+        use std::{
+            collections::hash_map::DefaultHasher,
+            hash::{Hash, Hasher},
+        };
+
+        /// Hash of something.
+        fn hash_of<T: Hash + ?Sized>(t: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            t.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        /// Hashes of some predicate applications, sorted.
+        fn apps_hashes(apps: &PredApps) -> Vec<u64> {
+            let mut hashes: Vec<u64> = apps
+                .iter()
+                .flat_map(|(pred, argss)| argss.iter().map(move |args| hash_of(&(pred, args))))
+                .collect();
+            hashes.sort_unstable();
+            hashes
+        }
+
+        /// Order-independent hash of some top terms.
+        fn tterms_hash(quant: &Option<Quant>, tterms: &TTermSet) -> u64 {
+            let (tag, vars) = match quant {
+                None => (0u8, None),
+                Some(Quant::Exists(vars)) => (1, Some(vars)),
+                Some(Quant::Forall(vars)) => (2, Some(vars)),
+            };
+            let mut vars: Vec<_> = vars.into_iter().flat_map(|vars| vars.iter()).collect();
+            vars.sort_unstable_by_key(|(var, _)| **var);
+            let mut terms: Vec<u64> = tterms.terms().iter().map(hash_of).collect();
+            terms.sort_unstable();
+            hash_of(&(tag, vars, terms, apps_hashes(tterms.preds())))
+        }
+
+        let mut hasher = DefaultHasher::new();
+
+        for pred in &self.preds {
+            pred.name.hash(&mut hasher);
+            for typ in pred.sig().iter() {
+                typ.hash(&mut hasher)
+            }
+            match pred.def() {
+                None => 0u8.hash(&mut hasher),
+                Some(TTerms::True) => 1u8.hash(&mut hasher),
+                Some(TTerms::False) => 2u8.hash(&mut hasher),
+                Some(TTerms::Conj { quant, tterms }) => {
+                    (3u8, tterms_hash(quant, tterms)).hash(&mut hasher)
+                }
+                Some(TTerms::Disj {
+                    quant,
+                    tterms,
+                    neg_preds,
+                }) => (4u8, tterms_hash(quant, tterms), apps_hashes(neg_preds)).hash(&mut hasher),
+                Some(TTerms::Dnf { disj }) => {
+                    let mut disj: Vec<u64> = disj
+                        .iter()
+                        .map(|(quant, tterms)| tterms_hash(quant, tterms))
+                        .collect();
+                    disj.sort_unstable();
+                    (5u8, disj).hash(&mut hasher)
+                }
+            }
+        }
+
+        let mut clauses: Vec<u64> = self
+            .clauses
+            .iter()
+            .map(|clause| {
+                let mut terms: Vec<u64> = clause.lhs_terms().iter().map(hash_of).collect();
+                terms.sort_unstable();
+                hash_of(&(terms, apps_hashes(clause.lhs_preds()), clause.rhs()))
+            })
+            .collect();
+        clauses.sort_unstable();
+        clauses.hash(&mut hasher);
+
+        hasher.finish()
+    }
+
     /// Pushes a new predicate and returns its index.
     pub fn push_pred<S: Into<String>>(&mut self, name: S, sig: Sig) -> PrdIdx {
         let idx = self.preds.next_index();
@@ -709,6 +1009,53 @@ impl Instance {
         Ok(())
     }
 
+    /// Renumbers the clauses in the order of the original clauses they come from.
+    ///
+    /// Clauses coming from the same original clause keep their relative order. Predicate indices
+    /// do not change, they are shared with the original instance. The clause sets of the
+    /// predicates are remapped, and the map from the old clause indices to the new ones is
+    /// returned so that callers can remap the clause indices they keep outside of the instance.
+    ///
+    /// Fails if the instance is already finalized, since the clause classification done by
+    /// `finalize` would be out of date.
+    pub fn compact_clauses(&mut self) -> Res<ClsMap<ClsIdx>> {
+        if self.is_finalized {
+            bail!("cannot compact the clauses of a finalized instance")
+        }
+
+        let mut order: Vec<ClsIdx> = self.clauses.index_iter().map(|(idx, _)| idx).collect();
+        // Stable sort, keeps the relative order of clauses from the same original clause.
+        order.sort_by_key(|idx| self.clauses[*idx].from());
+
+        let zero: ClsIdx = 0.into();
+        let mut map: ClsMap<ClsIdx> = vec![zero; self.clauses.len()].into();
+        for (new, old) in order.iter().enumerate() {
+            map[*old] = new.into()
+        }
+
+        let mut clauses: Vec<Option<Clause>> = Vec::with_capacity(self.clauses.len());
+        while let Some(clause) = self.clauses.pop() {
+            clauses.push(Some(clause))
+        }
+        clauses.reverse();
+        for old in order {
+            let clause = clauses[*old].take().expect("clause indices are unique");
+            self.clauses.push(clause)
+        }
+
+        for (lhs, rhs) in self.pred_to_clauses.iter_mut() {
+            *lhs = lhs.iter().map(|clause| map[*clause]).collect();
+            *rhs = rhs.iter().map(|clause| map[*clause]).collect()
+        }
+
+        Ok(map)
+    }
+
+    /// True if the instance is finalized.
+    pub fn is_finalized(&self) -> bool {
+        self.is_finalized
+    }
+
     /// Forget some clauses.
     ///
     /// Duplicates are handled as if there was only one.
@@ -777,6 +1124,50 @@ impl Instance {
         self.old_names.get(&cls)
     }
 
+    /// Names of the original clauses mentioning each predicate, see `:named`.
+    ///
+    /// Names are in the order of the clauses, without duplicates. Pre-processing removes
+    /// clauses, so this is only complete on the instance as it was parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::{ common::*, parse };
+    /// let instance = parse::instance("
+    ///   (declare-fun p ( Int ) Bool)
+    ///   (declare-fun q ( Int ) Bool)
+    ///   (assert (! (forall ( (n Int) ) (=> (= n 0) (p n))) :named init))
+    ///   (assert (! (forall ( (n Int) ) (=> (p n) (and (p (+ n 1)) (q n)))) :named step))
+    ///   (assert (forall ( (n Int) ) (=> (and (q n) (< n 0)) false)))
+    /// ");
+    /// let (p, q): (PrdIdx, PrdIdx) = (0.into(), 1.into());
+    /// let names = instance.clause_names_of_preds();
+    /// assert_eq! { names[&p], vec!["init".to_string(), "step".to_string()] }
+    /// assert_eq! { names[&q], vec!["step".to_string()] }
+    /// ```
+    pub fn clause_names_of_preds(&self) -> PrdHMap<Vec<String>> {
+        let mut res: PrdHMap<Vec<String>> = PrdHMap::new();
+        for clause in self.clauses.iter() {
+            let name = if let Some(name) = self.old_names.get(&clause.from()) {
+                name
+            } else {
+                continue;
+            };
+            let preds = clause
+                .lhs_preds()
+                .keys()
+                .cloned()
+                .chain(clause.rhs().map(|(pred, _)| pred));
+            for pred in preds {
+                let names = res.entry(pred).or_insert_with(Vec::new);
+                if !names.contains(name) {
+                    names.push(name.clone())
+                }
+            }
+        }
+        res
+    }
+
     /// Sets the name for an original clause.
     pub fn set_old_clause_name(&mut self, cls: ClsIdx, name: String) -> Res<()> {
         let prev = self.old_names.insert(cls, name);
@@ -790,6 +1181,29 @@ impl Instance {
         Ok(())
     }
 
+    /// The weight of an original clause, if it is a soft clause.
+    pub fn weight_of_old_clause(&self, cls: ClsIdx) -> Option<usize> {
+        self.old_weights.get(&cls).cloned()
+    }
+
+    /// Sets the weight of an original clause, making it a soft clause.
+    pub fn set_old_clause_weight(&mut self, cls: ClsIdx, weight: usize) -> Res<()> {
+        let prev = self.old_weights.insert(cls, weight);
+        if let Some(prev) = prev {
+            bail!(format!(
+                "trying to set the weight of clause #{}, but it already has weight {}",
+                cls,
+                conf.bad(&prev.to_string())
+            ))
+        }
+        Ok(())
+    }
+
+    /// True if some original clauses are soft clauses.
+    pub fn has_soft_clauses(&self) -> bool {
+        !self.old_weights.is_empty()
+    }
+
     /// Mutable accessor for side clauses.
     ///
     /// Does not expose function invariants.
@@ -1125,6 +1539,9 @@ impl Instance {
     }
 
     /// Dumps the instance as an SMT-LIB 2 problem.
+    ///
+    /// The strengthening terms of the predicates are written as clauses, so that a solution of the
+    /// dump is a solution of the instance.
     pub fn dump_as_smt2<File, Blah>(&self, w: &mut File, blah: Blah) -> Res<()>
     where
         File: Write,
@@ -1136,6 +1553,7 @@ impl Instance {
             writeln!(w, "; {}", line)?
         }
         writeln!(w)?;
+        self.write_infos(w)?;
         writeln!(w, "(set-logic HORN)")?;
         writeln!(w)?;
 
@@ -1167,10 +1585,6 @@ impl Instance {
 
         for (pred_idx, pred) in self.preds.index_iter() {
             if !self[pred_idx].is_defined() {
-                if let Some(term) = self[pred_idx].strength() {
-                    writeln!(w, "; Strengthening term:")?;
-                    writeln!(w, ";   {}", term)?
-                }
                 write!(w, "({}\n  {}\n  (", keywords::cmd::dec_fun, pred.name)?;
                 for typ in &pred.sig {
                     write!(w, " {}", typ)?
@@ -1224,6 +1638,41 @@ impl Instance {
             writeln!(w)?
         }
 
+        // Strengthening terms come from clauses that have been removed, they must appear in the
+        // dump for it to be equivalent to the instance.
+        for pred in &self.preds {
+            let term = match pred.strength() {
+                Some(term) if !pred.is_defined() => term,
+                _ => continue,
+            };
+            writeln!(w, "\n; Strengthening of `{}`", pred.name)?;
+            write!(w, "(assert\n  ")?;
+            if !pred.sig.is_empty() {
+                write!(w, "(forall (")?;
+                for (var, typ) in pred.sig.index_iter() {
+                    write!(w, " ({} {})", var.default_str(), typ)?
+                }
+                write!(w, " )\n    ")?
+            }
+            write!(w, "(=>\n      ")?;
+            if pred.sig.is_empty() {
+                write!(w, "{}", pred.name)?
+            } else {
+                write!(w, "({}", pred.name)?;
+                for (var, _) in pred.sig.index_iter() {
+                    write!(w, " {}", var.default_str())?
+                }
+                write!(w, ")")?
+            }
+            write!(w, "\n      ")?;
+            term.write(w, |w, var| var.default_write(w))?;
+            write!(w, "\n    )")?;
+            if !pred.sig.is_empty() {
+                write!(w, "\n  )")?
+            }
+            writeln!(w, "\n)")?
+        }
+
         writeln!(w, "\n(check-sat)")?;
 
         Ok(())
@@ -1261,21 +1710,73 @@ impl Instance {
 
     /// Writes a conjunction of top terms.
     pub fn write_tterms_conj<W: Write>(&self, w: &mut W, conj: &[TTerms]) -> Res<()> {
+        self.write_tterms_conj_with(w, conj, None)
+    }
+
+    /// Writes a conjunction of top terms, with optional let-bindings.
+    ///
+    /// Does not write the let-bindings themselves, only uses them.
+    fn write_tterms_conj_with<W: Write>(
+        &self,
+        w: &mut W,
+        conj: &[TTerms],
+        bindings: Option<&term::Bindings>,
+    ) -> Res<()> {
         if conj.is_empty() {
             write!(w, "true")?
         } else if conj.len() == 1 {
-            self.print_tterms_as_model(w, &conj[0])?
+            self.print_tterms_as_model_with(w, &conj[0], bindings)?
         } else {
             write!(w, "(and")?;
             for tterms in conj {
                 write!(w, " ")?;
-                self.print_tterms_as_model(w, tterms)?
+                self.print_tterms_as_model_with(w, tterms, bindings)?
             }
             write!(w, ")")?
         }
         Ok(())
     }
 
+    /// Let-bindings for the definition of a predicate, if `conf.model_let` is active.
+    ///
+    /// Binds the subterms appearing more than `conf.model_let_min` times. `None` if there is
+    /// nothing to bind, or if the definition is quantified.
+    fn def_bindings(&self, pred: PrdIdx, conj: &[TTerms]) -> Option<term::Bindings> {
+        if !conf.model_let || conj.iter().any(|tterms| tterms.is_quantified()) {
+            return None;
+        }
+        conj.iter()
+            .fold(term::bindings::Builder::new(), |builder, tterms| {
+                builder.scan_tterms(tterms)
+            })
+            .build_above(self[pred].original_sig().next_index(), conf.model_let_min)
+    }
+
+    /// Writes the body of a definition, with let-bindings if `conf.model_let` is active.
+    ///
+    /// Starts and ends at the beginning of a line.
+    fn write_def_body<W: Write>(
+        &self,
+        w: &mut W,
+        pref: &str,
+        pred: PrdIdx,
+        conj: &[TTerms],
+    ) -> Res<()> {
+        if let Some(bindings) = self.def_bindings(pred, conj) {
+            let pref = format!("{}  ", pref);
+            bindings.write_opening(w, |w, var| var.default_write(w), &pref)?;
+            write!(w, "{}", pref)?;
+            self.write_tterms_conj_with(w, conj, Some(&bindings))?;
+            writeln!(w)?;
+            bindings.write_closing(w, &pref)?
+        } else {
+            write!(w, "{}  ", pref)?;
+            self.write_tterms_conj(w, conj)?;
+            writeln!(w)?
+        }
+        Ok(())
+    }
+
     /// Writes a predicate signature.
     ///
     /// Does not write the name of the predicate.
@@ -1296,7 +1797,16 @@ impl Instance {
         model: ConjModelRef,
     ) -> Res<()> {
         fun::write_for_model(w, pref, &model)?;
+        self.write_pred_definitions(w, pref, model)
+    }
 
+    /// Writes some predicate definitions, without the functions they mention.
+    pub fn write_pred_definitions<W: Write>(
+        &self,
+        w: &mut W,
+        pref: &str,
+        model: ConjModelRef,
+    ) -> Res<()> {
         for defs in model {
             if defs.is_empty() {
                 ()
@@ -1306,21 +1816,20 @@ impl Instance {
                 writeln!(w, "{}({} {}", pref, keywords::cmd::def_fun, self[pred].name)?;
                 write!(w, "{}  ", pref)?;
                 self.write_pred_sig(w, pred)?;
-                write!(w, "\n{}  ", pref)?;
-                self.write_tterms_conj(w, tterms)?;
-                writeln!(w, "\n{})", pref)?
+                writeln!(w)?;
+                self.write_def_body(w, pref, pred, tterms)?;
+                writeln!(w, "{})", pref)?
             } else {
                 write!(w, "{}({} (", pref, keywords::cmd::def_funs_rec)?;
                 for &(pred, _) in defs {
                     write!(w, "\n{}  {} ", pref, self[pred].name)?;
                     self.write_pred_sig(w, pred)?;
                 }
-                write!(w, "\n{}) (", pref)?;
-                for &(_, ref tterms) in defs {
-                    write!(w, "\n{}  ", pref)?;
-                    self.write_tterms_conj(w, tterms)?;
+                writeln!(w, "\n{}) (", pref)?;
+                for &(pred, ref tterms) in defs {
+                    self.write_def_body(w, pref, pred, tterms)?;
                 }
-                writeln!(w, "\n{}) )", pref)?;
+                writeln!(w, "{}) )", pref)?;
             }
         }
 
@@ -1335,6 +1844,47 @@ impl Instance {
         Ok(())
     }
 
+    /// Writes a model as Prolog/Datalog rules.
+    ///
+    /// Each predicate definition is put in DNF, each disjunct yields a rule. Booleans are encoded
+    /// as `0`/`1`. Fails on quantified or non-linear definitions.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use hoice::{ common::*, parse };
+    /// let instance = parse::instance("
+    ///   (declare-fun p_1 ( Int Int ) Bool)
+    ///   (assert
+    ///     (forall ( (n Int) (m Int) )
+    ///       (=> (p_1 n m) (> n m))
+    ///     )
+    ///   )
+    /// ");
+    /// let pred: PrdIdx = 0.into();
+    /// let (v_0, v_1) = (term::int_var(0), term::int_var(1));
+    ///
+    /// let def = term::or(vec![
+    ///     term::ge(v_0.clone(), term::int(1)),
+    ///     term::ge(v_1.clone(), term::int(2)),
+    /// ]);
+    /// let model = vec![vec![(pred, vec![TTerms::of_term(None, def)])]];
+    /// let mut s: Vec<u8> = vec![];
+    /// instance.write_datalog_model(&model, &mut s).unwrap();
+    /// let s = String::from_utf8_lossy(&s);
+    /// assert! { s.contains("p_1(V_0, V_1) :-\n    V_0 >= 1.\n") }
+    /// assert! { s.contains("p_1(V_0, V_1) :-\n    V_1 >= 2.\n") }
+    /// assert_eq! { s.lines().count(), 4 }
+    ///
+    /// let def = term::ge(term::mul(vec![v_0, v_1]), term::int(0));
+    /// let model = vec![vec![(pred, vec![TTerms::of_term(None, def)])]];
+    /// let mut s: Vec<u8> = vec![];
+    /// assert! { instance.write_datalog_model(&model, &mut s).is_err() }
+    /// ```
+    pub fn write_datalog_model<W: Write>(&self, model: ConjModelRef, w: &mut W) -> Res<()> {
+        self::datalog::write_model(self, model, w)
+    }
+
     /// Sets print-success flag.
     pub fn set_print_success(&mut self, b: bool) {
         self.print_success = b
@@ -1366,6 +1916,51 @@ impl Instance {
         false // self.unsat_cores() || self.proofs()
     }
 
+    /// Positive samples of the original instance needed to derive a sample.
+    ///
+    /// - `original`: the instance before preprocessing
+    /// - `sample`: a sample of a predicate of this instance, over its current signature
+    ///
+    /// Runs the proof reconstruction on `sample` alone, independently of any unsat result. The
+    /// result is in terms of the original signatures, and is minimized if `conf.proof_minimize`,
+    /// see [`Entry::reconstruct`].
+    ///
+    /// [`Entry::reconstruct`]: ../unsat_core/entry_points/struct.Entry.html#method.reconstruct
+    /// (reconstruct method)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[macro_use]
+    /// extern crate hoice;
+    /// use hoice::{ common::*, data::sample::Sample, parse, var_vals };
+    ///
+    /// # fn main() {
+    /// let instance = parse::instance("
+    ///   (declare-fun p ( Int ) Bool)
+    ///   (assert (forall ( (n Int) ) (=> (= n 0) (p n))))
+    ///   (assert (forall ( (n Int) ) (=> (and (p n) (>= n 0)) (p (+ n 1)))))
+    /// ");
+    ///
+    /// let sample = Sample::new(0.into(), var_vals!( (int 2) ));
+    /// let entry = instance.reconstruct_sample(&instance, sample).unwrap();
+    /// assert_eq! { entry.samples.len(), 1 }
+    /// assert! { entry.samples.contains(&Sample::new(0.into(), var_vals!( (int 0) ))) }
+    /// # }
+    /// ```
+    pub fn reconstruct_sample(
+        &self,
+        original: &Instance,
+        sample: crate::data::sample::Sample,
+    ) -> Res<crate::unsat_core::entry_points::Entry> {
+        use crate::unsat_core::entry_points::{Entry, SampleSet};
+        let mut samples = SampleSet::new();
+        samples.insert(sample);
+        Entry::new(samples)
+            .reconstruct(self, original)
+            .chain_err(|| "during sample reconstruction")
+    }
+
     /// Converts `"true"` to `true`, `"false"` to `false`, and everything else to
     /// an error.
     fn bool_of_str(s: &str) -> Res<bool> {
@@ -1376,6 +1971,83 @@ impl Instance {
         }
     }
 
+    /// Registers a set-info, the value is kept verbatim.
+    pub fn set_info(&mut self, key: &str, val: &str) {
+        self.infos.push((key.into(), val.into()))
+    }
+
+    /// The set-infos parsed, in order.
+    pub fn infos(&self) -> &[(String, String)] {
+        &self.infos
+    }
+
+    /// Registers some hints for a predicate, expressed in terms of its current signature.
+    ///
+    /// The teacher checks them before learning anything, see `--hint`.
+    pub fn add_hints(&mut self, pred: PrdIdx, hints: Vec<Term>) {
+        let mut map = VarHMap::with_capacity(self[pred].sig().len());
+        for (var, typ) in self[pred].sig().index_iter() {
+            map.insert(
+                var,
+                term::var(self[pred].original_sig_map()[var], typ.clone()),
+            );
+        }
+        let res = self.hints.entry(pred).or_insert_with(Vec::new);
+        for hint in hints {
+            let hint = hint.subst(&map).0;
+            if !res.contains(&hint) {
+                res.push(hint)
+            }
+        }
+    }
+
+    /// Hints inferred by pre-processing or given through the library API, expressed in terms of
+    /// the original signature of the predicates.
+    pub fn hints(&self) -> &PrdHMap<Vec<Term>> {
+        &self.hints
+    }
+
+    /// Expected status of the instance, if any.
+    ///
+    /// Given by `(set-info :status sat/unsat)`, the last one wins.
+    ///
+    /// ```
+    /// # use hoice::common::*;
+    /// let mut instance = Instance::new();
+    /// assert_eq! { instance.status(), None }
+    /// instance.set_info("source", "|some benchmark|");
+    /// instance.set_info("status", "unsat");
+    /// assert_eq! { instance.status(), Some(false) }
+    /// instance.set_info("status", "unknown");
+    /// assert_eq! { instance.status(), None }
+    /// ```
+    pub fn status(&self) -> Option<bool> {
+        self.infos
+            .iter()
+            .rev()
+            .find(|(key, _)| key == "status")
+            .and_then(|(_, val)| match &val[..] {
+                "sat" => Some(true),
+                "unsat" => Some(false),
+                _ => None,
+            })
+    }
+
+    /// Writes the set-infos, verbatim.
+    pub fn write_infos<W: Write>(&self, w: &mut W) -> Res<()> {
+        if !self.infos.is_empty() {
+            for (key, val) in &self.infos {
+                if val.is_empty() {
+                    writeln!(w, "(set-info :{})", key)?
+                } else {
+                    writeln!(w, "(set-info :{} {})", key, val)?
+                }
+            }
+            writeln!(w)?
+        }
+        Ok(())
+    }
+
     /// Sets an option.
     pub fn set_option(&mut self, flag: &str, val: &str) -> Res<()> {
         let flag_err = || format!("while handling set-option for {}", flag);
@@ -1684,7 +2356,11 @@ impl<'a> PebcakFmt<'a> for Instance {
                     write!(w, " {}", typ)?
                 }
                 writeln!(w, " ) Bool\n)")?;
-                if pred.sig.len() != pred.original_sig().len() {
+                let permuted = pred
+                    .original_sig_map()
+                    .index_iter()
+                    .any(|(src, tgt)| src != *tgt);
+                if pred.sig.len() != pred.original_sig().len() || permuted {
                     write!(w, "; original signature:\n;")?;
                     for (var, typ) in pred.original_sig().index_iter() {
                         write!(w, " ({} {})", var.default_str(), typ)?
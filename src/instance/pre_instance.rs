@@ -651,6 +651,78 @@ impl<'a> PreInstance<'a> {
         })
     }
 
+    /// Distributes the top-level disjunctions of the lhs of a clause.
+    ///
+    /// Replaces the clause by one clause per combination of disjuncts, unless
+    /// there are more than `max` such combinations in which case the clause is
+    /// left untouched. Boolean if-then-elses are treated as disjunctions. The
+    /// clauses created have the same origin as the original clause, and are
+    /// tagged as coming from `disj_split`.
+    ///
+    /// Potentially voids the semantics of clause indices `>= clause`.
+    ///
+    /// # Used by
+    ///
+    /// - `DisjSplit`
+    pub fn split_disjs(&mut self, clause: ClsIdx, max: usize) -> Res<RedInfo> {
+        let mut info = RedInfo::new();
+
+        let mut disjs = vec![];
+        let mut combinations = 1usize;
+        for term in self.instance[clause].lhs_terms() {
+            let subs = if let Some(subs) = term.disj_inspect() {
+                subs.clone()
+            } else if let Some((c, t, e)) = term.ite_inspect() {
+                vec![
+                    term::and(vec![c.clone(), t.clone()]),
+                    term::and(vec![term::not(c.clone()), e.clone()]),
+                ]
+            } else {
+                continue;
+            };
+            combinations = combinations.saturating_mul(subs.len());
+            disjs.push((term.clone(), subs))
+        }
+
+        if disjs.is_empty() || combinations > max {
+            return Ok(info);
+        }
+
+        log! { @4
+          "splitting clause #{} into {} clause(s)", clause, combinations
+        }
+
+        let mut original = self.instance.forget_clause(clause)?;
+        info.clauses_rmed += 1;
+        for (disj, _) in &disjs {
+            let was_there = original.rm_term(disj);
+            debug_assert! { was_there }
+        }
+        original.info = "disj_split";
+
+        let mut clauses = vec![original];
+        for (_, subs) in disjs {
+            let mut nu_clauses = Vec::with_capacity(clauses.len() * subs.len());
+            for clause in clauses {
+                for sub in &subs {
+                    let mut clause = clause.clone();
+                    clause.insert_term(sub.clone());
+                    nu_clauses.push(clause)
+                }
+            }
+            clauses = nu_clauses
+        }
+
+        for clause in clauses {
+            info.clauses_added += 1;
+            if let Some(idx) = self.instance.push_clause(clause)? {
+                info += self.simplify_clause(idx)?
+            }
+        }
+
+        Ok(info)
+    }
+
     /// Removes redundant atoms.
     fn prune_atoms(&mut self, clause: ClsIdx) -> Res<()> {
         let atoms: Vec<Term> = self.instance[clause].lhs_terms().iter().cloned().collect();
@@ -697,7 +769,13 @@ impl<'a> PreInstance<'a> {
     ///
     /// - the terms in the lhs are equivalent to `false`, or
     /// - the rhs is a predicate application contained in the lhs.
-    fn is_clause_trivial(&mut self, clause_idx: ClsIdx) -> Res<bool> {
+    ///
+    /// Fails with [`ErrorKind::UnsatFrom`] if the clause has no predicate applications and its
+    /// lhs is satisfiable.
+    ///
+    /// [`ErrorKind::UnsatFrom`]: ../errors/enum.ErrorKind.html#variant.UnsatFrom
+    /// (ErrorKind's UnsatFrom variant)
+    pub fn is_clause_trivial(&mut self, clause_idx: ClsIdx) -> Res<bool> {
         if self.reset_solver {
             smt::reset(&mut self.solver, &self.instance)?;
         } else {
@@ -929,6 +1007,82 @@ impl<'a> PreInstance<'a> {
         Ok(info)
     }
 
+    /// Merges a predicate into another one with the same signature.
+    ///
+    /// All occurrences of `pred` are replaced by applications of `into`, and `pred` is defined
+    /// as `into`. Only sound if `pred` and `into` are interchangeable in the instance.
+    ///
+    /// Simplifies the clauses before returning.
+    ///
+    /// # Used by
+    ///
+    /// - `EqualPreds`
+    pub fn merge_pred(&mut self, pred: PrdIdx, into: PrdIdx) -> Res<RedInfo> {
+        self.check("before `merge_pred`")?;
+
+        if self.instance[pred].sig != self.instance[into].sig {
+            bail!(
+                "illegal context for `merge_pred`, {} and {} have different signatures",
+                conf.emph(&self.instance[pred].name),
+                conf.emph(&self.instance[into].name)
+            )
+        }
+
+        let mut info = RedInfo::new();
+
+        log_debug! {
+          "merging {} into {}...",
+          conf.emph(& self.instance[pred].name), conf.emph(& self.instance[into].name)
+        }
+
+        // Update lhs clauses.
+        debug_assert! { self.clauses_to_simplify.is_empty() }
+        self.instance
+            .unlink_pred_lhs(pred, &mut self.clauses_to_simplify);
+        for clause in &self.clauses_to_simplify {
+            let clause = *clause;
+            let argss = if let Some(argss) = self.instance.clauses[clause].drop_lhs_pred(pred) {
+                argss
+            } else {
+                bail!(
+                    "inconsistent instance state, \
+                     `pred_to_clauses` and clauses out of sync"
+                )
+            };
+            for args in argss {
+                self.instance
+                    .clause_add_lhs_pred(clause, into, args.iter().cloned().collect())
+            }
+        }
+
+        // Update rhs clauses.
+        let mut rhs_clauses = vec![];
+        self.instance.unlink_pred_rhs(pred, &mut rhs_clauses);
+        for clause in rhs_clauses {
+            if let Some((_, args)) = self.instance.clauses[clause].unset_rhs() {
+                self.instance
+                    .clause_force_rhs(clause, into, args.iter().cloned().collect())?
+            }
+            self.clauses_to_simplify.push(clause)
+        }
+
+        // Actually force the predicate.
+        let args: VarMap<Term> = self.instance[pred]
+            .sig
+            .index_iter()
+            .map(|(var, typ)| term::var(var, typ.clone()))
+            .collect();
+        let mut tterm_set = TTermSet::new();
+        tterm_set.insert_pred_app(into, var_to::terms::new(args));
+        self.force_pred(pred, TTerms::conj(None, tterm_set))?;
+
+        info += self.simplify_clauses()?;
+
+        self.check("after `merge_pred`")?;
+
+        Ok(info)
+    }
+
     /// Forces the lhs occurences of a predicate to be equal to something.
     ///
     /// If `pred` appears in `pred /\ apps /\ trms => rhs`, the clause will
@@ -1625,7 +1779,7 @@ impl<'a> PreInstance<'a> {
                     .clause_propagate(&mut nu_clause, self.instance.preds())?;
 
                 if !nu_clause.lhs_terms().contains(&fls) {
-                    // nu_clause.from_unrolling = true ;
+                    nu_clause.from_unrolling = true;
                     to_add.push(nu_clause)
                 }
             }
@@ -1651,6 +1805,81 @@ impl<'a> PreInstance<'a> {
         Ok(info)
     }
 
+    /// Factors some lhs top terms shared by some clauses.
+    ///
+    /// Introduces an auxiliary predicate `aux` over the variables of `common`, adds the clause
+    /// `common => (aux vars)` and replaces `common` by `(aux vars)` in the lhs of `clauses`. All
+    /// the clauses must have the top terms of `common` in their lhs, with the same variables.
+    ///
+    /// Returns the auxiliary predicate. Does not simplify the clauses.
+    pub fn factor_lhs(&mut self, clauses: &[ClsIdx], common: &[TTerm]) -> Res<(PrdIdx, RedInfo)> {
+        let mut info = RedInfo::new();
+        let clause_vars = if let Some(clause) = clauses.first() {
+            self.instance[*clause].vars().clone()
+        } else {
+            bail!("trying to factor the lhs of an empty set of clauses")
+        };
+
+        let mut var_set = VarSet::new();
+        for tterm in common {
+            var_set.extend(tterm.vars())
+        }
+        let mut vars: Vec<VarIdx> = var_set.iter().cloned().collect();
+        vars.sort();
+
+        let sig: Sig = vars
+            .iter()
+            .map(|var| clause_vars[*var].typ.clone())
+            .collect();
+        let args: VarMap<Term> = vars
+            .iter()
+            .map(|var| term::var(*var, clause_vars[*var].typ.clone()))
+            .collect();
+        let args: VarTerms = args.into();
+
+        let mut name = format!("hoice_factor_{}", self.instance.preds.next_index());
+        while self.instance.preds.iter().any(|pred| pred.name == name) {
+            name.push('_')
+        }
+        let aux = self.instance.push_pred(name, sig);
+        self.instance.preds[aux].set_aux();
+
+        let mut to_add = Vec::with_capacity(clauses.len());
+        for clause in clauses {
+            to_add.push(self.instance[*clause].clone_factoring(
+                common,
+                (aux, args.clone()),
+                "factoring",
+            )?)
+        }
+
+        let mut def_vars = clause_vars;
+        for var_info in def_vars.iter_mut() {
+            var_info.active = var_info.active && var_set.contains(&var_info.idx)
+        }
+        if self
+            .instance
+            .push_new_clause(def_vars, common.to_vec(), Some((aux, args)), "factoring")?
+            .is_some()
+        {
+            info.clauses_added += 1
+        }
+
+        let mut to_rm = clauses.to_vec();
+        info.clauses_rmed += to_rm.len();
+        self.instance.forget_clauses(&mut to_rm)?;
+
+        for clause in to_add {
+            if self.instance.push_clause(clause)?.is_some() {
+                info.clauses_added += 1
+            }
+        }
+
+        self.check("after factoring")?;
+
+        Ok((aux, info))
+    }
+
     /// Removes some arguments for a predicate.
     ///
     /// Returns `true` if something happened.
@@ -1720,6 +1949,56 @@ impl<'a> PreInstance<'a> {
         Ok(rmed)
     }
 
+    /// Permutes the arguments of a predicate.
+    ///
+    /// `perm` maps the variables of the new signature to the variables of the current one, it
+    /// must be a permutation. The map to the original signature is updated so that models are
+    /// reconstructed properly. Fails if the predicate is defined or has a strengthening term,
+    /// since they are expressed on its current signature.
+    pub fn permute_args_of(&mut self, pred: PrdIdx, perm: &VarMap<VarIdx>) -> Res<()> {
+        if self[pred].is_defined() || self[pred].strength().is_some() {
+            bail!(
+                "cannot permute the arguments of {}, it is defined or strengthened",
+                self[pred]
+            )
+        }
+        let mut seen = VarSet::with_capacity(perm.len());
+        for old in perm.iter() {
+            if old.get() >= perm.len() || !seen.insert(*old) {
+                bail!("illegal permutation for the arguments of {}", self[pred])
+            }
+        }
+        if perm.len() != self[pred].sig.len() {
+            bail!("illegal permutation for the arguments of {}", self[pred])
+        }
+
+        let mut var_map = VarMap::with_capacity(perm.len());
+        let mut nu_sig = VarMap::with_capacity(perm.len());
+        for old in perm.iter() {
+            var_map.push(self[pred].original_sig_map()[*old]);
+            nu_sig.push(self[pred].sig[*old].clone())
+        }
+        self.instance.preds[pred].set_sig(nu_sig, var_map);
+
+        let permute = |args: &VarTerms| -> VarTerms {
+            let nu_args: VarMap<_> = perm.iter().map(|old| args[*old].clone()).collect();
+            nu_args.into()
+        };
+
+        let (ref lhs, ref rhs) = self.instance.pred_to_clauses[pred];
+        for clause in lhs {
+            self.instance.clauses[*clause].lhs_map_args_of(pred, &permute)
+        }
+        for clause in rhs {
+            self.instance.clauses[*clause].rhs_map_args(|p, args| {
+                debug_assert_eq!(pred, p);
+                (p, permute(args))
+            })
+        }
+
+        Ok(())
+    }
+
     /// Removes all predicate arguments not in `to_keep`.
     ///
     /// Simplifies before returning.
@@ -2090,22 +2369,48 @@ impl ClauseSimplifier {
             skip!()
         }
 
-        match eq.as_subst() {
-            Some((var, term)) => {
-                if self.subst.get(&var).is_some() {
-                    skip!()
-                }
+        let substs = eq.as_substs();
+        let can_subst = !substs.is_empty();
 
-                let vars = term::vars(&term);
-                if vars.contains(&var)
-                    || self.var_set.contains(&var)
-                    || term::vars(&term)
-                        .into_iter()
-                        .any(|v| v == var || self.subst.get(&v).is_some())
-                {
-                    skip!()
+        // Variables appearing in predicate applications, eliminated only if needed.
+        let mut pred_vars = VarSet::new();
+        for argss in clause.lhs_preds().values() {
+            for args in argss {
+                for arg in args.iter() {
+                    pred_vars.extend(term::vars(arg))
                 }
+            }
+        }
+        if let Some((_, args)) = clause.rhs() {
+            for arg in args.iter() {
+                pred_vars.extend(term::vars(arg))
+            }
+        }
 
+        // Substitution to apply: the variable is not already substituted, does not appear in
+        // the value of a substitution, and its own value does not mention substituted variables.
+        let mut chosen = None;
+        for (var, term) in substs {
+            let vars = term::vars(&term);
+            if self.subst.get(&var).is_some()
+                || self.var_set.contains(&var)
+                || vars
+                    .iter()
+                    .any(|v| *v == var || self.subst.get(v).is_some())
+            {
+                continue;
+            }
+            let local = !pred_vars.contains(&var);
+            if chosen.is_none() || local {
+                chosen = Some((var, term, vars))
+            }
+            if local {
+                break;
+            }
+        }
+
+        match chosen {
+            Some((var, term, vars)) => {
                 log! { @4 "{} -> {}", var.default_str(), term }
 
                 let prev = self.subst.insert(var, term);
@@ -2114,6 +2419,9 @@ impl ClauseSimplifier {
                 self.var_set.extend(vars)
             }
 
+            // Could be a substitution, but it would create a cycle.
+            None if can_subst => skip!(),
+
             // Two terms.
             None => {
                 debug_assert_eq! { args[1].typ(), args[0].typ() }
@@ -0,0 +1,183 @@
+//! Summary of the shape of an instance.
+
+use crate::common::*;
+
+/// Summary of the shape of an instance, see [`Instance::stats`].
+///
+/// Clause kinds are computed from the clauses directly, so the summary does not require the
+/// instance to be finalized.
+///
+/// [`Instance::stats`]: struct.Instance.html#method.stats (stats function on Instance)
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::{ common::*, parse };
+/// let instance = parse::instance("
+///   (declare-fun p ( Int Int ) Bool)
+///   (declare-fun q ( Int ) Bool)
+///   (assert (forall ( (n Int) ) (=> (= n 0) (p n n))))
+///   (assert (forall ( (n Int) (m Int) ) (=> (p n m) (p (* n m) m))))
+///   (assert (forall ( (n Int) ) (=> (and (p n n) (q n)) false)))
+/// ");
+/// let stats = instance.stats();
+/// assert_eq! { stats.preds, 2 }
+/// assert_eq! { stats.clauses, 3 }
+/// assert_eq! { (stats.pos_clauses, stats.neg_clauses, stats.imp_clauses), (1, 1, 1) }
+/// assert_eq! { stats.max_clause_arity, 2 }
+/// assert_eq! { stats.sig_sizes[&1], 1 }
+/// assert_eq! { stats.sig_sizes[&2], 1 }
+/// assert! { stats.non_lin }
+/// assert! { !stats.adts && !stats.arrays }
+/// assert! { stats.to_string().starts_with("predicates: 2") }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstanceStats {
+    /// Number of predicates, not counting the ones that are defined.
+    pub preds: usize,
+    /// Number of clauses.
+    pub clauses: usize,
+    /// Number of positive clauses, *i.e.* no predicate application in the lhs.
+    pub pos_clauses: usize,
+    /// Number of negative clauses, *i.e.* no rhs.
+    pub neg_clauses: usize,
+    /// Number of implication clauses, *i.e.* neither positive nor negative.
+    pub imp_clauses: usize,
+    /// Maximum number of predicate applications in the lhs of a clause.
+    pub max_clause_arity: usize,
+    /// Maximum number of variables of a clause.
+    pub max_clause_vars: usize,
+    /// Maps signature sizes to the number of predicates with that many arguments.
+    pub sig_sizes: BTreeMap<usize, usize>,
+    /// True if some clause mentions non-linear arithmetic.
+    pub non_lin: bool,
+    /// True if some predicate argument or clause variable is a datatype.
+    pub adts: bool,
+    /// True if some predicate argument or clause variable is an array.
+    pub arrays: bool,
+}
+
+impl InstanceStats {
+    /// Computes the summary of an instance.
+    pub fn new(instance: &Instance) -> Self {
+        let mut stats = InstanceStats {
+            preds: 0,
+            clauses: instance.clauses().len(),
+            pos_clauses: 0,
+            neg_clauses: 0,
+            imp_clauses: 0,
+            max_clause_arity: 0,
+            max_clause_vars: 0,
+            sig_sizes: BTreeMap::new(),
+            non_lin: false,
+            adts: false,
+            arrays: false,
+        };
+
+        for pred in instance.preds() {
+            if pred.is_defined() {
+                continue;
+            }
+            stats.preds += 1;
+            *stats.sig_sizes.entry(pred.sig.len()).or_insert(0) += 1;
+            for typ in pred.sig.iter() {
+                stats.register_typ(typ)
+            }
+        }
+
+        for clause in instance.clauses() {
+            if clause.rhs().is_none() {
+                stats.neg_clauses += 1
+            } else if clause.lhs_preds().is_empty() {
+                stats.pos_clauses += 1
+            } else {
+                stats.imp_clauses += 1
+            }
+
+            stats.max_clause_arity =
+                ::std::cmp::max(stats.max_clause_arity, clause.lhs_pred_apps_len());
+            stats.max_clause_vars = ::std::cmp::max(
+                stats.max_clause_vars,
+                clause.vars().iter().filter(|info| info.active).count(),
+            );
+
+            for info in clause.vars() {
+                if info.active {
+                    stats.register_typ(&info.typ)
+                }
+            }
+
+            if !stats.non_lin {
+                let mut non_lin = false;
+                let _ = clause.all_pred_apps_do(|_, args| {
+                    for arg in args.iter() {
+                        non_lin = non_lin || is_non_lin(arg)
+                    }
+                    Ok(())
+                });
+                stats.non_lin = non_lin || clause.lhs_terms().iter().any(is_non_lin)
+            }
+        }
+
+        stats
+    }
+
+    /// Registers a type appearing in the instance.
+    fn register_typ(&mut self, typ: &Typ) {
+        if typ.is_dtyp() {
+            self.adts = true
+        } else if let Some((src, tgt)) = typ.array_inspect() {
+            self.arrays = true;
+            self.register_typ(src);
+            self.register_typ(tgt)
+        }
+    }
+}
+
+/// True if a term mentions non-linear arithmetic.
+///
+/// A multiplication is non-linear if at least two of its factors are not constants, a division or
+/// a modulo if its divisor is not a constant.
+fn is_non_lin(term: &Term) -> bool {
+    let mut non_lin = false;
+    term.iter(|term| {
+        if non_lin {
+            return;
+        }
+        if let Some((op, args)) = term.app_inspect() {
+            non_lin = match op {
+                Op::Mul => args.iter().filter(|arg| arg.val().is_none()).count() > 1,
+                Op::IDiv | Op::Div | Op::Mod | Op::Rem => {
+                    args.iter().skip(1).any(|arg| arg.val().is_none())
+                }
+                _ => false,
+            }
+        }
+    });
+    non_lin
+}
+
+mylib::impl_fmt! {
+    InstanceStats(self, fmt) {
+        writeln!(fmt, "predicates: {}", self.preds)?;
+        write!(fmt, "  predicates by signature size:")?;
+        let mut sep = " ";
+        for (size, count) in &self.sig_sizes {
+            write!(fmt, "{}{} of size {}", sep, count, size)?;
+            sep = ", "
+        }
+        writeln!(fmt)?;
+        writeln!(
+            fmt,
+            "clauses: {} ({} positive, {} negative, {} implication)",
+            self.clauses, self.pos_clauses, self.neg_clauses, self.imp_clauses
+        )?;
+        writeln!(fmt, "  max lhs predicate applications: {}", self.max_clause_arity)?;
+        writeln!(fmt, "  max variables: {}", self.max_clause_vars)?;
+        write!(
+            fmt,
+            "non-linear arithmetic: {}, datatypes: {}, arrays: {}",
+            self.non_lin, self.adts, self.arrays
+        )
+    }
+}
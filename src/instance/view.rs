@@ -0,0 +1,153 @@
+//! Read-only view of an instance.
+
+use crate::{common::*, info::Pred, var_to::terms::VarTermsSet};
+
+/// Read-only view of the clause structure of an instance.
+///
+/// Meant for tools built on top of hoice (visualizers, metrics...). The accessors of this facade
+/// are part of the stable API: they will not change across minor versions, even if the internal
+/// layout of [`Instance`] does. All of them borrow from the instance, nothing is cloned.
+///
+/// Clause and predicate indices are the ones of the instance the view was created from, see
+/// [`Instance::view`]. Functions relying on the finalization of the instance are marked as such.
+///
+/// [`Instance`]: struct.Instance.html (Instance struct)
+/// [`Instance::view`]: struct.Instance.html#method.view (view function on Instance)
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::{ common::*, parse };
+/// let instance = parse::instance("
+///   (declare-fun p ( Int ) Bool)
+///   (declare-fun q ( Int ) Bool)
+///   (assert (forall ( (n Int) ) (=> (= n 0) (p n))))
+///   (assert (forall ( (n Int) ) (=> (and (p n) (> n 0)) (q n))))
+///   (assert (forall ( (n Int) ) (=> (and (q n) (p n)) false)))
+/// ");
+/// let (p, q): (PrdIdx, PrdIdx) = (0.into(), 1.into());
+/// let view = instance.view();
+///
+/// assert_eq! { view.preds().count(), 2 }
+/// assert_eq! { view.clauses().count(), 3 }
+/// assert_eq! { view.lhs_clauses_of(p).count(), 2 }
+/// assert_eq! { view.rhs_clauses_of(p).count(), 1 }
+/// assert_eq! { view.rhs_clauses_of(q).count(), 1 }
+///
+/// let neg: Vec<_> = view.clauses().filter(|(_, clause)| clause.rhs().is_none()).collect();
+/// assert_eq! { neg.len(), 1 }
+/// let (neg, _) = neg[0];
+/// let mut lhs_preds: Vec<_> = view.lhs_pred_apps(neg).map(|(pred, _)| pred).collect();
+/// lhs_preds.sort();
+/// assert_eq! { lhs_preds, vec![p, q] }
+/// assert_eq! { view.lhs_terms(neg).count(), 0 }
+/// assert! { view.rhs(neg).is_none() }
+/// ```
+#[derive(Clone, Copy)]
+pub struct InstanceView<'a> {
+    /// Instance viewed.
+    instance: &'a Instance,
+}
+
+impl<'a> InstanceView<'a> {
+    /// Constructor.
+    pub fn new(instance: &'a Instance) -> Self {
+        InstanceView { instance }
+    }
+
+    /// Number of predicates.
+    pub fn pred_count(self) -> usize {
+        self.instance.preds().len()
+    }
+    /// Number of clauses.
+    pub fn clause_count(self) -> usize {
+        self.instance.clauses().len()
+    }
+
+    /// Predicates, by increasing index.
+    pub fn preds(self) -> impl Iterator<Item = &'a Pred> + 'a {
+        self.instance.preds().iter()
+    }
+    /// Predicate accessor.
+    pub fn pred(self, pred: PrdIdx) -> &'a Pred {
+        &self.instance[pred]
+    }
+
+    /// Clauses and their index, by increasing index.
+    pub fn clauses(self) -> impl Iterator<Item = (ClsIdx, &'a Clause)> + 'a {
+        self.instance.clauses().index_iter()
+    }
+    /// Clause accessor.
+    pub fn clause(self, clause: ClsIdx) -> &'a Clause {
+        &self.instance[clause]
+    }
+
+    /// Clauses mentioning `pred` in their lhs and rhs respectively, in no particular order.
+    pub fn clauses_of(
+        self,
+        pred: PrdIdx,
+    ) -> (
+        impl Iterator<Item = ClsIdx> + 'a,
+        impl Iterator<Item = ClsIdx> + 'a,
+    ) {
+        (self.lhs_clauses_of(pred), self.rhs_clauses_of(pred))
+    }
+    /// Clauses mentioning `pred` in their lhs, in no particular order.
+    pub fn lhs_clauses_of(self, pred: PrdIdx) -> impl Iterator<Item = ClsIdx> + 'a {
+        self.instance.lhs_clauses_of(pred).iter().cloned()
+    }
+    /// Clauses with `pred` as their rhs, in no particular order.
+    pub fn rhs_clauses_of(self, pred: PrdIdx) -> impl Iterator<Item = ClsIdx> + 'a {
+        self.instance.rhs_clauses_of(pred).iter().cloned()
+    }
+
+    /// Positive clauses, in no particular order. Requires finalization.
+    pub fn pos_clauses(self) -> impl Iterator<Item = ClsIdx> + 'a {
+        self.instance.pos_clauses().iter().cloned()
+    }
+    /// Negative clauses, in no particular order. Requires finalization.
+    pub fn neg_clauses(self) -> impl Iterator<Item = ClsIdx> + 'a {
+        self.instance.neg_clauses().iter().cloned()
+    }
+    /// Strict negative clauses (one predicate application in the lhs), in no particular order.
+    /// Requires finalization.
+    pub fn strict_neg_clauses(self) -> impl Iterator<Item = ClsIdx> + 'a {
+        self.instance.strict_neg_clauses().iter().cloned()
+    }
+    /// Implication clauses, in no particular order. Requires finalization.
+    pub fn imp_clauses(self) -> impl Iterator<Item = ClsIdx> + 'a {
+        self.instance.imp_clauses().iter().cloned()
+    }
+
+    /// Predicates of the lhs of a clause, with their arguments, in no particular order.
+    pub fn lhs_preds(self, clause: ClsIdx) -> impl Iterator<Item = (PrdIdx, &'a VarTermsSet)> + 'a {
+        self.instance[clause]
+            .lhs_preds()
+            .iter()
+            .map(|(pred, argss)| (*pred, argss))
+    }
+    /// Predicate applications of the lhs of a clause, in no particular order.
+    ///
+    /// Unlike [`lhs_preds`], a predicate applied several times yields several applications.
+    ///
+    /// [`lhs_preds`]: #method.lhs_preds (lhs_preds function)
+    pub fn lhs_pred_apps(
+        self,
+        clause: ClsIdx,
+    ) -> impl Iterator<Item = (PrdIdx, &'a VarTerms)> + 'a {
+        self.lhs_preds(clause)
+            .flat_map(|(pred, argss)| argss.iter().map(move |args| (pred, args)))
+    }
+    /// Terms of the lhs of a clause, in no particular order.
+    pub fn lhs_terms(self, clause: ClsIdx) -> impl Iterator<Item = &'a Term> + 'a {
+        self.instance[clause].lhs_terms().iter()
+    }
+    /// Rhs of a clause, `None` for negative clauses.
+    pub fn rhs(self, clause: ClsIdx) -> Option<(PrdIdx, &'a VarTerms)> {
+        self.instance[clause].rhs()
+    }
+    /// Variables of a clause.
+    pub fn vars(self, clause: ClsIdx) -> &'a VarInfos {
+        self.instance[clause].vars()
+    }
+}
@@ -634,7 +634,7 @@ impl EntropyBuilder {
 
                 match constraint.rhs() {
                     None => sum_neg += 1. / (lhs_len as f64),
-                    Some(&Sample { pred, ref args }) if pred == prd && args == sample => {
+                    Some(&Sample { pred, ref args, .. }) if pred == prd && args == sample => {
                         sum_imp_rhs += 1. / (1. + (lhs_len as f64))
                     }
                     _ => {
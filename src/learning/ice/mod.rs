@@ -65,8 +65,8 @@ pub mod quals;
 pub mod synth;
 
 use self::data::CData;
-use self::quals::NuQuals;
-use self::synth::SynthSys;
+use self::quals::{breaks_tie, NuQuals};
+use self::synth::{template::QualTemplates, SynthSys};
 
 /// Launcher.
 pub struct Launcher;
@@ -144,8 +144,12 @@ pub struct IceLearner<'core> {
     simple_rng: Rng,
     /// Rng to decide when skip preliminary.
     pre_skip_rng: Rng,
+    /// Rng to break ties between qualifiers, see `--dt_tiebreak`.
+    tiebreak_rng: Rng,
     /// Luby counter for restarts.
     luby: Option<LubyCount>,
+    /// True if the learner mines the instance for qualifiers.
+    mine: bool,
     /// Known qualifiers, factored for no reallocation. Used by synthesis.
     known_quals: TermSet,
     /// Gain pivot.
@@ -154,6 +158,10 @@ pub struct IceLearner<'core> {
     gain_pivot_synth: Option<f64>,
     /// Learn step counter.
     count: usize,
+    /// Samples separated by the qualifiers of the predicate being learned, for `--dump_quals`.
+    qual_counts: TermMap<usize>,
+    /// Qualifiers of the current candidate, for `--dump_quals`.
+    qual_stats: QualStats,
 }
 impl<'core> IceLearner<'core> {
     /// Ice learner constructor.
@@ -174,9 +182,16 @@ impl<'core> IceLearner<'core> {
         let candidate = vec![None; instance.preds().len()].into();
         let predicates = Vec::with_capacity(instance.preds().len());
 
+        let templates = Arc::new(if let Some(file) = conf.ice.qual_templates.as_ref() {
+            QualTemplates::of_file(file, &instance)?
+        } else {
+            QualTemplates::default()
+        });
         let mut synth_sys = PrdMap::with_capacity(instance.preds().len());
         for (pred, _) in instance.preds().index_iter() {
-            synth_sys.push(SynthSys::new(&instance[pred].sig))
+            let mut synth = SynthSys::new(&instance[pred].sig);
+            synth.set_templates(templates.clone());
+            synth_sys.push(synth)
         }
 
         let mut using_rec_funs = false;
@@ -192,8 +207,6 @@ impl<'core> IceLearner<'core> {
             (conf.ice.gain_pivot, conf.ice.gain_pivot_synth)
         };
 
-        use rand::SeedableRng;
-
         Ok(IceLearner {
             instance,
             qualifiers,
@@ -207,15 +220,19 @@ impl<'core> IceLearner<'core> {
             dec_mem,
             candidate,
             predicates,
-            sort_rng_1: { Rng::from_seed([42; 16]) },
-            sort_rng_2: { Rng::from_seed([79; 16]) },
-            simple_rng: { Rng::from_seed([107; 16]) },
-            pre_skip_rng: { Rng::from_seed([245; 16]) },
+            sort_rng_1: seeded_rng(42, conf.seed),
+            sort_rng_2: seeded_rng(79, conf.seed),
+            simple_rng: seeded_rng(107, conf.seed),
+            pre_skip_rng: seeded_rng(245, conf.seed),
+            tiebreak_rng: seeded_rng(173, conf.seed),
             luby: if mine { None } else { Some(LubyCount::new()) },
+            mine,
             known_quals: TermSet::new(),
             gain_pivot,
             gain_pivot_synth,
             count: 0,
+            qual_counts: TermMap::new(),
+            qual_stats: QualStats::new(),
         })
     }
 
@@ -224,6 +241,31 @@ impl<'core> IceLearner<'core> {
         self.luby.as_mut().map(|l| l.inc()).unwrap_or(false)
     }
 
+    /// Restarts the learner, as asked by the teacher.
+    ///
+    /// Forgets the qualifiers and re-seeds the random generators using `seed`. The learning data
+    /// is not affected. The teacher draws `seed` from a generator seeded by `conf.seed` already.
+    fn teacher_restart(&mut self, seed: u64) -> Res<()> {
+        profile! { |self.core._profiler| tick "mining" }
+        self.qualifiers = NuQuals::new(&self.instance, self.mine)
+            .chain_err(|| "while re-creating qualifier structure")?;
+        profile! { |self.core._profiler| mark "mining" }
+        self.qualifiers.reseed(seeded_rng(13, seed));
+
+        self.sort_rng_1 = seeded_rng(42, seed);
+        self.sort_rng_2 = seeded_rng(79, seed);
+        self.simple_rng = seeded_rng(107, seed);
+        self.pre_skip_rng = seeded_rng(245, seed);
+
+        self.known_quals.clear();
+        self.qual_counts.clear();
+        for synth_sys in self.synth_sys.iter_mut() {
+            synth_sys.restart()
+        }
+
+        Ok(())
+    }
+
     /// Runs the learner.
     pub fn run(&mut self) -> Res<()> {
         profile! { self "quals synthesized" => add 0 }
@@ -236,20 +278,36 @@ impl<'core> IceLearner<'core> {
             match profile! (
               |self.core._profiler| wrap { self.recv() } "waiting"
             ) {
-                Ok(data) => {
+                Ok((data, restart)) => {
+                    if let Some(seed) = restart {
+                        profile! { self "teacher restarts" => add 1 }
+                        self.teacher_restart(seed)?
+                    }
                     self.count += 1;
                     if self.count % 50 == 0 {
                         smt::reset(&mut self.solver, &self.instance)?
                     }
                     profile! { self "learn steps" => add 1 }
-                    if let Some(candidates) = profile!(
+                    let res = profile!(
                       |self.core._profiler| wrap {
                         self.solver.push(1) ? ;
                         let res = self.learn(data) ;
                         self.solver.pop(1) ? ;
                         res
                       } "learning"
-                    )? {
+                    );
+                    let candidates = match res {
+                        // New data arrived while learning, see `--pipeline`.
+                        Err(ref e) if e.is_preempted() => {
+                            profile! { self "preemptions" => add 1 }
+                            for set in self.dec_mem.iter_mut() {
+                                set.clear()
+                            }
+                            continue;
+                        }
+                        res => res?,
+                    };
+                    if let Some(candidates) = candidates {
                         self.send_cands(candidates)
                             .chain_err(|| "while sending candidates")?;
                         if self.restart() {
@@ -284,6 +342,10 @@ impl<'core> IceLearner<'core> {
     ///
     /// Also resets the solver and clears declaration memory.
     fn send_cands(&mut self, candidates: Candidates) -> Res<()> {
+        if conf.ice.dump_quals.is_some() {
+            let quals = ::std::mem::replace(&mut self.qual_stats, QualStats::new());
+            self.send_quals(quals)?
+        }
         profile!(
             | self._profiler | wrap {
                 self.send_candidates(candidates)
@@ -307,6 +369,7 @@ impl<'core> IceLearner<'core> {
 
         ::std::mem::swap(&mut data, &mut self.data);
         self.core.merge_set_prof("data", data.destroy());
+        self.qual_stats.clear();
 
         if self.count % conf.ice.gain_pivot_mod == 0 {
             self.gain_pivot += conf.ice.gain_pivot_inc;
@@ -555,6 +618,7 @@ impl<'core> IceLearner<'core> {
         debug_assert!(self.finished.is_empty());
         debug_assert!(self.unfinished.is_empty());
         self.classifier.clear();
+        self.qual_counts.clear();
 
         msg! { @verb
             self =>
@@ -607,6 +671,14 @@ impl<'core> IceLearner<'core> {
                 return Ok(None);
             };
 
+            if conf.ice.dump_quals.is_some() {
+                let separated = q_data.pos().len()
+                    + q_data.neg().len()
+                    + nq_data.pos().len()
+                    + nq_data.neg().len();
+                *self.qual_counts.entry(qual.clone()).or_insert(0) += separated
+            }
+
             // Remember the branch where qualifier is false.
             let mut nq_branch = branch.clone();
             nq_branch.push((qual.clone(), false));
@@ -619,6 +691,19 @@ impl<'core> IceLearner<'core> {
 
         profile! { self tick "learning", "pred finalize" }
         debug_assert!(self.unfinished.is_empty());
+        if conf.ice.dump_quals.is_some() {
+            let mut quals = vec![];
+            for branch in &self.finished {
+                for (term, _) in branch {
+                    if let Some(count) = self.qual_counts.remove(term) {
+                        quals.push((term.clone(), count))
+                    }
+                }
+            }
+            if !quals.is_empty() {
+                let _ = self.qual_stats.insert(pred, quals);
+            }
+        }
         let mut or_args = Vec::with_capacity(self.finished.len());
         for branch in self.finished.drain(0..) {
             let mut and_args = Vec::with_capacity(branch.len());
@@ -1247,10 +1332,17 @@ impl<'core> IceLearner<'core> {
     }
 
     /// Chooses between a qualifier and a synthesized qualifier.
-    fn choose_qual(&self, qual: Option<(Term, f64)>, squal: Option<(Term, f64)>) -> Res<Term> {
+    ///
+    /// Ties are broken by [`breaks_tie`].
+    ///
+    /// [`breaks_tie`]: quals/fn.breaks_tie.html (breaks_tie function)
+    fn choose_qual(&mut self, qual: Option<(Term, f64)>, squal: Option<(Term, f64)>) -> Res<Term> {
         let res = match (qual, squal) {
             (Some((qual, gain)), Some((squal, synth_gain))) => {
-                if synth_gain > gain {
+                if synth_gain > gain
+                    || ((synth_gain - gain).abs() < ::std::f64::EPSILON
+                        && breaks_tie(&qual, &squal, 1, &mut self.tiebreak_rng))
+                {
                     msg! {
                       self =>
                       "using synth qualifier {}, gain {} >= {} (for {})",
@@ -1321,6 +1413,9 @@ impl<'core> IceLearner<'core> {
 
           let self_core = & self.core ;
           let known_quals = & mut self.known_quals ;
+          let tiebreak_rng = & mut self.tiebreak_rng ;
+          // Number of qualifiers with the same gain as `best`, for tie-breaking.
+          let mut ties = 1 ;
           // let gain_pivot = self.gain_pivot ;
           let gain_pivot_synth = self.gain_pivot_synth ;
           known_quals.clear() ;
@@ -1331,7 +1426,7 @@ impl<'core> IceLearner<'core> {
             self_core.check_exit() ? ;
             let is_new = ! quals.quals_of_contains(
               pred, & term
-            ) && known_quals.insert(
+            ) && ! quals.is_blacklisted(& term) && known_quals.insert(
               term.clone()
             ) ;
 
@@ -1375,10 +1470,18 @@ impl<'core> IceLearner<'core> {
                   let diff = gain - * old_gain ;
                   if diff > ::std::f64::EPSILON {
                     * old_gain = gain ;
-                    * old_term = term
-                  } else if (* old_gain - gain).abs() < ::std::f64::EPSILON
-                  && term::vars(old_term).len() < term::vars(& term).len() {
-                    * old_term = term
+                    * old_term = term ;
+                    ties = 1
+                  } else if (* old_gain - gain).abs() < ::std::f64::EPSILON {
+                    let replace = if conf.ice.dt_tiebreak == DtTiebreak::First {
+                      term::vars(old_term).len() < term::vars(& term).len()
+                    } else {
+                      breaks_tie(old_term, & term, ties, tiebreak_rng)
+                    } ;
+                    ties += 1 ;
+                    if replace {
+                      * old_term = term
+                    }
                   }
                 } else {
                   * best = Some((term, gain))
@@ -426,17 +426,23 @@ fn apply_mappings(
 fn mine_instance(instance: &Instance, quals: &mut NuQuals) -> Res<()> {
     // Add boolean qualifiers for all predicate's bool vars.
     for pred in instance.preds() {
+        let mut bool_vars = Vec::new();
         for (var, typ) in pred.sig.index_iter() {
-            let mut bool_vars = Vec::new();
             if typ.is_bool() {
                 let var = term::var(var, typ::bool());
                 quals.insert(var.clone(), pred.idx)?;
+                quals.insert(term::not(var.clone()), pred.idx)?;
+                for other in &bool_vars {
+                    let eq = term::eq(other.clone(), var.clone());
+                    quals.insert(eq.clone(), pred.idx)?;
+                    quals.insert(term::not(eq), pred.idx)?;
+                }
                 bool_vars.push(var)
             }
-            if bool_vars.len() > 1 {
-                quals.insert(term::and(bool_vars.clone()), pred.idx)?;
-                quals.insert(term::or(bool_vars), pred.idx)?;
-            }
+        }
+        if bool_vars.len() > 1 {
+            quals.insert(term::and(bool_vars.clone()), pred.idx)?;
+            quals.insert(term::or(bool_vars), pred.idx)?;
         }
     }
 
@@ -448,28 +454,265 @@ fn mine_instance(instance: &Instance, quals: &mut NuQuals) -> Res<()> {
     Ok(())
 }
 
+/// Qualifier patterns the learner should ignore.
+///
+/// Patterns are matched structurally, up to variable renaming. See `--qual_blacklist`.
+///
+/// # Examples
+///
+/// ```rust
+/// use hoice::{ common::*, learning::ice::quals::QualBlacklist };
+///
+/// let instance = ::hoice::parse::mc_91();
+/// let blacklist = QualBlacklist::new("
+///     ; No comparison to zero.
+///     (<= v_0 0)
+///     (= (+ v_0 v_1) 7)
+/// ", &instance).unwrap();
+/// assert_eq! { blacklist.len(), 2 }
+///
+/// let (v_0, v_1) = (term::int_var(0), term::int_var(1));
+/// assert! { blacklist.matches(&term::le(v_1.clone(), term::int(0))) }
+/// assert! { blacklist.matches(&term::eq(
+///     term::add(vec![v_1.clone(), v_0.clone()]), term::int(7)
+/// )) }
+/// // Renaming must be injective.
+/// assert! { ! blacklist.matches(&term::eq(
+///     term::add(vec![v_0.clone(), v_0.clone()]), term::int(7)
+/// )) }
+/// assert! { ! blacklist.matches(&term::le(v_1.clone(), term::int(1))) }
+/// assert! { ! blacklist.matches(&term::ge(v_0, term::int(0))) }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct QualBlacklist {
+    /// Patterns.
+    patterns: Vec<Term>,
+}
+impl QualBlacklist {
+    /// Parses some patterns, one per line.
+    ///
+    /// Each pattern is parsed against the signatures of the predicates of the instance, and must
+    /// make sense for at least one of them.
+    pub fn new(patterns: &str, instance: &Instance) -> Res<Self> {
+        let mut sigs = Vec::with_capacity(instance.preds().len());
+        for pred in instance.preds() {
+            let mut var_infos = VarInfos::new();
+            for (var, typ) in pred.sig.index_iter() {
+                var_infos.push(crate::info::VarInfo::new(
+                    var.default_str(),
+                    typ.clone(),
+                    var,
+                ))
+            }
+            sigs.push(var_infos)
+        }
+
+        let mut res = Vec::new();
+
+        'all_lines: for (line_idx, line) in patterns.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue 'all_lines;
+            }
+            for var_infos in &sigs {
+                if let Ok(Some(pattern)) = crate::parse::term_opt(line, var_infos, instance) {
+                    res.push(pattern);
+                    continue 'all_lines;
+                }
+            }
+            bail!(
+                "illegal qualifier pattern on line {}: `{}` does not type-check for any predicate",
+                line_idx + 1,
+                line
+            )
+        }
+
+        Ok(QualBlacklist { patterns: res })
+    }
+
+    /// Loads some patterns from a file.
+    pub fn of_file(file: &str, instance: &Instance) -> Res<Self> {
+        let mut content = String::new();
+        ::std::fs::File::open(file)
+            .and_then(|mut f| f.read_to_string(&mut content))
+            .chain_err(|| format!("while reading qualifier blacklist `{}`", file))?;
+        Self::new(&content, instance)
+            .chain_err(|| format!("while parsing qualifier blacklist `{}`", file))
+    }
+
+    /// Number of patterns.
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+    /// True if there are no patterns.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// True if a qualifier matches one of the patterns.
+    pub fn matches(&self, qual: &Term) -> bool {
+        let (mut map, mut rev) = (VarHMap::new(), VarHMap::new());
+        self.patterns.iter().any(|pattern| {
+            map.clear();
+            rev.clear();
+            Self::matches_with(pattern, qual, &mut map, &mut rev)
+        })
+    }
+
+    /// Matches a pattern against a term, given a (partial) renaming and its inverse.
+    fn matches_with(
+        pattern: &Term,
+        term: &Term,
+        map: &mut VarHMap<VarIdx>,
+        rev: &mut VarHMap<VarIdx>,
+    ) -> bool {
+        use crate::term::RTerm;
+
+        macro_rules! all_match {
+            ($pat_args:expr, $args:expr) => {
+                $pat_args.len() == $args.len()
+                    && $pat_args
+                        .iter()
+                        .zip($args.iter())
+                        .all(|(pat, arg)| Self::matches_with(pat, arg, map, rev))
+            };
+        }
+
+        match (pattern.get(), term.get()) {
+            (RTerm::Var(pat_typ, pat_var), RTerm::Var(typ, var)) => {
+                if pat_typ != typ {
+                    return false;
+                }
+                match (map.get(pat_var), rev.get(var)) {
+                    (Some(v), Some(p)) => v == var && p == pat_var,
+                    (None, None) => {
+                        map.insert(*pat_var, *var);
+                        rev.insert(*var, *pat_var);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+
+            (RTerm::Cst(pat_val), RTerm::Cst(val)) => pat_val == val,
+
+            (
+                RTerm::CArray {
+                    typ: pat_typ,
+                    term: pat_term,
+                    ..
+                },
+                RTerm::CArray { typ, term, .. },
+            ) => pat_typ == typ && Self::matches_with(pat_term, term, map, rev),
+
+            (
+                RTerm::App {
+                    typ: pat_typ,
+                    op: pat_op,
+                    args: pat_args,
+                    ..
+                },
+                RTerm::App { typ, op, args, .. },
+            ) => pat_typ == typ && pat_op == op && all_match!(pat_args, args),
+
+            (
+                RTerm::DTypNew {
+                    typ: pat_typ,
+                    name: pat_name,
+                    args: pat_args,
+                    ..
+                },
+                RTerm::DTypNew {
+                    typ, name, args, ..
+                },
+            )
+            | (
+                RTerm::Fun {
+                    typ: pat_typ,
+                    name: pat_name,
+                    args: pat_args,
+                    ..
+                },
+                RTerm::Fun {
+                    typ, name, args, ..
+                },
+            ) => pat_typ == typ && pat_name == name && all_match!(pat_args, args),
+
+            (
+                RTerm::DTypSlc {
+                    typ: pat_typ,
+                    name: pat_name,
+                    term: pat_term,
+                    ..
+                },
+                RTerm::DTypSlc {
+                    typ, name, term, ..
+                },
+            )
+            | (
+                RTerm::DTypTst {
+                    typ: pat_typ,
+                    name: pat_name,
+                    term: pat_term,
+                    ..
+                },
+                RTerm::DTypTst {
+                    typ, name, term, ..
+                },
+            ) => pat_typ == typ && pat_name == name && Self::matches_with(pat_term, term, map, rev),
+
+            _ => false,
+        }
+    }
+}
+
 pub struct NuQuals {
     instance: Arc<Instance>,
     quals: PrdMap<VarHMap<TermSet>>,
     rng: Rng,
+    /// Qualifiers to ignore.
+    blacklist: QualBlacklist,
 }
 impl NuQuals {
     /// Mines a signature.
+    ///
+    /// Boolean variables yield themselves and their negation, and pairs of boolean variables
+    /// yield their equality and its negation.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, learning::ice::quals::NuQuals };
+    ///
+    /// let sig: Sig = vec![typ::bool(), typ::bool()].into();
+    /// let mut quals = TermSet::new();
+    /// NuQuals::mine_sig(&sig, |qual| {
+    ///     quals.insert(qual);
+    ///     Ok(())
+    /// }).unwrap();
+    ///
+    /// let (v_0, v_1) = (term::bool_var(0), term::bool_var(1));
+    /// assert! { quals.contains(&v_0) }
+    /// assert! { quals.contains(&term::not(v_1.clone())) }
+    /// let eq = term::eq(v_0, v_1);
+    /// assert! { quals.contains(&term::not(eq.clone())) }
+    /// assert! { quals.contains(&eq) }
+    /// ```
     pub fn mine_sig<F>(sig: &Sig, mut qual_do: F) -> Res<()>
     where
         F: FnMut(Term) -> Res<()>,
     {
         let mut prev: TypMap<VarSet> = TypMap::new();
 
-        let sig = sig.index_iter();
-
-        for (var, typ) in sig {
+        for (var, typ) in sig.index_iter() {
             if let Some(vars) = prev.get(typ) {
                 for v in vars {
-                    qual_do(term::eq(
-                        term::var(*v, typ.clone()),
-                        term::var(var, typ.clone()),
-                    ))?
+                    let eq = term::eq(term::var(*v, typ.clone()), term::var(var, typ.clone()));
+                    // Booleans also get their `xor`.
+                    if typ.is_bool() {
+                        qual_do(term::not(eq.clone()))?
+                    }
+                    qual_do(eq)?
                 }
             }
 
@@ -539,6 +782,7 @@ impl NuQuals {
 
                 typ::RTyp::Bool => {
                     let var = term::bool_var(var);
+                    qual_do(term::not(var.clone()))?;
                     qual_do(var)?
                 }
 
@@ -565,21 +809,55 @@ impl NuQuals {
             }
         }
 
+        // Reads arrays of integers at integer indices.
+        for (array, typ) in sig.index_iter() {
+            let is_int_array = typ
+                .array_inspect()
+                .map(|(src, tgt)| src.is_int() && tgt.is_int())
+                .unwrap_or(false);
+            if !is_int_array {
+                continue;
+            }
+            let array = term::var(array, typ.clone());
+
+            let selects: Vec<Term> = sig
+                .index_iter()
+                .filter(|(_, typ)| typ.is_int())
+                .map(|(idx, _)| term::select(array.clone(), term::int_var(idx)))
+                .collect();
+
+            for (cnt, select) in selects.iter().enumerate() {
+                qual_do(term::ge(select.clone(), term::int(0)))?;
+                qual_do(term::le(select.clone(), term::int(0)))?;
+                qual_do(term::eq(select.clone(), term::int(0)))?;
+                for other in &selects[cnt + 1..] {
+                    qual_do(term::le(select.clone(), other.clone()))?;
+                    qual_do(term::ge(select.clone(), other.clone()))?;
+                    qual_do(term::eq(select.clone(), other.clone()))?;
+                }
+            }
+        }
+
         Ok(())
     }
 
     /// Constructor.
     pub fn new(instance: &Arc<Instance>, mine: bool) -> Res<Self> {
-        use rand::SeedableRng;
-
         let mut quals = PrdMap::with_capacity(instance.preds().len());
         for _ in 0..instance.preds().len() {
             quals.push(VarHMap::new())
         }
+        let blacklist = if let Some(file) = conf.ice.qual_blacklist.as_ref() {
+            QualBlacklist::of_file(file, instance)?
+        } else {
+            QualBlacklist::default()
+        };
+
         let mut quals = NuQuals {
             quals,
             instance: instance.clone(),
-            rng: Rng::from_seed([42; 16]),
+            rng: seeded_rng(42, conf.seed),
+            blacklist,
         };
 
         if mine {
@@ -622,13 +900,29 @@ impl NuQuals {
                 })?
             }
 
-            mine_instance(instance, &mut quals).chain_err(|| "during qualifier mining")?
+            mine_instance(instance, &mut quals).chain_err(|| "during qualifier mining")?;
+
+            // Hints and their conjuncts are qualifiers.
+            for (pred, hints) in crate::teacher::hints(instance)? {
+                for hint in hints {
+                    if let Some(conj) = hint.conj_inspect() {
+                        for term in conj {
+                            quals.insert(term.clone(), pred)?;
+                        }
+                    }
+                    quals.insert(hint, pred)?;
+                }
+            }
         }
 
         Ok(quals)
     }
 
     pub fn insert(&mut self, term: Term, pred: PrdIdx) -> Res<bool> {
+        // Qualifiers mentioning uninterpreted functions cannot be evaluated on samples.
+        if self.is_blacklisted(&term) || term.has_uf_apps() {
+            return Ok(false);
+        }
         let var_count = term::vars(&term).len();
         let set = self.quals[pred]
             .entry(var_count.into())
@@ -638,6 +932,16 @@ impl NuQuals {
         Ok(is_new)
     }
 
+    /// True if a qualifier is blacklisted.
+    pub fn is_blacklisted(&self, term: &Term) -> bool {
+        !self.blacklist.is_empty() && self.blacklist.matches(term)
+    }
+
+    /// Replaces the random generator used to break ties between qualifiers.
+    pub fn reseed(&mut self, rng: Rng) {
+        self.rng = rng
+    }
+
     /// Real number of qualifiers considered.
     pub fn real_qual_count(&self) -> usize {
         let mut count = 0;
@@ -681,6 +985,10 @@ impl NuQuals {
     /// Returns the qualifier that maximized the input criterion in a non-zero
     /// fashion, if any. Early-returns if the criterion is `>=` to the gain pivot
     /// defined in the configuration at some point.
+    ///
+    /// Ties are broken by [`breaks_tie`].
+    ///
+    /// [`breaks_tie`]: fn.breaks_tie.html (breaks_tie function)
     pub fn maximize<Crit>(
         &mut self,
         pred: PrdIdx,
@@ -706,6 +1014,8 @@ impl NuQuals {
         };
 
         let mut best = None;
+        // Number of qualifiers with the same gain as `best`, for tie-breaking.
+        let mut ties = 0;
         let rng = &mut self.rng;
 
         let mut quals: Vec<_> = self.quals[pred]
@@ -749,11 +1059,21 @@ impl NuQuals {
                     } else if let Some((best, best_value)) = best {
                         let diff = value - best_value;
                         if diff > ::std::f64::EPSILON {
+                            ties = 1;
                             Some((term, value))
+                        } else if diff.abs() < ::std::f64::EPSILON {
+                            let replace = breaks_tie(best, term, ties, rng);
+                            ties += 1;
+                            if replace {
+                                Some((term, value))
+                            } else {
+                                Some((best, best_value))
+                            }
                         } else {
                             Some((best, best_value))
                         }
                     } else {
+                        ties = 1;
                         Some((term, value))
                     }
                 }
@@ -763,3 +1083,22 @@ impl NuQuals {
         Ok(best.map(|(t, v)| (t.clone(), v)))
     }
 }
+
+/// True if a qualifier should replace the best one so far, when they have the same gain.
+///
+/// Depends on `--dt_tiebreak`: never with `first`, if `qual` is strictly simpler than `best`
+/// with `simplest` (see [`RTerm::complexity`]). With `random`, `ties` is the number of
+/// qualifiers with this gain seen so far, so that each of them is kept with the same probability.
+///
+/// [`RTerm::complexity`]: ../../../term/enum.RTerm.html#method.complexity (complexity method)
+pub fn breaks_tie(best: &Term, qual: &Term, ties: usize, rng: &mut Rng) -> bool {
+    match conf.ice.dt_tiebreak {
+        DtTiebreak::First => false,
+        DtTiebreak::Simplest => qual.complexity() < best.complexity(),
+        DtTiebreak::Random => {
+            use rand::Rng;
+            let draw: f64 = rng.gen();
+            draw * ((ties + 1) as f64) < 1.0
+        }
+    }
+}
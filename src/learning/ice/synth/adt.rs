@@ -134,7 +134,10 @@ impl TheoSynth for AdtSynth {
         F: FnMut(Term) -> Res<bool>,
     {
         match self.expressivity {
-            0 => self.eq_synth(f, sample, others),
+            0 => {
+                let mut f = f;
+                Ok(self.tst_synth(&mut f, sample)? || self.eq_synth(f, sample, others)?)
+            }
 
             _ => Ok(false),
         }
@@ -160,6 +163,21 @@ impl AdtSynth {
         }
     }
 
+    /// Types of the selectors of the datatype.
+    fn selector_typs(&self) -> Vec<Typ> {
+        let mut typs = vec![];
+        if let Some((dtyp, prms)) = self.typ.dtyp_inspect() {
+            for args in dtyp.news.values() {
+                for (_, ptyp) in args {
+                    if let Ok(typ) = ptyp.to_type(Some(prms)) {
+                        typs.push(typ)
+                    }
+                }
+            }
+        }
+        typs
+    }
+
     /// True if the synthesizer can project values to int.
     pub fn can_project_to_int(&self) -> bool {
         for fun in &self.funs.from_typ {
@@ -167,7 +185,7 @@ impl AdtSynth {
                 return true;
             }
         }
-        false
+        self.selector_typs().iter().any(|typ| typ.is_int())
     }
 
     /// True if the synthesizer can project values to real.
@@ -177,7 +195,44 @@ impl AdtSynth {
                 return true;
             }
         }
-        false
+        self.selector_typs().iter().any(|typ| typ.is_real())
+    }
+
+    /// Generates tester qualifiers for the constructors of the sampled values.
+    ///
+    /// Also generates testers for the sub-values of the sampled values of the same type, as in
+    /// `(is-nil (tail x))`.
+    fn tst_synth<F>(&self, mut f: F, sample: &VarVals) -> Res<bool>
+    where
+        F: FnMut(Term) -> Res<bool>,
+    {
+        for (var, val) in sample.index_iter() {
+            if !val.is_known() || val.typ() != self.typ {
+                continue;
+            }
+            let var = term::var(var, self.typ.clone());
+
+            let (_, val_cons, val_args) = self.get_opt(val.dtyp_inspect())?;
+            if f(term::dtyp_tst(val_cons, var.clone()))? {
+                return Ok(true);
+            }
+
+            let (val_dtyp, _) = self.get_opt(self.typ.dtyp_inspect())?;
+            let selectors = self.get_opt(val_dtyp.news.get(val_cons))?;
+            for ((slc, _), val_arg) in selectors.iter().zip(val_args.iter()) {
+                if !val_arg.is_known() || val_arg.typ() != self.typ {
+                    continue;
+                }
+                if let Some((_, arg_cons, _)) = val_arg.dtyp_inspect() {
+                    let arg = term::dtyp_slc(self.typ.clone(), slc.clone(), var.clone());
+                    if f(term::dtyp_tst(arg_cons, arg))? {
+                        return Ok(true);
+                    }
+                }
+            }
+        }
+
+        Ok(false)
     }
 
     /// Generates equalities between variables of some ADT.
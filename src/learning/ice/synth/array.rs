@@ -0,0 +1,104 @@
+//! Array qualifier synthesis.
+//!
+//! Arrays do not have qualifiers of their own, but they project to the theory of their values by
+//! reading them at the indices of the sample.
+
+use crate::common::*;
+
+use super::{TermVals, TheoSynth};
+
+/// Array synthesizer.
+#[derive(Clone, Debug)]
+pub struct ArraySynth {
+    /// Type this synthesizer handles.
+    typ: Typ,
+    /// Type of the indices.
+    src: Typ,
+    /// Type of the values.
+    tgt: Typ,
+}
+
+impl ArraySynth {
+    /// Constructor.
+    ///
+    /// Returns `None` if `typ` is not an array type.
+    pub fn new(typ: Typ) -> Option<Self> {
+        let (src, tgt) = if let Some((src, tgt)) = typ.array_inspect() {
+            (src.clone(), tgt.clone())
+        } else {
+            return None;
+        };
+        Some(ArraySynth { typ, src, tgt })
+    }
+
+    /// Type of the values of the arrays.
+    pub fn tgt(&self) -> &Typ {
+        &self.tgt
+    }
+
+    /// Projects a single value.
+    ///
+    /// Reads the array at the indices from the sample, and at the indices the value stores
+    /// something at explicitly.
+    fn project_val(&self, sample: &VarVals, var: VarIdx, val: &Val, map: &mut TermVals) -> Res<()> {
+        if !val.is_known() || val.typ() != self.typ {
+            return Ok(());
+        }
+
+        let array = term::var(var, self.typ.clone());
+
+        for (idx_var, idx_val) in sample.index_iter() {
+            if idx_val.is_known() && idx_val.typ() == self.src {
+                let sel = val.select(idx_val.clone());
+                if sel.is_known() {
+                    let term = term::select(array.clone(), term::var(idx_var, self.src.clone()));
+                    let _ = map.insert(term, sel);
+                }
+            }
+        }
+
+        if let val::RVal::Array { ref vals, .. } = **val {
+            for (idx, sel) in vals {
+                if sel.is_known() {
+                    let term = term::select(array.clone(), term::cst(idx.clone()));
+                    let _ = map.insert(term, sel.clone());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl TheoSynth for ArraySynth {
+    fn typ(&self) -> &Typ {
+        &self.typ
+    }
+
+    fn is_done(&self) -> bool {
+        true
+    }
+
+    fn restart(&mut self) {}
+
+    fn increment(&mut self) {}
+
+    fn synth<F>(&mut self, _: F, _: &VarVals, _: &mut TermVals, _: &Profiler) -> Res<bool>
+    where
+        F: FnMut(Term) -> Res<bool>,
+    {
+        Ok(false)
+    }
+
+    fn project(&self, sample: &VarVals, typ: &Typ, map: &mut TermVals) -> Res<()> {
+        if &self.tgt != typ {
+            return Ok(());
+        }
+
+        for (var, val) in sample.index_iter() {
+            self.project_val(sample, var, val, map)?
+        }
+
+        Ok(())
+    }
+}
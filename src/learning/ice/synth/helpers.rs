@@ -261,6 +261,57 @@ macro_rules! arith_synth_three_terms {
     }};
 }
 
+/// Monomial synthesis.
+///
+/// Synthesizes qualifiers of the form `m = n`, `m <= n` and `m >= n`, where `m` is a product of
+/// between `2` and `max_degree` variables of type `typ`, possibly repeated, and `n` is the value
+/// of `m` in the sample.
+pub fn monomial_synth<F>(sample: &VarVals, typ: &Typ, max_degree: usize, mut f: F) -> Res<bool>
+where
+    F: FnMut(Term) -> Res<bool>,
+{
+    let vars: Vec<(Term, Val)> = sample
+        .index_iter()
+        .filter(|(_, val)| val.typ() == *typ && val.is_known())
+        .map(|(var, val)| (term::var(var, typ.clone()), val.clone()))
+        .collect();
+
+    // Monomials of the current degree: indices of their factors in `vars` in increasing order,
+    // and value.
+    let mut monomials: Vec<(Vec<usize>, Val)> = vars
+        .iter()
+        .enumerate()
+        .map(|(idx, (_, val))| (vec![idx], val.clone()))
+        .collect();
+
+    for _ in 1..max_degree {
+        let mut nu_monomials = Vec::with_capacity(monomials.len() * vars.len());
+
+        for (factors, val) in &monomials {
+            let last = factors[factors.len() - 1];
+            for (idx, (_, var_val)) in vars.iter().enumerate().skip(last) {
+                let mut nu_factors = factors.clone();
+                nu_factors.push(idx);
+                let nu_val = Op::Mul.eval(vec![val.clone(), var_val.clone()])?;
+
+                if let Some(cst) = nu_val.to_term() {
+                    let monomial =
+                        term::mul(nu_factors.iter().map(|idx| vars[*idx].0.clone()).collect());
+                    apply! { f to term::ge(monomial.clone(), cst.clone()) }
+                    apply! { f to term::le(monomial.clone(), cst.clone()) }
+                    apply! { f to term::eq(monomial, cst) }
+                }
+
+                nu_monomials.push((nu_factors, nu_val))
+            }
+        }
+
+        monomials = nu_monomials
+    }
+
+    Ok(false)
+}
+
 /// Bitvecs.
 pub struct Bitvec {
     value: u64,
@@ -1,6 +1,9 @@
 //! Qualifier synthesis in the theory of integers.
 
-use super::{helpers::n_term_arith_synth, TermVals, TheoSynth};
+use super::{
+    helpers::{monomial_synth, n_term_arith_synth},
+    TermVals, TheoSynth,
+};
 use crate::common::*;
 
 /// Integer qualifier synthesizer.
@@ -62,7 +65,11 @@ impl TheoSynth for IntSynth {
               |_profiler| wrap {
                 let done = n_term_arith_synth(
                   sample, others, & self.typ, 1, & mut f
-                ) ? ;
+                ) ? || mod_synth(sample, conf.ice.qual_max_modulo, & mut f) ? || (
+                  conf.ice.qual_octagon && octagon_synth(
+                    sample, conf.ice.qual_octagon_max_arity, & mut f
+                  ) ?
+                ) ;
                 if ! done {
                   n_term_arith_synth(sample, others, & self.typ, 2, f)
                 } else {
@@ -73,7 +80,12 @@ impl TheoSynth for IntSynth {
 
             1 => profile!(
               |_profiler| wrap {
-                non_lin_int_synth(sample, others, f)
+                let done = non_lin_int_synth(sample, others, & mut f) ? ;
+                if ! done && conf.ice.qual_max_degree > 1 {
+                  monomial_synth(sample, & self.typ, conf.ice.qual_max_degree, f)
+                } else {
+                  Ok(done)
+                }
               } "learning", "qual", "synthesis", "int", "level 1"
             ),
 
@@ -105,6 +117,167 @@ impl TheoSynth for IntSynth {
     }
 }
 
+/// Divisibility synthesis.
+///
+/// Generates `(= (mod v k) r)` for each integer variable `v` of the sample, where `r` is the value
+/// of `v` modulo `k`. The candidate moduli `k` are `2` and, for each pair of integer values of the
+/// sample, their gcd and the absolute value of their difference. Candidates lower than `2` or
+/// greater than `max` are ignored.
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::{ common::*, learning::ice::synth::int::mod_synth };
+/// let sample = var_to::vals::new(vec![val::int(6), val::int(9), val::int(20)]);
+/// let mut quals = vec![];
+/// mod_synth(&sample, 4, |term| {
+///     quals.push(term);
+///     Ok(false)
+/// })
+/// .unwrap();
+/// let (x, y, z): (VarIdx, VarIdx, VarIdx) = (0.into(), 1.into(), 2.into());
+/// let qual = |var: VarIdx, k: usize, r: usize| {
+///     term::eq(
+///         term::modulo(term::var(var, typ::int()), term::int(k)),
+///         term::int(r),
+///     )
+/// };
+/// // Parity, and `3`, both the gcd and the difference of `6` and `9`. The differences `14` and
+/// // `11` are too big.
+/// assert_eq! {
+///     quals,
+///     vec![
+///         qual(x, 2, 0), qual(x, 3, 0),
+///         qual(y, 2, 1), qual(y, 3, 0),
+///         qual(z, 2, 0), qual(z, 3, 2),
+///     ]
+/// }
+/// ```
+pub fn mod_synth<F>(sample: &VarVals, max: usize, mut f: F) -> Res<bool>
+where
+    F: FnMut(Term) -> Res<bool>,
+{
+    use num::{Integer, Signed};
+
+    if max < 2 {
+        return Ok(false);
+    }
+
+    let ints: Vec<(VarIdx, Int)> = sample
+        .index_iter()
+        .filter_map(|(var_idx, val)| {
+            if let val::RVal::I(ref val) = val.get() {
+                Some((var_idx, val.clone()))
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    let mut moduli = vec![Int::from(2)];
+    {
+        let max = Int::from(max);
+        let mut add = |k: Int| {
+            if k > Int::from(2) && k <= max && !moduli.contains(&k) {
+                moduli.push(k)
+            }
+        };
+        for (idx, (_, val)) in ints.iter().enumerate() {
+            for (_, other) in &ints[idx + 1..] {
+                add(val.gcd(other));
+                add((val - other).abs())
+            }
+        }
+    }
+    moduli.sort();
+
+    for (var_idx, val) in ints {
+        let var = term::var(var_idx, typ::int());
+        for k in &moduli {
+            let rem = val.mod_floor(k);
+            let term = term::eq(
+                term::modulo(var.clone(), term::int(k.clone())),
+                term::int(rem),
+            );
+            if f(term)? {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+/// Octagon synthesis.
+///
+/// Generates `(<= (+ (* s x) (* t y)) c)` for each pair of integer variables `x` and `y` of the
+/// sample and all signs `s` and `t` in `{-1, 1}`, where `c` is the value of `(+ (* s x) (* t y))`
+/// on the sample. Does nothing if the sample has more than `max_arity` integer variables.
+///
+/// # Examples
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate hoice;
+/// use hoice::{ common::*, learning::ice::synth::int::octagon_synth, var_vals };
+///
+/// # fn main() {
+/// let sample = var_vals!( (int 1) (int 2) (bool true) (int 3) );
+/// let mut quals = vec![];
+/// let done = octagon_synth(&sample, 8, |qual| {
+///     quals.push(qual);
+///     Ok(false)
+/// }).unwrap();
+/// assert! { !done }
+/// // Three pairs of integer variables, four sign combinations.
+/// assert_eq! { quals.len(), 12 }
+///
+/// let mut quals = vec![];
+/// octagon_synth(&sample, 2, |qual| {
+///     quals.push(qual);
+///     Ok(false)
+/// }).unwrap();
+/// assert! { quals.is_empty() }
+/// # }
+/// ```
+pub fn octagon_synth<F>(sample: &VarVals, max_arity: usize, mut f: F) -> Res<bool>
+where
+    F: FnMut(Term) -> Res<bool>,
+{
+    let mut ints: Vec<(Term, Int)> = vec![];
+    for (var_idx, val) in sample.index_iter() {
+        if let val::RVal::I(ref val) = val.get() {
+            ints.push((term::var(var_idx, typ::int()), val.clone()))
+        }
+    }
+    if ints.len() > max_arity {
+        return Ok(false);
+    }
+
+    let signed = |term: &Term, val: &Int, pos: bool| {
+        if pos {
+            (term.clone(), val.clone())
+        } else {
+            (term::cmul(-1, term.clone()), -val)
+        }
+    };
+
+    for (idx, (x, x_val)) in ints.iter().enumerate() {
+        for (y, y_val) in &ints[idx + 1..] {
+            for (x_pos, y_pos) in &[(true, true), (true, false), (false, true), (false, false)] {
+                let (x, x_val) = signed(x, x_val, *x_pos);
+                let (y, y_val) = signed(y, y_val, *y_pos);
+                let qual = term::le(term::add(vec![x, y]), term::int(x_val + y_val));
+                if f(qual)? {
+                    return Ok(true);
+                }
+            }
+        }
+    }
+
+    Ok(false)
+}
+
 /// Non-linear int synthesis.
 pub fn non_lin_int_synth<F>(sample: &VarVals, others: &mut TermVals, mut f: F) -> Res<bool>
 where
@@ -10,8 +10,10 @@ use crate::common::*;
 #[macro_use]
 pub mod helpers;
 pub mod adt;
+pub mod array;
 pub mod int;
 pub mod real;
+pub mod template;
 
 pub type TermVals = TermMap<Val>;
 
@@ -57,15 +59,22 @@ pub trait TheoSynth {
 }
 
 use self::adt::AdtSynth;
+use self::array::ArraySynth;
 use self::int::IntSynth;
 use self::real::RealSynth;
+use self::template::QualTemplates;
 
 /// Manages theory synthesizers.
 pub struct SynthSys {
     int: Option<IntSynth>,
     real: Option<RealSynth>,
     adt: Vec<AdtSynth>,
+    array: Vec<ArraySynth>,
     cross_synth: TermMap<Val>,
+    /// User-provided qualifier templates, see `--qual_templates`.
+    templates: Option<Arc<QualTemplates>>,
+    /// True if the templates have been instantiated since the last restart.
+    templates_done: bool,
 }
 impl SynthSys {
     /// Constructor.
@@ -87,6 +96,7 @@ impl SynthSys {
         }
 
         let mut adt: Vec<AdtSynth> = Vec::new();
+        let mut array: Vec<ArraySynth> = Vec::new();
         for typ in sig {
             match **typ {
                 typ::RTyp::Int => set!(int),
@@ -105,7 +115,21 @@ impl SynthSys {
                     }
                 }
 
-                typ::RTyp::Bool | typ::RTyp::Array { .. } | typ::RTyp::Unk => (),
+                typ::RTyp::Array { .. } => {
+                    if array.iter().all(|array| array.typ() != typ) {
+                        if let Some(synth) = ArraySynth::new(typ.clone()) {
+                            if synth.tgt().is_int() {
+                                set!(int)
+                            }
+                            if synth.tgt().is_real() {
+                                set!(real)
+                            }
+                            array.push(synth)
+                        }
+                    }
+                }
+
+                typ::RTyp::Bool | typ::RTyp::Unk => (),
             }
         }
 
@@ -113,7 +137,19 @@ impl SynthSys {
             int,
             real,
             adt,
+            array,
             cross_synth: TermMap::new(),
+            templates: None,
+            templates_done: false,
+        }
+    }
+
+    /// Sets the qualifier templates.
+    ///
+    /// Templates are instantiated on the samples until the first increment after each restart.
+    pub fn set_templates(&mut self, templates: Arc<QualTemplates>) {
+        if !templates.is_empty() {
+            self.templates = Some(templates)
         }
     }
 
@@ -126,6 +162,7 @@ impl SynthSys {
 
     /// Increments all synthesizers.
     pub fn increment(&mut self) {
+        self.templates_done = true;
         if let Some(i) = self.int.as_mut() {
             i.increment()
         }
@@ -139,6 +176,7 @@ impl SynthSys {
 
     /// Restarts all synthesizers.
     pub fn restart(&mut self) {
+        self.templates_done = false;
         if let Some(i) = self.int.as_mut() {
             i.restart()
         }
@@ -153,18 +191,46 @@ impl SynthSys {
     /// Synthesizes qualifiers for a sample, stops if input function returns
     /// `true`.
     ///
+    /// Qualifiers with a degree greater than `conf.ice.qual_max_degree` are ignored.
+    ///
     /// Returns `true` iff `f` returned true at some point.
     pub fn sample_synth<F>(&mut self, sample: &VarVals, mut f: F, _prof: &Profiler) -> Res<bool>
     where
         F: FnMut(Term) -> Res<bool>,
     {
-        let done = self.int_synth(sample, &mut f, _prof)?
+        let max_degree = conf.ice.qual_max_degree;
+        let mut f = |term: Term| {
+            if term.degree() > max_degree {
+                Ok(false)
+            } else {
+                f(term)
+            }
+        };
+
+        let done = self.template_synth(sample, &mut f, _prof)?
+            || self.int_synth(sample, &mut f, _prof)?
             || self.real_synth(sample, &mut f, _prof)?
             || self.adt_synth(sample, &mut f, _prof)?;
 
         Ok(done)
     }
 
+    /// Instantiates the qualifier templates, see `--qual_templates`.
+    pub fn template_synth<F>(&mut self, sample: &VarVals, f: F, _profiler: &Profiler) -> Res<bool>
+    where
+        F: FnMut(Term) -> Res<bool>,
+    {
+        if let (Some(templates), false) = (self.templates.as_ref(), self.templates_done) {
+            profile! (
+              |_profiler| wrap {
+                templates.synth(sample, f)
+              } "learning", "qual", "synthesis", "templates"
+            )
+        } else {
+            Ok(false)
+        }
+    }
+
     /// Runs integer synthesis.
     pub fn int_synth<F>(&mut self, sample: &VarVals, mut f: F, _profiler: &Profiler) -> Res<bool>
     where
@@ -194,6 +260,15 @@ impl SynthSys {
                     }
                     res?
                 }
+                for array_synth in &self.array {
+                    profile! (
+                      |_profiler| wrap {
+                        array_synth.project(
+                          sample, int_synth.typ(), & mut self.cross_synth
+                        )
+                      } "learning", "qual", "synthesis", "array project"
+                    )?
+                }
 
                 profile! { |_profiler| tick "learning", "qual", "synthesis", "int" }
                 let done = int_synth.synth(&mut f, sample, &mut self.cross_synth, _profiler);
@@ -235,6 +310,15 @@ impl SynthSys {
                     }
                     res?
                 }
+                for array_synth in &self.array {
+                    profile! (
+                      |_profiler| wrap {
+                        array_synth.project(
+                          sample, real_synth.typ(), & mut self.cross_synth
+                        )
+                      } "learning", "qual", "synthesis", "array project"
+                    )?
+                }
 
                 profile! { |_profiler| tick "learning", "qual", "synthesis", "real" }
                 let done = real_synth.synth(&mut f, sample, &mut self.cross_synth, _profiler);
@@ -1,6 +1,9 @@
 //! Qualifier synthesis in the theory of reals.
 
-use super::{helpers::n_term_arith_synth, TermVals, TheoSynth};
+use super::{
+    helpers::{monomial_synth, n_term_arith_synth},
+    TermVals, TheoSynth,
+};
 use crate::common::*;
 
 /// Real qualifier synthesizer.
@@ -73,7 +76,12 @@ impl TheoSynth for RealSynth {
 
             1 => profile!(
               |_profiler| wrap {
-                non_lin_real_synth(sample, others, f)
+                let done = non_lin_real_synth(sample, others, & mut f) ? ;
+                if ! done && conf.ice.qual_max_degree > 1 {
+                  monomial_synth(sample, & self.typ, conf.ice.qual_max_degree, f)
+                } else {
+                  Ok(done)
+                }
               } "learning", "qual", "synthesis", "real", "level 1"
             ),
 
@@ -83,6 +91,12 @@ impl TheoSynth for RealSynth {
               } "learning", "qual", "synthesis", "real", "level n > 1"
             ),
 
+            3 => profile!(
+              |_profiler| wrap {
+                lin_real_synth(sample, others, f)
+              } "learning", "qual", "synthesis", "real", "level 3"
+            ),
+
             _ => {
                 self.done = true;
                 Ok(false)
@@ -105,6 +119,116 @@ impl TheoSynth for RealSynth {
     }
 }
 
+/// Level 3 for real synthesis: linear qualifiers with rational coefficients.
+///
+/// For all pairs of real terms `t_1`, `t_2` with non-zero values `v_1`, `v_2`, synthesizes
+/// qualifiers of the form `t_1 + c * t_2 >= n` and `t_1 + c * t_2 <= n` with `n = v_1 + c * v_2`,
+/// for `c` in `2`, `1/2`, `v_1 / v_2` and their negations. Unit coefficients are left to
+/// [`n_term_arith_synth`].
+///
+/// [`n_term_arith_synth`]: ../helpers/fn.n_term_arith_synth.html (n_term_arith_synth function)
+///
+/// # Examples
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate hoice;
+/// use hoice::{ common::*, learning::ice::synth::real::lin_real_synth, var_vals };
+///
+/// # fn main() {
+/// let sample = var_vals!( (real 3) (int 7) (real 2) );
+/// let mut quals = vec![];
+/// lin_real_synth(&sample, &mut TermMap::new(), |qual| {
+///     quals.push(qual);
+///     Ok(false)
+/// }).unwrap();
+/// // Four constant coefficients, plus `2/3` and `-2/3`, two qualifiers each.
+/// assert_eq! { quals.len(), 12 }
+/// for qual in &quals {
+///     // Qualifiers go through the sample.
+///     assert_eq! { qual.eval(&*sample).unwrap().to_bool().unwrap(), Some(true) }
+///     // Only real arguments are considered.
+///     assert! { ! term::vars(qual).contains(&VarIdx::from(1)) }
+/// }
+/// # }
+/// ```
+pub fn lin_real_synth<F>(sample: &VarVals, others: &mut TermVals, mut f: F) -> Res<bool>
+where
+    F: FnMut(Term) -> Res<bool>,
+{
+    let mut previous: Vec<(Term, Rat)> = Vec::with_capacity(sample.len());
+
+    // Iterate over the sample.
+    for (var_idx, val) in sample.index_iter() {
+        if let val::RVal::R(ref r) = val.get() {
+            let var = term::var(var_idx, typ::real());
+            if lin_real_synth_pairs(&var, r, &previous, &mut f)? {
+                return Ok(true);
+            }
+            previous.push((var, r.clone()))
+        }
+    }
+
+    // Iterate over the cross-theory terms.
+    for (term, val) in others.drain() {
+        if let val::RVal::R(ref r) = val.get() {
+            if lin_real_synth_pairs(&term, r, &previous, &mut f)? {
+                return Ok(true);
+            }
+            previous.push((term.clone(), r.clone()))
+        } else {
+            bail!(
+                "real synthesis expects projected reals, got {} for {}",
+                val,
+                term
+            )
+        }
+    }
+
+    Ok(false)
+}
+
+/// Rational-coefficient synthesis between a term and the previous ones.
+fn lin_real_synth_pairs<F>(term: &Term, val: &Rat, previous: &[(Term, Rat)], f: &mut F) -> Res<bool>
+where
+    F: FnMut(Term) -> Res<bool>,
+{
+    if val.is_zero() {
+        return Ok(false);
+    }
+
+    let two = Rat::from_integer(2.into());
+    let half = Rat::new(1.into(), 2.into());
+
+    for &(ref other_term, ref other_val) in previous {
+        if other_val.is_zero() {
+            continue;
+        }
+
+        let mut coefs = vec![two.clone(), -two.clone(), half.clone(), -half.clone()];
+        let ratio = val / other_val;
+        if !ratio.abs().is_one() && !coefs.contains(&ratio) {
+            coefs.push(-ratio.clone());
+            coefs.push(ratio)
+        }
+
+        for coef in coefs {
+            let cst = val + &coef * other_val;
+            let lhs = term::add(vec![term.clone(), term::cmul(coef, other_term.clone())]);
+            let rhs = term::real(cst);
+
+            if f(term::ge(lhs.clone(), rhs.clone()))? {
+                return Ok(true);
+            }
+            if f(term::le(lhs, rhs))? {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
 /// Level 1 for real synthesis.
 pub fn non_lin_real_synth<F>(sample: &VarVals, others: &mut TermVals, mut f: F) -> Res<bool>
 where
@@ -0,0 +1,324 @@
+//! Qualifier synthesis from user-provided templates, see `--qual_templates`.
+
+use crate::common::*;
+
+/// Maximal number of instantiations of a template tried on a sample.
+pub const MAX_INSTANTIATIONS: usize = 10_000;
+
+/// A qualifier template.
+///
+/// A template is a boolean term over some variables and some *holes*, the variables the name of
+/// which starts with `?`:
+///
+/// ```text
+/// ((x Int) (y Int) (?a Int) (?b Int) (?c Int)) (<= (+ (* ?a x) (* ?b y)) ?c)
+/// ```
+///
+/// Given a sample, the variables are bound to the arguments of the same type, in all possible
+/// (injective) ways. If the template is a comparison between two arithmetic terms, its last hole
+/// is the *constant* hole: it gets the value making the comparison tight on the sample, if any.
+/// The other holes are *coefficients*, they range over the integers between
+/// `-conf.ice.qual_template_coef` and `conf.ice.qual_template_coef`.
+///
+/// The constant hole is solved by evaluating the difference between the two sides of the
+/// comparison for the hole values `0` and `1`, which assumes the template is linear in this hole.
+/// Instantiations that are not true on the sample are dropped.
+///
+/// There are `(2 * conf.ice.qual_template_coef + 1)^n` coefficient combinations for `n`
+/// coefficient holes, for each binding of the variables. At most [`MAX_INSTANTIATIONS`] of them
+/// are tried per sample.
+///
+/// [`MAX_INSTANTIATIONS`]: constant.MAX_INSTANTIATIONS.html (MAX_INSTANTIATIONS constant)
+#[derive(Debug, Clone)]
+pub struct QualTemplate {
+    /// The template.
+    term: Term,
+    /// Variables of the template, bound to the arguments of the samples.
+    vars: Vec<(VarIdx, Typ)>,
+    /// Coefficient holes.
+    coefs: Vec<(VarIdx, Typ)>,
+    /// Constant hole, and the difference between the two sides of the comparison.
+    cst: Option<(VarIdx, Typ, Term)>,
+}
+
+impl QualTemplate {
+    /// Parses a template.
+    pub fn new(s: &str, instance: &Instance) -> Res<Self> {
+        let (var_infos, term) = crate::parse::sorted_vars_term(s, instance)?;
+        if !term.typ().is_bool() {
+            bail!("expected a boolean template, got `{}`", term)
+        }
+
+        let (mut vars, mut holes) = (vec![], vec![]);
+        for info in &var_infos {
+            if info.name.starts_with('?') {
+                if !info.typ.is_arith() {
+                    bail!(
+                        "hole `{}` has type {}, expected Int or Real",
+                        info.name,
+                        info.typ
+                    )
+                }
+                holes.push((info.idx, info.typ.clone()))
+            } else {
+                vars.push((info.idx, info.typ.clone()))
+            }
+        }
+
+        let mut cst = None;
+        if let Some((op, args)) = term.app_inspect() {
+            let is_cmp = match op {
+                Op::Le | Op::Ge | Op::Lt | Op::Gt | Op::Eql => true,
+                _ => false,
+            };
+            if is_cmp && args.len() == 2 && args[0].typ().is_arith() {
+                if let Some((hole, typ)) = holes.pop() {
+                    let diff = term::sub(vec![args[0].clone(), args[1].clone()]);
+                    cst = Some((hole, typ, diff))
+                }
+            }
+        }
+
+        Ok(QualTemplate {
+            term,
+            vars,
+            coefs: holes,
+            cst,
+        })
+    }
+
+    /// Synthesizes the instances of the template for a sample.
+    ///
+    /// Returns `true` iff `f` returned `true` at some point.
+    pub fn synth<F>(&self, sample: &VarVals, f: &mut F) -> Res<bool>
+    where
+        F: FnMut(Term) -> Res<bool>,
+    {
+        let mut bindings = vec![];
+        self.bindings(sample, &mut vec![], &mut bindings);
+
+        let max = conf.ice.qual_template_coef as isize;
+        let mut count = 0;
+        for binding in bindings {
+            let mut map = VarHMap::new();
+            for ((var, typ), arg) in self.vars.iter().zip(binding.iter()) {
+                map.insert(*var, term::var(*arg, typ.clone()));
+            }
+
+            let mut coefs = vec![-max; self.coefs.len()];
+            'all_coefs: loop {
+                if count >= MAX_INSTANTIATIONS {
+                    log! { @verb "template instantiation limit reached on {}", self.term }
+                    return Ok(false);
+                }
+                count += 1;
+
+                for ((hole, typ), coef) in self.coefs.iter().zip(coefs.iter()) {
+                    let coef = if typ.is_int() {
+                        term::int(*coef)
+                    } else {
+                        term::real(Rat::from_integer((*coef).into()))
+                    };
+                    map.insert(*hole, coef);
+                }
+
+                if let Some(qual) = self.instantiate(sample, &mut map)? {
+                    if f(qual)? {
+                        return Ok(true);
+                    }
+                }
+
+                // Next coefficients.
+                for coef in coefs.iter_mut() {
+                    if *coef < max {
+                        *coef += 1;
+                        continue 'all_coefs;
+                    } else {
+                        *coef = -max
+                    }
+                }
+                break 'all_coefs;
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Instantiates the template, given the values of the variables and the coefficients.
+    ///
+    /// Solves the constant hole if any. `None` if the the constant hole has no solution, or if the
+    /// result is not a non-trivial qualifier true on the sample.
+    fn instantiate(&self, sample: &VarVals, map: &mut VarHMap<Term>) -> Res<Option<Term>> {
+        if let Some((hole, typ, diff)) = self.cst.as_ref() {
+            let mut eval_diff = |val: isize| -> Res<Option<Rat>> {
+                let val = if typ.is_int() {
+                    term::int(val)
+                } else {
+                    term::real(Rat::from_integer(val.into()))
+                };
+                map.insert(*hole, val);
+                if let Some((diff, _)) = diff.subst_total(&*map) {
+                    diff.eval(&**sample)?.to_real()
+                } else {
+                    Ok(None)
+                }
+            };
+            let (d_0, d_1) = match (eval_diff(0)?, eval_diff(1)?) {
+                (Some(d_0), Some(d_1)) => (d_0, d_1),
+                _ => return Ok(None),
+            };
+            let slope = d_1 - &d_0;
+            if slope.is_zero() {
+                return Ok(None);
+            }
+            let val = -d_0 / slope;
+            let val = if typ.is_int() {
+                if !val.is_integer() {
+                    return Ok(None);
+                }
+                term::int(val.to_integer())
+            } else {
+                term::real(val)
+            };
+            map.insert(*hole, val);
+        }
+
+        let qual = if let Some((qual, _)) = self.term.subst_total(&*map) {
+            qual
+        } else {
+            return Ok(None);
+        };
+        if qual.val().is_some() || qual.eval(&**sample)?.to_bool()? != Some(true) {
+            return Ok(None);
+        }
+        Ok(Some(qual))
+    }
+
+    /// All the ways to bind the variables of the template to the arguments of a sample.
+    fn bindings(&self, sample: &VarVals, current: &mut Vec<VarIdx>, res: &mut Vec<Vec<VarIdx>>) {
+        if let Some((_, typ)) = self.vars.get(current.len()) {
+            for (arg, val) in sample.index_iter() {
+                if val.is_known() && val.typ() == *typ && !current.contains(&arg) {
+                    current.push(arg);
+                    self.bindings(sample, current, res);
+                    current.pop();
+                }
+            }
+        } else {
+            res.push(current.clone())
+        }
+    }
+}
+
+/// Qualifier templates, see [`QualTemplate`] and `--qual_templates`.
+///
+/// [`QualTemplate`]: struct.QualTemplate.html (QualTemplate struct)
+///
+/// # Examples
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate hoice;
+/// use hoice::{ common::*, learning::ice::synth::template::QualTemplates, var_vals };
+///
+/// # fn main() {
+/// let templates = QualTemplates::new("
+///     ; Weighted sums of two integers.
+///     ((x Int) (y Int) (?a Int) (?c Int)) (<= (+ x (* ?a y)) ?c)
+/// ", &Instance::new()).unwrap();
+/// assert_eq! { templates.len(), 1 }
+///
+/// let sample = var_vals!( (int 3) (bool true) (int 5) );
+/// let mut quals = vec![];
+/// templates.synth(&sample, |qual| {
+///     quals.push(qual);
+///     Ok(false)
+/// }).unwrap();
+/// // Two bindings, five coefficients (the default bound is `2`).
+/// assert_eq! { quals.len(), 10 }
+/// for qual in &quals {
+///     assert_eq! { qual.eval(&*sample).unwrap().to_bool().unwrap(), Some(true) }
+/// }
+/// // Qualifiers are tight on the sample: `(<= (+ v_0 (* a v_2)) (+ 3 (* a 5)))`.
+/// let shifted = var_vals!( (int 4) (bool true) (int 5) );
+/// let falsified = quals.iter().filter(
+///     |qual| qual.eval(&*shifted).unwrap().to_bool().unwrap() == Some(false)
+/// ).count();
+/// assert_eq! { falsified, 5 + 2 }
+///
+/// // The holes of a real template get real values.
+/// let templates = QualTemplates::new(
+///     "((x Real) (?a Real) (?c Real)) (<= (* ?a x) ?c)", &Instance::new()
+/// ).unwrap();
+/// let sample = var_vals!( (real 1.5) );
+/// let mut quals = vec![];
+/// templates.synth(&sample, |qual| {
+///     quals.push(qual);
+///     Ok(false)
+/// }).unwrap();
+/// // `?a = 0` yields a trivial qualifier.
+/// assert_eq! { quals.len(), 4 }
+/// for qual in &quals {
+///     assert_eq! { qual.eval(&*sample).unwrap().to_bool().unwrap(), Some(true) }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct QualTemplates {
+    /// Templates.
+    templates: Vec<QualTemplate>,
+}
+
+impl QualTemplates {
+    /// Parses some templates, one per line.
+    ///
+    /// Lines starting with `;` are ignored.
+    pub fn new(templates: &str, instance: &Instance) -> Res<Self> {
+        let mut res = vec![];
+        for (line_idx, line) in templates.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+            res.push(
+                QualTemplate::new(line, instance)
+                    .chain_err(|| format!("illegal qualifier template on line {}", line_idx + 1))?,
+            )
+        }
+        Ok(QualTemplates { templates: res })
+    }
+
+    /// Loads some templates from a file.
+    pub fn of_file(file: &str, instance: &Instance) -> Res<Self> {
+        let mut content = String::new();
+        ::std::fs::File::open(file)
+            .and_then(|mut f| f.read_to_string(&mut content))
+            .chain_err(|| format!("while reading qualifier templates `{}`", file))?;
+        Self::new(&content, instance)
+            .chain_err(|| format!("while parsing qualifier templates `{}`", file))
+    }
+
+    /// Number of templates.
+    pub fn len(&self) -> usize {
+        self.templates.len()
+    }
+    /// True if there are no templates.
+    pub fn is_empty(&self) -> bool {
+        self.templates.is_empty()
+    }
+
+    /// Synthesizes the instances of all the templates for a sample.
+    ///
+    /// Returns `true` iff `f` returned `true` at some point.
+    pub fn synth<F>(&self, sample: &VarVals, mut f: F) -> Res<bool>
+    where
+        F: FnMut(Term) -> Res<bool>,
+    {
+        for template in &self.templates {
+            if template.synth(sample, &mut f)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
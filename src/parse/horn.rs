@@ -0,0 +1,418 @@
+//! Prolog-style Horn clause front-end, see `--horn`.
+//!
+//! Reads rules `head :- body.`, facts `head.` and goals `?- body.` (or `false :- body.`). Goals
+//! are the queries the program must *not* satisfy, they become the negative clauses of the
+//! instance. Variables start with an uppercase letter or `_`, and are scoped by clause. `_` alone
+//! is anonymous: each occurrence is a fresh variable.
+//!
+//! Predicates are declared implicitly by their first application, all their arguments are
+//! integers. Arithmetic supports integer constants, `+`, `-` and `*`. Comparisons are `=`, `is`,
+//! `=:=`, `\=`, `=\=`, `<`, `=<`, `>`, `>=`. Comments start with `%` and run until the end of the
+//! line.
+//!
+//! ```rust
+//! use hoice::common::*;
+//! let mut instance = Instance::new();
+//! let pred = instance.preds().next_index();
+//! hoice::parse::horn::parse("\
+//!     % Even numbers.
+//!     even(0).
+//!     even(Y) :- even(X), Y is X + 2.
+//!     ?- even(X), X < 0.
+//! ", &mut instance).expect("while parsing horn program");
+//! assert_eq! { instance.preds().len(), 1 }
+//! assert_eq! { &instance.preds()[pred].name, "even" }
+//! assert_eq! { instance.clauses().len(), 3 }
+//! ```
+
+use crate::{common::*, info::VarInfo};
+
+/// Tokens of the horn dialect.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    /// Lowercase identifier: predicate name, `true`, `false` or `is`.
+    Ident(String),
+    /// Variable.
+    Var(String),
+    /// Integer constant.
+    Int(Int),
+    /// Punctuation and operators.
+    Sym(&'static str),
+}
+
+/// Symbols, longest first so that prefixes do not shadow them.
+static SYMBOLS: &[&str] = &[
+    "=:=", "=\\=", ":-", "?-", "\\=", "=<", "<=", ">=", "=", "<", ">", "+", "-", "*", "(", ")",
+    ",", ".",
+];
+
+/// Splits some text into tokens, with their line.
+fn tokenize(text: &str) -> Res<Vec<(Token, usize)>> {
+    let mut tokens = vec![];
+    for (line_idx, line) in text.lines().enumerate() {
+        let line_nb = line_idx + 1;
+        let mut rest = line;
+        'line: loop {
+            rest = rest.trim_start();
+            let first = match rest.chars().next() {
+                Some('%') | None => break 'line,
+                Some(c) => c,
+            };
+
+            if first.is_alphanumeric() || first == '_' {
+                let len = rest
+                    .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                let (word, tail) = rest.split_at(len);
+                rest = tail;
+                let token = if first.is_numeric() {
+                    if let Some(int) = Int::parse_bytes(word.as_bytes(), 10) {
+                        Token::Int(int)
+                    } else {
+                        bail!("line {}: illegal integer `{}`", line_nb, conf.bad(word))
+                    }
+                } else if first.is_uppercase() || first == '_' {
+                    Token::Var(word.into())
+                } else {
+                    Token::Ident(word.into())
+                };
+                tokens.push((token, line_nb));
+                continue 'line;
+            }
+
+            for sym in SYMBOLS {
+                if rest.starts_with(*sym) {
+                    tokens.push((Token::Sym(*sym), line_nb));
+                    rest = &rest[sym.len()..];
+                    continue 'line;
+                }
+            }
+
+            bail!(
+                "line {}: unexpected character `{}`",
+                line_nb,
+                conf.bad(&first.to_string())
+            )
+        }
+    }
+    Ok(tokens)
+}
+
+/// A body literal.
+enum Lit {
+    /// Predicate application.
+    App(PrdIdx, VarTerms),
+    /// Arithmetic constraint.
+    Term(Term),
+}
+
+/// Horn front-end parser.
+struct HornParser<'a> {
+    /// Tokens.
+    tokens: Vec<(Token, usize)>,
+    /// Index of the next token.
+    cursor: usize,
+    /// Instance to populate.
+    instance: &'a mut Instance,
+    /// Predicates by name.
+    preds: BTreeMap<String, PrdIdx>,
+    /// Variables of the current clause.
+    vars: VarInfos,
+    /// Variables of the current clause by name.
+    var_map: BTreeMap<String, VarIdx>,
+}
+
+impl<'a> HornParser<'a> {
+    /// The next token, if any.
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.cursor).map(|(token, _)| token)
+    }
+
+    /// Line of the current token.
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.cursor)
+            .or_else(|| self.tokens.last())
+            .map(|(_, line)| *line)
+            .unwrap_or(0)
+    }
+
+    /// Consumes a symbol if it's the next token.
+    fn sym_opt(&mut self, sym: &str) -> bool {
+        if let Some(Token::Sym(s)) = self.peek() {
+            if *s == sym {
+                self.cursor += 1;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Consumes a symbol, fails if it's not the next token.
+    fn sym(&mut self, sym: &str) -> Res<()> {
+        if self.sym_opt(sym) {
+            Ok(())
+        } else {
+            bail!(
+                "line {}: expected `{}`, found {}",
+                self.line(),
+                conf.emph(sym),
+                self.describe_next()
+            )
+        }
+    }
+
+    /// Consumes an identifier if it's the next token.
+    fn ident_opt(&mut self, ident: &str) -> bool {
+        if let Some(Token::Ident(id)) = self.peek() {
+            if id == ident {
+                self.cursor += 1;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Describes the next token, for error messages.
+    fn describe_next(&self) -> String {
+        match self.peek() {
+            None => "<eof>".into(),
+            Some(Token::Ident(s)) | Some(Token::Var(s)) => format!("`{}`", conf.bad(s)),
+            Some(Token::Int(i)) => format!("`{}`", conf.bad(&i.to_string())),
+            Some(Token::Sym(s)) => format!("`{}`", conf.bad(s)),
+        }
+    }
+
+    /// Parses all the items.
+    fn parse(&mut self) -> Res<()> {
+        while self.peek().is_some() {
+            self.vars = VarInfos::new();
+            self.var_map.clear();
+            self.item()?
+        }
+        Ok(())
+    }
+
+    /// Parses a rule, a fact or a goal, and adds it to the instance.
+    fn item(&mut self) -> Res<()> {
+        let rhs = if self.sym_opt("?-") {
+            None
+        } else if self.ident_opt("false") {
+            self.sym(":-")?;
+            None
+        } else {
+            let head = self.atom()?;
+            if !self.sym_opt(":-") {
+                self.sym(".")?;
+                return self.add_clause(vec![], Some(head));
+            }
+            Some(head)
+        };
+
+        let mut lhs = vec![];
+        loop {
+            match self.literal()? {
+                Lit::App(pred, args) => lhs.push(TTerm::P { pred, args }),
+                Lit::Term(term) => lhs.push(TTerm::T(term)),
+            }
+            if !self.sym_opt(",") {
+                break;
+            }
+        }
+        self.sym(".")?;
+
+        self.add_clause(lhs, rhs)
+    }
+
+    /// Adds a clause to the instance.
+    fn add_clause(&mut self, lhs: Vec<TTerm>, rhs: Option<PredApp>) -> Res<()> {
+        if lhs.iter().any(|tterm| tterm.is_false()) {
+            return Ok(());
+        }
+        let lhs = lhs.into_iter().filter(|tterm| !tterm.is_true()).collect();
+        let vars = ::std::mem::replace(&mut self.vars, VarInfos::new());
+        let _ = self.instance.push_new_clause(vars, lhs, rhs, "parsing")?;
+        Ok(())
+    }
+
+    /// Parses a body literal.
+    fn literal(&mut self) -> Res<Lit> {
+        if self.ident_opt("true") {
+            return Ok(Lit::Term(term::tru()));
+        } else if self.ident_opt("false") {
+            return Ok(Lit::Term(term::fls()));
+        } else if let Some(Token::Ident(_)) = self.peek() {
+            let (pred, args) = self.atom()?;
+            return Ok(Lit::App(pred, args));
+        }
+
+        let lft = self.expr()?;
+        let line = self.line();
+        let op = match self.peek() {
+            Some(Token::Sym(sym)) => *sym,
+            Some(Token::Ident(id)) if id == "is" => "is",
+            _ => bail!(
+                "line {}: expected comparison operator, found {}",
+                line,
+                self.describe_next()
+            ),
+        };
+        self.cursor += 1;
+        let rgt = self.expr()?;
+        let term = match op {
+            "=" | "is" | "=:=" => term::eq(lft, rgt),
+            "\\=" | "=\\=" => term::not(term::eq(lft, rgt)),
+            "<" => term::lt(lft, rgt),
+            "=<" | "<=" => term::le(lft, rgt),
+            ">" => term::gt(lft, rgt),
+            ">=" => term::ge(lft, rgt),
+            _ => bail!(
+                "line {}: expected comparison operator, found `{}`",
+                line,
+                conf.bad(op)
+            ),
+        };
+        Ok(Lit::Term(term))
+    }
+
+    /// Parses a predicate application.
+    fn atom(&mut self) -> Res<PredApp> {
+        let line = self.line();
+        let name = if let Some(Token::Ident(name)) = self.peek() {
+            name.clone()
+        } else {
+            bail!(
+                "line {}: expected predicate application, found {}",
+                line,
+                self.describe_next()
+            )
+        };
+        self.cursor += 1;
+
+        let mut args = VarMap::new();
+        if self.sym_opt("(") {
+            loop {
+                args.push(self.expr()?);
+                if !self.sym_opt(",") {
+                    break;
+                }
+            }
+            self.sym(")")?
+        }
+
+        let pred = if let Some(pred) = self.preds.get(&name) {
+            *pred
+        } else {
+            let sig: Sig = args.iter().map(|_| typ::int()).collect();
+            let pred = self.instance.push_pred(name.clone(), sig);
+            self.preds.insert(name.clone(), pred);
+            pred
+        };
+        let arity = self.instance[pred].sig.len();
+        if arity != args.len() {
+            bail!(
+                "line {}: predicate `{}` has arity {}, but is applied to {} argument(s)",
+                line,
+                conf.bad(&name),
+                arity,
+                args.len()
+            )
+        }
+
+        Ok((pred, var_to::terms::new(args)))
+    }
+
+    /// Parses an arithmetic expression.
+    fn expr(&mut self) -> Res<Term> {
+        let mut kids = vec![self.product()?];
+        loop {
+            if self.sym_opt("+") {
+                kids.push(self.product()?)
+            } else if self.sym_opt("-") {
+                kids.push(term::u_minus(self.product()?))
+            } else {
+                break;
+            }
+        }
+        if kids.len() == 1 {
+            Ok(kids.pop().expect("non-empty"))
+        } else {
+            Ok(term::add(kids))
+        }
+    }
+
+    /// Parses a product.
+    fn product(&mut self) -> Res<Term> {
+        let mut kids = vec![self.unary()?];
+        while self.sym_opt("*") {
+            kids.push(self.unary()?)
+        }
+        if kids.len() == 1 {
+            Ok(kids.pop().expect("non-empty"))
+        } else {
+            Ok(term::mul(kids))
+        }
+    }
+
+    /// Parses a potentially negated primary expression.
+    fn unary(&mut self) -> Res<Term> {
+        if self.sym_opt("-") {
+            return Ok(term::u_minus(self.unary()?));
+        }
+        if self.sym_opt("(") {
+            let term = self.expr()?;
+            self.sym(")")?;
+            return Ok(term);
+        }
+
+        let line = self.line();
+        let token = self.peek().cloned();
+        match token {
+            Some(Token::Int(int)) => {
+                self.cursor += 1;
+                Ok(term::int(int))
+            }
+            Some(Token::Var(name)) => {
+                self.cursor += 1;
+                Ok(term::int_var(self.var(name)))
+            }
+            _ => bail!(
+                "line {}: expected arithmetic expression, found {}",
+                line,
+                self.describe_next()
+            ),
+        }
+    }
+
+    /// Retrieves a variable of the current clause, creates it if needed.
+    fn var(&mut self, name: String) -> VarIdx {
+        if name != "_" {
+            if let Some(idx) = self.var_map.get(&name) {
+                return *idx;
+            }
+        }
+        let idx = self.vars.next_index();
+        let name = if name == "_" {
+            format!("_{}", idx)
+        } else {
+            self.var_map.insert(name.clone(), idx);
+            name
+        };
+        self.vars.push(VarInfo::new(name, typ::int(), idx));
+        idx
+    }
+}
+
+/// Parses a horn program and populates an instance.
+pub fn parse(text: &str, instance: &mut Instance) -> Res<()> {
+    let tokens = tokenize(text)?;
+    let mut parser = HornParser {
+        tokens,
+        cursor: 0,
+        instance,
+        preds: BTreeMap::new(),
+        vars: VarInfos::new(),
+        var_map: BTreeMap::new(),
+    };
+    parser.parse().chain_err(|| "while parsing horn program")
+}
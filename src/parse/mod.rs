@@ -68,6 +68,7 @@
 
 use crate::{common::*, consts::keywords, info::VarInfo};
 
+pub mod horn;
 mod ptterms;
 pub use self::ptterms::*;
 
@@ -360,6 +361,14 @@ pub struct ParserCxt {
     mem: Vec<Cursor>,
     /// Map from predicate names to predicate indices.
     pred_name_map: BTreeMap<String, PrdIdx>,
+    /// Non-recursive `define-fun`s inlined during parsing.
+    ///
+    /// Only populated when `conf.inline_funs` is active.
+    macros: BTreeMap<String, (VarInfos, Term)>,
+    /// Unknown identifier the last parse failed on, if any.
+    ///
+    /// Used to delay items with forward references, see `conf.stream_parse`.
+    forward_ref: Option<String>,
 }
 impl ParserCxt {
     /// Constructor.
@@ -368,6 +377,8 @@ impl ParserCxt {
             term_stack: Vec::with_capacity(17),
             mem: Vec::with_capacity(17),
             pred_name_map: BTreeMap::new(),
+            macros: BTreeMap::new(),
+            forward_ref: None,
         }
     }
 
@@ -392,7 +403,27 @@ impl ParserCxt {
 
     /// Resets the parser.
     pub fn reset(&mut self) {
-        self.pred_name_map.clear()
+        self.pred_name_map.clear();
+        self.macros.clear();
+        self.forward_ref = None
+    }
+
+    /// Unknown identifier the last parse failed on, if any.
+    ///
+    /// Also forgets the backtracking memory of the failed parse, so that the context can be used
+    /// again.
+    pub fn take_forward_ref(&mut self) -> Option<String> {
+        self.mem.clear();
+        self.forward_ref.take()
+    }
+
+    /// True if an identifier is a known predicate or function.
+    pub fn knows(&self, ident: &str, instance: &Instance) -> bool {
+        self.pred_name_map.contains_key(ident)
+            || self.macros.contains_key(ident)
+            || instance.get_define_fun(ident).is_some()
+            || fun::get(ident).is_some()
+            || dtyp::of_constructor(ident).is_some()
     }
 }
 
@@ -743,15 +774,19 @@ impl<'cxt, 's> Parser<'cxt, 's> {
     }
 
     /// Parses a set-info.
-    fn set_info(&mut self) -> Res<bool> {
+    ///
+    /// Returns the key and the value, verbatim. The value is empty if there is
+    /// none.
+    fn set_info(&mut self) -> Res<Option<(&'s str, &'s str)>> {
         if !self.word_opt("set-info") {
-            return Ok(false);
+            return Ok(None);
         }
         self.ws_cmt();
         self.tag(":")?;
         self.ws_cmt();
-        let _ = self.ident()?;
+        let (_, key) = self.ident()?;
         self.ws_cmt();
+        let val_start = self.pos();
         if self.tag_opt("\"") {
             let found_it = self.eat_until('"', true);
             if !found_it {
@@ -759,8 +794,11 @@ impl<'cxt, 's> Parser<'cxt, 's> {
             }
         } else if self.ident_opt()?.is_some() {
             ()
+        } else if !self.eat_until(')', false) {
+            bail!(self.error_here("could not find closing `)` for this set-info"))
         }
-        Ok(true)
+        let val = self.string[*val_start..self.cursor].trim();
+        Ok(Some((key, val)))
     }
 
     /// Set-option.
@@ -1594,7 +1632,19 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         self.tag(")")?;
         self.ws_cmt();
         if !self.word_opt("Bool") {
-            bail!(self.error_here("expected Bool sort"))
+            // Not a predicate, uninterpreted function.
+            let typ = self.sort()?;
+            if self.cxt.pred_name_map.contains_key(ident) || fun::get(ident).is_some() {
+                bail!(self.error(pos, format!("redeclaration of {}", conf.bad(ident))))
+            }
+            let mut sig = VarInfos::with_capacity(sorts.len());
+            for sort in sorts {
+                let idx = sig.next_index();
+                sig.push(VarInfo::new(idx.default_str(), sort, idx))
+            }
+            let fun = fun::RFun::uninterpreted(ident, sig, typ);
+            let _ = fun::new(fun).chain_err(|| self.error(pos, "while declaring this function"))?;
+            return Ok(true);
         }
 
         let pred_index = instance.push_pred(ident, VarMap::of(sorts));
@@ -2176,6 +2226,10 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         args_pos: &[Pos],
         args: Vec<Term>,
     ) -> Res<(Term, Pos)> {
+        if let Some((sig, def)) = self.cxt.macros.get(&name) {
+            return self.inline_macro(&name, sig, def, name_pos, args_pos, args);
+        }
+
         match term::try_fun(name, args) {
             Ok(term) => Ok((term, name_pos)),
 
@@ -2212,6 +2266,50 @@ impl<'cxt, 's> Parser<'cxt, 's> {
             }
         }
     }
+
+    /// Inlines the application of a non-recursive `define-fun`.
+    fn inline_macro(
+        &self,
+        name: &str,
+        sig: &VarInfos,
+        def: &Term,
+        name_pos: Pos,
+        args_pos: &[Pos],
+        args: Vec<Term>,
+    ) -> Res<(Term, Pos)> {
+        if sig.len() != args.len() {
+            bail!(self.error(
+                name_pos,
+                format!(
+                    "wrong number of arguments, expected {} but got {}",
+                    sig.len(),
+                    args.len()
+                )
+            ))
+        }
+        for (index, (info, arg)) in sig.iter().zip(args.iter()).enumerate() {
+            if info.typ != arg.typ() {
+                err_chain! {
+                  self.error(
+                    args_pos[index], format!(
+                      "expected an expression of sort {}, found {}", info.typ, arg.typ()
+                    )
+                  )
+                  => self.error(name_pos, "in this function application")
+                }
+            }
+        }
+
+        let args: VarMap<Term> = args.into_iter().collect();
+        if let Some((term, _)) = def.subst_total(&args) {
+            Ok((term, name_pos))
+        } else {
+            bail!(self.error(
+                name_pos,
+                format!("while inlining the application of {}", conf.emph(name))
+            ))
+        }
+    }
 }
 
 impl<'cxt, 's> Parser<'cxt, 's> {
@@ -2410,7 +2508,28 @@ impl<'cxt, 's> Parser<'cxt, 's> {
                 } else {
                     bail!("inconsistent datatype map internal state")
                 }
+            } else if let Some((sig, def)) = self.cxt.macros.get(id) {
+                if sig.is_empty() {
+                    // Nullary macro, see `conf.inline_funs`.
+                    def.clone()
+                } else {
+                    bail!(self.error(
+                        pos,
+                        format!(
+                            "function `{}` takes {} argument(s), applied here to none",
+                            conf.bad(id),
+                            sig.len()
+                        )
+                    ))
+                }
+            } else if fun::get(id)
+                .map(|fun| fun.sig().is_empty())
+                .unwrap_or(false)
+            {
+                // Nullary function, typically an uninterpreted constant.
+                term::fun(id, vec![])
             } else {
+                self.cxt.forward_ref = Some(id.into());
                 bail!(self.error(pos, format!("unknown identifier `{}`", conf.bad(id))))
             }
         } else if self.tag_opt("(") {
@@ -2502,7 +2621,10 @@ impl<'cxt, 's> Parser<'cxt, 's> {
                         op_pos,
                         bind_count,
                     )));
-                } else if self.functions.get(id).is_some() || fun::get(id).is_some() {
+                } else if self.functions.get(id).is_some()
+                    || fun::get(id).is_some()
+                    || self.cxt.macros.contains_key(id)
+                {
                     let op = FrameOp::Fun(id.into());
                     return Ok(TermTokenRes::Push(TermFrame::new(op, op_pos, bind_count)));
                 }
@@ -2513,6 +2635,7 @@ impl<'cxt, 's> Parser<'cxt, 's> {
                     // for fun in self.functions.keys() {
                     //     println!("- {}", fun)
                     // }
+                    self.cxt.forward_ref = Some(id.into());
                     bail!(self.error(pos, format!("unknown identifier (term) `{}`", conf.bad(id))))
                 }
             } else if self.cxt.term_stack.is_empty() {
@@ -2710,6 +2833,13 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         }
 
         if let Some(term) = body.to_term()? {
+            if conf.inline_funs {
+                if self.cxt.macros.contains_key(name) || fun::get(name).is_some() {
+                    bail!(self.error(name_pos, format!("redefinition of {}", conf.emph(name))))
+                }
+                self.cxt.macros.insert(name.into(), (var_info, term));
+                return Ok(true);
+            }
             use crate::fun::FunSig;
             let fun = FunSig::new(name, var_info, out_sort).into_fun(term);
             let _ = fun::new(fun)
@@ -2726,6 +2856,153 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         Ok(true)
     }
 
+    /// Parses a hint `(define-fun <pred> ( (<id> <ty>) ... ) Bool <term>)`.
+    ///
+    /// If `original`, the arguments must match the original signature of the predicate and the
+    /// term is expressed in terms of this original signature. Otherwise, the current signature is
+    /// used.
+    fn hint_def(&mut self, instance: &Instance, original: bool) -> Res<Option<(PrdIdx, Term)>> {
+        if !self.word_opt(keywords::cmd::def_fun) {
+            return Ok(None);
+        }
+        self.ws_cmt();
+
+        let (name_pos, name) = self.ident()?;
+        self.ws_cmt();
+
+        let pred = if let Some(pred) = instance.preds().iter().find(|pred| pred.name == name) {
+            pred
+        } else {
+            bail!(self.error(name_pos, format!("unknown predicate `{}`", conf.bad(name))))
+        };
+
+        let args_pos = self.pos();
+        let mut var_info = VarInfos::new();
+        let mut map = BTreeMap::new();
+        self.args(&mut var_info, &mut map)?;
+        self.ws_cmt();
+
+        let sig = if original {
+            pred.original_sig()
+        } else {
+            &pred.sig
+        };
+        if var_info.len() != sig.len() {
+            bail!(self.error(
+                args_pos,
+                format!(
+                    "{} has arity {}, but this hint has {} argument(s)",
+                    conf.emph(name),
+                    sig.len(),
+                    var_info.len()
+                )
+            ))
+        }
+        for (info, typ) in var_info.iter().zip(sig.iter()) {
+            if info.typ != *typ {
+                bail!(self.error(
+                    args_pos,
+                    format!(
+                        "argument `{}` has sort {}, but {} expects {}",
+                        conf.bad(&info.name),
+                        conf.emph(&format!("{}", info.typ)),
+                        conf.emph(name),
+                        conf.emph(&format!("{}", typ))
+                    )
+                ))
+            }
+        }
+
+        let sort_pos = self.pos();
+        let out_sort = self.sort()?;
+        self.ws_cmt();
+        if !out_sort.is_bool() {
+            bail!(self.error(
+                sort_pos,
+                format!(
+                    "hints must have sort {}, found {}",
+                    conf.emph("Bool"),
+                    conf.bad(&format!("{}", out_sort))
+                )
+            ))
+        }
+
+        let body_pos = self.pos();
+        let body = if let Some(term) = self.term_opt(&var_info, &map, instance)? {
+            term
+        } else {
+            bail!(self.error(body_pos, "expected a term"))
+        };
+        self.ws_cmt();
+
+        if !body.typ().is_bool() {
+            Err::<_, Error>(
+                self.error(name_pos, format!("in this hint for {}", conf.emph(name)))
+                    .into(),
+            )
+            .chain_err(|| {
+                self.error(
+                    body_pos,
+                    format!(
+                        "body has type {}, but expected {}",
+                        conf.emph(&format!("{}", body.typ())),
+                        conf.emph("Bool")
+                    ),
+                )
+            })?
+        }
+
+        Ok(Some((pred.idx, body)))
+    }
+
+    /// Parses some hints, possibly wrapped in a `(model ...)`.
+    ///
+    /// Hints for the same predicate are grouped, in the order in which they appear. See
+    /// [`hint_def`](#method.hint_def) for `original`.
+    pub fn hints(&mut self, instance: &Instance, original: bool) -> Res<PrdHMap<Vec<Term>>> {
+        let mut res = PrdHMap::new();
+
+        self.ws_cmt();
+        if self.word_opt("sat") {
+            self.ws_cmt()
+        }
+
+        let start_pos = self.pos();
+        let mut in_model = false;
+        if self.tag_opt("(") {
+            self.ws_cmt();
+            if self.word_opt("model") {
+                in_model = true
+            } else {
+                self.backtrack_to(start_pos)
+            }
+        }
+        self.ws_cmt();
+
+        while self.tag_opt("(") {
+            self.ws_cmt();
+            if let Some((pred, term)) = self.hint_def(instance, original)? {
+                res.entry(pred).or_insert_with(Vec::new).push(term)
+            } else {
+                bail!(self.error_here("expected `define-fun`"))
+            }
+            self.ws_cmt();
+            self.tag(")")?;
+            self.ws_cmt()
+        }
+
+        if in_model {
+            self.tag(")")?;
+            self.ws_cmt()
+        }
+
+        if self.has_next() {
+            bail!(self.error_here("expected `define-fun` or end of file"))
+        }
+
+        Ok(res)
+    }
+
     /// Parses some PTTerm arguments.
     fn ptterm_args(
         &mut self,
@@ -2942,6 +3219,7 @@ impl<'cxt, 's> Parser<'cxt, 's> {
 
                     Ok(Some(res))
                 } else {
+                    self.cxt.forward_ref = Some(ident.into());
                     bail!(self.error(
                         ident_pos,
                         format!("unknown identifier (tterm) `{}`", conf.bad(ident))
@@ -2970,16 +3248,45 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         map: &BTreeMap<&'s str, VarIdx>,
         instance: &Instance,
     ) -> Res<PTTerms> {
-        enum Frame {
+        self.parse_ptterms_quant(var_map, map, instance, None)
+            .map(|(ptterms, _)| ptterms)
+    }
+
+    /// Parses some top terms, merging the nested quantifiers with the ones of the clause.
+    ///
+    /// If `negated` is `None`, quantifiers are illegal. Otherwise, it indicates whether the top
+    /// terms appear negated in the clause. Universal quantifiers in positive positions and
+    /// existential ones in negative positions are merged: their variables are added to the
+    /// variables of the clause, and the extended variables are returned. Other quantifiers are
+    /// not Horn and cause an error.
+    fn parse_ptterms_quant(
+        &mut self,
+        var_map: &VarInfos,
+        map: &BTreeMap<&'s str, VarIdx>,
+        instance: &Instance,
+        negated: Option<bool>,
+    ) -> Res<(PTTerms, Option<VarInfos>)> {
+        enum Frame<'a> {
             And(Vec<PTTerms>),
             Or(Vec<PTTerms>),
             Impl(Vec<PTTerms>),
             Not,
             Let(LetCount),
+            /// Stores the variable map of the scope the quantifier appears in.
+            Quant(BTreeMap<&'a str, VarIdx>),
         }
-        let mut stack: Vec<Frame> = vec![];
+        let mut stack: Vec<Frame<'s>> = vec![];
+
+        // Variables and map extended with the nested quantifiers, if any.
+        let mut quant_vars: Option<(VarInfos, BTreeMap<&'s str, VarIdx>)> = None;
 
         'go_down: loop {
+            let (var_map, map) = if let Some((ref var_map, ref map)) = quant_vars {
+                (var_map, map)
+            } else {
+                (var_map, map)
+            };
+
             self.ws_cmt();
 
             let bind_count = self.let_bindings(var_map, &map, instance)?;
@@ -2993,7 +3300,48 @@ impl<'cxt, 's> Parser<'cxt, 's> {
             let mut ptterm = if let Some(pos) = self.tag_opt_pos("(") {
                 self.ws_cmt();
 
-                if self.tag_opt("and") {
+                let quant = if negated.is_none() {
+                    None
+                } else if self.word_opt(keywords::forall) {
+                    Some(true)
+                } else if self.word_opt(keywords::exists) {
+                    Some(false)
+                } else {
+                    None
+                };
+
+                if let Some(universal) = quant {
+                    // Negations and lhs of implications flip the polarity.
+                    let mut negative = negated == Some(true);
+                    for frame in &stack {
+                        match frame {
+                            Frame::Not => negative = !negative,
+                            Frame::Impl(args) if args.is_empty() => negative = !negative,
+                            _ => (),
+                        }
+                    }
+                    if universal == negative {
+                        let (quant, position) = if universal {
+                            ("universal", "negative")
+                        } else {
+                            ("existential", "positive")
+                        };
+                        bail!(self.error(
+                            start_pos,
+                            format!(
+                                "{} quantifier in {} position, not a Horn clause",
+                                quant, position
+                            )
+                        ))
+                    }
+                    let (mut nu_var_map, mut nu_map) = (var_map.clone(), map.clone());
+                    let prev_map = map.clone();
+                    self.ws_cmt();
+                    self.args(&mut nu_var_map, &mut nu_map)?;
+                    quant_vars = Some((nu_var_map, nu_map));
+                    stack.push(Frame::Quant(prev_map));
+                    continue 'go_down;
+                } else if self.tag_opt("and") {
                     stack.push(Frame::And(vec![]));
                     continue 'go_down;
                 } else if self.tag_opt("or") {
@@ -3014,7 +3362,7 @@ impl<'cxt, 's> Parser<'cxt, 's> {
                             // If we get here, it means what we're parsing does not have type
                             // bool. Which means we're not inside a top-term (we're most
                             // likely parsing a let-binding).
-                            return Ok(top);
+                            return Ok((top, None));
                         } else {
                             err_chain! {
                               "while parsing top term"
@@ -3032,7 +3380,7 @@ impl<'cxt, 's> Parser<'cxt, 's> {
                             // If we get here, it means what we're parsing does not have type
                             // bool. Which means we're not inside a top-term (we're most
                             // likely parsing a let-binding).
-                            return Ok(PTTerms::TTerm(TTerm::T(top)));
+                            return Ok((PTTerms::TTerm(TTerm::T(top)), None));
                         } else {
                             err_chain! {
                               "while parsing subterm"
@@ -3054,7 +3402,7 @@ impl<'cxt, 's> Parser<'cxt, 's> {
                     // If we get here, it means what we're parsing does not have type
                     // bool. Which means we're not inside a top-term (we're most likely
                     // parsing a let-binding).
-                    return Ok(top);
+                    return Ok((top, None));
                 } else {
                     err_chain! {
                       "while parsing top term"
@@ -3072,7 +3420,7 @@ impl<'cxt, 's> Parser<'cxt, 's> {
                     // If we get here, it means what we're parsing does not have type
                     // bool. Which means we're not inside a top-term (we're most likely
                     // parsing a let-binding).
-                    return Ok(PTTerms::TTerm(TTerm::T(top)));
+                    return Ok((PTTerms::TTerm(TTerm::T(top)), None));
                 } else {
                     err_chain! {
                       "while parsing subterm (ident or constant)"
@@ -3139,7 +3487,17 @@ impl<'cxt, 's> Parser<'cxt, 's> {
                         self.close_let_bindings(bind_count)?;
                         continue 'go_up;
                     }
-                    None => break 'go_down Ok(ptterm),
+                    Some(Frame::Quant(prev_map)) => {
+                        self.ws_cmt();
+                        self.tag(")")?;
+                        // The variables of the quantifier go out of scope, but they stay in the
+                        // variables of the clause.
+                        if let Some((_, ref mut current)) = quant_vars {
+                            *current = prev_map
+                        }
+                        continue 'go_up;
+                    }
+                    None => break 'go_down Ok((ptterm, quant_vars.map(|(var_map, _)| var_map))),
                 }
             }
         }
@@ -3268,7 +3626,9 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         self.ws_cmt();
 
         let start_pos = self.pos();
-        let mut ptterms = self.parse_ptterms(&var_map, &map, instance)?;
+        let (mut ptterms, quant_vars) =
+            self.parse_ptterms_quant(&var_map, &map, instance, Some(negated))?;
+        let var_map = quant_vars.unwrap_or(var_map);
         if !ptterms.typ().is_bool() {
             err_chain! {
               "while parsing clause terms"
@@ -3285,7 +3645,13 @@ impl<'cxt, 's> Parser<'cxt, 's> {
 
         let (mut at_least_one, idx) = (false, instance.next_clause_index());
 
-        let mut clauses = ptterms.into_clauses()?.into_iter();
+        let mut clauses = match ptterms.into_clauses() {
+            Ok(clauses) => clauses.into_iter(),
+            Err(e) => bail!(self.error(
+                start_pos,
+                format!("this assertion is not a Horn clause: {}", e)
+            )),
+        };
 
         if let Some((last_lhs, last_rhs)) = clauses.next() {
             for (lhs, rhs) in clauses {
@@ -3307,6 +3673,24 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         }
     }
 
+    /// True if a clause is trivially true, syntactically.
+    ///
+    /// That is, if its rhs is `true` or appears in its lhs, or if its lhs has two complementary
+    /// terms. Used to drop clauses online in streaming mode, the `trivial_clauses` pre-processing
+    /// pass catches the other ones.
+    fn is_tautology(lhs: &[TTerm], rhs: &TTerm) -> bool {
+        if rhs.bool() == Some(true) || lhs.contains(rhs) {
+            return true;
+        }
+        lhs.iter().any(|lhs_term| match lhs_term {
+            TTerm::T(term) => {
+                let neg = TTerm::T(term::not(term.clone()));
+                lhs.contains(&neg)
+            }
+            TTerm::P { .. } => false,
+        })
+    }
+
     /// Adds a clause to an instance.
     fn add_clause(
         &self,
@@ -3327,6 +3711,12 @@ impl<'cxt, 's> Parser<'cxt, 's> {
                 }
             }
         }
+        if conf.stream_parse && !lhs_is_false && Self::is_tautology(&nu_lhs, &rhs) {
+            // No need to keep it around until preprocessing.
+            profile! { self "trivial clauses dropped" => add 1 }
+            return Ok(false);
+        }
+
         let rhs = match rhs {
             TTerm::P { pred, args } => Some((pred, args)),
             TTerm::T(t) => {
@@ -3400,16 +3790,47 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         self.close_let_bindings(bind_count)?;
 
         if tagged {
+            use num::ToPrimitive;
+
+            let idx = idx.into_option();
             self.ws_cmt();
-            self.word(":named").chain_err(|| "unexpected tag")?;
-            self.ws_cmt();
-            let (_, ident) = self
-                .ident()
-                .chain_err(|| "expected identifier after `:named` tag")?;
-            if let Some(idx) = idx.into_option() {
-                instance.set_old_clause_name(idx, ident.into())?
+            let mut attribute_count = 0;
+            loop {
+                if self.word_opt(":named") {
+                    self.ws_cmt();
+                    let (_, ident) = self
+                        .ident()
+                        .chain_err(|| "expected identifier after `:named` tag")?;
+                    // Names all the clauses the assertion yields.
+                    if let Some(mut clause) = idx {
+                        while clause < instance.next_clause_index() {
+                            instance.set_old_clause_name(clause, ident.into())?;
+                            clause.inc()
+                        }
+                    }
+                } else if self.word_opt(":weight") {
+                    self.ws_cmt();
+                    let weight_pos = self.pos();
+                    let weight = if let Some(weight) = self.numeral().and_then(|n| n.to_usize()) {
+                        weight
+                    } else {
+                        bail!(self.error(weight_pos, "expected a natural after `:weight` tag"))
+                    };
+                    // Weighs all the clauses the assertion yields.
+                    if let Some(mut clause) = idx {
+                        while clause < instance.next_clause_index() {
+                            instance.set_old_clause_weight(clause, weight)?;
+                            clause.inc()
+                        }
+                    }
+                } else if attribute_count == 0 {
+                    bail!(self.error_here("unexpected tag, expected `:named` or `:weight`"))
+                } else {
+                    break;
+                }
+                attribute_count += 1;
+                self.ws_cmt()
             }
-            self.ws_cmt();
             self.tag(")")?;
         }
 
@@ -3448,11 +3869,60 @@ impl<'cxt, 's> Parser<'cxt, 's> {
         self.word_opt(keywords::cmd::reset)
     }
 
+    /// Kind of the single item of the input, see `--batch`.
+    pub fn batch_item(mut self) -> BatchItem {
+        use num::ToPrimitive;
+
+        self.ws_cmt();
+        if !self.tag_opt("(") {
+            return BatchItem::Command;
+        }
+        self.ws_cmt();
+        let boundary = if self.reset() {
+            Some(BatchItem::Reset)
+        } else if self.word_opt(keywords::cmd::push) {
+            Some(BatchItem::Push(1))
+        } else if self.word_opt(keywords::cmd::pop) {
+            Some(BatchItem::Pop(1))
+        } else {
+            None
+        };
+
+        if let Some(boundary) = boundary {
+            self.ws_cmt();
+            let count = self.numeral().and_then(|n| n.to_usize());
+            self.ws_cmt();
+            if self.tag_opt(")") {
+                self.ws_cmt();
+                if !self.has_next() {
+                    return match (boundary, count) {
+                        (BatchItem::Push(_), Some(count)) => BatchItem::Push(count),
+                        (BatchItem::Pop(_), Some(count)) => BatchItem::Pop(count),
+                        (boundary, _) => boundary,
+                    };
+                }
+            }
+            // Not a single boundary, let the actual parser report the problem.
+            BatchItem::Command
+        } else if self.check_sat()
+            || self.get_model()
+            || self.get_unsat_core()
+            || self.get_proof()
+            || self.exit()
+            || self.word_opt("echo")
+        {
+            BatchItem::Command
+        } else {
+            BatchItem::Scoped
+        }
+    }
+
     /// Parses items, returns true if it found a check-sat.
     pub fn parse(mut self, instance: &mut Instance) -> Res<Parsed> {
         self.ws_cmt();
         let mut res = Parsed::Eof;
         self.cxt.term_stack.clear();
+        self.cxt.forward_ref = None;
 
         while self.has_next() {
             self.ws_cmt();
@@ -3464,7 +3934,8 @@ impl<'cxt, 's> Parser<'cxt, 's> {
 
             let start_pos = self.pos();
 
-            res = if self.set_info()? {
+            res = if let Some((key, val)) = self.set_info()? {
+                instance.set_info(key, val);
                 Parsed::Items
             } else if let Some((key, val)) = self.set_option()? {
                 instance.set_option(key, val).chain_err(|| {
@@ -3583,6 +4054,124 @@ pub fn term(s: &str, var_infos: &VarInfos, instance: &Instance) -> Term {
     }
 }
 
+/// Tries to parse a term from an SMT 2 string.
+///
+/// Fails if there is anything but whitespace or comments after the term.
+pub fn term_opt(s: &str, var_infos: &VarInfos, instance: &Instance) -> Res<Option<Term>> {
+    let mut map = BTreeMap::new();
+    for info in var_infos {
+        map.insert(&info.name as &str, info.idx);
+    }
+
+    let mut cxt = ParserCxt::new();
+    let dummy_profiler = Profiler::new();
+    let mut parser = cxt.parser(s, 0, &dummy_profiler);
+    let res = parser.term_opt(var_infos, &map, instance)?;
+    parser.ws_cmt();
+    if !parser.rest().is_empty() {
+        bail!("unexpected text after term: `{}`", parser.rest())
+    }
+    Ok(res)
+}
+
+/// Parses a list of sorted variables followed by a term over these variables.
+///
+/// For instance `((x Int) (y Int)) (<= x y)`. Fails if there is anything but whitespace or
+/// comments after the term.
+///
+/// ```rust
+/// # use hoice::{ common::*, parse };
+/// let instance = Instance::new();
+/// let (var_infos, term) = parse::sorted_vars_term("((x Int) (?c Int)) (<= x ?c)", &instance)
+///     .unwrap();
+/// assert_eq! { var_infos.len(), 2 }
+/// assert_eq! { var_infos[VarIdx::from(1)].name, "?c" }
+/// assert_eq! { term, term::le(term::int_var(0), term::int_var(1)) }
+/// assert! { parse::sorted_vars_term("((x Int)) (<= x y)", &instance).is_err() }
+/// ```
+pub fn sorted_vars_term(s: &str, instance: &Instance) -> Res<(VarInfos, Term)> {
+    let mut var_infos = VarInfos::new();
+    let mut map = BTreeMap::new();
+
+    let mut cxt = ParserCxt::new();
+    let dummy_profiler = Profiler::new();
+    let mut parser = cxt.parser(s, 0, &dummy_profiler);
+    parser.ws_cmt();
+    parser.args(&mut var_infos, &mut map)?;
+    parser.ws_cmt();
+    let term = if let Some(term) = parser.term_opt(&var_infos, &map, instance)? {
+        term
+    } else {
+        bail!("expected a term after the variables in `{}`", s)
+    };
+    parser.ws_cmt();
+    if !parser.rest().is_empty() {
+        bail!("unexpected text after term: `{}`", parser.rest())
+    }
+    Ok((var_infos, term))
+}
+
+/// Parses some hints from an SMT 2 string.
+///
+/// Hints are `define-fun`s for some of the predicates of the instance, possibly wrapped in a
+/// `(model ...)` as produced by hoice. Fails if a hint does not match the original signature of
+/// its predicate.
+///
+/// ```rust
+/// # use hoice::{ common::*, parse };
+/// let instance = parse::instance("
+///   (declare-fun inv ( Int Int ) Bool)
+///   (assert (forall ((n Int)) (inv n 0)))
+/// ");
+/// let hints = parse::hints("
+///   (model
+///     (define-fun inv ((x Int) (y Int)) Bool (>= x y))
+///     (define-fun inv ((x Int) (y Int)) Bool (= y 0))
+///   )
+/// ", &instance).unwrap();
+/// assert_eq! { hints.len(), 1 }
+/// assert_eq! { hints[&PrdIdx::from(0)].len(), 2 }
+///
+/// assert! { parse::hints("(define-fun inv ((x Int)) Bool (>= x 0))", &instance).is_err() }
+/// assert! { parse::hints("(define-fun inv ((x Int) (y Bool)) Bool y)", &instance).is_err() }
+/// assert! { parse::hints("(define-fun inv ((x Int) (y Int)) Int y)", &instance).is_err() }
+/// ```
+pub fn hints(s: &str, instance: &Instance) -> Res<PrdHMap<Vec<Term>>> {
+    let mut cxt = ParserCxt::new();
+    let dummy_profiler = Profiler::new();
+    let mut parser = cxt.parser(s, 0, &dummy_profiler);
+    parser.hints(instance, true)
+}
+
+/// Parses a model for the current signature of the predicates of an instance.
+///
+/// Same format as [`hints`](fn.hints.html), but the arguments of each definition must match the
+/// *current* signature of the predicate. Used to read back the model of a sub-instance solved by
+/// a child process, see `--split_subprocess`.
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::{ common::*, parse };
+/// let instance = parse::instance("
+///   (declare-fun inv ( Int Int ) Bool)
+///   (assert (forall ((n Int)) (inv n 0)))
+/// ");
+/// let model = parse::current_sig_model("
+///   sat
+///   (model
+///     (define-fun inv ((v_0 Int) (v_1 Int)) Bool (>= v_0 v_1))
+///   )
+/// ", &instance).unwrap();
+/// assert_eq! { model[&PrdIdx::from(0)].len(), 1 }
+/// ```
+pub fn current_sig_model(s: &str, instance: &Instance) -> Res<PrdHMap<Vec<Term>>> {
+    let mut cxt = ParserCxt::new();
+    let dummy_profiler = Profiler::new();
+    let mut parser = cxt.parser(s, 0, &dummy_profiler);
+    parser.hints(instance, false)
+}
+
 /// Parses an instance from an SMT 2 string.
 ///
 /// Stops at the end of the string or at the first non-declaration non-assert non-definition
@@ -3599,6 +4188,43 @@ pub fn instance(s: &str) -> Instance {
     instance
 }
 
+/// Kind of an item in batch mode, see `--batch`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchItem {
+    /// `(reset)`.
+    Reset,
+    /// `(push n)`, `n` is one if omitted.
+    Push(usize),
+    /// `(pop n)`, `n` is one if omitted.
+    Pop(usize),
+    /// Command the scopes do not remember, like `check-sat` or `get-model`.
+    Command,
+    /// Declaration, definition, assertion or option: belongs to the current scope.
+    Scoped,
+}
+
+/// Kind of an item in batch mode, see `--batch`.
+///
+/// `(reset)`, `(push [n])` and `(pop [n])` separate the instances of the input.
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::parse::{ batch_item, BatchItem };
+/// assert_eq! { batch_item("(reset)"), BatchItem::Reset }
+/// assert_eq! { batch_item("; comment\n( push 2 )\n"), BatchItem::Push(2) }
+/// assert_eq! { batch_item("(pop)"), BatchItem::Pop(1) }
+/// assert_eq! { batch_item("(check-sat)"), BatchItem::Command }
+/// assert_eq! { batch_item("(reset) (check-sat)"), BatchItem::Command }
+/// assert_eq! { batch_item("(declare-fun p (Int) Bool)"), BatchItem::Scoped }
+/// ```
+pub fn batch_item(item: &str) -> BatchItem {
+    let mut cxt = ParserCxt::new();
+    let dummy_profiler = Profiler::new();
+    let parser = cxt.parser(item, 0, &dummy_profiler);
+    parser.batch_item()
+}
+
 /// Parses some functions/datatypes.
 pub fn fun_dtyp(s: &str) {
     let mut dummy = Instance::new();
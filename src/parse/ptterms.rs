@@ -223,6 +223,41 @@ impl PTTerms {
         PTTerms::TTerm(tterm)
     }
 
+    /// True if the top terms are a conjunction.
+    fn is_and(&self) -> bool {
+        if let PTTerms::And(_) = *self {
+            true
+        } else {
+            false
+        }
+    }
+
+    /// True if `PTTerms` mentions a predicate, negated or not.
+    fn has_preds(&self) -> bool {
+        match *self {
+            PTTerms::And(ref args) | PTTerms::Or(ref args) => args.iter().any(Self::has_preds),
+            PTTerms::NTTerm(_) => true,
+            PTTerms::TTerm(ref tterm) => tterm.pred().is_some(),
+        }
+    }
+
+    /// True if the top terms are a conjunction a disjunction must be distributed over to obtain
+    /// Horn clauses.
+    ///
+    /// That is, if one of its conjuncts is a non-negated predicate application, or a non-atomic
+    /// top term mentioning a predicate.
+    fn needs_distribution(&self) -> bool {
+        if let PTTerms::And(ref args) = *self {
+            args.iter().any(|arg| match *arg {
+                PTTerms::TTerm(ref tterm) => tterm.pred().is_some(),
+                PTTerms::NTTerm(_) => false,
+                ref arg => arg.has_preds(),
+            })
+        } else {
+            false
+        }
+    }
+
     /// True if `PTTerms` does not contain a non-negated predicate.
     pub fn is_legal_lhs(&self) -> bool {
         let mut to_do = Vec::with_capacity(37);
@@ -257,20 +292,55 @@ impl PTTerms {
             PTTerms::TTerm(tterm) => Ok(vec![(vec![], tterm)]),
             PTTerms::NTTerm(tterm) => Ok(vec![(vec![tterm], TTerm::fls())]),
 
-            PTTerms::Or(ptterms) => {
+            PTTerms::Or(mut ptterms) => {
+                // Distributes the disjunction over the conjunctions that cannot be turned into
+                // (alternative) lhs directly, *e.g.* `a \/ (b /\ (c \/ p))` where `p` is a
+                // predicate application. Happens with nested, curried implications.
+                let index = ptterms
+                    .iter()
+                    .position(PTTerms::needs_distribution)
+                    .or_else(|| {
+                        // More than two conjunctions are only handled if they are pure terms.
+                        let conj_count = ptterms.iter().filter(|ptt| ptt.is_and()).count();
+                        if conj_count > 2 {
+                            ptterms
+                                .iter()
+                                .position(|ptt| ptt.is_and() && ptt.has_preds())
+                        } else {
+                            None
+                        }
+                    });
+                if let Some(index) = index {
+                    if let PTTerms::And(conj) = ptterms.remove(index) {
+                        let mut clauses = Vec::with_capacity(conj.len());
+                        for ptt in conj {
+                            let mut disj = ptterms.clone();
+                            disj.push(ptt);
+                            clauses.extend(PTTerms::or(disj).into_clauses()?)
+                        }
+                        return Ok(clauses);
+                    } else {
+                        bail!("ecountered normalization issue (or, 0)")
+                    }
+                }
+
                 let mut lhs = Vec::with_capacity(ptterms.len());
                 let mut multipliers = Vec::with_capacity(3);
-                let mut rhs = None;
+                let mut rhs: Option<TTerm> = None;
 
                 for ptt in ptterms {
                     match ptt {
                         PTTerms::TTerm(tt) => {
                             if let TTerm::T(term) = tt {
                                 lhs.push(TTerm::T(term::not(term)))
-                            } else if rhs.is_none() {
-                                rhs = Some(vec![tt])
+                            } else if let Some(rhs) = rhs.as_ref() {
+                                bail!(
+                                    "more than one positive predicate application, `{}` and `{}`",
+                                    rhs,
+                                    tt
+                                )
                             } else {
-                                bail!("ill-formed horn clause (or, 1)")
+                                rhs = Some(tt)
                             }
                         }
 
@@ -278,18 +348,12 @@ impl PTTerms {
 
                         PTTerms::And(ptts) => {
                             let mut tts = Vec::with_capacity(ptts.len());
-                            let mut positive_preds = None;
                             for ptt in ptts {
                                 match ptt {
                                     PTTerms::NTTerm(tterm) => tts.push(tterm),
-                                    PTTerms::TTerm(tterm) => match tterm {
-                                        TTerm::T(term) => tts.push(TTerm::T(term::not(term))),
-                                        tterm => {
-                                            let positive_preds = positive_preds
-                                                .get_or_insert_with(|| Vec::with_capacity(7));
-                                            positive_preds.push(tterm)
-                                        }
-                                    },
+                                    PTTerms::TTerm(TTerm::T(term)) => {
+                                        tts.push(TTerm::T(term::not(term)))
+                                    }
                                     ptt => {
                                         if let Some(term) = ptt.to_term()? {
                                             tts.push(TTerm::T(term::not(term)))
@@ -299,12 +363,7 @@ impl PTTerms {
                                     }
                                 }
                             }
-                            if let Some(pos_preds) = positive_preds {
-                                tts.extend(pos_preds);
-                                rhs = Some(tts)
-                            } else {
-                                multipliers.push(tts)
-                            }
+                            multipliers.push(tts)
                         }
 
                         _ => bail!("ecountered normalization issue (or, 3)"),
@@ -349,23 +408,8 @@ impl PTTerms {
                     vec![lhs]
                 };
 
-                if let Some(rhs) = rhs {
-                    let mut res = Vec::with_capacity(rhs.len());
-                    let mut rhs = rhs.into_iter();
-                    if let Some(last) = rhs.next() {
-                        for rhs in rhs {
-                            for lhs in &nu_lhs {
-                                res.push((lhs.clone(), rhs.clone()))
-                            }
-                        }
-                        for lhs in nu_lhs {
-                            res.push((lhs, last.clone()))
-                        }
-                    }
-                    Ok(res)
-                } else {
-                    Ok(nu_lhs.into_iter().map(|lhs| (lhs, TTerm::fls())).collect())
-                }
+                let rhs = rhs.unwrap_or_else(TTerm::fls);
+                Ok(nu_lhs.into_iter().map(|lhs| (lhs, rhs.clone())).collect())
             }
 
             PTTerms::And(ppterms) => {
@@ -378,7 +422,8 @@ impl PTTerms {
                     //
                     // - there can be no `PTTerms::And` inside a `PTTerms::And` by
                     //   construction
-                    // - this is the only place `into_clauses` calls itself.
+                    // - the only other place `into_clauses` calls itself is distribution in
+                    //   the `PTTerms::Or` case, on disjunctions with one less conjunction.
                 }
                 Ok(clauses)
             }
@@ -0,0 +1,135 @@
+//! Canonical ordering of the arguments of the predicates.
+
+use crate::{
+    common::*,
+    preproc::{PreInstance, RedStrat},
+};
+
+/// Canonical ordering of the arguments of the predicates.
+///
+/// Frontends do not always pass corresponding arguments in the same position to different
+/// predicates, which prevents the learner from reusing what it learnt on one predicate on the
+/// other ones. This pass reorders the arguments of each predicate by
+///
+/// - type first,
+/// - then by the position of the same variable in the arguments of a predicate ordered before,
+///   in the first clause mentioning both predicates,
+/// - then by original position.
+///
+/// Predicates are ordered by index. Defined predicates and predicates with a strengthening term
+/// are left as they are. Permutations are recorded in the map to the original signature of the
+/// predicates, so that models are reconstructed properly.
+///
+/// The pass does not reduce the instance, it always reports no reduction.
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::{ common::*, parse, preproc::{ PreInstance, RedStrat, ArgOrder } };
+/// let mut instance = parse::instance("
+///   (declare-fun p ( Int Bool Int ) Bool)
+///   (declare-fun q ( Int Int Bool ) Bool)
+///   (assert (forall ( (n Int) (m Int) (b Bool) ) (=> (and (= n 0) (= m 1)) (p n b m))))
+///   (assert (forall ( (n Int) (m Int) (b Bool) ) (=> (p n b m) (q m n b))))
+///   (assert (forall ( (n Int) (m Int) (b Bool) ) (=> (and (q n m b) (> n m)) false)))
+/// ");
+///
+/// let mut arg_order = ArgOrder::new(& instance);
+/// let mut instance = PreInstance::new(& mut instance).unwrap();
+/// arg_order.apply(& mut instance).unwrap();
+///
+/// let (p, q): (PrdIdx, PrdIdx) = (0.into(), 1.into());
+/// // Integers come before booleans: `p` is now `(p n m b)`.
+/// let map: VarMap<VarIdx> = vec![ 0.into(), 2.into(), 1.into() ].into();
+/// assert_eq! { instance[p].original_sig_map(), & map }
+/// // Arguments of `q` follow the ones of `p` they receive: `q` is now `(q n m b)`.
+/// let map: VarMap<VarIdx> = vec![ 1.into(), 0.into(), 2.into() ].into();
+/// assert_eq! { instance[q].original_sig_map(), & map }
+/// ```
+pub struct ArgOrder;
+
+impl ArgOrder {
+    /// Canonical ordering of the arguments of a predicate.
+    ///
+    /// Maps the variables of the new signature to the variables of the current one.
+    fn order_of(instance: &Instance, pred: PrdIdx, done: &PrdSet) -> VarMap<VarIdx> {
+        let sig = &instance[pred].sig;
+
+        // Position of each argument in the arguments of a predicate ordered before.
+        let mut ranks: VarMap<usize> = vec![::std::usize::MAX; sig.len()].into();
+
+        let (lhs, rhs) = instance.clauses_of(pred);
+        let mut clauses: Vec<ClsIdx> = lhs.union(rhs).cloned().collect();
+        clauses.sort();
+
+        'find_clause: for clause in clauses {
+            let clause = &instance[clause];
+            let apps: Vec<(PrdIdx, &VarTerms)> = clause
+                .lhs_preds()
+                .iter()
+                .flat_map(|(p, argss)| argss.iter().map(move |args| (*p, args)))
+                .chain(clause.rhs())
+                .collect();
+            let args = if let Some((_, args)) = apps.iter().find(|(p, _)| *p == pred) {
+                args
+            } else {
+                continue 'find_clause;
+            };
+            let other = apps.iter().find(|(p, _)| *p != pred && done.contains(p));
+            if let Some((_, other_args)) = other {
+                for (var, arg) in args.index_iter() {
+                    if arg.var_idx().is_some() {
+                        if let Some(pos) = other_args.iter().position(|other| other == arg) {
+                            ranks[var] = pos
+                        }
+                    }
+                }
+                break 'find_clause;
+            }
+        }
+
+        let mut order: Vec<VarIdx> = sig.index_iter().map(|(var, _)| var).collect();
+        order.sort_by(|v_1, v_2| {
+            sig[*v_1]
+                .get()
+                .cmp(sig[*v_2].get())
+                .then(ranks[*v_1].cmp(&ranks[*v_2]))
+                .then(v_1.cmp(v_2))
+        });
+        order.into()
+    }
+}
+
+impl RedStrat for ArgOrder {
+    fn name(&self) -> &'static str {
+        "arg_order"
+    }
+
+    fn new(_: &Instance) -> Self {
+        ArgOrder
+    }
+
+    fn apply(&mut self, instance: &mut PreInstance) -> Res<RedInfo> {
+        let mut done = PrdSet::new();
+
+        let preds: Vec<PrdIdx> = instance
+            .preds()
+            .index_iter()
+            .map(|(pred, _)| pred)
+            .collect();
+        for pred in preds {
+            if instance[pred].is_defined() || instance[pred].strength().is_some() {
+                continue;
+            }
+            let order = Self::order_of(instance, pred, &done);
+            if order.index_iter().any(|(new, old)| new != *old) {
+                log! { @verb "reordering the arguments of {}", instance[pred] }
+                instance.permute_args_of(pred, &order)?
+            }
+            done.insert(pred);
+            conf.check_timeout()?
+        }
+
+        Ok(RedInfo::new())
+    }
+}
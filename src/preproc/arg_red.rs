@@ -13,12 +13,37 @@ use crate::{
 /// [paper]: https://link.springer.com/chapter/10.1007%2F3-540-62718-9_6
 /// (Redundant argument filtering of logic programs)
 ///
+/// Also drops the arguments of a predicate that are fresh variables in all the clauses the
+/// predicate is the rhs of, *i.e.* variables that appear nowhere else in these clauses. By
+/// induction on the clauses, the predicate holds of its other arguments for all the values of
+/// such an argument, which is thus irrelevant even if some lhs constrains it.
+///
 /// # Examples
 ///
 /// ```rust
 /// // See this file for a non-trivial example.
 /// ::std::fs::OpenOptions::new().read(true).open("rsc/sat/arg_red.smt2").unwrap();
 /// ```
+///
+/// ```rust
+/// # use hoice::{ common::*, parse, preproc::{ PreInstance, RedStrat, ArgRed } };
+/// let mut instance = parse::instance("
+///   (declare-fun p ( Int Int ) Bool)
+///   (assert (forall ( (x Int) (z Int) ) (=> (= x 0) (p x z))))
+///   (assert (forall ( (x Int) (z Int) (w Int) ) (=> (and (p x z) (>= x 0)) (p (+ x 1) w))))
+///   (assert (forall ( (x Int) (z Int) ) (=> (and (p x z) (> z 5) (< x 0)) false)))
+/// ");
+///
+/// let mut arg_red = ArgRed::new(& instance);
+/// let mut instance = PreInstance::new(& mut instance).unwrap();
+/// arg_red.apply(& mut instance).unwrap();
+///
+/// // The second argument of `p` is fresh in all the rhs of `p`, it is dropped.
+/// let p: PrdIdx = 0.into();
+/// assert_eq! { instance[p].sig.len(), 1 }
+/// let map: VarMap<VarIdx> = vec![ 0.into() ].into();
+/// assert_eq! { instance[p].original_sig_map(), & map }
+/// ```
 pub struct ArgRed {
     inner: ArgReductor,
 }
@@ -208,6 +233,36 @@ impl ArgReductor {
         changed
     }
 
+    /// True if an argument of a predicate is a fresh variable in all the clauses it is the rhs
+    /// of.
+    ///
+    /// Fresh means that the variable appears in no other argument of the rhs, and not at all in
+    /// the lhs. False if the predicate is the rhs of no clause.
+    fn is_fresh_arg(&self, pred: PrdIdx, pvar: VarIdx, instance: &Instance) -> bool {
+        let clauses = instance.rhs_clauses_of(pred);
+        !clauses.is_empty()
+            && clauses.iter().all(|idx| {
+                let cvar = match instance[*idx].rhs() {
+                    Some((_, args)) => args.index_iter().find(|(var, _)| *var == pvar),
+                    None => None,
+                }
+                .and_then(|(_, arg)| arg.var_idx());
+                let cvar = if let Some(cvar) = cvar {
+                    cvar
+                } else {
+                    return false;
+                };
+                let in_rhs = match &self.rhs_vars[*idx] {
+                    Some((_, pvar_map)) => pvar_map
+                        .iter()
+                        .filter(|cvars| cvars.contains(&cvar))
+                        .count(),
+                    None => 0,
+                };
+                in_rhs == 1 && !self.lhs_vars[*idx].contains_key(&cvar)
+            })
+    }
+
     /// Runs itself on all clauses of an instance.
     pub fn run(&mut self, instance: &Instance) -> PrdHMap<VarSet> {
         self.init(instance);
@@ -229,6 +284,22 @@ impl ArgReductor {
             }
         }
 
+        // Fresh arguments only appear in rhs, dropping them does not change what the lhs keep.
+        for pred in instance.pred_indices() {
+            if instance[pred].is_defined() || instance[pred].strength().is_some() {
+                continue;
+            }
+            let fresh: Vec<VarIdx> = self.keep[pred]
+                .iter()
+                .filter(|pvar| self.is_fresh_arg(pred, **pvar, instance))
+                .cloned()
+                .collect();
+            for pvar in fresh {
+                log! { @4 "argument {} of {} is fresh", pvar.default_str(), instance[pred] }
+                self.keep[pred].remove(&pvar);
+            }
+        }
+
         let mut res = PrdHMap::new();
         for (pred, vars) in ::std::mem::replace(&mut self.keep, PrdMap::new()).into_index_iter() {
             if !instance[pred].is_defined() {
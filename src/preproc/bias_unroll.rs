@@ -39,6 +39,8 @@ pub struct BiasedUnroll {
     neg_new_preds: PrdHMap<(PrdSet, PrdSet)>,
     /// Maximum number of new clauses we can create by predicate.
     max_new_clauses: usize,
+    /// Predicates within `--bias_unroll_radius` of a negative clause, if the radius is active.
+    in_radius: Option<PrdSet>,
 }
 
 impl BiasedUnroll {
@@ -160,11 +162,23 @@ impl BiasedUnroll {
         println!();
     }
 
+    /// True if clauses can be generated for a predicate, given `--bias_unroll_radius`.
+    fn is_in_radius(&self, pred: PrdIdx) -> bool {
+        self.in_radius
+            .as_ref()
+            .map(|preds| preds.contains(&pred))
+            .unwrap_or(true)
+    }
+
     /// Sets up the unroller by scanning the instance.
     ///
     /// Returns `true` if there's nothing to do.
     fn setup(&mut self, instance: &mut PreInstance) -> Res<bool> {
         self.max_new_clauses = ::std::cmp::min(10, instance.clauses().len() / 20);
+        self.in_radius = conf
+            .preproc
+            .bias_unroll_radius
+            .map(|radius| DepGraph::new(instance).neg_reachable(Some(radius)));
 
         for (pred, _) in instance.preds().index_iter() {
             if instance[pred].is_defined() {
@@ -860,6 +874,11 @@ impl BiasedUnroll {
             }
             // self.print(instance) ;
 
+            if !self.is_in_radius(pred) {
+                log! { @4 | "-> out of radius, skipping" }
+                continue;
+            }
+
             if self
                 .neg_new_preds
                 .get(&pred)
@@ -920,6 +939,11 @@ impl BiasedUnroll {
             }
             // self.print(instance) ;
 
+            if !self.is_in_radius(pred) {
+                log! { @4 | "-> out of radius, skipping" }
+                continue;
+            }
+
             if self
                 .pos_new_preds
                 .get(&pred)
@@ -991,6 +1015,7 @@ impl RedStrat for BiasedUnroll {
             pos_new_preds: PrdHMap::new(),
             neg_new_preds: PrdHMap::new(),
             max_new_clauses: 0,
+            in_radius: None,
         }
     }
 
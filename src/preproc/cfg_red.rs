@@ -837,6 +837,20 @@ impl Graph {
                 },
                 &res,
             )? {
+                if let Some(max) = conf.preproc.cfg_red_max_qvars {
+                    let qvars = res
+                        .def
+                        .iter()
+                        .map(|(qvars, _)| qvars.len())
+                        .max()
+                        .unwrap_or(0);
+                    if qvars > max {
+                        log! { @4 |
+                            "not inlining {}: {} quantified variables", instance[pred], qvars
+                        }
+                        keep_and_continue!()
+                    }
+                }
                 upper_bound += res.estimation;
                 log! { @4 |
                     "inlining {} (blow-up estimation: {})",
@@ -1124,12 +1138,43 @@ impl Graph {
 /// breaks all the cycle, meaning the remaining predicates can be expressed in terms of the ones
 /// removed.
 ///
+/// The predicates that are not kept are eliminated in topological order, leaves first, by
+/// substituting their definition, the disjunction of the lhs of the clauses they are the rhs of.
+/// So predicates defined by several clauses are eliminated too, unlike with `one_rhs`. The
+/// elimination is guarded against blowup: a predicate is kept if inlining it would generate more
+/// clauses than an upper bound computed from the size of the original instance, or if its
+/// definition would quantify more variables than `--cfg_red_max_qvars`. The definitions of the
+/// eliminated predicates are remembered, and restored in the model.
+///
 /// # Examples
 ///
 /// ```rust
 /// // See this file for a non-trivial example.
 /// ::std::fs::OpenOptions::new().read(true).open("rsc/sat/cfg_red.smt2").unwrap();
 /// ```
+///
+/// Eliminating an acyclic predicate defined by two clauses:
+///
+/// ```
+/// # use hoice::{ common::*, parse, preproc::{ PreInstance, RedStrat, CfgRed } };
+/// let mut instance = parse::instance("
+///   (declare-fun p ( Int ) Bool)
+///   (declare-fun q ( Int ) Bool)
+///   (assert (forall ( (n Int) ) (=> (= n 0) (p n))))
+///   (assert (forall ( (n Int) ) (=> (p n) (p (+ n 1)))))
+///   (assert (forall ( (n Int) ) (=> (and (p n) (> n 7)) (q n))))
+///   (assert (forall ( (n Int) ) (=> (and (p n) (< n 2)) (q (- n)))))
+///   (assert (forall ( (n Int) ) (=> (and (q n) (< n (- 3))) false)))
+/// ");
+///
+/// let mut cfg_red = CfgRed::new(& instance);
+/// let mut instance = PreInstance::new(& mut instance).unwrap();
+/// let info = cfg_red.apply(& mut instance).unwrap();
+/// assert_eq! { info.preds, 1 }
+/// let (p, q): (PrdIdx, PrdIdx) = (0.into(), 1.into());
+/// assert! { !instance[p].is_defined() }
+/// assert! { instance[q].is_defined() }
+/// ```
 pub struct CfgRed {
     /// Internal counter for log files.
     cnt: usize,
@@ -0,0 +1,68 @@
+//! Dead predicate elimination.
+
+use crate::{
+    common::*,
+    preproc::{PreInstance, RedStrat},
+};
+
+/// Forces to true the predicates that cannot reach a negative clause.
+///
+/// A predicate is *relevant* if it appears in the lhs of a negative clause, or in the lhs of a
+/// clause the rhs of which is relevant. The clauses an irrelevant predicate appears in have an
+/// irrelevant rhs, so forcing all irrelevant predicates to true yields a solution for these
+/// clauses without constraining the others. The clauses mentioning them are dropped, and their
+/// definition in the model is `true`.
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::{ common::*, parse, preproc::{ PreInstance, RedStrat, DeadPreds } };
+/// let mut instance = parse::instance("
+///   (declare-fun p ( Int ) Bool)
+///   (declare-fun q ( Int ) Bool)
+///   (declare-fun r ( Int ) Bool)
+///   (assert (forall ( (n Int) ) (=> (= n 0) (p n))))
+///   (assert (forall ( (n Int) ) (=> (p n) (p (+ n 1)))))
+///   (assert (forall ( (n Int) ) (=> (p n) (q (+ n 1)))))
+///   (assert (forall ( (n Int) ) (=> (q n) (r (* 2 n)))))
+///   (assert (forall ( (n Int) ) (=> (p n) (>= n 0))))
+/// ");
+///
+/// let mut dead_preds = DeadPreds::new(& instance);
+/// let mut instance = PreInstance::new(& mut instance).unwrap();
+/// let info = dead_preds.apply(& mut instance).unwrap();
+/// assert_eq! { info.preds, 2 }
+/// let (p, q, r): (PrdIdx, PrdIdx, PrdIdx) = (0.into(), 1.into(), 2.into());
+/// assert! { !instance[p].is_defined() }
+/// assert! { instance[q].is_defined() }
+/// assert! { instance[r].is_defined() }
+/// ```
+pub struct DeadPreds;
+
+impl RedStrat for DeadPreds {
+    fn name(&self) -> &'static str {
+        "dead_preds"
+    }
+
+    fn new(_: &Instance) -> Self {
+        DeadPreds
+    }
+
+    fn apply(&mut self, instance: &mut PreInstance) -> Res<RedInfo> {
+        let mut red_info = RedInfo::new();
+
+        let relevant = DepGraph::new(instance).neg_reachable(None);
+
+        for pred in instance.pred_indices() {
+            if instance[pred].is_defined() || relevant.contains(&pred) {
+                continue;
+            }
+            conf.check_timeout()?;
+            log! { @3 "{} cannot reach a negative clause", instance[pred] }
+            red_info.preds += 1;
+            red_info += instance.force_true(pred)?
+        }
+
+        Ok(red_info)
+    }
+}
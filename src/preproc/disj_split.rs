@@ -0,0 +1,68 @@
+//! Disjunction splitting.
+
+use crate::{
+    common::*,
+    preproc::{PreInstance, RedStrat},
+};
+
+/// Distributes the top-level disjunctions of the lhs of the clauses.
+///
+/// A clause `(or t_1 t_2) /\ (or t_3 t_4) /\ ... => rhs` becomes the four clauses
+/// `t_i /\ t_j /\ ... => rhs` with `i` in `1, 2` and `j` in `3, 4`. Boolean if-then-elses
+/// `(ite c t e)` are seen as the disjunction of `c /\ t` and `(not c) /\ e`. After this pass, the
+/// guards of the clauses are conjunctions, unless splitting a clause would create more than
+/// `--disj_split_max` clauses, in which case it is left as is.
+///
+/// The clauses created remember the clause they originate from, see `Clause::from`.
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::{ common::*, parse, preproc::{ PreInstance, RedStrat, DisjSplit } };
+/// let mut instance = parse::instance("
+///   (declare-fun p ( Int ) Bool)
+///   (assert (forall ( (n Int) ) (=> (ite (> n 0) (= n 7) (= n 0)) (p n))))
+///   (assert (forall ( (n Int) ) (=> (p n) (>= n 0))))
+/// ");
+///
+/// let mut disj_split = DisjSplit::new(& instance);
+/// let mut instance = PreInstance::new(& mut instance).unwrap();
+/// let info = disj_split.apply(& mut instance).unwrap();
+/// assert_eq! { info.clauses_rmed, 1 }
+/// assert_eq! { info.clauses_added, 2 }
+/// for clause in instance.clauses() {
+///     for term in clause.lhs_terms() {
+///         assert! { term.disj_inspect().is_none() }
+///         assert! { term.ite_inspect().is_none() }
+///     }
+/// }
+/// ```
+pub struct DisjSplit;
+
+impl RedStrat for DisjSplit {
+    fn name(&self) -> &'static str {
+        "disj_split"
+    }
+
+    fn new(_: &Instance) -> Self {
+        DisjSplit
+    }
+
+    fn apply(&mut self, instance: &mut PreInstance) -> Res<RedInfo> {
+        let mut red_info = RedInfo::new();
+
+        let mut clause: ClsIdx = 0.into();
+        while clause < instance.clauses().next_index() {
+            conf.check_timeout()?;
+            let info = instance.split_disjs(clause, conf.preproc.disj_split_max)?;
+            // Splitting voids the index, the clause it now refers to is new.
+            if info.non_zero() {
+                red_info += info
+            } else {
+                clause.inc()
+            }
+        }
+
+        Ok(red_info)
+    }
+}
@@ -0,0 +1,150 @@
+//! Equal predicates detection.
+
+use crate::{
+    common::*,
+    preproc::{PreInstance, RedStrat},
+};
+
+/// Merges predicates that are interchangeable.
+///
+/// Two predicates `p` and `q` with the same signature are interchangeable if swapping them maps
+/// the set of clauses onto itself. In that case, the intersection of any solution with its swap
+/// is also a solution, in which `p` and `q` are equal. So `q` can be replaced by `p` everywhere
+/// and defined as `p` in the model.
+///
+/// Clauses are compared syntactically, once `p` and `q` are swapped. Candidate pairs are first
+/// filtered by their *shape*: the sorted list, for each clause they appear in, of the number of
+/// terms and applications of the clause. The shape does not depend on variable naming.
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::{ common::*, parse, preproc::{ PreInstance, RedStrat, EqualPreds } };
+/// let mut instance = parse::instance("
+///   (declare-fun p ( Int ) Bool)
+///   (declare-fun q ( Int ) Bool)
+///   (assert (forall ( (n Int) ) (=> (= n 0) (p n))))
+///   (assert (forall ( (n Int) ) (=> (= n 0) (q n))))
+///   (assert (forall ( (n Int) ) (=> (and (p n) (q n)) (>= n 0))))
+/// ");
+///
+/// let mut equal_preds = EqualPreds::new(& instance);
+/// let mut instance = PreInstance::new(& mut instance).unwrap();
+/// let info = equal_preds.apply(& mut instance).unwrap();
+/// assert_eq! { info.preds, 1 }
+/// let (p, q): (PrdIdx, PrdIdx) = (0.into(), 1.into());
+/// assert! { !instance[p].is_defined() }
+/// assert! { instance[q].is_defined() }
+/// ```
+pub struct EqualPreds;
+
+/// Shape of a clause, see `EqualPreds`.
+type Shape = (usize, usize, bool);
+
+impl EqualPreds {
+    /// Shape of the clauses a predicate appears in.
+    fn shape_of(instance: &PreInstance, pred: PrdIdx) -> Vec<Shape> {
+        let (lhs, rhs) = instance.clauses_of(pred);
+        let mut shape: Vec<Shape> = lhs
+            .iter()
+            .chain(rhs.iter())
+            .map(|idx| {
+                let clause = &instance[*idx];
+                let apps: usize = clause.lhs_preds().values().map(|argss| argss.len()).sum();
+                (clause.lhs_terms().len(), apps, clause.rhs().is_some())
+            })
+            .collect();
+        shape.sort_unstable();
+        shape
+    }
+
+    /// True if `other` is `clause` where `p` and `q` are swapped.
+    fn is_swap_of(clause: &Clause, other: &Clause, p: PrdIdx, q: PrdIdx) -> bool {
+        let swap = |pred: PrdIdx| {
+            if pred == p {
+                q
+            } else if pred == q {
+                p
+            } else {
+                pred
+            }
+        };
+
+        match (clause.rhs(), other.rhs()) {
+            (None, None) => (),
+            (Some((pred, args)), Some((other_pred, other_args)))
+                if swap(pred) == other_pred && args == other_args => {}
+            _ => return false,
+        }
+
+        clause.lhs_terms() == other.lhs_terms()
+            && clause.lhs_preds().len() == other.lhs_preds().len()
+            && clause
+                .lhs_preds()
+                .iter()
+                .all(|(pred, argss)| other.lhs_preds().get(&swap(*pred)) == Some(argss))
+    }
+
+    /// True if swapping `p` and `q` maps the clauses onto themselves.
+    fn interchangeable(instance: &PreInstance, p: PrdIdx, q: PrdIdx) -> bool {
+        let mut clauses = ClsSet::new();
+        for pred in &[p, q] {
+            let (lhs, rhs) = instance.clauses_of(*pred);
+            clauses.extend(lhs.iter().cloned());
+            clauses.extend(rhs.iter().cloned())
+        }
+
+        clauses.iter().all(|idx| {
+            clauses
+                .iter()
+                .any(|other| Self::is_swap_of(&instance[*idx], &instance[*other], p, q))
+        })
+    }
+
+    /// Looks for two interchangeable predicates.
+    fn find_pair(instance: &PreInstance) -> Option<(PrdIdx, PrdIdx)> {
+        let preds: Vec<(PrdIdx, Vec<Shape>)> = instance
+            .pred_indices()
+            .filter(|pred| !instance[*pred].is_defined())
+            .map(|pred| (pred, Self::shape_of(instance, pred)))
+            .collect();
+
+        for (index, (p, p_shape)) in preds.iter().enumerate() {
+            for (q, q_shape) in &preds[index + 1..] {
+                if instance[*p].sig == instance[*q].sig
+                    && p_shape == q_shape
+                    && Self::interchangeable(instance, *p, *q)
+                {
+                    return Some((*p, *q));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl RedStrat for EqualPreds {
+    fn name(&self) -> &'static str {
+        "equal_preds"
+    }
+
+    fn new(_: &Instance) -> Self {
+        EqualPreds
+    }
+
+    fn apply(&mut self, instance: &mut PreInstance) -> Res<RedInfo> {
+        let mut red_info = RedInfo::new();
+
+        while let Some((p, q)) = Self::find_pair(instance) {
+            conf.check_timeout()?;
+            log! { @debug
+              "{} and {} are interchangeable", instance[p], instance[q]
+            }
+            red_info.preds += 1;
+            red_info += instance.merge_pred(q, p)?
+        }
+
+        Ok(red_info)
+    }
+}
@@ -0,0 +1,113 @@
+//! Ground fact propagation.
+
+use crate::{
+    common::*,
+    preproc::{PreInstance, RedStrat},
+};
+
+/// Propagates ground facts.
+///
+/// A *ground fact* is a clause `true => (p c_1 ... c_n)` where the `c_i`s are constants. If all
+/// the clauses `p` is the consequent of are ground facts, then the least model of `p` is the set
+/// of these facts. Since `p` only appears in antecedents otherwise, replacing it by its least
+/// model does not change satisfiability: `p` is forced to the disjunction of the facts.
+///
+/// Simplification usually turns some other clauses into ground facts, which cascades. The pass
+/// runs until fixed point. Predicates with more than `--fact_prop_max` facts are ignored, as
+/// unfolding them multiplies the clauses they appear in.
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::{ common::*, parse, preproc::{ PreInstance, RedStrat, FactProp } };
+/// let mut instance = parse::instance("
+///   (declare-fun p ( Int ) Bool)
+///   (declare-fun q ( Int ) Bool)
+///   (assert (p 0))
+///   (assert (p 1))
+///   (assert (forall ( (n Int) ) (=> (p n) (q (+ n 1)))))
+///   (assert (forall ( (n Int) ) (=> (q n) (> n 0))))
+/// ");
+///
+/// let mut fact_prop = FactProp::new(& instance);
+/// let mut instance = PreInstance::new(& mut instance).unwrap();
+/// let info = fact_prop.apply(& mut instance).unwrap();
+/// assert! { info.preds >= 1 }
+/// let p: PrdIdx = 0.into();
+/// assert! { instance[p].is_defined() }
+/// ```
+pub struct FactProp;
+
+impl FactProp {
+    /// The ground facts of a predicate, if all its rhs clauses are ground facts.
+    fn facts_of(instance: &PreInstance, pred: PrdIdx) -> Option<VarTermsSet> {
+        let mut facts = VarTermsSet::new();
+        for clause in &instance.clauses_of(pred).1 {
+            let clause = &instance[*clause];
+            if !clause.lhs_preds().is_empty() || !clause.lhs_terms().is_empty() {
+                return None;
+            }
+            if let Some((_, args)) = clause.rhs() {
+                if args.iter().any(|arg| arg.val().is_none()) {
+                    return None;
+                }
+                facts.insert(args.clone());
+            }
+        }
+        if facts.is_empty() || facts.len() > conf.preproc.fact_prop_max {
+            None
+        } else {
+            Some(facts)
+        }
+    }
+
+    /// Turns some facts into a DNF over the signature of a predicate.
+    fn dnf_of(instance: &PreInstance, pred: PrdIdx, facts: VarTermsSet) -> Dnf {
+        let mut dnf = Vec::with_capacity(facts.len());
+        for fact in facts {
+            let mut tterm_set = TTermSet::new();
+            for (var, typ) in instance[pred].sig.index_iter() {
+                tterm_set.insert_term(term::eq(term::var(var, typ.clone()), fact[var].clone()));
+            }
+            dnf.push((Quantfed::new(), tterm_set))
+        }
+        dnf
+    }
+}
+
+impl RedStrat for FactProp {
+    fn name(&self) -> &'static str {
+        "fact_prop"
+    }
+
+    fn new(_: &Instance) -> Self {
+        FactProp
+    }
+
+    fn apply(&mut self, instance: &mut PreInstance) -> Res<RedInfo> {
+        let mut red_info = RedInfo::new();
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for pred in instance.pred_indices() {
+                if instance[pred].is_defined() {
+                    continue;
+                }
+                conf.check_timeout()?;
+
+                if let Some(facts) = Self::facts_of(instance, pred) {
+                    log! { @3 "propagating {} fact(s) for {}", facts.len(), instance[pred] }
+                    let def = Self::dnf_of(instance, pred, facts);
+                    red_info.preds += 1;
+                    red_info += instance.rm_rhs_clauses_of(pred)?;
+                    red_info += instance.force_dnf_left(pred, def)?;
+                    changed = true
+                }
+            }
+        }
+
+        Ok(red_info)
+    }
+}
@@ -0,0 +1,194 @@
+//! Factoring of the lhs top terms shared by several clauses.
+
+use crate::{
+    common::*,
+    preproc::{PreInstance, RedStrat},
+};
+
+/// Factors the lhs top terms shared by several clauses.
+///
+/// Looks for the largest group of clauses the lhs of which all contain the same top terms
+/// `common`, at least two of them. The top terms are compared syntactically, including their
+/// variables. It then introduces an auxiliary predicate `aux` over the variables of `common`, adds
+/// the clause `common => (aux vars)`, and replaces `common` by `(aux vars)` in the clauses of the
+/// group. This is repeated until no two clauses share two top terms. See
+/// [`PreInstance::factor_lhs`].
+///
+/// Applications of auxiliary predicates are never factored, which guarantees termination. The
+/// auxiliary predicates are inlined away when building the final model, see
+/// [`Instance::extend_model`].
+///
+/// Only runs on the top instance, not on the splits, and not when producing proofs. It does not
+/// run either when splitting is active and the instance has several negative (positive with
+/// `--split_pos`) clauses: pre-processing the splits could then define auxiliary predicates in
+/// terms of other predicates, and such definitions cannot be inlined in the final model.
+///
+/// Finding the best group is cubic in the number of clauses, the pass does nothing on instances
+/// with more than [`MAX_CLAUSES`] clauses and stops after [`MAX_ROUNDS`] factorings.
+///
+/// [`PreInstance::factor_lhs`]: ../../instance/struct.PreInstance.html#method.factor_lhs
+/// (factor_lhs method)
+/// [`Instance::extend_model`]: ../../instance/struct.Instance.html#method.extend_model
+/// (extend_model method)
+/// [`MAX_CLAUSES`]: constant.MAX_CLAUSES.html (MAX_CLAUSES constant)
+/// [`MAX_ROUNDS`]: constant.MAX_ROUNDS.html (MAX_ROUNDS constant)
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::{ common::*, parse, preproc::{ PreInstance, RedStrat, Factor } };
+/// let mut instance = parse::instance("
+///   (declare-fun p ( Int ) Bool)
+///   (declare-fun q ( Int ) Bool)
+///   (assert (forall ( (n Int) ) (=> (= n 0) (p n))))
+///   (assert (forall ( (n Int) ) (=> (and (p n) (> n 0) (< n 10)) (q n))))
+///   (assert (forall ( (n Int) ) (=> (and (p n) (> n 0) (< n 10) (q n)) false)))
+/// ");
+///
+/// let mut factor = Factor::new(& instance);
+/// let mut instance = PreInstance::new(& mut instance).unwrap();
+/// let info = factor.apply(& mut instance).unwrap();
+/// instance.finalize().unwrap();
+/// assert_eq! { info.clauses_rmed, 2 }
+/// assert_eq! { info.clauses_added, 3 }
+/// assert_eq! { instance.clauses().len(), 4 }
+///
+/// let (q, aux): (PrdIdx, PrdIdx) = (1.into(), 2.into());
+/// assert! { instance[aux].is_aux() }
+/// assert_eq! { instance.rhs_clauses_of(aux).len(), 1 }
+/// assert_eq! { instance.lhs_clauses_of(aux).len(), 2 }
+///
+/// // A model where `q` mentions the auxiliary predicate.
+/// let v_0 = term::int_var(0);
+/// let mut app = TTermSet::new();
+/// let args: VarMap<Term> = vec![v_0.clone()].into();
+/// app.insert_pred_app(aux, args.into());
+/// let mut candidates = PrdHMap::new();
+/// candidates.insert(0.into(), vec![TTerms::of_term(None, term::ge(v_0.clone(), term::int(0)))]);
+/// candidates.insert(q, vec![TTerms::conj(None, app)]);
+/// candidates.insert(aux, vec![TTerms::of_term(None, term::ge(v_0.clone(), term::int(1)))]);
+///
+/// let model = instance.extend_model(candidates).unwrap();
+/// assert_eq! { model.len(), 2 }
+/// let mut s: Vec<u8> = vec![];
+/// instance.write_model(& model, & mut s).unwrap();
+/// let s = String::from_utf8_lossy(&s);
+/// assert! { !s.contains("hoice_factor") }
+/// assert! { s.contains("(>= v_0 1)") }
+/// ```
+pub struct Factor;
+
+/// Maximal number of clauses of the instances factoring runs on.
+pub const MAX_CLAUSES: usize = 200;
+
+/// Maximal number of factorings on an instance.
+pub const MAX_ROUNDS: usize = 20;
+
+impl RedStrat for Factor {
+    fn name(&self) -> &'static str {
+        "factor"
+    }
+
+    fn new(_: &Instance) -> Self {
+        Factor
+    }
+
+    fn apply(&mut self, instance: &mut PreInstance) -> Res<RedInfo> {
+        let mut info = RedInfo::new();
+        if instance.proofs() || Self::may_split(instance) {
+            return Ok(info);
+        }
+
+        let mut rounds = 0;
+        while rounds < MAX_ROUNDS && instance.clauses().len() <= MAX_CLAUSES {
+            let (clauses, common) = if let Some(factor) = Self::best_factor(instance)? {
+                factor
+            } else {
+                break;
+            };
+            let (aux, factor_info) = instance.factor_lhs(&clauses, &common)?;
+            log! { @verb
+                "factored {} top term(s) of {} clauses as {}",
+                common.len(), clauses.len(), instance[aux]
+            }
+            info += factor_info;
+            rounds += 1
+        }
+
+        Ok(info)
+    }
+}
+
+impl Factor {
+    /// True if the instance may be split.
+    fn may_split(instance: &Instance) -> bool {
+        let (mut pos, mut neg) = (0, 0);
+        for clause in instance.clauses() {
+            if clause.rhs().is_none() {
+                neg += 1
+            } else if clause.lhs_preds().is_empty() {
+                pos += 1
+            }
+        }
+        (conf.split && neg > 1) || (conf.split_pos && pos > 1)
+    }
+
+    /// The lhs top terms of a clause, except for the applications of auxiliary predicates.
+    fn lhs_of(instance: &Instance, clause: &Clause) -> HashSet<TTerm> {
+        let mut lhs = HashSet::new();
+        for term in clause.lhs_terms() {
+            lhs.insert(TTerm::T(term.clone()));
+        }
+        for (pred, argss) in clause.lhs_preds() {
+            if instance[*pred].is_aux() {
+                continue;
+            }
+            for args in argss {
+                lhs.insert(TTerm::P {
+                    pred: *pred,
+                    args: args.clone(),
+                });
+            }
+        }
+        lhs
+    }
+
+    /// Finds the largest group of clauses sharing at least two lhs top terms.
+    ///
+    /// Returns the clauses of the group and the top terms they share. Groups of the same size are
+    /// ranked by number of top terms shared.
+    fn best_factor(instance: &Instance) -> Res<Option<(Vec<ClsIdx>, Vec<TTerm>)>> {
+        let lhss: ClsMap<HashSet<TTerm>> = instance
+            .clauses()
+            .iter()
+            .map(|clause| Self::lhs_of(instance, clause))
+            .collect();
+
+        let mut best: Option<(Vec<ClsIdx>, Vec<TTerm>)> = None;
+        for (idx_1, lhs_1) in lhss.index_iter() {
+            conf.check_timeout()?;
+            for (_, lhs_2) in lhss.index_iter().filter(|(idx_2, _)| *idx_2 > idx_1) {
+                let common: HashSet<TTerm> = lhs_1.intersection(lhs_2).cloned().collect();
+                if common.len() < 2 {
+                    continue;
+                }
+                let clauses: Vec<ClsIdx> = lhss
+                    .index_iter()
+                    .filter(|(_, lhs)| common.is_subset(lhs))
+                    .map(|(idx, _)| idx)
+                    .collect();
+
+                let better = if let Some((best_clauses, best_tterms)) = best.as_ref() {
+                    (clauses.len(), common.len()) > (best_clauses.len(), best_tterms.len())
+                } else {
+                    true
+                };
+                if better {
+                    best = Some((clauses, common.into_iter().collect()))
+                }
+            }
+        }
+
+        Ok(best)
+    }
+}
@@ -0,0 +1,414 @@
+//! Hint inference for linearly recursive predicates.
+
+use crate::{
+    common::*,
+    preproc::{PreInstance, RedStrat},
+};
+
+/// Infers hints for linearly recursive predicates.
+///
+/// A predicate `p` is linearly recursive if it only depends on itself, and each clause with `p`
+/// as its rhs is either positive (an *init* clause) or has exactly one predicate application in
+/// its lhs, on `p` (a *step* clause). This pass looks at the integer arguments of `p` that are
+/// *counters*: in all the step clauses, the argument in the rhs is the one in the lhs plus a
+/// constant. It then generates
+///
+/// - bounds on the counters from their initial values, and from the guards of the steps that
+///   move them away from their initial values,
+/// - `(= (mod (- v k) c) 0)` if all the steps add `c` to a counter `v` the initial value of which
+///   is always `k`,
+/// - `(= (- v w) d)` if two counters `v` and `w` move together and always start with a difference
+///   of `d`.
+///
+/// These candidates are not necessarily inductive: they are registered as hints in the instance
+/// (see [`Instance::add_hints`]), and the teacher only keeps the ones that are. Hence this pass
+/// never modifies the clauses.
+///
+/// [`Instance::add_hints`]: ../../instance/struct.Instance.html#method.add_hints
+/// (add_hints method)
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::{ common::*, parse, preproc::{ PreInstance, RedStrat, LinAccel } };
+/// let mut instance = parse::instance("
+///   (declare-fun p ( Int Int ) Bool)
+///   (assert (forall ( (x Int) (y Int) ) (=> (and (= x 0) (= y 0)) (p x y))))
+///   (assert (forall ( (x Int) (y Int) )
+///     (=> (and (p x y) (< x 10)) (p (+ x 1) (+ y 1)))
+///   ))
+///   (assert (forall ( (x Int) (y Int) ) (=> (and (p x y) (> y 10)) false)))
+/// ");
+///
+/// let mut lin_accel = LinAccel::new(& instance);
+/// let mut pre_instance = PreInstance::new(& mut instance).unwrap();
+/// let info = lin_accel.apply(& mut pre_instance).unwrap();
+/// assert! { !info.non_zero() }
+/// pre_instance.destroy().unwrap();
+///
+/// let p: PrdIdx = 0.into();
+/// let (v_0, v_1) = (term::int_var(0), term::int_var(1));
+/// let hints = &instance.hints()[&p];
+/// assert! { hints.contains(&term::ge(v_0.clone(), term::int(0))) }
+/// assert! { hints.contains(&term::le(v_0.clone(), term::int(10))) }
+/// assert! { hints.contains(&term::ge(v_1.clone(), term::int(0))) }
+/// assert! { hints.contains(&term::eq(term::sub(vec![v_0, v_1]), term::int(0))) }
+/// ```
+pub struct LinAccel;
+
+impl RedStrat for LinAccel {
+    fn name(&self) -> &'static str {
+        "lin_accel"
+    }
+
+    fn new(_: &Instance) -> Self {
+        LinAccel
+    }
+
+    fn apply(&mut self, instance: &mut PreInstance) -> Res<RedInfo> {
+        let graph = DepGraph::new(instance);
+
+        for pred in instance.pred_indices() {
+            if instance[pred].is_defined()
+                || !graph.is_recursive(pred)
+                || graph.lhs_preds_of(pred).iter().any(|lhs| *lhs != pred)
+            {
+                continue;
+            }
+            conf.check_timeout()?;
+
+            let hints = if let Some(hints) = Self::hints_of(instance, pred) {
+                hints
+            } else {
+                continue;
+            };
+            if hints.is_empty() {
+                continue;
+            }
+
+            if_log! { @verb
+                log! { @verb | "{} hint(s) for {}", hints.len(), instance[pred] }
+                for hint in &hints {
+                    log! { @verb | "  {}", hint }
+                }
+            }
+            instance.add_hints(pred, hints)
+        }
+
+        Ok(RedInfo::new())
+    }
+}
+
+impl LinAccel {
+    /// Generates the hints for a predicate, `None` if it is not linearly recursive.
+    fn hints_of(instance: &Instance, pred: PrdIdx) -> Option<Vec<Term>> {
+        let mut inits = vec![];
+        let mut steps = vec![];
+
+        for clause in instance.rhs_clauses_of(pred) {
+            let clause = &instance[*clause];
+            let (_, rhs_args) = clause.rhs()?;
+            if clause.lhs_preds().is_empty() {
+                inits.push(Init::new(clause, rhs_args));
+                continue;
+            }
+            if clause.lhs_preds().len() != 1 {
+                return None;
+            }
+            let argss = clause.lhs_preds().get(&pred)?;
+            if argss.len() != 1 {
+                return None;
+            }
+            let lhs_args = argss.iter().next()?;
+            steps.push(Step::new(clause, lhs_args, rhs_args))
+        }
+
+        if inits.is_empty() || steps.is_empty() {
+            return None;
+        }
+
+        let mut hints = vec![];
+        let sig = instance[pred].sig();
+
+        // Variables that are counters.
+        let counters: Vec<VarIdx> = sig
+            .index_iter()
+            .filter(|(var, typ)| {
+                typ.is_int() && steps.iter().all(|step| step.deltas[*var].is_some())
+            })
+            .map(|(var, _)| var)
+            .collect();
+
+        for var in &counters {
+            let var = *var;
+            let deltas: Vec<&Int> = steps
+                .iter()
+                .filter_map(|step| step.deltas[var].as_ref())
+                .collect();
+            let v = term::int_var(var);
+
+            if deltas.iter().all(|delta| !delta.is_negative()) {
+                // Non-decreasing: the initial lower bound holds, the upper bound depends on the
+                // guards of the steps that do increase the counter.
+                if let Some(lo) = Self::min(inits.iter().map(|init| init.bounds[var].0.clone())) {
+                    hints.push(term::ge(v.clone(), term::int(lo)))
+                }
+                let hi = Self::max(inits.iter().map(|init| init.bounds[var].1.clone()).chain(
+                    steps.iter().filter_map(|step| {
+                        let delta = step.deltas[var].as_ref()?;
+                        if delta.is_zero() {
+                            None
+                        } else {
+                            Some(step.bounds[var].1.as_ref().map(|hi| hi + delta))
+                        }
+                    }),
+                ));
+                if let Some(hi) = hi {
+                    hints.push(term::le(v.clone(), term::int(hi)))
+                }
+            }
+
+            if deltas.iter().all(|delta| !delta.is_positive()) {
+                // Non-increasing, dual case.
+                if let Some(hi) = Self::max(inits.iter().map(|init| init.bounds[var].1.clone())) {
+                    hints.push(term::le(v.clone(), term::int(hi)))
+                }
+                let lo = Self::min(inits.iter().map(|init| init.bounds[var].0.clone()).chain(
+                    steps.iter().filter_map(|step| {
+                        let delta = step.deltas[var].as_ref()?;
+                        if delta.is_zero() {
+                            None
+                        } else {
+                            Some(step.bounds[var].0.as_ref().map(|lo| lo + delta))
+                        }
+                    }),
+                ));
+                if let Some(lo) = lo {
+                    hints.push(term::ge(v.clone(), term::int(lo)))
+                }
+            }
+
+            // Congruence.
+            let delta = deltas[0].abs();
+            if delta > Int::one() && deltas.iter().all(|d| d.abs() == delta) {
+                if let Some(init) = Self::same(inits.iter().map(|init| init.value(var))) {
+                    hints.push(term::eq(
+                        term::modulo(
+                            term::sub(vec![v.clone(), term::int(init)]),
+                            term::int(delta),
+                        ),
+                        term::int(0),
+                    ))
+                }
+            }
+        }
+
+        // Counters moving together.
+        for (idx, v) in counters.iter().enumerate() {
+            for w in &counters[idx + 1..] {
+                let together = steps.iter().all(|step| step.deltas[*v] == step.deltas[*w]);
+                if !together {
+                    continue;
+                }
+                if let Some(diff) = Self::same(inits.iter().map(|init| init.diff(*v, *w))) {
+                    hints.push(term::eq(
+                        term::sub(vec![term::int_var(*v), term::int_var(*w)]),
+                        term::int(diff),
+                    ))
+                }
+            }
+        }
+
+        hints.sort();
+        hints.dedup();
+        Some(hints)
+    }
+
+    /// Minimum of some values, `None` if one of them is `None`.
+    fn min<I: Iterator<Item = Option<Int>>>(mut vals: I) -> Option<Int> {
+        let mut res = vals.next()??;
+        for val in vals {
+            let val = val?;
+            if val < res {
+                res = val
+            }
+        }
+        Some(res)
+    }
+
+    /// Maximum of some values, `None` if one of them is `None`.
+    fn max<I: Iterator<Item = Option<Int>>>(mut vals: I) -> Option<Int> {
+        let mut res = vals.next()??;
+        for val in vals {
+            let val = val?;
+            if val > res {
+                res = val
+            }
+        }
+        Some(res)
+    }
+
+    /// The value of some values if they are all the same, `None` otherwise.
+    fn same<I: Iterator<Item = Option<Int>>>(mut vals: I) -> Option<Int> {
+        let res = vals.next()??;
+        for val in vals {
+            if val? != res {
+                return None;
+            }
+        }
+        Some(res)
+    }
+}
+
+/// Initial values of the arguments of a predicate, from a positive clause.
+struct Init<'a> {
+    /// Arguments of the rhs of the clause.
+    args: &'a VarTerms,
+    /// Lower and upper bounds of the arguments.
+    bounds: VarMap<(Option<Int>, Option<Int>)>,
+}
+impl<'a> Init<'a> {
+    /// Constructor.
+    fn new(clause: &Clause, args: &'a VarTerms) -> Self {
+        let bounds = args
+            .iter()
+            .map(|arg| {
+                if let Some(val) = arg.int_val() {
+                    (Some(val.clone()), Some(val.clone()))
+                } else if let Some(var) = arg.var_idx() {
+                    guard_bounds(clause, var)
+                } else {
+                    (None, None)
+                }
+            })
+            .collect();
+        Init { args, bounds }
+    }
+
+    /// Value of an argument, if it is known exactly.
+    fn value(&self, var: VarIdx) -> Option<Int> {
+        match &self.bounds[var] {
+            (Some(lo), Some(hi)) if lo == hi => Some(lo.clone()),
+            _ => None,
+        }
+    }
+
+    /// Difference between two arguments, if it is known exactly.
+    fn diff(&self, v: VarIdx, w: VarIdx) -> Option<Int> {
+        let diff = term::sub(vec![self.args[v].clone(), self.args[w].clone()]);
+        if let Some(diff) = diff.int_val() {
+            Some(diff.clone())
+        } else {
+            Some(self.value(v)? - self.value(w)?)
+        }
+    }
+}
+
+/// A step of a linearly recursive predicate.
+struct Step {
+    /// Difference between each argument in the rhs and in the lhs, if constant.
+    deltas: VarMap<Option<Int>>,
+    /// Bounds on the arguments of the lhs application from the guard of the clause.
+    bounds: VarMap<(Option<Int>, Option<Int>)>,
+}
+impl Step {
+    /// Constructor.
+    fn new(clause: &Clause, lhs_args: &VarTerms, rhs_args: &VarTerms) -> Self {
+        let deltas = lhs_args
+            .iter()
+            .zip(rhs_args.iter())
+            .map(|(lhs, rhs)| {
+                if lhs.typ().is_int() {
+                    term::sub(vec![rhs.clone(), lhs.clone()]).int_val().cloned()
+                } else {
+                    None
+                }
+            })
+            .collect();
+        let bounds = lhs_args
+            .iter()
+            .map(|arg| {
+                if let Some(var) = arg.var_idx() {
+                    guard_bounds(clause, var)
+                } else {
+                    (None, None)
+                }
+            })
+            .collect();
+        Step { deltas, bounds }
+    }
+}
+
+/// Lower and upper bounds on an integer variable from the lhs terms of a clause.
+///
+/// Only looks at the terms that only mention this variable.
+fn guard_bounds(clause: &Clause, var: VarIdx) -> (Option<Int>, Option<Int>) {
+    let (mut lo, mut hi): (Option<Int>, Option<Int>) = (None, None);
+    for term in clause.lhs_terms() {
+        let vars = term::vars(term);
+        if vars.len() != 1 || !vars.contains(&var) {
+            continue;
+        }
+        let (t_lo, t_hi) = term_bounds(term, var, true);
+        if let Some(t_lo) = t_lo {
+            if lo.as_ref().map(|lo| t_lo > *lo).unwrap_or(true) {
+                lo = Some(t_lo)
+            }
+        }
+        if let Some(t_hi) = t_hi {
+            if hi.as_ref().map(|hi| t_hi < *hi).unwrap_or(true) {
+                hi = Some(t_hi)
+            }
+        }
+    }
+    (lo, hi)
+}
+
+/// Lower and upper bounds on an integer variable from a comparison.
+///
+/// The comparison is negated if `pos` is false.
+fn term_bounds(term: &Term, var: VarIdx, pos: bool) -> (Option<Int>, Option<Int>) {
+    let (op, args) = match term.app_inspect() {
+        Some((Op::Not, args)) if args.len() == 1 => return term_bounds(&args[0], var, !pos),
+        Some((op, args)) if args.len() == 2 => (op, args),
+        _ => return (None, None),
+    };
+    let diff = term::sub(vec![args[0].clone(), args[1].clone()]);
+    // Turns the comparison into `diff >= 0`, or `diff = 0`.
+    let (diff, is_eq) = match (op, pos) {
+        (Op::Ge, true) | (Op::Lt, false) => (diff, false),
+        (Op::Gt, true) | (Op::Le, false) => (term::sub(vec![diff, term::int(1)]), false),
+        (Op::Le, true) | (Op::Gt, false) => (term::u_minus(diff), false),
+        (Op::Lt, true) | (Op::Ge, false) => {
+            (term::sub(vec![term::u_minus(diff), term::int(1)]), false)
+        }
+        (Op::Eql, true) => (diff, true),
+        _ => return (None, None),
+    };
+
+    // `diff` is `a * var + b`.
+    let eval = |val: i64| {
+        let mut map = VarHMap::new();
+        map.insert(var, term::int(val));
+        diff.subst(&map).0.int_val().cloned()
+    };
+    let (b, a) = match (eval(0), eval(1)) {
+        (Some(at_0), Some(at_1)) => {
+            let a = at_1 - &at_0;
+            (at_0, a)
+        }
+        _ => return (None, None),
+    };
+
+    if a == Int::one() {
+        // `var + b >= 0`.
+        let lo = Some(-b.clone());
+        (lo, if is_eq { Some(-b) } else { None })
+    } else if a == -Int::one() {
+        // `b - var >= 0`.
+        let hi = Some(b.clone());
+        (if is_eq { Some(b) } else { None }, hi)
+    } else {
+        (None, None)
+    }
+}
@@ -3,10 +3,15 @@
 //! All strategies are `struct`s that implement the [`RedStrat`] trait. The [`Reductor`] then
 //! combines them in a cohesive preprocessing run in its [`run`] function.
 //!
+//! Library users can add their own passes by implementing [`PreprocPass`], the object-safe part
+//! of [`RedStrat`], and giving them to [`work_with`].
+//!
 //! During preprocessing, the [`Instance`] is wrapped into a [`PreInstance`] which provides
 //! high-level functions to act on the predicates and the clauses of the instance.
 //!
 //! [`RedStrat`]: trait.RedStrat.html (RedStrat trait)
+//! [`PreprocPass`]: trait.PreprocPass.html (PreprocPass trait)
+//! [`work_with`]: fn.work_with.html (work_with function)
 //! [`Reductor`]: struct.Reductor.html (Reductor struct)
 //! [`run`]: struct.Reductor.html#method.run (Reductor's run function)
 //! [`Instance`]: ../common/struct.Instance.html (Instance struct)
@@ -16,21 +21,54 @@ use crate::{common::*, instance::*, preproc::utils::ExtractRes};
 
 pub mod utils;
 
+pub mod arg_order;
 pub mod arg_red;
 pub mod bias_unroll;
 pub mod cfg_red;
+pub mod dead_preds;
+pub mod disj_split;
+pub mod equal_preds;
+pub mod fact_prop;
+pub mod factor;
 pub mod fun_preds;
+pub mod lin_accel;
 pub mod one_lhs;
 pub mod one_rhs;
 pub mod strict_neg_clauses;
+pub mod trivial_clauses;
 pub mod unroll;
 
 pub use self::{
-    arg_red::ArgRed, bias_unroll::BiasedUnroll, cfg_red::CfgRed, fun_preds::FunPreds,
-    one_lhs::OneLhs, one_rhs::OneRhs, strict_neg_clauses::StrictNeg, unroll::RUnroll,
+    arg_order::ArgOrder, arg_red::ArgRed, bias_unroll::BiasedUnroll, cfg_red::CfgRed,
+    dead_preds::DeadPreds, disj_split::DisjSplit, equal_preds::EqualPreds, fact_prop::FactProp,
+    factor::Factor, fun_preds::FunPreds, lin_accel::LinAccel, one_lhs::OneLhs, one_rhs::OneRhs,
+    strict_neg_clauses::StrictNeg, trivial_clauses::TrivialClauses, unroll::RUnroll,
 };
 pub use crate::instance::PreInstance;
 
+/// Names of the pre-processing passes, as returned by their `name` function.
+///
+/// These are the names `--no_preproc` accepts.
+pub const PASS_NAMES: &[&str] = &[
+    "arg_order",
+    "arg_reduce",
+    "biased_unroll",
+    "cfg_red",
+    "dead_preds",
+    "disj_split",
+    "equal_preds",
+    "fact_prop",
+    "factor",
+    "fun_preds",
+    "lin_accel",
+    "one_lhs",
+    "one_rhs",
+    "runroll",
+    "simplify",
+    "strict_neg",
+    "trivial_clauses",
+];
+
 /// Extension for a predicate.
 ///
 /// Used by `extend_pred_left`.
@@ -43,24 +81,82 @@ pub type PredExtension = (TermSet, Vec<(Quantfed, Term)>);
 ///
 /// Finalizes the instance.
 pub fn work(instance: &mut Instance, profiler: &Profiler) -> Res<()> {
+    work_with(instance, profiler, &mut [])
+}
+
+/// Runs pre-processing with some additional passes.
+///
+/// The passes run in order in each round of pre-processing, after the cheap built-in passes. A
+/// round where one of them changes the instance is followed by another round.
+///
+/// Finalizes the instance.
+pub fn work_with(
+    instance: &mut Instance,
+    profiler: &Profiler,
+    passes: &mut [Box<dyn PreprocPass>],
+) -> Res<()> {
     let res = {
         let instance = profile! {
           |profiler| wrap {
             PreInstance::new(instance) ?
           } "preproc", "pre-instance creation"
         };
-        run(instance, profiler, true)
+        run(instance, profiler, true, passes)
     };
     finalize(res, instance, profiler)
 }
 
+/// Runs pre-processing on a whole instance, without solving it.
+///
+/// Returns the model of the instance if pre-processing solved it, and the pre-processed instance
+/// otherwise. In the latter case, the instance remembers the definitions of the predicates
+/// pre-processing eliminated: a model for it can be turned into a model of the original instance
+/// with `Instance::model_of` or `Instance::extend_model`. It can be written back in SMT-LIB 2
+/// with `Instance::dump_as_smt2`.
+///
+/// Fails with an unsat error if pre-processing proved the instance unsat.
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::{ common::*, parse, preproc };
+/// let instance = parse::instance("
+///   (declare-fun p ( Int ) Bool)
+///   (assert (forall ( (n Int) ) (=> (= n 0) (p n))))
+///   (assert (forall ( (n Int) ) (=> (p n) (>= n 0))))
+/// ");
+///
+/// match preproc::simplify(instance).unwrap() {
+///     Either::Left(instance) => {
+///         let mut buf: Vec<u8> = vec![];
+///         instance.dump_as_smt2(&mut buf, "").unwrap()
+///     }
+///     Either::Right(model) => assert_eq! { model.len(), 1 },
+/// }
+/// ```
+pub fn simplify(mut instance: Instance) -> Res<Either<Instance, Model>> {
+    let profiler = Profiler::new();
+    work(&mut instance, &profiler)?;
+
+    match instance.is_trivial_model()? {
+        None => Ok(Either::Left(instance)),
+        Some(MaybeModel::Model(model)) => Ok(Either::Right(model)),
+        Some(MaybeModel::Unsat) => bail!(ErrorKind::Unsat),
+    }
+}
+
 /// Runs pre-processing from a pre-instance.
-fn run(instance: PreInstance, profiler: &Profiler, simplify_first: bool) -> Res<()> {
+fn run(
+    instance: PreInstance,
+    profiler: &Profiler,
+    simplify_first: bool,
+    passes: &mut [Box<dyn PreprocPass>],
+) -> Res<()> {
     profile! { |profiler| tick "preproc" }
 
     let mut reductor = profile! {
       |profiler| wrap {
-        Reductor::new(instance) ?
+        Reductor::new_with(instance, passes) ?
       } "preproc", "creation"
     };
     let res = reductor.run(profiler, simplify_first).and_then(|_| {
@@ -77,6 +173,16 @@ fn run(instance: PreInstance, profiler: &Profiler, simplify_first: bool) -> Res<
 
 /// Finalizes pre-processing
 fn finalize(res: Res<()>, instance: &mut Instance, _profiler: &Profiler) -> Res<()> {
+    // Nothing outside of the instance refers to its clauses yet, the renumbering map is not
+    // needed. Finalized instances (pre-processing ran already) keep their numbering.
+    if conf.preproc.compact && res.is_ok() && !instance.is_finalized() {
+        let _ = profile!(
+            |_profiler| wrap {
+                instance.compact_clauses()
+            } "compacting"
+        )?;
+    }
+
     profile!(
         |_profiler| wrap {
             instance.finalize()
@@ -111,12 +217,17 @@ fn finalize(res: Res<()>, instance: &mut Instance, _profiler: &Profiler) -> Res<
 
 /// Runs pre-processing on a split version of the input instance.
 ///
-/// Fails if `to_keep` is not a negative clause in `instance`.
+/// If `pos`, removes all positive clauses but `to_keep`. Otherwise, removes all negative clauses
+/// but `to_keep`, and strengthens the instance with the strict negative clauses removed that are
+/// not in `ignore`.
+///
+/// Fails if `to_keep` is not a positive (if `pos`, negative otherwise) clause in `instance`.
 ///
 /// Does **not** remove clauses that are tagged as being from unrolling.
 pub fn work_on_split(
     instance: &Instance,
     to_keep: ClsIdx,
+    pos: bool,
     ignore: &ClsSet,
     profiler: &Profiler,
 ) -> Res<Instance> {
@@ -124,19 +235,22 @@ pub fn work_on_split(
 
     let mut split_instance = instance.clone_with_clauses(to_keep);
 
-    let mut to_forget: Vec<_> = instance
-        .neg_clauses()
-        .iter()
-        .filter_map(|c| {
-            if c == &to_keep
-            /* || instance[* c].from_unrolling */
-            {
-                None
-            } else {
-                Some(*c)
-            }
-        })
-        .collect();
+    let mut to_forget: Vec<_> = if pos {
+        instance.pos_clauses()
+    } else {
+        instance.neg_clauses()
+    }
+    .iter()
+    .filter_map(|c| {
+        if c == &to_keep
+        /* || instance[* c].from_unrolling */
+        {
+            None
+        } else {
+            Some(*c)
+        }
+    })
+    .collect();
 
     let mut strict_neg_clauses = Vec::with_capacity(instance.neg_clauses().len());
 
@@ -145,7 +259,8 @@ pub fn work_on_split(
     for clause_idx in to_forget {
         if clause_idx != to_keep {
             let clause = split_instance.forget_clause(clause_idx)?;
-            if conf.preproc.split_strengthen
+            if !pos
+                && conf.preproc.split_strengthen
                 && !ignore.contains(&clause_idx)
                 && instance.strict_neg_clauses().contains(&clause_idx)
             {
@@ -252,7 +367,7 @@ pub fn work_on_split(
             }
         }
 
-        run(pre_instance, profiler, true)
+        run(pre_instance, profiler, true, &mut [])
     };
 
     finalize(res, &mut split_instance, profiler)?;
@@ -268,6 +383,16 @@ pub struct Reductor<'a> {
     simplify: Option<Simplify>,
     /// Optional predicate argument reduction pre-processor.
     arg_red: Option<ArgRed>,
+    /// Optional canonical ordering of the predicate arguments.
+    arg_order: Option<ArgOrder>,
+    /// Optional dead predicate elimination.
+    dead_preds: Option<DeadPreds>,
+    /// Optional disjunction splitting.
+    disj_split: Option<DisjSplit>,
+    /// Optional equal predicates merging.
+    equal_preds: Option<EqualPreds>,
+    /// Optional ground fact propagation.
+    fact_prop: Option<FactProp>,
     /// Optional one rhs pre-processor.
     one_rhs: Option<OneRhs>,
     /// Optional one lhs pre-processor.
@@ -280,14 +405,34 @@ pub struct Reductor<'a> {
     runroll: Option<RUnroll>,
     /// Optional strengthener by strict negative clauses.
     strict_neg: Option<StrictNeg>,
+    /// Optional detection of trivial clauses.
+    trivial_clauses: Option<TrivialClauses>,
     /// Optional predicate-to-function reduction.
     fun_preds: Option<FunPreds>,
+    /// Optional hint inference for linearly recursive predicates.
+    lin_accel: Option<LinAccel>,
+    /// Optional factoring of the lhs shared by several clauses.
+    factor: Option<Factor>,
+    /// Additional passes.
+    passes: &'a mut [Box<dyn PreprocPass>],
+    /// Statistics of the passes, if `conf.preproc.preproc_stats`.
+    stats: Option<utils::PreprocStats>,
 }
 impl<'a> Reductor<'a> {
     /// Constructor.
     ///
     /// Checks the configuration to initialize the pre-processors.
     pub fn new(instance: PreInstance<'a>) -> Res<Self> {
+        Self::new_with(instance, &mut [])
+    }
+
+    /// Constructor with additional passes.
+    ///
+    /// Checks the configuration to initialize the pre-processors.
+    pub fn new_with(
+        instance: PreInstance<'a>,
+        passes: &'a mut [Box<dyn PreprocPass>],
+    ) -> Res<Self> {
         macro_rules! some_new {
             ($red:ident if $flag:ident $(and $flags:ident )*) => (
                 some_new! { $red |if| conf.preproc.$flag $( && conf.preproc.$flags )* }
@@ -312,6 +457,11 @@ impl<'a> Reductor<'a> {
 
         let simplify = Some(Simplify::new(&instance));
         let arg_red = some_new! { ArgRed if active and arg_red };
+        let arg_order = some_new! { ArgOrder if active and arg_order };
+        let dead_preds = some_new! { DeadPreds if active and dead_preds };
+        let disj_split = some_new! { DisjSplit if active and disj_split };
+        let equal_preds = some_new! { EqualPreds if active and equal_preds };
+        let fact_prop = some_new! { FactProp if active and fact_prop };
 
         let one_rhs = some_new! {
           OneRhs if active and one_rhs
@@ -331,23 +481,36 @@ impl<'a> Reductor<'a> {
         let strict_neg = some_new! {
           StrictNeg if active and strict_neg
         };
+        let trivial_clauses = some_new! { TrivialClauses if active and trivial_clauses };
         let fun_preds = if !dtyp::one_or_more()? {
             None
         } else {
             some_new! { FunPreds if active and fun_preds }
         };
+        let lin_accel = some_new! { LinAccel if active and lin_accel };
+        let factor = some_new! { Factor if active and factor_clauses };
 
         Ok(Reductor {
             instance,
             simplify,
             arg_red,
+            arg_order,
+            dead_preds,
+            disj_split,
+            equal_preds,
+            fact_prop,
             one_rhs,
             one_lhs,
             cfg_red,
             biased_unroll,
             runroll,
             strict_neg,
+            trivial_clauses,
             fun_preds,
+            lin_accel,
+            factor,
+            passes,
+            stats: None,
         })
     }
 
@@ -357,7 +520,35 @@ impl<'a> Reductor<'a> {
     }
 
     /// Runs the full pre-processing.
+    ///
+    /// Prints the statistics of the passes if `conf.preproc.preproc_stats`.
     pub fn run(&mut self, _profiler: &Profiler, simplify_first: bool) -> Res<()> {
+        if conf.preproc.preproc_stats {
+            self.stats = Some(utils::PreprocStats::new(utils::InstanceSize::of(
+                &self.instance,
+            )))
+        }
+        let res = self.run_passes(_profiler, simplify_first);
+        if let Some(stats) = self.stats.take() {
+            if let Some(clause) = self.instance.split() {
+                println!("; pre-processing statistics, split on clause #{}:", clause)
+            } else {
+                println!("; pre-processing statistics:")
+            }
+            for line in stats
+                .to_string(utils::InstanceSize::of(&self.instance))
+                .lines()
+            {
+                println!(";   {}", line)
+            }
+        }
+        res
+    }
+
+    /// Runs the passes, see [`run`].
+    ///
+    /// [`run`]: #method.run (run function)
+    fn run_passes(&mut self, _profiler: &Profiler, simplify_first: bool) -> Res<()> {
         // Counter for preproc dumping.
         //
         // Starts at `1`, `0` is reserved for the fixed point.
@@ -379,7 +570,8 @@ impl<'a> Reductor<'a> {
                 if let Some(preproc) = self.$preproc.as_mut() {
                     // println!("clauses count: {}", self.instance.clauses().len());
                     if let Some(red_info) = utils::run_preproc(
-                        & mut self.instance, _profiler, preproc, & mut count
+                        & mut self.instance, _profiler, preproc, & mut count,
+                        self.stats.as_mut()
                     ) ? {
                         run! { @ $($tail)* Some(red_info) }
                     } else {
@@ -396,6 +588,7 @@ impl<'a> Reductor<'a> {
         utils::register_stats(&self.instance, _profiler, count)?;
 
         if simplify_first {
+            run! { trivial_clauses };
             run! { simplify };
         }
 
@@ -407,15 +600,52 @@ impl<'a> Reductor<'a> {
         // last run.
         let mut changed_since_cfg_red = true;
 
+        // Number of rounds so far, and shape hashes of the instance at the beginning of each
+        // round. Used to stop when the passes keep cycling between the same instances.
+        let mut rounds = 0;
+        let mut shapes = HashSet::new();
+
         loop {
             if self.instance.is_solved() {
                 break;
             }
             conf.check_timeout()?;
 
+            if conf
+                .preproc
+                .max_rounds
+                .map(|max| rounds >= max)
+                .unwrap_or(false)
+            {
+                log! { @verb "preprocessing: reached maximum number of rounds ({})", rounds }
+                break;
+            }
+            if !shapes.insert(self.instance.shape_hash()) {
+                log! { @verb "preprocessing: instance stable after {} round(s)", rounds }
+                break;
+            }
+            rounds += 1;
+
             run! { arg_red };
 
-            let changed = false;
+            let changed = run! { dead_preds };
+            let changed = run! { disj_split } || changed;
+            let changed = run! { equal_preds } || changed;
+            let mut changed = run! { fact_prop } || changed;
+
+            for pass in self.passes.iter_mut() {
+                if let Some(red_info) = utils::run_preproc(
+                    &mut self.instance,
+                    _profiler,
+                    pass.as_mut(),
+                    &mut count,
+                    self.stats.as_mut(),
+                )? {
+                    changed = red_info.non_zero() || changed
+                } else {
+                    return Ok(());
+                }
+            }
 
             if changed {
                 changed_since_cfg_red = true;
@@ -454,6 +684,8 @@ impl<'a> Reductor<'a> {
             }
         }
 
+        run! { arg_order };
+
         conf.check_timeout()?;
 
         if self.instance.split().is_none() && self.instance.clauses().len() > 20 {
@@ -477,6 +709,13 @@ impl<'a> Reductor<'a> {
 
         run! { strict_neg };
 
+        run! { lin_accel };
+
+        // Splits cannot introduce predicates, the top instance would not know them.
+        if self.instance.split().is_none() {
+            run! { factor };
+        }
+
         utils::register_final_stats(&self.instance, _profiler)?;
 
         Ok(())
@@ -496,6 +735,84 @@ pub trait RedStrat {
     fn apply(&mut self, instance: &mut PreInstance) -> Res<RedInfo>;
 }
 
+/// A pre-processing pass, see [`work_with`].
+///
+/// Same as [`RedStrat`] without the constructor, so that passes can be boxed. All reduction
+/// strategies are passes.
+///
+/// [`work_with`]: fn.work_with.html (work_with function)
+/// [`RedStrat`]: trait.RedStrat.html (RedStrat trait)
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::{ common::*, parse, preproc::{ self, PreInstance, PreprocPass } };
+/// /// Remembers how many clauses the instance had each time it ran.
+/// struct Spy(Arc<RwLock<Vec<usize>>>);
+/// impl PreprocPass for Spy {
+///     fn name(&self) -> &'static str {
+///         "spy"
+///     }
+///     fn apply(&mut self, instance: &mut PreInstance) -> Res<RedInfo> {
+///         self.0.write().unwrap().push(instance.clauses().len());
+///         Ok(RedInfo::new())
+///     }
+/// }
+///
+/// /// Fails.
+/// struct Fail;
+/// impl PreprocPass for Fail {
+///     fn name(&self) -> &'static str {
+///         "fail"
+///     }
+///     fn apply(&mut self, _: &mut PreInstance) -> Res<RedInfo> {
+///         bail!("custom pass failure")
+///     }
+/// }
+///
+/// let input = "
+///   (declare-fun p ( Int ) Bool)
+///   (assert (forall ( (n Int) ) (=> (= n 0) (p n))))
+///   (assert (forall ( (n Int) ) (=> (and (p n) (< n 10)) (p (+ n 1)))))
+///   (assert (forall ( (n Int) ) (=> (and (p n) (> n 10)) false)))
+/// ";
+///
+/// let seen = Arc::new(RwLock::new(vec![]));
+/// let mut instance = parse::instance(input);
+/// let mut passes: Vec<Box<dyn PreprocPass>> = vec![Box::new(Spy(seen.clone()))];
+/// preproc::work_with(&mut instance, &Profiler::new(), &mut passes).unwrap();
+/// // The pass ran on the instance, which the built-in passes cannot solve.
+/// assert_eq! { seen.read().unwrap().first(), Some(&3) }
+/// assert! { !instance[PrdIdx::from(0)].is_defined() }
+///
+/// // Errors in passes abort pre-processing, the passes after the failing one do not run.
+/// let seen = Arc::new(RwLock::new(vec![]));
+/// let mut instance = parse::instance(input);
+/// let mut passes: Vec<Box<dyn PreprocPass>> = vec![
+///     Box::new(Fail), Box::new(Spy(seen.clone()))
+/// ];
+/// let err = preproc::work_with(&mut instance, &Profiler::new(), &mut passes).unwrap_err();
+/// assert! { err.iter().any(|e| format!("{}", e).contains("custom pass failure")) }
+/// assert! { seen.read().unwrap().is_empty() }
+/// ```
+pub trait PreprocPass {
+    /// Pass' name.
+    fn name(&self) -> &'static str;
+
+    /// Applies the pass. Returns the number of predicates reduced and the number of clauses
+    /// forgotten.
+    fn apply(&mut self, instance: &mut PreInstance) -> Res<RedInfo>;
+}
+
+impl<T: RedStrat> PreprocPass for T {
+    fn name(&self) -> &'static str {
+        RedStrat::name(self)
+    }
+    fn apply(&mut self, instance: &mut PreInstance) -> Res<RedInfo> {
+        RedStrat::apply(self, instance)
+    }
+}
+
 /// Calls `PredInstance::simplify_all`.
 pub struct Simplify;
 
@@ -7,6 +7,14 @@ use crate::{
 
 /// Works on predicates that appear in only one rhs.
 ///
+/// A predicate is not unfolded if the clause defining it mentions it in its lhs, or if
+/// unfolding it would make the instance grow beyond `--one_rhs_max_blowup` times its size when
+/// the pass started.
+///
+/// When producing proofs, a predicate is only unfolded if its definition mentions only
+/// predicates this pass already turned into *safe* definitions, *i.e.* definitions proof
+/// reconstruction can inline.
+///
 /// # Examples
 ///
 /// | Clause                             | `p v_0 v_1 =`               |
@@ -57,13 +65,50 @@ use crate::{
 ///     ",
 ///     &String::from_utf8_lossy(&s)
 /// }
+///
+/// let mut instance = parse::instance("
+///   (declare-fun p_1 ( Int ) Bool)
+///   (assert (forall ( (n Int) ) (=> (and (p_1 n) (> n 0)) (p_1 (+ n 1)))))
+///   (assert (forall ( (n Int) ) (=> (p_1 n) (> n 0))))
+/// ");
+///
+/// let mut one_rhs = OneRhs::new(& instance);
+/// let mut instance = PreInstance::new(& mut instance).unwrap();
+/// let info = one_rhs.apply(& mut instance).unwrap();
+/// // `p_1` is recursive, it is not unfolded.
+/// assert_eq! { info.preds, 0 }
+/// assert! { !instance[pred].is_defined() }
 /// ```
 pub struct OneRhs {
     /// True if introducing quantifiers is okay.
     quantifiers: bool,
+    /// Predicates defined by this pass that mention only other safe predicates.
+    safe_preds: PrdSet,
+}
+
+/// Size of a clause, in number of atoms.
+fn clause_size(clause: &Clause) -> usize {
+    let apps: usize = clause.lhs_preds().values().map(|argss| argss.len()).sum();
+    clause.lhs_terms().len() + apps + if clause.rhs().is_some() { 1 } else { 0 }
 }
 
 impl OneRhs {
+    /// Size increase caused by unfolding `pred`, defined by `clause`.
+    ///
+    /// Each application of `pred` in an lhs is replaced by the lhs of `clause`, and `clause` goes
+    /// away.
+    fn unfolding_growth(pred: PrdIdx, clause: ClsIdx, instance: &PreInstance) -> (usize, usize) {
+        let def = &instance.clauses()[clause];
+        let def_size = clause_size(def) - 1;
+        let mut added = 0;
+        for idx in &instance.clauses_of(pred).0 {
+            if let Some(argss) = instance.clauses()[*idx].lhs_preds().get(&pred) {
+                added += argss.len() * def_size
+            }
+        }
+        (added, clause_size(def))
+    }
+
     /// Logs an extraction result.
     fn log_extraction(&self, _instance: &Instance, _quantfed: &Quantfed, _tterms: &TTermSet) {
         if_log! { @4
@@ -116,19 +161,14 @@ impl OneRhs {
 
             if let Some((_this_pred, args)) = clause.rhs() {
                 debug_assert_eq!(pred, _this_pred);
-
-                // Does `pred` appear in the lhs?
-                match clause.lhs_preds().get(&pred) {
-                    Some(apps) if !apps.is_empty() => ExtractRes::SuccessFalse,
-                    _ => extraction.terms_of_rhs_app(
-                        self.quantifiers,
-                        instance,
-                        clause.vars(),
-                        clause.lhs_terms(),
-                        clause.lhs_preds(),
-                        (pred, args),
-                    )?,
-                }
+                extraction.terms_of_rhs_app(
+                    self.quantifiers,
+                    instance,
+                    clause.vars(),
+                    clause.lhs_terms(),
+                    clause.lhs_preds(),
+                    (pred, args),
+                )?
             } else {
                 bail!("inconsistent instance state")
             }
@@ -174,12 +214,22 @@ impl RedStrat for OneRhs {
     }
 
     fn new(_: &Instance) -> Self {
-        OneRhs { quantifiers: false }
+        OneRhs {
+            quantifiers: false,
+            safe_preds: PrdSet::new(),
+        }
     }
 
     fn apply(&mut self, instance: &mut PreInstance) -> Res<RedInfo> {
         let mut red_info = RedInfo::new();
 
+        // Current size of the instance and maximal size allowed, if any.
+        let mut size: usize = instance.clauses().iter().map(clause_size).sum();
+        let max_size = match conf.preproc.one_rhs_max_blowup {
+            0 => None,
+            factor => Some(size * factor),
+        };
+
         'all_preds: for pred in instance.pred_indices() {
             if instance[pred].is_defined() || instance.clauses_of(pred).1.len() > 1 {
                 continue 'all_preds;
@@ -206,11 +256,44 @@ impl RedStrat for OneRhs {
               instance.clauses_of(pred).1.len(),
             }
 
+            // Does `pred` appear in the lhs?
+            if instance[clause].lhs_preds().contains_key(&pred) {
+                log! { @3 "not unfolding {}: recursive definition", instance[pred] }
+                continue 'all_preds;
+            }
+
+            // Would the definition of `pred` be unsafe for proof reconstruction?
+            if instance.proofs()
+                && instance[clause]
+                    .lhs_preds()
+                    .keys()
+                    .any(|p| !self.safe_preds.contains(p))
+            {
+                log! { @3 "not unfolding {}: unsafe definition", instance[pred] }
+                continue 'all_preds;
+            }
+
+            let (added, removed) = Self::unfolding_growth(pred, clause, instance);
+            if let Some(max_size) = max_size {
+                if size + added > max_size + removed {
+                    log! { @3 "not unfolding {}: size blowup", instance[pred] }
+                    continue 'all_preds;
+                }
+            }
+
             if let Some(info) = self.work_on(pred, clause, instance)? {
+                size = (size + added).saturating_sub(removed);
                 red_info.preds += 1;
                 red_info += info;
                 instance.check("after unfolding (one_rhs)")?;
                 debug_assert! { instance[pred].is_defined() }
+                let safe = instance[pred]
+                    .def()
+                    .map(|def| def.preds().is_subset(&self.safe_preds))
+                    .unwrap_or(false);
+                if safe {
+                    self.safe_preds.insert(pred);
+                }
             } else {
                 log! { @4 "failed to unfold {}", instance[pred] }
             }
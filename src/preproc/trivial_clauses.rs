@@ -0,0 +1,126 @@
+//! Detects trivially true and trivially contradictory clauses.
+
+use crate::{
+    common::*,
+    preproc::{PreInstance, RedStrat},
+};
+
+/// Detects trivially true and trivially contradictory clauses.
+///
+/// A clause is trivially true if its lhs is unsatisfiable or contains its rhs, it is removed. A
+/// clause is contradictory if it has no predicate application and its lhs is satisfiable: the
+/// instance is unsat, and the pass fails with [`ErrorKind::UnsatFrom`] on this clause. This
+/// clause is the entry point of the unsat proof, if any.
+///
+/// Cheap syntactic checks run first: a `false` lhs term, two complementary lhs terms or a rhs
+/// appearing in the lhs make the clause trivially true, and a clause without predicate
+/// applications the lhs terms of which are all `true` is contradictory. The solver is only used
+/// for the clauses without predicate applications these checks do not decide, the other clauses
+/// are left to the simplification pass.
+///
+/// Runs at the very beginning of top pre-processing, so the clause it reports is a clause of the
+/// original instance.
+///
+/// [`ErrorKind::UnsatFrom`]: ../../errors/enum.ErrorKind.html#variant.UnsatFrom
+/// (ErrorKind's UnsatFrom variant)
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::{ common::*, parse, preproc::{ PreInstance, RedStrat, TrivialClauses } };
+/// let mut instance = parse::instance("
+///   (declare-fun p ( Int ) Bool)
+///   (assert (forall ( (n Int) ) (=> (= n 0) (p n))))
+///   (assert (forall ( (n Int) ) (=> (and (p n) (>= n 0)) (p n))))
+///   (assert (forall ( (n Int) ) (=> (p n) (>= n 0))))
+/// ");
+///
+/// let mut trivial = TrivialClauses::new(& instance);
+/// let mut instance = PreInstance::new(& mut instance).unwrap();
+/// let info = trivial.apply(& mut instance).unwrap();
+/// assert_eq! { info.clauses_rmed, 1 }
+/// assert_eq! { instance.clauses().len(), 2 }
+///
+/// let mut instance = parse::instance("
+///   (declare-fun p ( Int ) Bool)
+///   (assert (forall ( (n Int) ) (=> (= n 0) (p n))))
+///   (assert (forall ( (n Int) ) (=> (>= n 7) false)))
+/// ");
+///
+/// let mut instance = PreInstance::new(& mut instance).unwrap();
+/// let err = trivial.apply(& mut instance).unwrap_err();
+/// assert! { err.is_unsat() }
+/// assert_eq! { err.unsat_cause(), Some(1.into()) }
+/// ```
+pub struct TrivialClauses;
+
+impl TrivialClauses {
+    /// True if a clause is trivially true, syntactically.
+    fn is_syntactically_trivial(clause: &Clause) -> bool {
+        for term in clause.lhs_terms() {
+            if term.bool() == Some(false) {
+                return true;
+            }
+            let neg = term::not(term.clone());
+            if clause.lhs_terms().contains(&neg) {
+                return true;
+            }
+        }
+        if let Some((pred, args)) = clause.rhs() {
+            clause
+                .lhs_preds()
+                .get(&pred)
+                .map(|argss| argss.contains(args))
+                .unwrap_or(false)
+        } else {
+            false
+        }
+    }
+}
+
+impl RedStrat for TrivialClauses {
+    fn name(&self) -> &'static str {
+        "trivial_clauses"
+    }
+
+    fn new(_: &Instance) -> Self {
+        TrivialClauses
+    }
+
+    fn apply(&mut self, instance: &mut PreInstance) -> Res<RedInfo> {
+        let mut info = RedInfo::new();
+
+        // Go through the clauses in reverse so that swap removes are safe.
+        let mut clause = instance.clauses().next_index();
+
+        while clause > 0 {
+            clause.dec();
+
+            let trivial = if Self::is_syntactically_trivial(&instance[clause]) {
+                true
+            } else if instance[clause].lhs_preds().is_empty() && instance[clause].rhs().is_none() {
+                if instance[clause]
+                    .lhs_terms()
+                    .iter()
+                    .all(|term| term.bool() == Some(true))
+                {
+                    log! { @verb "unsat because of clause #{}", clause }
+                    bail!(ErrorKind::UnsatFrom(clause))
+                }
+                instance.is_clause_trivial(clause)?
+            } else {
+                false
+            };
+
+            if trivial {
+                log! { @debug "  removing trivial clause #{}", clause }
+                instance.forget_clause(clause)?;
+                info.clauses_rmed += 1
+            }
+
+            conf.check_timeout()?
+        }
+
+        Ok(info)
+    }
+}
@@ -104,10 +104,14 @@ use crate::{
 //   }
 // }
 
-/// Reverse-unrolls negative constraints once.
+/// Reverse-unrolls negative constraints.
+///
+/// Each predicate is reverse-unrolled at most once, unless `--runroll_depths` says otherwise.
 pub struct RUnroll {
     max_new_clauses: usize,
     ignore: PrdSet,
+    /// Number of times each predicate was reverse-unrolled.
+    unrolled: PrdHMap<usize>,
 }
 
 impl RUnroll {
@@ -211,24 +215,9 @@ impl RUnroll {
 
         Ok(())
     }
-}
-
-impl RedStrat for RUnroll {
-    fn name(&self) -> &'static str {
-        "runroll"
-    }
-
-    fn new(instance: &Instance) -> Self {
-        RUnroll {
-            max_new_clauses: ::std::cmp::max(
-                5,
-                instance.preds().len() + instance.clauses().len() * 5 / 100,
-            ),
-            ignore: PrdSet::new(),
-        }
-    }
 
-    fn apply(&mut self, instance: &mut PreInstance) -> Res<RedInfo> {
+    /// Runs one round of reverse unrolling.
+    fn round(&mut self, instance: &mut PreInstance) -> Res<RedInfo> {
         let mut prd_map: PrdHMap<Vec<(Option<Quant>, TermSet)>> = PrdHMap::with_capacity(17);
 
         scoped! {
@@ -242,6 +231,11 @@ impl RedStrat for RUnroll {
 
         let mut info = RedInfo::new();
         for (pred, terms) in prd_map {
+            let count = self.unrolled.get(&pred).cloned().unwrap_or(0);
+            if count >= conf.preproc.runroll_depth(&instance[pred].name) {
+                continue;
+            }
+
             // Anticipate blowup.
             let appearances = instance.clauses_of(pred).0.len();
             if appearances >= self.max_new_clauses {
@@ -261,9 +255,42 @@ impl RedStrat for RUnroll {
                   conf.emph(& instance[pred].name),
                   terms.len()
                 }
+                self.unrolled.insert(pred, count + 1);
                 info += instance.reverse_unroll(pred, &terms)?
             }
         }
         Ok(info)
     }
 }
+
+impl RedStrat for RUnroll {
+    fn name(&self) -> &'static str {
+        "runroll"
+    }
+
+    fn new(instance: &Instance) -> Self {
+        RUnroll {
+            max_new_clauses: ::std::cmp::max(
+                5,
+                instance.preds().len() + instance.clauses().len() * 5 / 100,
+            ),
+            ignore: PrdSet::new(),
+            unrolled: PrdHMap::new(),
+        }
+    }
+
+    fn apply(&mut self, instance: &mut PreInstance) -> Res<RedInfo> {
+        let mut info = RedInfo::new();
+
+        for _ in 0..conf.preproc.runroll_rounds() {
+            let round_info = self.round(instance)?;
+            let done = !round_info.non_zero();
+            info += round_info;
+            if done {
+                break;
+            }
+        }
+
+        Ok(info)
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::{common::*, var_to::terms::VarTermsSet};
 
-use super::{PreInstance, RedStrat};
+use super::{PreInstance, PreprocPass};
 
 /// Result of extracting the terms for a predicate application in a clause.
 #[derive(Clone, PartialEq, Eq)]
@@ -758,22 +758,39 @@ pub fn check_solved(instance: &mut PreInstance, _profiler: &Profiler) -> Res<boo
 
 /// Runs a technique, returns it's info.
 ///
-/// Returns `None` if the instance is solved.
-pub fn run_preproc<Strat: RedStrat>(
+/// Returns `None` if the instance is solved. Passes disabled by `--no_preproc` do nothing. The
+/// run is recorded in `stats`, if any.
+pub fn run_preproc<Strat: PreprocPass + ?Sized>(
     instance: &mut PreInstance,
     _profiler: &Profiler,
     preproc: &mut Strat,
     count: &mut usize,
+    stats: Option<&mut PreprocStats>,
 ) -> Res<Option<RedInfo>> {
+    if !conf.preproc.pass_enabled(preproc.name()) {
+        return Ok(Some(RedInfo::new()));
+    }
+    conf.check_mem("pre-processing")?;
+    conf.check_timeout()?;
+    let before = if stats.is_some() {
+        Some(InstanceSize::of(instance))
+    } else {
+        None
+    };
     profile! {
       |_profiler| tick "preproc", preproc.name()
     }
     log! { @verb
       "running {}", conf.emph( preproc.name() )
     }
-    let red_info = preproc
-        .apply(instance)
-        .chain_err(|| format!("while running preprocessor {}", conf.bad(preproc.name())));
+    // Unsat errors are not chained, they can carry the clause causing them.
+    let red_info = preproc.apply(instance).map_err(|e| {
+        if e.is_unsat() {
+            e
+        } else {
+            e.chain_err(|| format!("while running preprocessor {}", conf.bad(preproc.name())))
+        }
+    });
     profile! {
       |_profiler| mark "preproc", preproc.name()
     }
@@ -782,9 +799,225 @@ pub fn run_preproc<Strat: RedStrat>(
 
     process_red_info(instance, _profiler, preproc.name(), count, &red_info)?;
 
-    if check_solved(instance, _profiler)? {
+    let solved = check_solved(instance, _profiler)?;
+
+    if let (Some(stats), Some(before)) = (stats, before) {
+        stats.record(
+            preproc.name(),
+            before,
+            InstanceSize::of(instance),
+            &red_info,
+        )
+    }
+
+    if solved {
         Ok(None)
     } else {
         Ok(Some(red_info))
     }
 }
+
+/// Size of an instance, see [`PreprocStats`].
+///
+/// [`PreprocStats`]: struct.PreprocStats.html (PreprocStats struct)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InstanceSize {
+    /// Number of clauses.
+    pub clauses: usize,
+    /// Number of predicates that are not defined.
+    pub preds: usize,
+    /// Number of arguments of the predicates that are not defined.
+    pub args: usize,
+    /// Number of terms in the lhs of the clauses, predicate applications excluded.
+    pub terms: usize,
+}
+impl InstanceSize {
+    /// Size of an instance.
+    pub fn of(instance: &Instance) -> Self {
+        let mut size = InstanceSize {
+            clauses: instance.clauses().len(),
+            terms: instance
+                .clauses()
+                .iter()
+                .map(|clause| clause.lhs_terms().len())
+                .sum(),
+            ..Self::default()
+        };
+        for pred in instance.preds() {
+            if !pred.is_defined() {
+                size.preds += 1;
+                size.args += pred.sig.len()
+            }
+        }
+        size
+    }
+}
+
+/// Contribution of a pass to pre-processing, see [`PreprocStats`].
+///
+/// [`PreprocStats`]: struct.PreprocStats.html (PreprocStats struct)
+#[derive(Debug, Default)]
+pub struct PassStats {
+    /// Number of times the pass ran.
+    pub runs: usize,
+    /// Number of runs that changed the instance.
+    pub changed: usize,
+    /// Sum of the reduction info the pass reported.
+    pub info: RedInfo,
+    /// Sum of the variations of the number of clauses.
+    pub clauses: isize,
+    /// Sum of the variations of the number of predicates.
+    pub preds: isize,
+    /// Sum of the variations of the number of arguments.
+    pub args: isize,
+    /// Sum of the variations of the number of lhs terms, negative when terms are simplified away.
+    pub terms: isize,
+}
+
+/// Pre-processing statistics, see `--preproc_stats`.
+///
+/// Records, for each pass, the reduction info it reported and the variation of the size of the
+/// instance it caused. Since the instance only changes when a pass runs, the variations sum to
+/// the difference between the original and the final size of the instance.
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::{ common::*, preproc::utils::{ InstanceSize, PreprocStats } };
+/// let original = InstanceSize { clauses: 7, preds: 3, args: 5, terms: 12 };
+/// let mut stats = PreprocStats::new(original);
+/// let mid = InstanceSize { clauses: 5, preds: 2, args: 4, terms: 10 };
+/// stats.record("one_rhs", original, mid, &(1, 3, 1).into());
+/// let last = InstanceSize { clauses: 6, preds: 2, args: 3, terms: 10 };
+/// stats.record("arg_reduce", mid, last, &RedInfo::new());
+/// let simplified = InstanceSize { terms: 7, ..last };
+/// stats.record("simplify", last, simplified, &RedInfo::new());
+/// stats.record("one_rhs", simplified, simplified, &RedInfo::new());
+///
+/// let one_rhs = stats.get("one_rhs").unwrap();
+/// assert_eq! { (one_rhs.runs, one_rhs.changed), (2, 1) }
+/// assert_eq! { (one_rhs.clauses, one_rhs.preds, one_rhs.args), (-2, -1, -1) }
+/// let arg_red = stats.get("arg_reduce").unwrap();
+/// assert_eq! { (arg_red.clauses, arg_red.preds, arg_red.args), (1, 0, -1) }
+/// let simplify = stats.get("simplify").unwrap();
+/// assert_eq! { (simplify.changed, simplify.terms), (1, -3) }
+///
+/// let table = stats.to_string(simplified);
+/// assert! { table.contains("7 -> 6 clause(s)") }
+/// assert! { table.contains("12 -> 7 lhs term(s)") }
+/// # println!("{}", table)
+/// ```
+#[derive(Debug)]
+pub struct PreprocStats {
+    /// Size of the instance before pre-processing.
+    original: InstanceSize,
+    /// Statistics of the passes, in the order they first ran.
+    passes: Vec<(&'static str, PassStats)>,
+}
+impl PreprocStats {
+    /// Constructor, from the size of the instance before pre-processing.
+    pub fn new(original: InstanceSize) -> Self {
+        PreprocStats {
+            original,
+            passes: vec![],
+        }
+    }
+
+    /// Statistics of a pass, `None` if it never ran.
+    pub fn get(&self, pass: &str) -> Option<&PassStats> {
+        self.passes
+            .iter()
+            .find(|(name, _)| *name == pass)
+            .map(|(_, stats)| stats)
+    }
+
+    /// Records a run of a pass.
+    pub fn record(
+        &mut self,
+        pass: &'static str,
+        before: InstanceSize,
+        after: InstanceSize,
+        info: &RedInfo,
+    ) {
+        let index = if let Some(index) = self.passes.iter().position(|(name, _)| *name == pass) {
+            index
+        } else {
+            self.passes.push((pass, PassStats::default()));
+            self.passes.len() - 1
+        };
+        let stats = &mut self.passes[index].1;
+        stats.runs += 1;
+        if info.non_zero() || before != after {
+            stats.changed += 1
+        }
+        stats.info += RedInfo {
+            preds: info.preds,
+            clauses_rmed: info.clauses_rmed,
+            clauses_added: info.clauses_added,
+            args_rmed: info.args_rmed,
+        };
+        stats.clauses += after.clauses as isize - before.clauses as isize;
+        stats.preds += after.preds as isize - before.preds as isize;
+        stats.args += after.args as isize - before.args as isize;
+        stats.terms += after.terms as isize - before.terms as isize;
+    }
+
+    /// Table of the statistics, given the size of the instance after pre-processing.
+    ///
+    /// The first columns are the reduction info reported by the passes, the last four ones are
+    /// the variations of the size of the instance.
+    pub fn to_string(&self, last: InstanceSize) -> String {
+        let mut s = format!(
+            "{:<15} {:>5} {:>7} {:>6} {:>7} {:>7} {:>6} | {:>7} {:>6} {:>6} {:>6}",
+            "pass",
+            "runs",
+            "changed",
+            "preds-",
+            "clause-",
+            "clause+",
+            "args-",
+            "clauses",
+            "preds",
+            "args",
+            "terms"
+        );
+        let (mut clauses, mut preds, mut args, mut terms) = (0, 0, 0, 0);
+        for (name, stats) in &self.passes {
+            s += &format!(
+                "\n{:<15} {:>5} {:>7} {:>6} {:>7} {:>7} {:>6} | {:>7} {:>6} {:>6} {:>6}",
+                name,
+                stats.runs,
+                stats.changed,
+                stats.info.preds,
+                stats.info.clauses_rmed,
+                stats.info.clauses_added,
+                stats.info.args_rmed,
+                stats.clauses,
+                stats.preds,
+                stats.args,
+                stats.terms
+            );
+            clauses += stats.clauses;
+            preds += stats.preds;
+            args += stats.args;
+            terms += stats.terms;
+        }
+        s += &format!(
+            "\n{:<15} {:>5} {:>7} {:>6} {:>7} {:>7} {:>6} | {:>7} {:>6} {:>6} {:>6}",
+            "total", "", "", "", "", "", "", clauses, preds, args, terms
+        );
+        s += &format!(
+            "\ninstance: {} -> {} clause(s), {} -> {} predicate(s), {} -> {} argument(s), \
+             {} -> {} lhs term(s)",
+            self.original.clauses,
+            last.clauses,
+            self.original.preds,
+            last.preds,
+            self.original.args,
+            last.args,
+            self.original.terms,
+            last.terms
+        );
+        s
+    }
+}
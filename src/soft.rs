@@ -0,0 +1,196 @@
+//! Greedy relaxation of soft clauses.
+//!
+//! Soft clauses are the clauses with a `:weight` attribute: `(assert (! <clause> :weight <n>))`.
+//! The other clauses are *hard*. When the instance is unsat and `conf.soft_clauses` is active,
+//! [`work`] looks for a model of the hard clauses that satisfies soft clauses of maximal total
+//! weight. This is a greedy heuristic, the result is not necessarily optimal:
+//!
+//! - the lightest soft clauses are dropped one by one until the instance is sat;
+//! - the dropped clauses are put back, heaviest first, whenever the instance stays sat.
+//!
+//! The clauses still dropped at the end are violated by the model: putting any of them back
+//! yields an instance that was found unsat, and that the model is a model of minus that clause.
+//!
+//! [`work`]: fn.work.html (work function)
+
+use crate::{common::*, unwrap_arc};
+
+/// A soft clause: index in the instance before pre-processing, and weight.
+type Soft = (ClsIdx, usize);
+
+/// Result of the relaxation of the soft clauses of an instance.
+pub struct Relaxed {
+    /// Pre-processed instance the model is expressed on.
+    pub instance: Instance,
+    /// Model of the hard clauses and of the soft clauses that are not violated.
+    pub model: ConjModel,
+    /// Soft clauses violated by the model: **original** clause index and weight, sorted.
+    pub violated: Vec<(ClsIdx, usize)>,
+}
+
+/// Relaxes the soft clauses of an unsat instance, prints the result in comments.
+///
+/// `instance` is the instance **before** pre-processing. Each relaxation step solves the whole
+/// instance again, pre-processing included.
+pub fn work(instance: &Instance, profiler: &Profiler) -> Res<()> {
+    let total: usize = instance
+        .clauses()
+        .iter()
+        .filter_map(|clause| instance.weight_of_old_clause(clause.from()))
+        .sum();
+
+    let Relaxed {
+        instance: mut solved_instance,
+        mut model,
+        violated,
+    } = if let Some(relaxed) = relax(instance, profiler)? {
+        relaxed
+    } else {
+        println!("; soft clauses: the hard clauses are unsat");
+        return Ok(());
+    };
+
+    let violated_weight: usize = violated.iter().map(|(_, weight)| weight).sum();
+    println!(
+        "; soft clauses: model satisfying weight {} out of {}, violating {} clause(s)",
+        total - violated_weight,
+        total,
+        violated.len()
+    );
+    for (from, weight) in &violated {
+        if let Some(name) = instance.name_of_old_clause(*from) {
+            println!(";   #{} `{}` (weight {})", from, name, weight)
+        } else {
+            println!(";   #{} (weight {})", from, weight)
+        }
+    }
+
+    if conf.factor_model {
+        solved_instance.factor_model(&mut model)
+    }
+    let mut buf: Vec<u8> = vec![];
+    solved_instance.write_model(&model, &mut buf)?;
+    for line in String::from_utf8_lossy(&buf).lines() {
+        println!("; {}", line)
+    }
+
+    Ok(())
+}
+
+/// Relaxes the soft clauses of an unsat instance.
+///
+/// `instance` is the instance **before** pre-processing. Returns `None` if the hard clauses are
+/// unsat.
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::{ common::*, parse, soft };
+/// let instance = parse::instance("
+///   (declare-fun inv ( Int ) Bool)
+///   (assert (forall ( (n Int) ) (=> (= n 0) (inv n))))
+///   (assert (! (forall ( (n Int) ) (=> (and (inv n) (< n 10)) (inv (+ n 1)))) :weight 10))
+///   (assert (! (forall ( (n Int) ) (=> (inv n) (and (< n 5) (> n (- 3))))) :weight 1))
+/// ");
+///
+/// let relaxed = soft::relax(& instance, & Profiler::new()).unwrap().unwrap();
+/// // The last assertion yields two clauses, only `(=> (inv n) (< n 5))` is violated.
+/// assert_eq! { relaxed.violated.len(), 1 }
+/// assert_eq! { relaxed.violated[0].1, 1 }
+/// ```
+pub fn relax(instance: &Instance, profiler: &Profiler) -> Res<Option<Relaxed>> {
+    let mut soft: Vec<Soft> = instance
+        .clauses()
+        .index_iter()
+        .filter_map(|(idx, clause)| {
+            instance
+                .weight_of_old_clause(clause.from())
+                .map(|weight| (idx, weight))
+        })
+        .collect();
+    // Lightest last, since we're popping.
+    soft.sort_by(|(c_1, w_1), (c_2, w_2)| w_2.cmp(w_1).then_with(|| c_2.cmp(c_1)));
+
+    let mut dropped: Vec<Soft> = vec![];
+    let mut solved = None;
+    while let Some(clause) = soft.pop() {
+        log! { @info "soft clauses: dropping #{} (weight {})", clause.0, clause.1 }
+        dropped.push(clause);
+        solved = solve(instance, &dropped, profiler)?;
+        if solved.is_some() {
+            break;
+        }
+    }
+
+    let (mut solved_instance, mut model) = if let Some(solved) = solved {
+        solved
+    } else {
+        return Ok(None);
+    };
+
+    // The instance was unsat before dropping the last clause, no need to try putting it back.
+    let mut violated = vec![dropped
+        .pop()
+        .expect("the instance is unsat, some soft clause was dropped")];
+    while let Some(clause) = dropped.pop() {
+        log! { @info "soft clauses: putting back #{} (weight {})", clause.0, clause.1 }
+        let others: Vec<Soft> = dropped.iter().chain(violated.iter()).cloned().collect();
+        if let Some((nu_instance, nu_model)) = solve(instance, &others, profiler)? {
+            solved_instance = nu_instance;
+            model = nu_model
+        } else {
+            violated.push(clause)
+        }
+    }
+
+    let mut violated: Vec<Soft> = violated
+        .into_iter()
+        .map(|(idx, weight)| (instance[idx].from(), weight))
+        .collect();
+    violated.sort();
+
+    Ok(Some(Relaxed {
+        instance: solved_instance,
+        model,
+        violated,
+    }))
+}
+
+/// Solves an instance without some of its soft clauses.
+///
+/// Returns the pre-processed instance and its model if sat, `None` if unsat. Fails if the result
+/// is unknown.
+fn solve(
+    instance: &Instance,
+    dropped: &[Soft],
+    profiler: &Profiler,
+) -> Res<Option<(Instance, ConjModel)>> {
+    let mut instance = instance.clone();
+    let mut clauses: Vec<ClsIdx> = dropped.iter().map(|(idx, _)| *idx).collect();
+    instance.forget_clauses(&mut clauses)?;
+
+    match crate::preproc::work(&mut instance, &Profiler::new()) {
+        Ok(()) => (),
+        Err(ref e) if e.is_unsat() => return Ok(None),
+        Err(e) => bail!(e),
+    }
+
+    if let Some(maybe_model) = instance.is_trivial_conj()? {
+        return Ok(maybe_model.into_option().map(|model| (instance, model)));
+    }
+
+    let instance = Arc::new(instance);
+    let res = crate::split::work(&instance, profiler);
+    let instance = unwrap_arc(instance).chain_err(|| "while trying to recover instance")?;
+
+    match res {
+        Ok(Some(Either::Left(candidates))) => {
+            let model = instance.extend_model(candidates)?;
+            Ok(Some((instance, model)))
+        }
+        Ok(Some(Either::Right(_))) => Ok(None),
+        Err(ref e) if e.is_unsat() => Ok(None),
+        Ok(None) => bail!(ErrorKind::Unknown),
+        Err(e) => bail!(e),
+    }
+}
@@ -5,6 +5,15 @@
 //! sub-instance per negative clause. Negative clauses that have been removed are injected in
 //! non-negative clauses to strengthen the instance: this avoids losing too much information when
 //! dropping some negative clauses.
+//!
+//! With `conf.split_pos`, splitting is done on positive clauses instead, provided the instance is
+//! linear (at most one predicate application in the LHS of each clause). Each sub-instance then has
+//! a single positive clause, and the model of the instance is the disjunction of the sub-models.
+
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
 
 use crate::common::*;
 use crate::unsat_core::UnsatRes;
@@ -27,26 +36,107 @@ pub fn work(
     let mut model = ConjCandidates::new();
 
     let mut splitter = Splitter::new(real_instance.clone());
+    let pos = splitter.is_pos();
+    // Disjunction of the sub-models when splitting on positive clauses.
+    let mut pos_model = PrdHMap::new();
+    // Number of splits that reached `conf.split_timeout`.
+    let mut timed_out = 0;
+
+    let mut eta = SplitEta::new();
+
+    // The teacher(s) append to the qualifier dump file, start from scratch.
+    if let Some(file) = conf.ice.dump_quals.as_ref() {
+        let _ = ::std::fs::File::create(file)
+            .chain_err(|| format!("while creating qualifier dump file `{}`", file))?;
+    }
+
+    let mut checkpoint = if let Some(file) = conf.split_resume_file() {
+        Some(Checkpoint::load(file, real_instance, None)?)
+    } else if let Some(file) = conf.split_cache_file() {
+        let context = context_hash(real_instance, pos);
+        Some(Checkpoint::load(file, real_instance, Some(context))?)
+    } else {
+        None
+    };
+
+    if conf.split_par && conf.infer && checkpoint.is_none() {
+        if let Some(clauses) = splitter.take_clauses() {
+            return work_par(real_instance, clauses, pos, _profiler);
+        }
+    }
 
-    'split_loop: while let Some(preproc_res) = {
+    // Adds a submodel to the (partial) model, and records it in the checkpoint if any.
+    macro_rules! register_submodel {
+        ($submodel:expr) => {{
+            let submodel = $submodel;
+            print_anytime(&real_instance, splitter.current(), &submodel)?;
+            if let (Some(checkpoint), Some(clause)) = (checkpoint.as_mut(), splitter.current()) {
+                checkpoint.record(&real_instance, clause, &submodel)?
+            }
+            if pos {
+                add_pos_submodel(&real_instance, &mut pos_model, submodel)?
+            } else {
+                add_submodel(&real_instance, &mut model, submodel)
+            }
+        }};
+    }
+
+    if let Some(checkpoint) = checkpoint.as_mut() {
+        let keys = splitter.keys(checkpoint);
+        checkpoint.retain(&keys);
+        log! { @info
+            "resuming {} split(s) from `{}`", checkpoint.handled.len(), checkpoint.file
+        }
+        for submodel in checkpoint.submodels.drain(0..) {
+            if pos {
+                add_pos_submodel(&real_instance, &mut pos_model, submodel)?
+            } else {
+                add_submodel(&real_instance, &mut model, submodel)
+            }
+        }
+        splitter.skip(checkpoint)
+    }
+
+    'split_loop: loop {
         if_not_bench! {
           if let Some((clause, handled, total)) = splitter.info() {
-            log! { conf.stats || conf.split_step, || @info
-              "\n{}{}{}{}{} Splitting on negative clause #{} ({} of {})",
+            if conf.split_eta {
+              eta.split_start()
+            }
+            log! { conf.stats || conf.split_step || conf.split_eta, || @info
+              "\n{}{}{}{}{} Splitting on {} clause #{} ({} of {}){}",
               conf.emph("|"),
               conf.happy("="),
               conf.sad("="),
               conf.happy("="),
               conf.emph("|"),
-              clause, handled + 1, total
+              if pos { "positive" } else { "negative" },
+              clause, handled + 1, total,
+              if conf.split_eta { eta.to_string(handled, total) } else { "".into() }
             }
             if conf.split_step {
               pause("to start sub-preprocessing", _profiler) ;
             }
           }
         }
-        splitter.next_instance(&_profiler)
-    }? {
+
+        // Covers the pre-processing and the solving of the split.
+        let deadline = split_deadline(splitter.info().is_some());
+        let preproc_res = match with_deadline(deadline, || splitter.next_instance(&_profiler)) {
+            Err(ref e) if is_split_timeout(e) => {
+                warn!("split reached its timeout during pre-processing, ignoring it");
+                profile! { |_profiler| "sub-system(s) timeout" => add 1 }
+                timed_out += 1;
+                continue 'split_loop;
+            }
+            res => res?,
+        };
+        let preproc_res = if let Some(preproc_res) = preproc_res {
+            preproc_res
+        } else {
+            break 'split_loop;
+        };
+
         if let Some(prof) = splitter.profiler() {
             print_stats("sub-preproc", prof)
         }
@@ -59,14 +149,34 @@ pub fn work(
             },
             Either::Right(MaybeModel::Model(this_model)) => {
                 log_info! { "sat by preproc\n\n" }
-                add_submodel(&real_instance, &mut model, this_model);
+                profile! { |_profiler| "sub-system(s) solved by preproc" => add 1 }
+                register_submodel!(this_model);
 
                 continue 'split_loop;
             }
         };
 
-        match run_on(_profiler, instance, &model)? {
-            Some(Either::Left(this_model)) => add_submodel(&real_instance, &mut model, this_model),
+        let res = match with_deadline(deadline, || run_on(_profiler, instance, &model, None)) {
+            Err(ref e) if is_split_timeout(e) => {
+                warn!("split reached its timeout, ignoring it");
+                profile! { |_profiler| "sub-system(s) timeout" => add 1 }
+                timed_out += 1;
+                continue 'split_loop;
+            }
+            Err(ref e) if is_split_crash(e) => {
+                warn!("{}, ignoring it", e);
+                profile! { |_profiler| "sub-system(s) crash" => add 1 }
+                timed_out += 1;
+                continue 'split_loop;
+            }
+            res => res?,
+        };
+
+        match res {
+            Some(Either::Left(this_model)) => {
+                profile! { |_profiler| "sub-system(s) solved by learning" => add 1 }
+                register_submodel!(this_model)
+            }
 
             Some(Either::Right(reason)) => return Ok(Some(Either::Right(reason))),
 
@@ -74,18 +184,529 @@ pub fn work(
         }
     }
 
-    if conf.infer {
+    if pos {
+        model = of_pos_model(&real_instance, pos_model)
+    }
+
+    if timed_out > 0 {
+        print_partial_model(real_instance, &model, timed_out);
+        Ok(None)
+    } else if conf.infer {
         Ok(Some(Either::Left(model)))
     } else {
         Ok(None)
     }
 }
 
+/// Checkpoint for split mode.
+///
+/// The checkpoint file lists the splits solved so far and their sub-model:
+///
+/// ```text
+/// (split <clause hash>)
+/// (cand <predicate> <term>)
+/// ...
+/// ```
+///
+/// Clauses are identified by a hash of their content (see [`clause_hash`]) so that the checkpoint
+/// survives across runs on the same instance. Terms are expressed in terms of the original
+/// signature of the predicates, with the default variable names.
+///
+/// In cache mode (`--split_cache`), the hash of a split also covers the clauses shared by all
+/// the splits (see [`context_hash`]). A split is then only skipped if neither its clause nor the
+/// shared clauses changed, and the file can be reused across runs on different versions of an
+/// instance: the entries of the splits that changed are ignored.
+///
+/// [`clause_hash`]: fn.clause_hash.html (clause_hash function)
+/// [`context_hash`]: fn.context_hash.html (context_hash function)
+struct Checkpoint {
+    /// Checkpoint file.
+    file: String,
+    /// Hash of the clauses shared by all the splits, in cache mode.
+    context: Option<u64>,
+    /// Hashes of the splits already handled.
+    handled: HashSet<u64>,
+    /// Sub-models loaded from the checkpoint file, drained when resuming.
+    submodels: Vec<Model>,
+    /// Hash of the split of each sub-model in `submodels`.
+    submodel_keys: Vec<u64>,
+}
+impl Checkpoint {
+    /// Loads a checkpoint, creates the file if it does not exist.
+    ///
+    /// `context` is the hash of the clauses shared by all the splits in cache mode, `None`
+    /// otherwise.
+    fn load(file: &str, instance: &Instance, context: Option<u64>) -> Res<Self> {
+        let mut checkpoint = Checkpoint {
+            file: file.into(),
+            context,
+            handled: HashSet::new(),
+            submodels: vec![],
+            submodel_keys: vec![],
+        };
+
+        if !::std::path::Path::new(file).exists() {
+            let mut out = ::std::fs::File::create(file)
+                .chain_err(|| format!("while creating split checkpoint `{}`", file))?;
+            if context.is_some() {
+                writeln!(out, "; hoice split cache")?
+            } else {
+                writeln!(out, "; hoice split checkpoint")?
+            }
+            return Ok(checkpoint);
+        }
+
+        let mut content = String::new();
+        ::std::fs::File::open(file)
+            .and_then(|mut f| f.read_to_string(&mut content))
+            .chain_err(|| format!("while reading split checkpoint `{}`", file))?;
+
+        let mut current: Option<(u64, Model)> = None;
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            } else if line.starts_with("(split ") && line.ends_with(')') {
+                if let Some((hash, submodel)) = current.take() {
+                    checkpoint.add(hash, submodel)
+                }
+                let hash = line["(split ".len()..line.len() - 1].trim();
+                let hash = hash.parse::<u64>().chain_err(|| {
+                    format!(
+                        "illegal clause hash `{}` in split checkpoint `{}`",
+                        hash, file
+                    )
+                })?;
+                current = Some((hash, vec![]))
+            } else if line.starts_with("(cand ") && line.ends_with(')') {
+                let cand = Self::parse_cand(&line["(cand ".len()..line.len() - 1], instance)
+                    .chain_err(|| format!("in split checkpoint `{}`", file))?;
+                if let Some((_, submodel)) = current.as_mut() {
+                    submodel.push(cand)
+                } else {
+                    bail!(
+                        "illegal split checkpoint `{}`: candidate appears before any split",
+                        file
+                    )
+                }
+            } else {
+                bail!("illegal line in split checkpoint `{}`: `{}`", file, line)
+            }
+        }
+
+        if let Some((hash, submodel)) = current {
+            checkpoint.add(hash, submodel)
+        }
+
+        Ok(checkpoint)
+    }
+
+    /// Adds a split loaded from the file, ignores it if it is already there.
+    fn add(&mut self, key: u64, submodel: Model) {
+        if self.handled.insert(key) {
+            self.submodels.push(submodel);
+            self.submodel_keys.push(key)
+        }
+    }
+
+    /// Forgets the splits loaded from the file that are not in `keys`.
+    ///
+    /// In cache mode, these are the splits the clause or context of which changed.
+    fn retain(&mut self, keys: &HashSet<u64>) {
+        let mut submodels = Vec::with_capacity(self.submodels.len());
+        for (key, submodel) in self.submodel_keys.drain(0..).zip(self.submodels.drain(0..)) {
+            if keys.contains(&key) {
+                submodels.push(submodel)
+            } else {
+                log! { @verb "ignoring split {} of `{}`: it changed", key, self.file }
+                self.handled.remove(&key);
+            }
+        }
+        self.submodels = submodels
+    }
+
+    /// Hash identifying the split on a clause.
+    fn key(&self, instance: &Instance, clause: ClsIdx) -> u64 {
+        use std::hash::Hasher;
+
+        let hash = clause_hash(instance, clause);
+        if let Some(context) = self.context {
+            let mut hasher = Fnv::new();
+            hasher.write_u64(hash);
+            hasher.write_u64(context);
+            hasher.finish()
+        } else {
+            hash
+        }
+    }
+
+    /// Parses a candidate `<predicate> <term>`.
+    fn parse_cand(s: &str, instance: &Instance) -> Res<(PrdIdx, TTerms)> {
+        let name_end = if s.starts_with('|') {
+            s[1..].find('|').map(|idx| idx + 2)
+        } else {
+            s.find(char::is_whitespace)
+        };
+        let name_end = if let Some(idx) = name_end {
+            idx
+        } else {
+            bail!("illegal candidate `{}`", s)
+        };
+        let (name, term) = s.split_at(name_end);
+
+        let pred = if let Some(pred) = instance.preds().iter().find(|pred| pred.name == name) {
+            pred
+        } else {
+            bail!("unknown predicate `{}`", name)
+        };
+
+        let mut var_infos = VarInfos::new();
+        for (var, typ) in pred.original_sig().index_iter() {
+            var_infos.push(crate::info::VarInfo::new(
+                var.default_str(),
+                typ.clone(),
+                var,
+            ))
+        }
+
+        if let Some(term) = crate::parse::term_opt(term.trim(), &var_infos, instance)
+            .chain_err(|| format!("while parsing candidate for `{}`", name))?
+        {
+            Ok((pred.idx, TTerms::of_term(None, term)))
+        } else {
+            bail!("illegal term for `{}`: `{}`", name, term.trim())
+        }
+    }
+
+    /// Records a solved split.
+    ///
+    /// Does nothing if the sub-model is not quantifier-free, in which case the split will be
+    /// solved again when resuming.
+    fn record(&mut self, instance: &Instance, clause: ClsIdx, submodel: &Model) -> Res<()> {
+        let hash = self.key(instance, clause);
+        let mut s = format!("(split {})\n", hash);
+        for (pred, tterms) in submodel {
+            if instance[*pred].is_defined() {
+                continue;
+            }
+            if let Some(term) = tterms.to_term() {
+                let term = format!("{}", term).replace('\n', " ");
+                s += &format!("(cand {} {})\n", instance[*pred], term)
+            } else {
+                log! { @verb
+                    "not checkpointing split on clause #{}: sub-model is not quantifier-free",
+                    clause
+                }
+                return Ok(());
+            }
+        }
+
+        let mut out = ::std::fs::OpenOptions::new()
+            .append(true)
+            .open(&self.file)
+            .chain_err(|| format!("while opening split checkpoint `{}`", self.file))?;
+        write!(out, "{}", s)?;
+        self.handled.insert(hash);
+        Ok(())
+    }
+}
+
+/// Estimates the time remaining in split mode.
+///
+/// Keeps the mean of the durations of the completed splits. The duration of a split more than
+/// five times longer than the mean is ignored; two such splits in a row reset the mean.
+struct SplitEta {
+    /// Start of the current split.
+    start: Option<Instant>,
+    /// Number of splits in the mean.
+    count: usize,
+    /// Mean split duration, in seconds.
+    mean: f64,
+    /// Number of outliers seen in a row.
+    outliers: usize,
+}
+impl SplitEta {
+    /// Constructor.
+    fn new() -> Self {
+        SplitEta {
+            start: None,
+            count: 0,
+            mean: 0.,
+            outliers: 0,
+        }
+    }
+
+    /// Registers the start of a split, *i.e.* the end of the previous one if any.
+    #[cfg(not(feature = "bench"))]
+    fn split_start(&mut self) {
+        let now = Instant::now();
+        if let Some(start) = ::std::mem::replace(&mut self.start, Some(now)) {
+            let duration = now - start;
+            let secs = duration.as_secs() as f64 + f64::from(duration.subsec_millis()) / 1000.;
+            if self.count > 0 && secs > 5. * self.mean {
+                self.outliers += 1;
+                if self.outliers < 2 {
+                    return;
+                }
+                self.count = 0;
+                self.mean = 0.
+            }
+            self.outliers = 0;
+            self.count += 1;
+            self.mean += (secs - self.mean) / self.count as f64
+        }
+    }
+
+    /// String representation of the progress and ETA.
+    #[cfg(not(feature = "bench"))]
+    fn to_string(&self, handled: usize, total: usize) -> String {
+        let percent = (handled * 100) / ::std::cmp::max(total, 1);
+        if self.count == 0 {
+            format!(", {}% done", percent)
+        } else {
+            let eta = self.mean * (total - handled) as f64;
+            format!(", {}% done, eta {:.1}s", percent, eta)
+        }
+    }
+}
+
+/// Deadline of a split, if `splitting` and `conf.split_timeout` is not zero.
+fn split_deadline(splitting: bool) -> Option<Instant> {
+    match conf.split_timeout {
+        0 => None,
+        _ if !splitting => None,
+        n => Some(Instant::now() + Duration::new(n as u64, 0)),
+    }
+}
+
+/// True if an error is a timeout of a split, *i.e.* not a global timeout.
+fn is_split_timeout(e: &Error) -> bool {
+    conf.split_timeout > 0 && e.is_timeout() && conf.check_timeout().is_ok()
+}
+
+/// True if an error is the failure of the child process solving a split.
+fn is_split_crash(e: &Error) -> bool {
+    match e.kind() {
+        ErrorKind::SplitCrash(_, _) => true,
+        _ => false,
+    }
+}
+
+/// Prints a partial model as a comment, used when some splits timed out.
+fn print_partial_model(instance: &Instance, model: &ConjCandidates, timed_out: usize) {
+    println!(
+        "; {} split(s) timed out, partial model from the other splits:",
+        timed_out
+    );
+    let mut preds: Vec<_> = model.keys().cloned().collect();
+    preds.sort();
+    for pred in preds {
+        for tterms in &model[&pred] {
+            if let Some(term) = tterms.to_term() {
+                println!(";   ({} {})", instance[pred], term)
+            } else {
+                println!(";   ({} <quantified>)", instance[pred])
+            }
+        }
+    }
+}
+
+/// Prints the model of a split as commented `define-fun`s, if `--anytime` is active.
+///
+/// Only the predicates that are not defined in `instance` are printed.
+fn print_anytime(instance: &Instance, clause: Option<ClsIdx>, submodel: &Model) -> Res<()> {
+    if !conf.anytime {
+        return Ok(());
+    }
+    let model: ConjModel = submodel
+        .iter()
+        .filter(|(pred, _)| !instance[*pred].is_defined())
+        .map(|(pred, tterms)| vec![(*pred, vec![tterms.clone()])])
+        .collect();
+    let mut buf: Vec<u8> = vec![];
+    instance.write_definitions(&mut buf, "", &model)?;
+    if let Some(clause) = clause {
+        println!("; partial model of the split on clause #{}:", clause)
+    } else {
+        println!("; partial model:")
+    }
+    for line in String::from_utf8_lossy(&buf).lines() {
+        println!("; {}", line)
+    }
+    ::std::io::stdout().flush()?;
+    Ok(())
+}
+
+/// Solves the sub-instances for some negative clauses in parallel.
+///
+/// Sub-instances are independent: they do not reuse solutions from one another. The partial
+/// models are merged at the end in ascending clause order, with the predicates of each submodel
+/// sorted by index, so that the result does not depend on the order in which workers finish.
+///
+/// An unsat result (or an error) from any worker stops the dispatch of new splits and is
+/// propagated right away.
+fn work_par(
+    real_instance: &Arc<Instance>,
+    clauses: Vec<ClsIdx>,
+    pos: bool,
+    _profiler: &Profiler,
+) -> Res<Option<Either<ConjCandidates, UnsatRes>>> {
+    use std::sync::{mpsc::channel, Mutex};
+
+    let total = clauses.len();
+    let workers = ::std::cmp::min(conf.split_workers, total);
+    log! { @info "solving {} splits with {} worker(s)", total, workers }
+
+    let queue = Arc::new(Mutex::new(clauses));
+    let cancel = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = channel();
+    let mut handles = Vec::with_capacity(workers);
+
+    for index in 0..workers {
+        let (queue, cancel, sender) = (queue.clone(), cancel.clone(), sender.clone());
+        let instance = real_instance.clone();
+        let config = current_conf();
+        let handle = ::std::thread::Builder::new()
+            .name(format!("split worker {}", index))
+            .spawn(move || {
+                with_conf(config, || {
+                    while !cancel.load(Ordering::SeqCst) {
+                        let clause = match queue.lock() {
+                            Ok(mut clauses) => clauses.pop(),
+                            Err(_) => None,
+                        };
+                        let clause = if let Some(clause) = clause {
+                            clause
+                        } else {
+                            break;
+                        };
+                        let profiler = Profiler::new();
+                        let res = with_deadline(split_deadline(true), || {
+                            let sub_instance =
+                                preproc(&instance, clause, pos, &ClsSet::new(), &profiler);
+                            sub_instance.and_then(|res| match res {
+                                Either::Left(sub_instance) => run_on(
+                                    &profiler,
+                                    Arc::new(sub_instance),
+                                    &ConjCandidates::new(),
+                                    Some(&cancel),
+                                ),
+                                Either::Right(MaybeModel::Unsat) => unsat! {
+                                  "by preprocessing"
+                                },
+                                Either::Right(MaybeModel::Model(model)) => {
+                                    Ok(Some(Either::Left(model)))
+                                }
+                            })
+                        });
+                        if sender.send((clause, res)).is_err() {
+                            break;
+                        }
+                    }
+                })
+            })
+            .chain_err(|| format!("while spawning split worker {}", index));
+        match handle {
+            Ok(handle) => handles.push(handle),
+            Err(e) => {
+                stop_workers(&cancel, handles);
+                return Err(e);
+            }
+        }
+    }
+    drop(sender);
+
+    let res = collect_par(real_instance, receiver, total, pos, _profiler);
+    // Cancels the splits still running, and waits for the workers to release the instance.
+    stop_workers(&cancel, handles);
+    res
+}
+
+/// Stops the workers of [`work_par`] and waits for them to finish.
+///
+/// [`work_par`]: fn.work_par.html (work_par function)
+fn stop_workers(cancel: &AtomicBool, handles: Vec<::std::thread::JoinHandle<()>>) {
+    cancel.store(true, Ordering::SeqCst);
+    for handle in handles {
+        if handle.join().is_err() {
+            warn!("a split worker panicked")
+        }
+    }
+}
+
+/// Collects the results of the workers of [`work_par`], returns as soon as a split is unsat.
+///
+/// [`work_par`]: fn.work_par.html (work_par function)
+fn collect_par(
+    real_instance: &Arc<Instance>,
+    receiver: ::std::sync::mpsc::Receiver<(ClsIdx, Res<Option<Either<Model, UnsatRes>>>)>,
+    total: usize,
+    pos: bool,
+    _profiler: &Profiler,
+) -> Res<Option<Either<ConjCandidates, UnsatRes>>> {
+    let mut submodels = Vec::with_capacity(total);
+    let mut timed_out = 0;
+
+    for (_clause, res) in receiver {
+        profile! { |_profiler| "sub-system(s)" => add 1 }
+        match res {
+            Err(ref e) if is_split_timeout(e) => {
+                warn!(
+                    "split on clause #{} reached its timeout, ignoring it",
+                    _clause
+                );
+                profile! { |_profiler| "sub-system(s) timeout" => add 1 }
+                timed_out += 1
+            }
+            Err(ref e) if is_split_crash(e) => {
+                warn!("{}, ignoring it", e);
+                profile! { |_profiler| "sub-system(s) crash" => add 1 }
+                timed_out += 1
+            }
+            Ok(Some(Either::Left(submodel))) => {
+                log! { @verb "split on clause #{} is sat", _clause }
+                print_anytime(real_instance, Some(_clause), &submodel)?;
+                submodels.push((_clause, submodel))
+            }
+            Ok(Some(Either::Right(reason))) => return Ok(Some(Either::Right(reason))),
+            Ok(None) => (),
+            Err(e) => return Err(e),
+        }
+    }
+
+    submodels.sort_unstable_by(|(c_1, _), (c_2, _)| c_1.cmp(c_2));
+
+    let mut model = ConjCandidates::new();
+    let mut pos_model = PrdHMap::new();
+    for (_, mut submodel) in submodels {
+        submodel.sort_by(|(p_1, _), (p_2, _)| p_1.cmp(p_2));
+        if pos {
+            add_pos_submodel(real_instance, &mut pos_model, submodel)?
+        } else {
+            add_submodel(real_instance, &mut model, submodel)
+        }
+    }
+    if pos {
+        model = of_pos_model(real_instance, pos_model)
+    }
+
+    if timed_out > 0 {
+        print_partial_model(real_instance, &model, timed_out);
+        Ok(None)
+    } else {
+        Ok(Some(Either::Left(model)))
+    }
+}
+
 /// Runs on a pre-processed instance.
+///
+/// Solving stops with a timeout when `cancel` is set.
 fn run_on(
     _profiler: &Profiler,
     mut instance: Arc<Instance>,
     model: &ConjCandidates,
+    cancel: Option<&Arc<AtomicBool>>,
 ) -> Res<Option<Either<Model, UnsatRes>>> {
     if !conf.infer {
         if conf.split_step {
@@ -101,9 +722,19 @@ fn run_on(
         log_info! { "Starting learning..." }
     }
 
+    if conf.split_subprocess {
+        if let Some(clause) = instance.split() {
+            return profile!(
+              |_profiler| wrap {
+                run_subprocess(&instance, clause, cancel)
+              } "solving"
+            );
+        }
+    }
+
     let res = profile!(
       |_profiler| wrap {
-        run_teacher(instance.clone(), & model)
+        run_teacher(instance.clone(), & model, cancel)
       } "solving"
     )?;
 
@@ -122,6 +753,177 @@ fn run_on(
     }
 }
 
+/// Solves a sub-instance in a child hoice process.
+///
+/// The sub-instance is dumped to a temporary file, and solved by running the current executable
+/// on it with the same solver, without pre-processing (it is already pre-processed) nor
+/// splitting. The model the child prints is parsed back, and turned into a model for the original
+/// signature of the predicates like [`run_on`] does.
+///
+/// The child is killed when the split reaches `conf.split_timeout` or when `cancel` is set, in
+/// which case this function fails with a timeout. Any other failure of the child (crash, error, `unknown` result, output
+/// hoice cannot parse) yields an [`ErrorKind::SplitCrash`]. Note that the previous splits' model
+/// is not passed to the child.
+///
+/// [`run_on`]: fn.run_on.html (run_on function)
+/// [`ErrorKind::SplitCrash`]: ../errors/enum.ErrorKind.html#variant.SplitCrash
+/// (SplitCrash variant of ErrorKind)
+fn run_subprocess(
+    instance: &Arc<Instance>,
+    clause: ClsIdx,
+    cancel: Option<&Arc<AtomicBool>>,
+) -> Res<Option<Either<Model, UnsatRes>>> {
+    let mut path = ::std::env::temp_dir();
+    path.push(format!(
+        "hoice_{}_split_{}.smt2",
+        ::std::process::id(),
+        clause
+    ));
+    let mut out_path = path.clone();
+    out_path.set_extension("out");
+
+    let res = subprocess_output(instance, clause, &path, &out_path);
+    let _ = ::std::fs::remove_file(&path);
+    let _ = ::std::fs::remove_file(&out_path);
+    let output = res?;
+
+    let crash = |msg: String| -> Error { ErrorKind::SplitCrash(clause, msg).into() };
+
+    let mut lines = output
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with(';'));
+    match lines.next() {
+        Some("sat") => (),
+        Some("unsat") => {
+            log_info! { "unsat\n\n" }
+            return Ok(Some(Either::Right(UnsatRes::None)));
+        }
+        Some(line) => return Err(crash(format!("unexpected result `{}`", line))),
+        None => return Err(crash("no result".into())),
+    }
+
+    let defs = crate::parse::current_sig_model(&output, instance)
+        .map_err(|e| crash(format!("could not parse model: {}", e)))?;
+
+    let mut candidates: Candidates = PrdMap::with_capacity(instance.preds().len());
+    for pred in instance.preds() {
+        let def = defs.get(&pred.idx).map(|terms| term::and(terms.clone()));
+        if def.is_none() && !pred.is_defined() {
+            return Err(crash(format!("no definition for {}", pred.name)));
+        }
+        candidates.push(def)
+    }
+
+    log_info! { "sat\n\n" }
+    let mut this_model = instance.model_of(candidates)?;
+    let mut instance = instance.clone();
+    if let Some(instance) = Arc::get_mut(&mut instance) {
+        instance.simplify_pred_defs(&mut this_model)?
+    }
+
+    Ok(Some(Either::Left(this_model)))
+}
+
+/// Runs a child hoice process on a sub-instance, and returns its output.
+///
+/// Helper for [`run_subprocess`], which removes the files this function creates.
+///
+/// [`run_subprocess`]: fn.run_subprocess.html (run_subprocess function)
+fn subprocess_output(
+    instance: &Instance,
+    clause: ClsIdx,
+    path: &::std::path::Path,
+    out_path: &::std::path::Path,
+) -> Res<String> {
+    use std::process::{Command, Stdio};
+
+    let crash = |msg: String| -> Error { ErrorKind::SplitCrash(clause, msg).into() };
+
+    {
+        let mut file = ::std::fs::File::create(path)
+            .chain_err(|| format!("while creating split file `{}`", path.display()))?;
+        instance.dump_as_smt2(&mut file, format!("Split on clause #{}.", clause))?;
+        writeln!(file, "(get-model)")?
+    }
+    let out_file = ::std::fs::File::create(out_path)
+        .chain_err(|| format!("while creating split output file `{}`", out_path.display()))?;
+
+    let exe = ::std::env::current_exe().chain_err(|| "while retrieving hoice's executable")?;
+    let solver = &conf.solver;
+    let mut args: Vec<String> = vec![
+        "--split".into(),
+        "off".into(),
+        "--preproc".into(),
+        "off".into(),
+        "--color".into(),
+        "off".into(),
+        "--solver".into(),
+        solver.backend_kind().to_string(),
+    ];
+    let cmd = solver.conf().get_cmd().to_string();
+    match solver.backend_kind() {
+        crate::common::backend::BackendKind::Z3 => args.extend(vec!["--z3".into(), cmd]),
+        _ => args.extend(vec!["--cvc_cmd".into(), cmd]),
+    }
+    if !solver.opts.is_empty() {
+        args.push("--solver_opts".into());
+        args.push(solver.opts.join(" "))
+    }
+
+    let mut child = Command::new(&exe)
+        .args(&args)
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::from(out_file))
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| crash(format!("could not spawn `{}`: {}", exe.display(), e)))?;
+
+    let status = loop {
+        if let Some(status) = child
+            .try_wait()
+            .chain_err(|| "while waiting for split process")?
+        {
+            break status;
+        }
+        let cancelled = cancel
+            .map(|cancel| cancel.load(Ordering::SeqCst))
+            .unwrap_or(false);
+        // Covers the deadline of the split, if any.
+        if cancelled || conf.check_timeout().is_err() {
+            let _ = child.kill();
+            let _ = child.wait();
+            bail!(ErrorKind::Timeout)
+        }
+        ::std::thread::sleep(Duration::from_millis(10))
+    };
+
+    let mut output = String::new();
+    ::std::fs::File::open(out_path)
+        .and_then(|mut f| f.read_to_string(&mut output))
+        .chain_err(|| format!("while reading split output file `{}`", out_path.display()))?;
+
+    if !status.success() {
+        let msg = match status.code() {
+            Some(code) => format!("exit code {}", code),
+            None => "killed by a signal".into(),
+        };
+        let error = output
+            .lines()
+            .skip_while(|line| !line.starts_with("(error"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        if error.is_empty() {
+            return Err(crash(msg));
+        } else {
+            return Err(crash(format!("{}, {}", msg, error)));
+        }
+    }
+
+    Ok(output)
+}
+
 /// Adds a model for a subinstance to a partial model.
 fn add_submodel(instance: &Arc<Instance>, model: &mut ConjCandidates, submodel: Model) {
     for (pred, tterms) in submodel {
@@ -143,10 +945,153 @@ fn add_submodel(instance: &Arc<Instance>, model: &mut ConjCandidates, submodel:
     }
 }
 
+/// Adds a model for a subinstance to a partial disjunctive model.
+///
+/// Used when splitting on positive clauses. Fails if the submodel is not quantifier-free.
+fn add_pos_submodel(
+    instance: &Arc<Instance>,
+    model: &mut PrdHMap<Vec<Term>>,
+    submodel: Model,
+) -> Res<()> {
+    for (pred, tterms) in submodel {
+        if !instance[pred].is_defined() {
+            if let Some(term) = tterms.to_term() {
+                let disj = model.entry(pred).or_insert_with(Vec::new);
+                if !disj.contains(&term) {
+                    disj.push(term)
+                }
+            } else {
+                bail!(
+                    "cannot merge non quantifier-free sub-model for {} in positive split mode",
+                    conf.emph(&instance[pred].name)
+                )
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Turns a disjunctive model into conjunctive candidates.
+///
+/// Predicates with no disjunct are false.
+fn of_pos_model(instance: &Arc<Instance>, mut model: PrdHMap<Vec<Term>>) -> ConjCandidates {
+    let mut res = ConjCandidates::new();
+    for pred in instance.preds() {
+        if !pred.is_defined() {
+            let disj = model.remove(&pred.idx).unwrap_or_else(Vec::new);
+            res.insert(pred.idx, vec![TTerms::of_term(None, term::or(disj))]);
+        }
+    }
+    res
+}
+
+/// FNV-1a hasher.
+///
+/// Unlike `DefaultHasher`, the hashes it produces do not depend on the version of Rust or on the
+/// platform, which matters since checkpoint and cache files store them.
+struct Fnv(u64);
+impl Fnv {
+    /// FNV offset basis.
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    /// FNV prime.
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    /// Constructor.
+    fn new() -> Self {
+        Fnv(Self::OFFSET)
+    }
+}
+impl ::std::hash::Hasher for Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(Self::PRIME)
+        }
+    }
+    fn write_u64(&mut self, n: u64) {
+        // Independent of the endianness of the platform.
+        self.write(&n.to_le_bytes())
+    }
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Hash of the content of a clause.
+///
+/// Only depends on the terms of the clause and its predicate applications, where predicates are
+/// identified by their name. In particular, it does not depend on the clause's index or the
+/// predicates' indices.
+fn clause_hash(instance: &Instance, clause: ClsIdx) -> u64 {
+    use std::hash::Hasher;
+
+    let clause = &instance[clause];
+    let mut content: Vec<String> = clause
+        .lhs_terms()
+        .iter()
+        .map(|term| term.to_string())
+        .collect();
+    for (pred, argss) in clause.lhs_preds() {
+        for args in argss {
+            content.push(format!("({} {})", instance[*pred], args))
+        }
+    }
+    content.sort();
+    if let Some((pred, args)) = clause.rhs() {
+        content.push(format!("=> ({} {})", instance[pred], args))
+    }
+
+    let mut hasher = Fnv::new();
+    for elem in content {
+        hasher.write(elem.as_bytes());
+        hasher.write_u8(0)
+    }
+    hasher.finish()
+}
+
+/// Hash of the clauses shared by all the splits.
+///
+/// These are the non-negative clauses when splitting on negative clauses, and the non-positive
+/// ones when splitting on positive clauses (`pos`). Does not depend on the order of the clauses,
+/// see [`clause_hash`].
+///
+/// [`clause_hash`]: fn.clause_hash.html (clause_hash function)
+fn context_hash(instance: &Instance, pos: bool) -> u64 {
+    use std::hash::Hasher;
+
+    let mut hashes: Vec<u64> = instance
+        .clauses()
+        .index_iter()
+        .filter(|(idx, _)| {
+            if pos {
+                !instance.pos_clauses().contains(idx)
+            } else {
+                !instance.neg_clauses().contains(idx)
+            }
+        })
+        .map(|(idx, _)| clause_hash(instance, idx))
+        .collect();
+    hashes.sort_unstable();
+
+    let mut hasher = Fnv::new();
+    for hash in hashes {
+        hasher.write_u64(hash)
+    }
+    hasher.finish()
+}
+
 /// Runs the teacher on an instance.
-fn run_teacher(instance: Arc<Instance>, model: &ConjCandidates) -> Res<TeachRes> {
+///
+/// The teacher stops with a timeout when the deadline of the split is reached (see
+/// `conf.split_timeout`), or when `cancel` is set.
+fn run_teacher(
+    instance: Arc<Instance>,
+    model: &ConjCandidates,
+    cancel: Option<&Arc<AtomicBool>>,
+) -> Res<TeachRes> {
     let teacher_profiler = Profiler::new();
-    let solve_res = crate::teacher::start_class(instance, model, &teacher_profiler);
+    let solve_res =
+        crate::teacher::start_class(instance, model, &teacher_profiler, cancel.cloned());
     print_stats("teacher", teacher_profiler);
     solve_res
 }
@@ -163,7 +1108,13 @@ struct Splitter {
     /// will return `self.instance` if `! once` and `None` otherwise.
     clauses: Either<Vec<ClsIdx>, bool>,
     /// Negative clauses for which we already have a solution.
+    ///
+    /// Only populated if `conf.split_reuse`.
     prev_clauses: ClsSet,
+    /// True if splitting on positive clauses.
+    pos: bool,
+    /// Clause of the last instance generated.
+    current: Option<ClsIdx>,
     /// Total number of clauses considered.
     _clause_count: usize,
     /// Profiler.
@@ -173,42 +1124,62 @@ struct Splitter {
 impl Splitter {
     /// Constructor.
     pub fn new(instance: Arc<Instance>) -> Self {
-        let (clauses, _clause_count) = if conf.split && instance.neg_clauses().len() > 1 {
+        let pos = conf.split_pos
+            && instance.pos_clauses().len() > 1
+            && instance
+                .clauses()
+                .iter()
+                .all(|clause| clause.lhs_pred_apps_len() <= 1);
+        if conf.split_pos && !pos {
+            log! { @verb "not splitting on positive clauses: instance is not linear" }
+        }
+
+        let (clauses, _clause_count) = if pos {
+            // Popping, so the smallest clause indices go last.
+            let mut clauses: Vec<_> = instance.pos_clauses().iter().cloned().collect();
+            clauses.sort_unstable_by(|c_1, c_2| c_2.cmp(c_1));
+            let len = clauses.len();
+            (Either::Left(clauses), len)
+        } else if conf.split && instance.neg_clauses().len() > 1 {
             // We want the predicates that appear in the most lhs last (since
             // we're popping).
             let mut clauses: Vec<_> = instance
                 .neg_clauses()
                 .iter()
                 .map(|c| {
-                    (
-                        *c,
-                        if conf.preproc.split_sort {
-                            instance[*c]
-                                .lhs_preds()
-                                .iter()
-                                .fold(0, |mut sum, (pred, _)| {
-                                    for clause in instance.clauses_of(*pred).0 {
-                                        if instance[*clause].rhs().is_some() {
-                                            sum += 1
-                                        }
+                    let count = if conf.preproc.split_sort {
+                        instance[*c]
+                            .lhs_preds()
+                            .iter()
+                            .fold(0, |mut sum, (pred, _)| {
+                                for clause in instance.clauses_of(*pred).0 {
+                                    if instance[*clause].rhs().is_some() {
+                                        sum += 1
                                     }
+                                }
 
-                                    for clause in instance.clauses_of(*pred).1 {
-                                        if instance[*clause].lhs_preds().is_empty() {
-                                            // Positive clauses are bad.
-                                            sum = 0;
-                                            break;
-                                        } else {
-                                            // sum -= ::std::cmp::min(sum, 1)
-                                        }
+                                for clause in instance.clauses_of(*pred).1 {
+                                    if instance[*clause].lhs_preds().is_empty() {
+                                        // Positive clauses are bad.
+                                        sum = 0;
+                                        break;
+                                    } else {
+                                        // sum -= ::std::cmp::min(sum, 1)
                                     }
+                                }
 
-                                    sum
-                                })
-                        } else {
-                            -(**c as isize)
-                        },
-                    )
+                                sum
+                            })
+                    } else {
+                        0
+                    };
+                    let hash = if conf.preproc.split_hash_sort {
+                        clause_hash(&instance, *c)
+                    } else {
+                        0
+                    };
+                    // Clause index last, to have a total order.
+                    (*c, (count, hash, -(**c as isize)))
                 })
                 .collect();
 
@@ -267,10 +1238,70 @@ impl Splitter {
             clauses,
             _clause_count,
             prev_clauses: ClsSet::new(),
+            pos,
+            current: None,
             _profiler: None,
         }
     }
 
+    /// True if splitting on positive clauses.
+    pub fn is_pos(&self) -> bool {
+        self.pos
+    }
+
+    /// Clause of the last instance generated, if splitting is active.
+    pub fn current(&self) -> Option<ClsIdx> {
+        self.current
+    }
+
+    /// Hashes of the splits left, see [`Checkpoint::key`].
+    ///
+    /// [`Checkpoint::key`]: struct.Checkpoint.html#method.key (key method)
+    fn keys(&self, checkpoint: &Checkpoint) -> HashSet<u64> {
+        if let Either::Left(ref clauses) = self.clauses {
+            clauses
+                .iter()
+                .map(|clause| checkpoint.key(&self.instance, *clause))
+                .collect()
+        } else {
+            HashSet::new()
+        }
+    }
+
+    /// Skips the clauses the checkpoint has already handled.
+    ///
+    /// Skipped clauses are considered solved.
+    fn skip(&mut self, checkpoint: &Checkpoint) {
+        if let Either::Left(ref mut clauses) = self.clauses {
+            let instance = &self.instance;
+            let prev_clauses = &mut self.prev_clauses;
+            clauses.retain(|clause| {
+                let key = checkpoint.key(instance, *clause);
+                if checkpoint.handled.contains(&key) {
+                    if conf.split_reuse {
+                        prev_clauses.insert(*clause);
+                    }
+                    false
+                } else {
+                    true
+                }
+            })
+        }
+    }
+
+    /// Takes the clauses to split on, if splitting is active.
+    ///
+    /// After this, the splitter has nothing left to do.
+    pub fn take_clauses(&mut self) -> Option<Vec<ClsIdx>> {
+        match ::std::mem::replace(&mut self.clauses, Either::Right(true)) {
+            Either::Left(clauses) => Some(clauses),
+            clauses => {
+                self.clauses = clauses;
+                None
+            }
+        }
+    }
+
     /// Retrieves the profiler.
     pub fn profiler(&mut self) -> Option<Profiler> {
         let mut res = None;
@@ -304,15 +1335,29 @@ impl Splitter {
         match self.clauses {
             Either::Left(ref mut clauses) => {
                 if let Some(clause) = clauses.pop() {
+                    self.current = Some(clause);
+                    if_not_bench! {
+                        for prev in &self.prev_clauses {
+                            if self.instance.strict_neg_clauses().contains(prev) {
+                                log! { @verb
+                                    "reusing solution of clause #{}: not used for strengthening",
+                                    prev
+                                }
+                                profile! { |_prof| "split reuse: clauses pruned" => add 1 }
+                            }
+                        }
+                    }
                     let profiler = Profiler::new();
                     let preproc_res = profile! (
                       |_prof| wrap {
                         preproc(
-                          self.instance.as_ref(), clause, & self.prev_clauses, & profiler
+                          self.instance.as_ref(), clause, self.pos, & self.prev_clauses, & profiler
                         )
                       } "sub-preproc"
                     )?;
-                    self.prev_clauses.insert(clause);
+                    if conf.split_reuse {
+                        self.prev_clauses.insert(clause);
+                    }
                     self._profiler = Some(profiler);
                     Ok(Some(preproc_res.map_left(Arc::new)))
                 } else {
@@ -337,18 +1382,23 @@ impl Splitter {
 /// negative otherwise) clauses but `clause`. Preprocesses it and returns the
 /// result.
 ///
-/// Fails in debug if the clause is not negative.
+/// Fails in debug if the clause is not positive (if `pos`, negative otherwise).
 fn preproc(
     instance: &Instance,
     clause: ClsIdx,
+    pos: bool,
     prev_clauses: &ClsSet,
     profiler: &Profiler,
 ) -> Res<Either<Instance, MaybeModel<Model>>> {
     debug_assert! {
-      instance[clause].rhs().is_none()
+      if pos {
+        instance[clause].is_positive()
+      } else {
+        instance[clause].rhs().is_none()
+      }
     }
 
-    let instance = crate::preproc::work_on_split(instance, clause, prev_clauses, profiler)?;
+    let instance = crate::preproc::work_on_split(instance, clause, pos, prev_clauses, profiler)?;
 
     if let Some(maybe_model) = instance.is_trivial_model()? {
         Ok(Either::Right(maybe_model))
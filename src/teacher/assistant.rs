@@ -312,7 +312,7 @@ impl Assistant {
                 }};
             }
 
-            if let Some(&Sample { pred, ref args }) = data.constraints[cstr].rhs() {
+            if let Some(&Sample { pred, ref args, .. }) = data.constraints[cstr].rhs() {
                 profile! { self tick "try force" }
                 match self.try_force(data, pred, args)? {
                     ForceRes::None => (),
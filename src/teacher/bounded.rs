@@ -0,0 +1,175 @@
+//! Bounded model-finding for the predicates over small finite domains, see `--bounded_fallback`.
+
+use crate::{common::*, data::Data};
+
+/// Values of a type, if it is finite.
+///
+/// Finite types are booleans, and the datatypes the constructors of which have no arguments.
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::{ common::*, teacher::bounded::domain_of };
+/// assert_eq! { domain_of(&typ::bool()), Some(vec![val::bool(false), val::bool(true)]) }
+/// assert_eq! { domain_of(&typ::int()), None }
+/// ```
+pub fn domain_of(typ: &Typ) -> Option<Vec<Val>> {
+    if typ.is_bool() {
+        return Some(vec![val::bool(false), val::bool(true)]);
+    }
+    let (dtyp, _) = typ.dtyp_inspect()?;
+    let mut vals = Vec::with_capacity(dtyp.news.len());
+    for (name, args) in &dtyp.news {
+        if !args.is_empty() {
+            return None;
+        }
+        vals.push(val::dtyp_new(typ.clone(), name.clone(), vec![]))
+    }
+    Some(vals)
+}
+
+/// A finite predicate: the tuples of its domain, and the value the data forces for each of them.
+type FinPred = (PrdIdx, Vec<Vec<Val>>, Vec<Option<bool>>);
+
+/// Enumerates the truth tables of the predicates over small finite domains.
+///
+/// The values the learning data forces are not enumerated: a tuple of values that is a positive
+/// (negative) sample is always true (false).
+pub struct Tables {
+    /// The finite predicates.
+    preds: Vec<FinPred>,
+    /// True if all the predicates that are not defined are finite.
+    complete: bool,
+    /// Number of tuples the data does not force, the enumeration is over `2^free` tables.
+    free: usize,
+    /// Index of the next table, `None` if the enumeration is over.
+    next: Option<usize>,
+}
+
+impl Tables {
+    /// Constructor.
+    ///
+    /// `None` if no predicate is finite, if the data forces a tuple to be both true and false, or
+    /// if there are more than `max` tables to enumerate.
+    pub fn new(instance: &Instance, data: &Data, max: usize) -> Option<Self> {
+        let mut preds = vec![];
+        let mut complete = true;
+        let mut free = 0;
+
+        'preds: for pred in instance.preds() {
+            if pred.is_defined() {
+                continue;
+            }
+            let mut tuples: Vec<Vec<Val>> = vec![vec![]];
+            for typ in pred.sig.iter() {
+                let domain = if let Some(domain) = domain_of(typ) {
+                    domain
+                } else {
+                    complete = false;
+                    continue 'preds;
+                };
+                if tuples.len() * domain.len() > max {
+                    return None;
+                }
+                tuples = tuples
+                    .into_iter()
+                    .flat_map(|tuple| {
+                        domain.iter().map(move |val| {
+                            let mut tuple = tuple.clone();
+                            tuple.push(val.clone());
+                            tuple
+                        })
+                    })
+                    .collect()
+            }
+
+            let mut forced = vec![None; tuples.len()];
+            for (samples, pos) in &[(&data.pos[pred.idx], true), (&data.neg[pred.idx], false)] {
+                for sample in samples.iter() {
+                    let index = tuples
+                        .iter()
+                        .position(|tuple| tuple.iter().zip(sample.iter()).all(|(t, s)| t == s));
+                    if let Some(index) = index {
+                        if forced[index] == Some(!pos) {
+                            return None;
+                        }
+                        forced[index] = Some(*pos)
+                    }
+                }
+            }
+
+            free += forced.iter().filter(|value| value.is_none()).count();
+            if free >= 64 || (1u64 << free) > max as u64 {
+                return None;
+            }
+            preds.push((pred.idx, tuples, forced))
+        }
+
+        if preds.is_empty() {
+            None
+        } else {
+            Some(Tables {
+                preds,
+                complete,
+                free,
+                next: Some(0),
+            })
+        }
+    }
+
+    /// True if all the predicates that are not defined are finite.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Number of tables to enumerate.
+    pub fn count(&self) -> usize {
+        1 << self.free
+    }
+
+    /// Next candidates: the candidates of the finite predicates are replaced by the next table.
+    pub fn next(&mut self, instance: &Instance, candidates: &Candidates) -> Option<Candidates> {
+        let mut table = self.next?;
+        self.next = if table + 1 < self.count() {
+            Some(table + 1)
+        } else {
+            None
+        };
+
+        let mut candidates = candidates.clone();
+        for (pred, tuples, forced) in &self.preds {
+            let sig = &instance[*pred].sig;
+            let mut disj = vec![];
+            for (tuple, forced) in tuples.iter().zip(forced.iter()) {
+                let value = if let Some(value) = forced {
+                    *value
+                } else {
+                    let value = table & 1 == 1;
+                    table >>= 1;
+                    value
+                };
+                if value {
+                    let conj: Vec<Term> = tuple
+                        .iter()
+                        .zip(sig.index_iter())
+                        .map(|(val, (var, typ))| {
+                            term::eq(term::var(var, typ.clone()), term::val(val.clone()))
+                        })
+                        .collect();
+                    disj.push(if conj.is_empty() {
+                        term::tru()
+                    } else {
+                        term::and(conj)
+                    })
+                }
+            }
+            candidates[*pred] = Some(if disj.is_empty() {
+                term::fls()
+            } else {
+                term::or(disj)
+            })
+        }
+
+        Some(candidates)
+    }
+}
@@ -3,7 +3,10 @@
 //! [teach]: fn.teach.html
 //! (Teacher's teach function)
 
-use std::time::Duration;
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
 
 use crate::{
     common::{
@@ -16,25 +19,50 @@ use crate::{
 };
 
 pub mod assistant;
+pub mod bounded;
 mod cex_bias;
 use self::assistant::Assistant;
 
 pub use self::cex_bias::CexBias;
 
+/// Maximum number of additional check-sats when selecting a counterexample.
+///
+/// See `--cex_strategy`.
+const MAX_CEX_SELECTION_STEPS: usize = 5;
+
+lazy_static! {
+    /// Model corresponding to the last candidates of the learners, for `--best_effort`.
+    static ref best_effort: ::std::sync::Mutex<Option<Model>> = ::std::sync::Mutex::new(None);
+}
+
+/// Model corresponding to the last candidates the learners produced, if any.
+///
+/// Only available with `--best_effort`. This model is **not** a model of the instance, it is
+/// whatever the learners came up with last.
+pub fn best_effort_model() -> Option<Model> {
+    best_effort.lock().ok().and_then(|mut model| model.take())
+}
+
 /// Starts the teaching process.
 ///
 /// The partial model stores conjunction of top terms for some of the top
 /// terms, and is expressed in terms of the predicates' original signatures.
+///
+/// The teacher and its learners stop with a timeout error when the local deadline of the current
+/// thread is reached (see [`with_deadline`]), or when `cancel`, if any, is set.
+///
+/// [`with_deadline`]: ../common/fn.with_deadline.html (with_deadline function)
 pub fn start_class(
     instance: Arc<Instance>,
     partial_model: &ConjCandidates,
     profiler: &Profiler,
+    cancel: Option<Arc<AtomicBool>>,
 ) -> Res<TeachRes> {
     log! { @debug
         "starting the learning process" ;
         "  launching solver kid..."
     }
-    let mut teacher = Teacher::new(instance, profiler, partial_model)?;
+    let mut teacher = Teacher::new(instance, profiler, partial_model, cancel)?;
 
     let res = match teach(&mut teacher) {
         Ok(res) => Ok(res),
@@ -51,7 +79,7 @@ pub fn start_class(
             }
 
             _ => {
-                if let Err(tmo) = conf.check_timeout() {
+                if let Err(tmo) = teacher.check_timeout() {
                     Err(tmo)
                 } else {
                     Err(e)
@@ -66,12 +94,24 @@ pub fn start_class(
 
 /// Teaching to the learners.
 pub fn teach(teacher: &mut Teacher) -> Res<TeachRes> {
+    if conf.teacher.learn_only.is_some() && conf.teacher.replay.is_some() {
+        bail!("options `--learn_only` and `--replay` are incompatible")
+    }
+    if let Some(file) = conf.teacher.learn_only.as_ref() {
+        teacher.learn_only(file)?;
+        unknown!("learning data written to `{}` (--learn_only)", file)
+    }
+
     log_debug! { "spawning ice learner(s)..." }
     if conf.ice.pure_synth {
         teacher.add_learner(crate::learning::ice::Launcher, false)?;
     }
     teacher.add_learner(crate::learning::ice::Launcher, true)?;
 
+    if let Some(file) = conf.teacher.replay.as_ref() {
+        return teacher.replay(file);
+    }
+
     if let Some(res) = teacher.init()? {
         return Ok(res);
     }
@@ -82,6 +122,9 @@ pub fn teach(teacher: &mut Teacher) -> Res<TeachRes> {
     let mut learner: Option<LrnIdx> = None;
 
     loop {
+        teacher.check_timeout()?;
+        conf.check_mem("learning")?;
+
         log_verb! {
           "all learning data:\n{}", teacher.data.string_do(
             & (), |s| s.to_string()
@@ -98,8 +141,12 @@ pub fn teach(teacher: &mut Teacher) -> Res<TeachRes> {
                     &teacher._profiler,
                 );
             }
-            let _ = teacher.send(idx)?;
-            ()
+            if teacher.streamed_all {
+                // The learner already has the data, see `--pipeline`.
+                teacher.streamed_all = false
+            } else {
+                let _ = teacher.send(idx)?;
+            }
         } else {
             if conf.teacher.step {
                 pause("to broadcast data... (--step on)", &teacher._profiler);
@@ -129,6 +176,71 @@ pub fn teach(teacher: &mut Teacher) -> Res<TeachRes> {
     }
 }
 
+/// Sets the timeout of the check-sats of a solver to the one from `--clause_timeout`, if any.
+fn set_clause_timeout<P>(solver: &mut Solver<P>) -> Res<()> {
+    if let Some(millis) = conf.teacher.clause_timeout {
+        solver.set_option(
+            conf.solver.backend().check_timeout_opt(),
+            &millis.to_string(),
+        )?
+    }
+    Ok(())
+}
+
+/// Loads the hints from the file given by `--hint` and from the instance, if any.
+///
+/// The hints of the instance come from pre-processing and from the library API, see
+/// [`Instance::add_hints`]. Hints are expressed in terms of the current signature of the
+/// predicates. Hints for predicates that are defined are ignored, as are hints mentioning
+/// arguments removed by pre-processing.
+///
+/// [`Instance::add_hints`]: ../common/struct.Instance.html#method.add_hints
+/// (add_hints method)
+pub fn hints(instance: &Instance) -> Res<PrdHMap<Vec<Term>>> {
+    let mut res = PrdHMap::new();
+
+    let mut hints = if let Some(file) = conf.hint_file() {
+        let mut content = String::new();
+        ::std::fs::File::open(file)
+            .and_then(|mut f| f.read_to_string(&mut content))
+            .chain_err(|| format!("while reading hint file `{}`", file))?;
+        crate::parse::hints(&content, instance)
+            .chain_err(|| format!("while parsing hint file `{}`", file))?
+    } else {
+        PrdHMap::new()
+    };
+    for (pred, terms) in instance.hints() {
+        hints
+            .entry(*pred)
+            .or_insert_with(Vec::new)
+            .extend(terms.iter().cloned())
+    }
+
+    for (pred, terms) in hints {
+        if instance[pred].is_defined() {
+            log! { @verb "ignoring hints for {}, predicate is defined", instance[pred] }
+            continue;
+        }
+        let subst = instance.map_from_original_sig_of(pred);
+        let mut nu_terms = Vec::with_capacity(terms.len());
+        for term in terms {
+            if let Some((nu_term, _)) = term.subst_total(&subst) {
+                nu_terms.push(nu_term)
+            } else {
+                log! { @verb
+                    "ignoring hint `{}` for {}, it mentions removed arguments",
+                    term, instance[pred]
+                }
+            }
+        }
+        if !nu_terms.is_empty() {
+            let _ = res.insert(pred, nu_terms);
+        }
+    }
+
+    Ok(res)
+}
+
 /// The teacher, stores a solver.
 pub struct Teacher<'a> {
     /// The solver.
@@ -149,7 +261,11 @@ pub struct Teacher<'a> {
     /// Stores the channel to the learner, its name (for log), and a flag
     /// indicating whether the data has changed since this learner's last
     /// candidates.
-    pub learners: LrnMap<(Option<Sender<FromTeacher>>, String, bool)>,
+    pub learners: LrnMap<(Option<ToLearner>, String, bool)>,
+    /// Number of data messages sent to each learner.
+    rounds: LrnMap<usize>,
+    /// Qualifiers of the last candidates of each learner, for `--dump_quals`.
+    quals: LrnHMap<QualStats>,
     /// Assistant for implication constraint breaking.
     ///
     /// The boolean flag indicates whether the assistant was sent some stuff.
@@ -181,6 +297,45 @@ pub struct Teacher<'a> {
     using_rec_funs: bool,
     /// Forces to restart the solver after each check.
     restart_on_cex: bool,
+    /// Predicates with the names of their current definitions, when the teacher is incremental.
+    ///
+    /// `None` if the teacher is not incremental, in which case the definitions of the candidates
+    /// are popped after each check.
+    def_preds: Option<Preds>,
+    /// Candidates the predicates are currently defined as, when the teacher is incremental.
+    defs: PrdHMap<Term>,
+    /// Counter used to generate fresh definition names.
+    def_count: usize,
+    /// Clauses that appeared in a clause core, checked first. See `--clause_cores`.
+    relevant: ClsSet,
+    /// Stops the teacher with a timeout when set, used to cancel splits solved in parallel.
+    cancel: Option<Arc<AtomicBool>>,
+    /// Fewest clauses falsified by a candidate so far, for `--teacher_restart`.
+    restart_best: Option<usize>,
+    /// Number of candidates since `restart_best` last decreased.
+    restart_stale: usize,
+    /// Generates the seeds of the restarts.
+    restart_rng: Rng,
+    /// Seed of the restart to send with the next data, if any.
+    restart_seed: Option<u64>,
+    /// Fewest clauses falsified by a candidate so far, for `--bounded_fallback`.
+    bounded_best: Option<usize>,
+    /// Number of candidates since `bounded_best` last decreased.
+    bounded_stale: usize,
+    /// True if the bounded fallback already enumerated all the predicates.
+    bounded_done: bool,
+    /// Learner the counterexamples are streamed to, with `--pipeline`.
+    streaming: Option<LrnIdx>,
+    /// Clauses the counterexamples of which were already streamed to the learner.
+    streamed: ClsSet,
+    /// True if all the counterexamples of the last candidates were streamed.
+    streamed_all: bool,
+    /// Candidates of the predicates of each clause the last time it had no counterexample.
+    valid_memo: ClsHMap<Vec<(PrdIdx, Option<Term>)>>,
+    /// Counterexample yield of the clauses, decayed at each candidate. See `--cex_yield`.
+    cex_scores: ClsHMap<f64>,
+    /// Number of consecutive timeouts of the clauses, see `--clause_timeout`.
+    timeouts: ClsHMap<usize>,
 }
 
 impl<'a> Teacher<'a> {
@@ -189,8 +344,10 @@ impl<'a> Teacher<'a> {
         instance: Arc<Instance>,
         profiler: &'a Profiler,
         partial_model: &'a ConjCandidates,
+        cancel: Option<Arc<AtomicBool>>,
     ) -> Res<Self> {
-        let solver = conf.solver.spawn("teacher", Parser, &instance)?;
+        let mut solver = conf.solver.spawn("teacher", Parser, &instance)?;
+        set_clause_timeout(&mut solver)?;
 
         // let partial_model = PrdHMap::new() ;
         let partial_model = partial_model
@@ -242,6 +399,12 @@ impl<'a> Teacher<'a> {
         let restart_on_cex =
             conf.teacher.restart_on_cex || !dtyp::get_all().is_empty() || using_rec_funs;
 
+        let def_preds = if conf.teacher.incremental && !restart_on_cex {
+            Some(instance.preds().clone())
+        } else {
+            None
+        };
+
         Ok(Teacher {
             solver,
             instance,
@@ -249,6 +412,8 @@ impl<'a> Teacher<'a> {
             from_learners,
             to_teacher: Some(to_teacher),
             learners,
+            rounds: LrnMap::with_capacity(2),
+            quals: LrnHMap::new(),
             assistant,
             _profiler: profiler,
             partial_model,
@@ -259,9 +424,43 @@ impl<'a> Teacher<'a> {
             bias: CexBias::new(),
             using_rec_funs,
             restart_on_cex,
+            def_preds,
+            defs: PrdHMap::new(),
+            def_count: 0,
+            relevant: ClsSet::new(),
+            cancel,
+            restart_best: None,
+            restart_stale: 0,
+            restart_rng: seeded_rng(42, conf.seed ^ conf.teacher.restart_seed as u64),
+            restart_seed: None,
+            bounded_best: None,
+            bounded_stale: 0,
+            bounded_done: false,
+            streaming: None,
+            streamed: ClsSet::new(),
+            streamed_all: false,
+            valid_memo: ClsHMap::new(),
+            cex_scores: ClsHMap::new(),
+            timeouts: ClsHMap::new(),
         })
     }
 
+    /// Checks if we're out of time, taking cancellation into account.
+    pub fn check_timeout(&self) -> Res<()> {
+        conf.check_timeout()?;
+        if let Some(cancel) = self.cancel.as_ref() {
+            if cancel.load(Ordering::SeqCst) {
+                bail!(ErrorKind::Timeout)
+            }
+        }
+        Ok(())
+    }
+
+    /// Time until timeout.
+    pub fn until_timeout(&self) -> Option<Duration> {
+        conf.until_timeout()
+    }
+
     /// Model from some candidates.
     fn model_of_candidates(&self, mut cands: Candidates) -> Candidates {
         for (pred, cand) in cands.index_iter_mut() {
@@ -310,9 +509,184 @@ impl<'a> Teacher<'a> {
         }
         self.run_assistant()?;
 
+        if let Some(res) = self.check_hints()? {
+            return Ok(Some(res));
+        }
+
         Ok(None)
     }
 
+    /// Writes the learning data of the trivial candidates to a file, see `--learn_only`.
+    ///
+    /// Checks the candidates where all predicates are `true`, then the ones where they are all
+    /// `false`, and turns the counterexamples into learning data. The data is written even if it
+    /// is contradictory.
+    pub fn learn_only(&mut self, file: &str) -> Res<()> {
+        self.to_teacher = None;
+
+        let mut res = Ok(());
+        for &value in &[true, false] {
+            let mut cands = PrdMap::with_capacity(self.instance.preds().len());
+            for pred in self.instance.pred_indices() {
+                if self.instance[pred].is_defined() {
+                    cands.push(None)
+                } else {
+                    cands.push(Some(term::bool(value)))
+                }
+            }
+            let cands = self.complete_candidates(cands);
+
+            let cexs = self.get_cexs(&cands)?;
+            log! { @verb
+                "learn-only: {} clause(s) falsified by the `{}` candidates", cexs.len(), value
+            }
+            res = self.instance.cexs_to_data(&mut self.data, cexs).map(|_| ());
+            if res.is_err() {
+                break;
+            }
+        }
+
+        let mut w = ::std::fs::OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .create(true)
+            .open(file)
+            .chain_err(|| format!("while opening learn-only file `{}`", file))?;
+        self.data
+            .write_json_lines(&mut w)
+            .chain_err(|| format!("while writing learning data to `{}`", file))?;
+
+        match res {
+            Err(ref e) if e.is_unsat() => {
+                log! { @verb "learn-only: the learning data is contradictory" }
+                Ok(())
+            }
+            res => res,
+        }
+    }
+
+    /// Gives some recorded learning data to the learners, see `--replay`.
+    ///
+    /// The candidates are not checked: the first ones the learners produce are printed, and the
+    /// result is `unknown`, unless the data is contradictory.
+    pub fn replay(&mut self, file: &str) -> Res<TeachRes> {
+        self.to_teacher = None;
+
+        let mut content = String::new();
+        ::std::fs::File::open(file)
+            .and_then(|mut f| f.read_to_string(&mut content))
+            .chain_err(|| format!("while reading learning data `{}`", file))?;
+        match self.data.read_json_lines(&content) {
+            Err(ref e) if e.is_unsat() => return Ok(TeachRes::Unsat(self.unsat_core()?)),
+            res => res.chain_err(|| format!("while loading learning data `{}`", file))?,
+        }
+        log! { @verb
+            "replay: {} positive, {} negative sample(s), {} constraint(s)",
+            self.data.pos_neg_count().0,
+            self.data.pos_neg_count().1,
+            self.data.constraints.len()
+        }
+
+        if !self.broadcast() {
+            unknown!("all learners are dead")
+        }
+
+        match self.get_candidates(false)? {
+            Either::Right(unsat) => Ok(TeachRes::Unsat(unsat)),
+            Either::Left((_, candidates)) => {
+                let model: ConjModel = self
+                    .instance
+                    .model_of(candidates)?
+                    .into_iter()
+                    .map(|(pred, tterms)| vec![(pred, vec![tterms])])
+                    .collect();
+                let mut buf: Vec<u8> = vec![];
+                self.instance.write_model(&model, &mut buf)?;
+                println!("; candidates learned from `{}`, UNVERIFIED", file);
+                for line in String::from_utf8_lossy(&buf).lines() {
+                    println!("; {}", line)
+                }
+                unknown!("candidates from replayed data are not checked (--replay)")
+            }
+        }
+    }
+
+    /// Checks the hints, if any.
+    ///
+    /// Houdini-style: the candidate for each predicate is the conjunction of its hints, `true` if
+    /// it has none. The hints falsified by the consequent of a counterexample are dropped, until
+    /// the candidates are a model or no hint can be dropped.
+    ///
+    /// All counterexamples found are turned into learning data, so that the learners can fill in
+    /// the rest when the hints are not enough.
+    fn check_hints(&mut self) -> Res<Option<TeachRes>> {
+        let mut hints = hints(&self.instance)?;
+        if hints.is_empty() {
+            return Ok(None);
+        }
+
+        log_debug! { "checking hints..." }
+
+        loop {
+            self.check_timeout()?;
+            profile! { self "hint checks" => add 1 }
+
+            let mut cands = PrdMap::with_capacity(self.instance.preds().len());
+            for pred in self.instance.pred_indices() {
+                if self.instance[pred].is_defined() {
+                    cands.push(None)
+                } else if let Some(terms) = hints.get(&pred) {
+                    cands.push(Some(term::and(terms.clone())))
+                } else {
+                    cands.push(Some(term::tru()))
+                }
+            }
+            let cands = self.complete_candidates(cands);
+
+            let cexs = self.get_cexs(&cands)?;
+            if cexs.is_empty() {
+                log_info! { "hints verify all clauses" }
+                return Ok(Some(TeachRes::Model(self.model_of_candidates(cands))));
+            }
+
+            let mut dropped = 0;
+            for (clause, cexs) in &cexs {
+                let (pred, args) = if let Some(rhs) = self.instance[*clause].rhs() {
+                    rhs
+                } else {
+                    continue;
+                };
+                let terms = if let Some(terms) = hints.get_mut(&pred) {
+                    terms
+                } else {
+                    continue;
+                };
+                for (cex, _) in cexs {
+                    let mut sample = var_to::vals::RVarVals::with_capacity(args.len());
+                    for arg in args.get() {
+                        sample.push(arg.eval(cex)?)
+                    }
+                    let len = terms.len();
+                    terms.retain(|term| {
+                        term.eval(&sample)
+                            .and_then(|val| val.to_bool())
+                            .map(|b| b != Some(false))
+                            .unwrap_or(true)
+                    });
+                    dropped += len - terms.len()
+                }
+            }
+
+            profile! { self "hints dropped" => add dropped }
+            let _ = self.instance.cexs_to_data(&mut self.data, cexs)?;
+
+            if dropped == 0 {
+                log_debug! { "hints are not enough, falling back to learning" }
+                return Ok(None);
+            }
+        }
+    }
+
     /// Runs the assistant (if any) on the current data.
     pub fn run_assistant(&mut self) -> Res<()> {
         if let Some(assistant) = self.assistant.as_mut() {
@@ -377,19 +751,25 @@ impl<'a> Teacher<'a> {
             let name = learner.description(mine);
             let instance = self.instance.clone();
             let data = self.data.to_lrn_data();
-            let (to_learner, learner_recv) = FromTeacher::channel();
+            let (to_learner, learner_recv) = FromTeacher::learner_channel();
+            let (config, deadline) = (current_conf(), current_deadline());
             ::std::thread::Builder::new()
                 .name(name.clone())
                 .spawn(move || {
-                    learner.run(
-                        MsgCore::new_learner(index, to_teacher.clone(), learner_recv),
-                        instance,
-                        data,
-                        mine,
-                    )
+                    with_conf(config, || {
+                        with_deadline(deadline, || {
+                            learner.run(
+                                MsgCore::new_learner(index, to_teacher.clone(), learner_recv),
+                                instance,
+                                data,
+                                mine,
+                            )
+                        })
+                    })
                 })
                 .chain_err(|| format!("while spawning learner `{}`", conf.emph(&name)))?;
             self.learners.push((Some(to_learner), name, false));
+            self.rounds.push(0);
             Ok(())
         } else {
             bail!("trying to add learner after teacher's finalization")
@@ -400,46 +780,161 @@ impl<'a> Teacher<'a> {
     /// learner left.
     ///
     /// Only used for the data from the first check.
-    pub fn broadcast(&self) -> bool {
+    pub fn broadcast(&mut self) -> bool {
         profile! { self tick "sending" }
         let mut one_alive = false;
         log_verb! { "broadcasting..." }
-        for &(ref sender, ref name, _) in self.learners.iter() {
+        for (idx, &(ref sender, ref name, _)) in self.learners.index_iter() {
             if let Some(sender) = sender.as_ref() {
-                if sender
-                    .send(FromTeacher::Data(Box::new(self.data.to_lrn_data())))
-                    .is_err()
-                {
-                    warn!("learner `{}` is dead...", name)
-                } else {
+                if sender.send(self.data_msg()).is_ok() {
+                    self.rounds[idx] += 1;
                     one_alive = true
+                } else {
+                    warn!("learner `{}` is dead...", name)
                 }
             }
         }
         log_verb! { "done broadcasting..." }
+        self.restart_seed = None;
         profile! { self mark "sending" }
         one_alive
     }
 
     /// Sends data to a specific learner.
-    pub fn send(&self, learner: LrnIdx) -> Res<bool> {
+    pub fn send(&mut self, learner: LrnIdx) -> Res<bool> {
         profile! { self tick "sending" }
         let (ref sender, ref name, _) = self.learners[learner];
         let alive = if let Some(sender) = sender.as_ref() {
-            sender
-                .send(FromTeacher::Data(Box::new(self.data.to_lrn_data())))
-                .is_ok()
+            sender.send(self.data_msg()).is_ok()
         } else {
             false
         };
-        if !alive {
+        if alive {
+            self.rounds[learner] += 1
+        } else {
             warn!("learner `{}` is dead...", name);
         }
+        self.restart_seed = None;
         profile! { self mark "sending" }
 
         Ok(alive)
     }
 
+    /// Message carrying the learning data, with a restart request if one is pending.
+    fn data_msg(&self) -> FromTeacher {
+        let data = Box::new(self.data.to_lrn_data());
+        if let Some(seed) = self.restart_seed {
+            FromTeacher::Restart(data, seed)
+        } else {
+            FromTeacher::Data(data)
+        }
+    }
+
+    /// Registers the number of clauses the current candidates falsify, for `--teacher_restart`.
+    ///
+    /// Schedules a restart if this number did not decrease for `conf.teacher.restart` rounds.
+    fn register_progress(&mut self, falsified: usize) {
+        let max_stale = if let Some(max_stale) = conf.teacher.restart {
+            max_stale
+        } else {
+            return;
+        };
+
+        if self
+            .restart_best
+            .map(|best| falsified < best)
+            .unwrap_or(true)
+        {
+            self.restart_best = Some(falsified);
+            self.restart_stale = 0
+        } else {
+            self.restart_stale += 1
+        }
+
+        if self.restart_stale >= max_stale {
+            log! { @verb
+                "no progress for {} round(s), restarting the learners", self.restart_stale
+            }
+            profile! { self "teacher restarts" => add 1 }
+            self.restart_best = None;
+            self.restart_stale = 0;
+            self.restart_seed = Some(self.restart_rng.gen())
+        }
+    }
+
+    /// Bounded model-finding over the predicates with small finite domains, for
+    /// `--bounded_fallback`.
+    ///
+    /// Engages if the number of clauses the candidates falsify did not decrease for
+    /// `conf.teacher.bounded_fallback` rounds. Enumerates the truth tables of the finite
+    /// predicates, keeping the current candidates of the other ones, and returns the first
+    /// candidates that have no counterexample. Only engages if there are at most
+    /// `conf.teacher.bounded_fallback_max` tables, see [`bounded::Tables`].
+    ///
+    /// [`bounded::Tables`]: bounded/struct.Tables.html (Tables struct)
+    fn bounded_fallback(
+        &mut self,
+        candidates: &Candidates,
+        falsified: usize,
+    ) -> Res<Option<Candidates>> {
+        let max_stale = match conf.teacher.bounded_fallback {
+            Some(max_stale) if !self.bounded_done => max_stale,
+            _ => return Ok(None),
+        };
+
+        if self
+            .bounded_best
+            .map(|best| falsified < best)
+            .unwrap_or(true)
+        {
+            self.bounded_best = Some(falsified);
+            self.bounded_stale = 0;
+            return Ok(None);
+        }
+        self.bounded_stale += 1;
+        if self.bounded_stale < max_stale {
+            return Ok(None);
+        }
+        self.bounded_stale = 0;
+
+        let mut tables = if let Some(tables) = bounded::Tables::new(
+            &self.instance,
+            &self.data,
+            conf.teacher.bounded_fallback_max,
+        ) {
+            tables
+        } else {
+            log! { @verb "bounded fallback: no finite predicate with a small enough domain" }
+            return Ok(None);
+        };
+        // Enumerating everything again would yield the same tables.
+        self.bounded_done = tables.is_complete();
+
+        log! { @verb "bounded fallback: enumerating {} table(s)", tables.count() }
+        profile! { self "bounded fallback runs" => add 1 }
+        profile! {
+            self wrap { self.bounded_enumerate(&mut tables, candidates) } "bounded fallback"
+        }
+    }
+
+    /// Enumerates some truth tables, returns the first candidates with no counterexample.
+    fn bounded_enumerate(
+        &mut self,
+        tables: &mut bounded::Tables,
+        candidates: &Candidates,
+    ) -> Res<Option<Candidates>> {
+        let instance = self.instance.clone();
+        while let Some(nu_candidates) = tables.next(&instance, candidates) {
+            self.check_timeout()?;
+            profile! { self "bounded fallback tables" => add 1 }
+            if self.get_cexs(&nu_candidates)?.is_empty() {
+                log! { @verb "bounded fallback: found a model" }
+                return Ok(Some(nu_candidates));
+            }
+        }
+        Ok(None)
+    }
+
     /// Receives a message with a timeout.
     fn receive_msg_tmo(&mut self, drain: bool, timeout: Duration) -> Res<Msg> {
         macro_rules! all_dead {
@@ -456,7 +951,7 @@ impl<'a> Teacher<'a> {
                 Ok(msg) => msg,
                 Err(_) => {
                     profile! { self mark "waiting" }
-                    conf.check_timeout()?;
+                    self.check_timeout()?;
                     all_dead!()
                 }
             }
@@ -494,7 +989,7 @@ impl<'a> Teacher<'a> {
         }
 
         profile! { self tick "waiting" }
-        let Msg { id, msg } = if let Some(timeout) = conf.until_timeout() {
+        let Msg { id, msg } = if let Some(timeout) = self.until_timeout() {
             self.receive_msg_tmo(drain, timeout)?
         } else {
             match profile! {
@@ -547,14 +1042,49 @@ impl<'a> Teacher<'a> {
             );
         }
 
+        if conf.best_effort {
+            let model = self.instance.model_of(candidates.clone())?;
+            if let Ok(mut best) = best_effort.lock() {
+                *best = Some(model)
+            }
+        }
+
+        if conf.teacher.pipeline.is_some() {
+            self.streaming = Some(idx);
+            self.streamed.clear()
+        }
         let cexs = profile! {
           self wrap { self.get_cexs(& candidates) } "cexs"
-        }?;
+        };
+        self.streaming = None;
+        let mut cexs = match cexs {
+            Ok(cexs) => cexs,
+            Err(ref e) if e.is_unsat() && !self.streamed.is_empty() => {
+                return Ok(Some(TeachRes::Unsat(self.unsat_core()?)))
+            }
+            Err(e) => bail!(e),
+        };
 
         if cexs.is_empty() {
+            self.dump_quals(idx)?;
             return Ok(Some(TeachRes::Model(self.model_of_candidates(candidates))));
         }
 
+        self.register_progress(cexs.len());
+
+        if let Some(candidates) = self.bounded_fallback(&candidates, cexs.len())? {
+            return Ok(Some(TeachRes::Model(self.model_of_candidates(candidates))));
+        }
+
+        if !self.streamed.is_empty() {
+            let streamed = &self.streamed;
+            cexs.retain(|clause, _| !streamed.contains(clause));
+            if cexs.is_empty() {
+                self.streamed_all = true;
+                return Ok(None);
+            }
+        }
+
         profile! { self tick "data" }
         profile! { self tick "data", "registration" }
         let res = self.instance.cexs_to_data(&mut self.data, cexs);
@@ -591,11 +1121,115 @@ impl<'a> Teacher<'a> {
         profile! { self tick "data", "propagation" }
         self.data.propagate()?;
         profile! { self mark "data", "propagation" }
+        if let Some(period) = conf.teacher.compact_data {
+            if self.count % period == 0 {
+                profile! { self tick "data", "compaction" }
+                let _removed = self.data.compact()?;
+                log! { @verb "compaction removed {} constraint(s)", _removed }
+                profile! { self mark "data", "compaction" }
+            }
+        }
         profile! { self mark "data" }
 
         Ok(None)
     }
 
+    /// Sends the counterexamples found so far to the learner, with `--pipeline`.
+    ///
+    /// Only sends the counterexamples of clauses that were not streamed already. The learner
+    /// starts working on them while the teacher looks for more counterexamples.
+    fn stream_cexs(&mut self, learner: LrnIdx, map: &Cexs) -> Res<()> {
+        let mut cexs = Cexs::new();
+        for (clause, clause_cexs) in map {
+            if self.streamed.insert(*clause) {
+                let mut clause_cexs = clause_cexs.clone();
+                clause_cexs.dedup();
+                let prev = cexs.insert(*clause, clause_cexs);
+                debug_assert! { prev.is_none() }
+            }
+        }
+        if cexs.is_empty() {
+            return Ok(());
+        }
+
+        profile! { self tick "data" }
+        profile! { self tick "data", "registration" }
+        let new_data = self.instance.cexs_to_data(&mut self.data, cexs);
+        profile! { self mark "data", "registration" }
+        profile! { self mark "data" }
+
+        if new_data? {
+            for (index, &mut (_, _, ref mut changed)) in self.learners.index_iter_mut() {
+                *changed = *changed || index != learner
+            }
+            profile! { self tick "data" }
+            profile! { self tick "data", "propagation" }
+            self.data.propagate()?;
+            profile! { self mark "data", "propagation" }
+            profile! { self mark "data" }
+            profile! { self "streamed data" => add 1 }
+            let _ = self.send(learner)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the qualifiers of the last candidates of a learner to `--dump_quals`, if active.
+    ///
+    /// Qualifiers are written as `define-fun`s over the original signature of their predicate,
+    /// and appended to the file so that all splits are dumped. The file is truncated by
+    /// [`split::work`].
+    ///
+    /// [`split::work`]: ../split/fn.work.html (split work function)
+    fn dump_quals(&mut self, idx: LrnIdx) -> Res<()> {
+        let file = if let Some(file) = conf.ice.dump_quals.as_ref() {
+            file
+        } else {
+            return Ok(());
+        };
+        let mut quals: Vec<_> = if let Some(quals) = self.quals.remove(&idx) {
+            quals.into_iter().collect()
+        } else {
+            return Ok(());
+        };
+        quals.sort_by_key(|(pred, _)| *pred);
+
+        let mut s = String::new();
+        if let Some(clause) = self.instance.split() {
+            s += &format!("; split on clause #{}\n", clause)
+        }
+        for (pred, quals) in quals {
+            let info = &self.instance[pred];
+            let sig: Vec<_> = info
+                .original_sig()
+                .index_iter()
+                .map(|(var, typ)| format!("({} {})", var.default_str(), typ))
+                .collect();
+            let mut subst = VarHMap::with_capacity(info.sig.len());
+            for (tgt, src) in info.original_sig_map().index_iter() {
+                let _ = subst.insert(tgt, term::var(*src, info.sig[tgt].clone()));
+            }
+            for (qual, count) in quals {
+                if let Some((qual, _)) = qual.subst_total(&subst) {
+                    s += &format!(
+                        "(define-fun {} ({}) Bool {}) ; separated {} sample(s)\n",
+                        info,
+                        sig.join(" "),
+                        format!("{}", qual).replace('\n', " "),
+                        count
+                    )
+                }
+            }
+        }
+
+        let mut out = ::std::fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(file)
+            .chain_err(|| format!("while opening qualifier dump file `{}`", file))?;
+        write!(out, "{}", s)?;
+        Ok(())
+    }
+
     /// Waits for some candidates.
     ///
     /// Returns `None` when there are no more kids. Otherwise, the second
@@ -608,15 +1242,28 @@ impl<'a> Teacher<'a> {
             let (id, msg) = self.receive_msg(drain)?;
 
             match msg {
-                MsgKind::Cands(cands) => {
+                MsgKind::Cands(cands, round) => {
                     profile! { self "candidates" => add 1 }
                     if let Id::Learner(idx) = id {
+                        if conf.teacher.pipeline.is_some() && round != self.rounds[idx] {
+                            // Learned from outdated data, the learner is working on new data.
+                            profile! { self "outdated candidates" => add 1 }
+                            continue;
+                        }
                         return Ok(Either::Left((idx, self.complete_candidates(cands))));
                     } else {
                         bail!("received candidates from {}", id)
                     }
                 }
 
+                MsgKind::Quals(quals) => {
+                    if let Id::Learner(idx) = id {
+                        let _ = self.quals.insert(idx, quals);
+                    } else {
+                        bail!("received qualifiers from {}", id)
+                    }
+                }
+
                 MsgKind::Samples(samples) => {
                     let (_pos, _neg) = samples.pos_neg_count();
                     profile! { self "assistant pos       " => add _pos }
@@ -779,6 +1426,58 @@ impl<'a> Teacher<'a> {
         Ok(())
     }
 
+    /// Defines the predicates whose candidate changed since the last check.
+    ///
+    /// Only used when the teacher is incremental. Definitions cannot be retracted, so each new
+    /// definition gets a fresh name which replaces the name of the predicate in
+    /// `self.def_preds`. Clauses are written with these names, so old definitions are simply not
+    /// used anymore. Unchanged definitions are kept as they are.
+    fn update_defs(&mut self, cands: &Candidates) -> Res<()> {
+        let mut reused = 0;
+        for (pred, cand) in cands.index_iter() {
+            let term = match *cand {
+                Some(ref term) if term.bool().is_none() => term,
+                _ => continue,
+            };
+            if self.defs.get(&pred) == Some(term) {
+                reused += 1;
+                continue;
+            }
+
+            self.def_count += 1;
+            let name = format!("hoice_reserved_def@{}_{}", pred, self.def_count);
+            let sig: Vec<_> = self.instance[pred]
+                .sig
+                .index_iter()
+                .map(|(var, typ)| (var, typ.get()))
+                .collect();
+            self.solver
+                .define_fun(&name, &sig, typ::bool().get(), &SmtTerm::new(term))?;
+
+            if let Some(def_preds) = self.def_preds.as_mut() {
+                def_preds[pred].name = name
+            }
+            let _ = self.defs.insert(pred, term.clone());
+        }
+        profile! { self "definitions reused" => add reused }
+        Ok(())
+    }
+
+    /// Resets the solver, forgetting the definitions of the incremental teacher.
+    fn reset_solver(&mut self) -> Res<()> {
+        self.defs.clear();
+        smt::reset(&mut self.solver, &self.instance)?;
+        set_clause_timeout(&mut self.solver)
+    }
+
+    /// Predicates to use when writing clauses for the solver.
+    ///
+    /// When the teacher is incremental, the names of the predicates are the ones of their
+    /// current definitions.
+    fn smt_preds<'b>(def_preds: &'b Option<Preds>, instance: &'b Instance) -> &'b Preds {
+        def_preds.as_ref().unwrap_or_else(|| instance.preds())
+    }
+
     /// Registers predicates that are trivially true/false in a candidate.
     ///
     /// Returns the clauses that are trivially true given the candidate.
@@ -817,7 +1516,9 @@ impl<'a> Teacher<'a> {
 
         let mut map = ClsHMap::with_capacity(self.instance.clauses().len());
 
-        if !self.restart_on_cex {
+        if self.def_preds.is_some() {
+            self.update_defs(cands)?
+        } else if !self.restart_on_cex {
             self.solver.push(1)?;
             self.define_preds(cands)?
         }
@@ -832,9 +1533,13 @@ impl<'a> Teacher<'a> {
                     Ok(()) => Ok(()),
                     Err(e) => {
                         if e.is_unknown() {
-                            smt::reset(&mut self.solver, &self.instance)?;
-                            self.solver.push(1)?;
-                            self.define_preds(cands)?;
+                            self.reset_solver()?;
+                            if self.def_preds.is_some() {
+                                self.update_defs(cands)?
+                            } else {
+                                self.solver.push(1)?;
+                                self.define_preds(cands)?
+                            }
                             got_unknown = true;
                             Ok(())
                         } else {
@@ -852,16 +1557,16 @@ impl<'a> Teacher<'a> {
             "looking for counterexamples in positive clauses ({})...",
             instance.pos_clauses().len()
         }
-        for clause in instance.pos_clauses() {
-            handle_clause_res!(self.get_cexs_of_clause(cands, *clause, &mut map, false))?
+        for clause in self.batch_filter(instance.pos_clauses())? {
+            handle_clause_res!(self.get_cexs_of_clause(cands, clause, &mut map, false))?
         }
 
         log! { @verb |
             "looking for counterexamples in strict negative clauses ({})...",
             instance.strict_neg_clauses().len()
         }
-        for clause in instance.strict_neg_clauses() {
-            handle_clause_res!(self.get_cexs_of_clause(cands, *clause, &mut map, false))?
+        for clause in self.batch_filter(instance.strict_neg_clauses())? {
+            handle_clause_res!(self.get_cexs_of_clause(cands, clause, &mut map, false))?
         }
 
         // got_pos_neg_samples = ! map.is_empty() ;
@@ -871,40 +1576,68 @@ impl<'a> Teacher<'a> {
                 "looking for counterexamples in non-strict negative clauses ({})...",
                 instance.non_strict_neg_clauses().len()
             }
-            for clause in instance.non_strict_neg_clauses() {
+            for clause in self.batch_filter(instance.non_strict_neg_clauses())? {
                 handle_clause_res!(self.get_cexs_of_clause(
                     cands,
-                    *clause,
+                    clause,
                     &mut map,
                     conf.teacher.bias_cexs
                 ))?
             }
         }
 
+        if let Some(learner) = self.streaming {
+            if !map.is_empty() && !conf.teacher.max_bias {
+                self.stream_cexs(learner, &map)?
+            }
+        }
+
         if map.is_empty() || !conf.teacher.max_bias {
             log! { @verb |
                 "looking for counterexamples in implication clauses ({})...",
                 instance.imp_clauses().len()
             }
 
-            for clause in instance.imp_clauses() {
+            for clause in self.batch_filter(instance.imp_clauses())? {
                 handle_clause_res!(self.get_cexs_of_clause(
                     cands,
-                    *clause,
+                    clause,
                     &mut map,
                     conf.teacher.bias_cexs
                 ))?
             }
         }
 
+        // With `--clause_timeout`, the clauses that timed out are checked again, unless one of
+        // them timed out too many times.
+        let mut retry = false;
         if map.is_empty() && got_unknown {
-            bail!(ErrorKind::SmtError(::rsmt2::errors::ErrorKind::Unknown))
+            if conf.teacher.clause_timeout.is_some() && !self.timeouts.is_empty() {
+                let mut exhausted: Vec<ClsIdx> = self
+                    .timeouts
+                    .iter()
+                    .filter(|(_, count)| **count >= conf.teacher.clause_timeout_tries)
+                    .map(|(clause, _)| *clause)
+                    .collect();
+                if !exhausted.is_empty() {
+                    exhausted.sort();
+                    bail!(ErrorKind::ClauseTimeout(self.clause_names(&exhausted)))
+                }
+                log! { @verb | "{} clause(s) timed out, checking again", self.timeouts.len() }
+                retry = true
+            } else {
+                bail!(ErrorKind::SmtError(::rsmt2::errors::ErrorKind::Unknown))
+            }
         }
 
         for (_, cexs) in map.iter_mut() {
             cexs.dedup()
         }
 
+        if conf.teacher.cex_yield {
+            self.update_cex_scores(&map)
+        }
+
         log! { @debug
             "extracted {} cexs", map.iter().fold(0, |acc, (_, cexs)| acc + cexs.len())
         }
@@ -919,14 +1652,249 @@ impl<'a> Teacher<'a> {
         // }
 
         if self.count % 100 == 0 || self.restart_on_cex {
-            smt::reset(&mut self.solver, &self.instance)?;
-        } else {
+            self.reset_solver()?;
+        } else if self.def_preds.is_none() {
             self.solver.pop(1)?
         }
 
+        if retry {
+            return self.get_cexs(cands);
+        }
+
         Ok(map)
     }
 
+    /// String representation of some clauses, by their index in the original instance and their
+    /// name if they have one.
+    fn clause_names(&self, clauses: &[ClsIdx]) -> String {
+        let mut res = String::new();
+        for clause in clauses {
+            let from = self.instance[*clause].from();
+            if !res.is_empty() {
+                res.push_str(", ")
+            }
+            res.push_str(&format!("#{}", from));
+            if let Some(name) = self.instance.name_of_old_clause(from) {
+                res.push_str(&format!(" ({})", name))
+            }
+        }
+        res
+    }
+
+    /// Filters out the clauses that batch checking proves not falsifiable.
+    ///
+    /// Also filters out the clauses to ignore. Clauses mentioning the same predicates over
+    /// variables of the same types are grouped, and each group is checked with a single
+    /// check-sat (see [`batch_check`]). Returns the clauses that still need to be checked, in
+    /// the order of the input set.
+    ///
+    /// Batch checking only discards clauses that have no counterexample, so the counterexamples
+    /// eventually extracted are the same as without batching.
+    ///
+    /// With `--clause_cores`, falsifiable groups go through [`clause_core`], and the clauses in
+    /// a core so far come first in the result. With `--cex_yield`, the result is then sorted by
+    /// counterexample yield, see [`sort_by_cex_yield`].
+    ///
+    /// [`sort_by_cex_yield`]: #method.sort_by_cex_yield (sort_by_cex_yield method)
+    /// [`batch_check`]: #method.batch_check (batch_check method)
+    /// [`clause_core`]: #method.clause_core (clause_core method)
+    fn batch_filter(&mut self, clauses: &ClsSet) -> Res<Vec<ClsIdx>> {
+        let mut clauses: Vec<ClsIdx> = clauses
+            .iter()
+            .filter(|clause| !self.clauses_to_ignore.contains(*clause))
+            .cloned()
+            .collect();
+
+        if !conf.teacher.batch_cexs
+            || self.restart_on_cex
+            || self.using_rec_funs
+            || clauses.len() < 2
+        {
+            self.sort_by_cex_yield(&mut clauses);
+            return Ok(clauses);
+        }
+
+        // Ordered so that the checks happen in the same order from one run to the other.
+        let mut groups: BTreeMap<_, Vec<ClsIdx>> = BTreeMap::new();
+        for clause in &clauses {
+            let info = &self.instance[*clause];
+            let mut lhs: Vec<PrdIdx> = info.lhs_preds().keys().cloned().collect();
+            lhs.sort();
+            let rhs = info.rhs().map(|(pred, _)| pred);
+            let vars: Vec<(Typ, bool)> = info
+                .vars()
+                .iter()
+                .map(|var| (var.typ.clone(), var.active))
+                .collect();
+            groups
+                .entry((lhs, rhs, vars))
+                .or_insert_with(Vec::new)
+                .push(*clause)
+        }
+
+        let mut valid = ClsSet::new();
+        for (_, group) in groups {
+            if group.len() > 1 {
+                if self.batch_check(&group)? {
+                    valid.extend(group)
+                } else if conf.teacher.clause_cores {
+                    let core = self.clause_core(&group)?;
+                    self.relevant.extend(core)
+                }
+            }
+        }
+
+        let (mut res, others): (Vec<_>, Vec<_>) = clauses
+            .into_iter()
+            .filter(|clause| !valid.contains(clause))
+            .partition(|clause| self.relevant.contains(clause));
+        res.extend(others);
+        self.sort_by_cex_yield(&mut res);
+        Ok(res)
+    }
+
+    /// Decays the counterexample yield scores by `--cex_yield_decay`, and bumps the ones of the
+    /// clauses that yielded counterexamples.
+    ///
+    /// Scores that become negligible are dropped.
+    fn update_cex_scores(&mut self, cexs: &Cexs) {
+        for score in self.cex_scores.values_mut() {
+            *score *= conf.teacher.cex_yield_decay
+        }
+        self.cex_scores.retain(|_, score| *score > 0.01);
+        for (clause, cexs) in cexs {
+            if !cexs.is_empty() {
+                *self.cex_scores.entry(*clause).or_insert(0.0) += 1.0
+            }
+        }
+    }
+
+    /// Sorts some clauses by decreasing counterexample yield score, see `--cex_yield`.
+    ///
+    /// The sort is stable, so clauses with the same score stay in the same order.
+    fn sort_by_cex_yield(&self, clauses: &mut [ClsIdx]) {
+        if !conf.teacher.cex_yield || self.cex_scores.is_empty() {
+            return;
+        }
+        let score = |clause: &ClsIdx| self.cex_scores.get(clause).cloned().unwrap_or(0.0);
+        clauses.sort_by(|c_1, c_2| {
+            score(c_2)
+                .partial_cmp(&score(c_1))
+                .unwrap_or(::std::cmp::Ordering::Equal)
+        })
+    }
+
+    /// Extracts the clauses of a group of similar clauses that are relevant to the group being
+    /// falsifiable.
+    ///
+    /// Asserts each clause under its own activation literal, together with the disjunction of
+    /// their negations: this is unsatisfiable when all the clauses are active. The activation
+    /// literals are the assumptions of the checks. Then, drops chunks of clauses as long as it
+    /// stays unsatisfiable, halving the size of the chunks until it is one: this takes a number of
+    /// checks logarithmic in the size of the group per clause of the result, instead of one check
+    /// per clause of the group. The remaining clauses form an unsat core: whenever a clause of the
+    /// group is falsified, one of the core is too.
+    ///
+    /// Returns the whole group if the solver does not answer `unsat` with all clauses active.
+    fn clause_core(&mut self, clauses: &[ClsIdx]) -> Res<Vec<ClsIdx>> {
+        profile! { self tick "cexs", "clause core" }
+        self.solver.push(1)?;
+        self.instance[clauses[0]].declare(&mut self.solver)?;
+        let preds = Self::smt_preds(&self.def_preds, &self.instance);
+        self.solver.assert_with(
+            &smt::NegClauses::new(&self.instance, clauses),
+            (&self.tru_preds, &self.fls_preds, preds),
+        )?;
+
+        let mut core = Vec::with_capacity(clauses.len());
+        for clause in clauses {
+            let actlit = self.solver.get_actlit()?;
+            write!(self.solver, "(assert (=> ")?;
+            actlit.expr_to_smt2(&mut self.solver, ())?;
+            write!(self.solver, " ")?;
+            self.instance[*clause].expr_to_smt2(
+                &mut self.solver,
+                &(true, &self.tru_preds, &self.fls_preds, preds),
+            )?;
+            writeln!(self.solver, "))")?;
+            core.push((*clause, actlit))
+        }
+
+        let unsat = self
+            .solver
+            .check_sat_act_or_unk(core.iter().map(|(_, actlit)| actlit))?
+            == Some(false);
+
+        if unsat {
+            let mut size = core.len() / 2;
+            while size > 0 {
+                let mut start = 0;
+                while start < core.len() {
+                    let end = ::std::cmp::min(start + size, core.len());
+                    let sat = self.solver.check_sat_act_or_unk(
+                        core.iter()
+                            .enumerate()
+                            .filter(|(index, _)| *index < start || *index >= end)
+                            .map(|(_, (_, actlit))| actlit),
+                    )?;
+                    if sat == Some(false) {
+                        let _ = core.drain(start..end);
+                    } else {
+                        start = end
+                    }
+                }
+                size /= 2
+            }
+            profile! { self "clause cores" => add 1 }
+            profile! { self "clauses out of cores" => add clauses.len() - core.len() }
+        }
+
+        self.solver.pop(1)?;
+        profile! { self mark "cexs", "clause core" }
+
+        if unsat {
+            Ok(core.into_iter().map(|(clause, _)| clause).collect())
+        } else {
+            Ok(clauses.to_vec())
+        }
+    }
+
+    /// Checks whether some clauses are all non-falsifiable with a single check-sat.
+    ///
+    /// The clauses must have the same variables. Returns `false` if at least one of them might be
+    /// falsifiable, or if the solver answered `unknown`.
+    fn batch_check(&mut self, clauses: &[ClsIdx]) -> Res<bool> {
+        self.solver.push(1)?;
+        self.instance[clauses[0]].declare(&mut self.solver)?;
+        self.solver.assert_with(
+            &smt::NegClauses::new(&self.instance, clauses),
+            (
+                &self.tru_preds,
+                &self.fls_preds,
+                Self::smt_preds(&self.def_preds, &self.instance),
+            ),
+        )?;
+        let res: Res<bool> = profile! {
+            self wrap {
+                self.solver.check_sat().map_err(|e| e.into())
+            } "cexs", "batch check-sat"
+        };
+        self.solver.pop(1)?;
+
+        match res {
+            Ok(false) => {
+                profile! { self "solver calls saved" => add clauses.len() - 1 }
+                Ok(true)
+            }
+            Ok(true) => {
+                profile! { self "batch check misses" => add 1 }
+                Ok(false)
+            }
+            Err(ref e) if e.is_unknown() => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Retrieves counterexamples for a clause.
     pub fn get_cexs_of_clause(
         &mut self,
@@ -936,29 +1904,71 @@ impl<'a> Teacher<'a> {
         bias: bool,
     ) -> Res<()> {
         if !self.clauses_to_ignore.contains(&clause) {
+            let memo_key = if conf.teacher.memo_checks {
+                let key = self.memo_key(cands, clause);
+                if self.valid_memo.get(&clause) == Some(&key) {
+                    profile! { self "memoized checks" => add 1 }
+                    return Ok(());
+                }
+                Some(key)
+            } else {
+                None
+            };
+
             if self.restart_on_cex {
                 self.define_preds(cands)?
             } else {
                 self.solver.push(1)?
             }
 
-            let cexs = self.get_cex(clause, bias, conf.teacher.max_bias, !map.is_empty())?;
+            let cexs = self.get_cex(clause, bias, conf.teacher.max_bias, !map.is_empty());
+            if conf.teacher.clause_timeout.is_some() {
+                match cexs {
+                    Ok(_) => {
+                        let _ = self.timeouts.remove(&clause);
+                    }
+                    Err(ref e) if e.is_unknown() => {
+                        *self.timeouts.entry(clause).or_insert(0) += 1;
+                    }
+                    Err(_) => (),
+                }
+            }
+            let cexs =
+                cexs.map_err(|e| smt::solver_failure(e, "teacher", Some(clause), &self.instance))?;
 
             if self.restart_on_cex {
-                smt::reset(&mut self.solver, &self.instance)?
+                smt::reset(&mut self.solver, &self.instance)?;
+                set_clause_timeout(&mut self.solver)?
             } else {
                 self.solver.pop(1)?
             }
 
             if !cexs.is_empty() {
+                let _ = self.valid_memo.remove(&clause);
                 let prev = map.insert(clause, cexs);
                 debug_assert_eq!(prev, None)
+            } else if let Some(key) = memo_key {
+                let _ = self.valid_memo.insert(clause, key);
             }
         }
 
         Ok(())
     }
 
+    /// Candidates of the predicates appearing in a clause, see `--memo_checks`.
+    fn memo_key(&self, cands: &Candidates, clause: ClsIdx) -> Vec<(PrdIdx, Option<Term>)> {
+        let clause = &self.instance[clause];
+        let mut key: Vec<_> = clause
+            .lhs_preds()
+            .keys()
+            .chain(clause.rhs().map(|(pred, _)| pred).iter())
+            .map(|pred| (*pred, cands[*pred].clone()))
+            .collect();
+        key.sort_by_key(|(pred, _)| *pred);
+        key.dedup_by_key(|(pred, _)| *pred);
+        key
+    }
+
     /// Retrieves a counterexample given some bias.
     fn get_bias_cex(&mut self, clause: ClsIdx, bias: &Bias) -> Res<Cex> {
         profile! {
@@ -975,6 +1985,167 @@ impl<'a> Teacher<'a> {
         )
     }
 
+    /// Selects a counterexample for a clause, following `conf.teacher.cex_strategy`.
+    ///
+    /// Assumes the last check-sat found the clause falsifiable, and that `cex` was extracted from
+    /// it. The assertions of the solver are the same before and after the call.
+    fn select_cex(&mut self, clause: ClsIdx, bias: &Bias, cex: Cex) -> Res<Cex> {
+        if self.using_rec_funs {
+            return Ok(cex);
+        }
+        match conf.teacher.cex_strategy {
+            CexStrategy::First => Ok(cex),
+            CexStrategy::Smallest => profile! {
+                self wrap { self.smallest_cex(clause, bias, cex) } "cexs", "selection"
+            },
+            CexStrategy::Diverse => profile! {
+                self wrap { self.diverse_cex(clause, bias, cex) } "cexs", "selection"
+            },
+        }
+    }
+
+    /// Looks for a counterexample minimizing the sum of the absolute values of the active
+    /// arithmetic variables of a clause.
+    ///
+    /// Strengthens the sum until the solver cannot improve on it, or after
+    /// `MAX_CEX_SELECTION_STEPS` steps. Returns the best counterexample found.
+    fn smallest_cex(&mut self, clause: ClsIdx, bias: &Bias, mut cex: Cex) -> Res<Cex> {
+        let has_reals = self.instance[clause]
+            .vars()
+            .iter()
+            .any(|info| info.active && info.typ.is_real());
+        let mut kids = vec![];
+        for info in self.instance[clause].vars() {
+            if !info.active || !info.typ.is_arith() {
+                continue;
+            }
+            let (var, zero) = if has_reals {
+                let var = term::var(info.idx, info.typ.clone());
+                let var = if info.typ.is_int() {
+                    term::to_real(var)
+                } else {
+                    var
+                };
+                (var, term::real_zero())
+            } else {
+                (term::var(info.idx, info.typ.clone()), term::int_zero())
+            };
+            kids.push(term::ite(
+                term::ge(var.clone(), zero),
+                var.clone(),
+                term::u_minus(var),
+            ))
+        }
+        let sum = if kids.len() > 1 {
+            term::add(kids)
+        } else if let Some(kid) = kids.pop() {
+            kid
+        } else {
+            return Ok(cex);
+        };
+
+        self.solver.push(1)?;
+        for _ in 0..MAX_CEX_SELECTION_STEPS {
+            if cex.is_partial() {
+                break;
+            }
+            let value = if let Some(value) = sum.eval(&cex)?.to_term() {
+                value
+            } else {
+                break;
+            };
+            self.solver
+                .assert(&SmtTerm::new(&term::lt(sum.clone(), value)))?;
+            if self.solver.check_sat_or_unk()? != Some(true) {
+                break;
+            }
+            cex = self.get_bias_cex(clause, bias)?;
+            profile! { self "cex selection improvements" => add 1 }
+        }
+        self.solver.pop(1)?;
+
+        Ok(cex)
+    }
+
+    /// Looks for a counterexample far away from the samples the learner knows.
+    ///
+    /// Blocks the last counterexample and asks for a new one, at most `MAX_CEX_SELECTION_STEPS`
+    /// times. Returns the counterexample with the largest `sample_distance`.
+    fn diverse_cex(&mut self, clause: ClsIdx, bias: &Bias, cex: Cex) -> Res<Cex> {
+        let mut best_dist = if let Some(dist) = self.sample_distance(clause, &cex)? {
+            dist
+        } else {
+            // No samples to be far away from.
+            return Ok(cex);
+        };
+        let mut best = cex.clone();
+        let mut last = cex;
+
+        self.solver.push(1)?;
+        for _ in 0..MAX_CEX_SELECTION_STEPS {
+            let mut eqs = vec![];
+            for info in self.instance[clause].vars() {
+                if !info.active {
+                    continue;
+                }
+                if let Some(val) = last[info.idx].to_term() {
+                    eqs.push(term::eq(term::var(info.idx, info.typ.clone()), val))
+                }
+            }
+            if eqs.is_empty() {
+                break;
+            }
+            self.solver
+                .assert(&SmtTerm::new(&term::not(term::and(eqs))))?;
+            if self.solver.check_sat_or_unk()? != Some(true) {
+                break;
+            }
+            let cex = self.get_bias_cex(clause, bias)?;
+            if let Some(dist) = self.sample_distance(clause, &cex)? {
+                if dist > best_dist {
+                    profile! { self "cex selection improvements" => add 1 }
+                    best_dist = dist;
+                    best = cex.clone()
+                }
+            }
+            last = cex
+        }
+        self.solver.pop(1)?;
+
+        Ok(best)
+    }
+
+    /// Distance between a counterexample for a clause and the samples the learner knows.
+    ///
+    /// Minimum L1 distance between the arguments of the predicate applications of the clause
+    /// under the counterexample, and the positive/negative samples of the same predicates.
+    /// `None` if there are no such samples.
+    fn sample_distance(&self, clause: ClsIdx, cex: &Cex) -> Res<Option<Rat>> {
+        let clause = &self.instance[clause];
+        let apps = clause
+            .lhs_preds()
+            .iter()
+            .flat_map(|(pred, argss)| argss.iter().map(move |args| (*pred, args)))
+            .chain(clause.rhs());
+
+        let mut res: Option<Rat> = None;
+        for (pred, args) in apps {
+            let mut sample = var_to::vals::RVarVals::with_capacity(args.len());
+            for arg in args.get() {
+                sample.push(arg.eval(cex)?)
+            }
+            for known in self.data.pos[pred].iter().chain(self.data.neg[pred].iter()) {
+                if let Some(dist) = sample.l1_distance(known) {
+                    if res.as_ref().map(|res| dist < *res).unwrap_or(true) {
+                        res = Some(dist)
+                    }
+                }
+            }
+        }
+
+        Ok(res)
+    }
+
     /// Check-sats given an optional bias.
     fn check_sat_cex(
         &mut self,
@@ -1064,6 +2235,7 @@ impl<'a> Teacher<'a> {
                     Bias::Non
                 };
                 let cex = self.get_bias_cex(clause, &bias)?;
+                let cex = self.select_cex(clause, &bias, cex)?;
                 log! { @debug "  {}", cex }
                 Ok(Some((cex, bias)))
             } else {
@@ -1151,7 +2323,7 @@ impl<'a> Teacher<'a> {
                     false,
                     &self.tru_preds,
                     &self.fls_preds,
-                    self.instance.preds(),
+                    Self::smt_preds(&self.def_preds, &self.instance),
                 ),
             )?
         }
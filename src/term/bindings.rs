@@ -110,11 +110,18 @@ impl Builder {
     }
 
     /// Builds bindings from the information it accumulated so far.
-    pub fn build(mut self, mut fresh: VarIdx) -> Option<Bindings> {
+    ///
+    /// Only binds the terms that appear more than twice.
+    pub fn build(self, fresh: VarIdx) -> Option<Bindings> {
+        self.build_above(fresh, 2)
+    }
+
+    /// Builds bindings for the terms that appear more than `threshold` times.
+    pub fn build_above(mut self, mut fresh: VarIdx, threshold: usize) -> Option<Bindings> {
         let first_fresh = fresh;
         let mut empty = 0;
         for term_map in self.depth_map.values_mut() {
-            term_map.retain(|_, count| *count > 2);
+            term_map.retain(|_, count| *count > threshold);
             if term_map.is_empty() {
                 empty += 1
             }
@@ -194,6 +201,26 @@ impl Builder {
         self
     }
 
+    /// Scans a set of top terms.
+    pub fn scan_tterm_set(self, tterms: &TTermSet) -> Self {
+        self.scan_terms(tterms.terms())
+            .scan_pred_apps(tterms.preds())
+    }
+
+    /// Scans some top terms.
+    pub fn scan_tterms(self, tterms: &TTerms) -> Self {
+        match tterms {
+            TTerms::True | TTerms::False => self,
+            TTerms::Conj { tterms, .. } => self.scan_tterm_set(tterms),
+            TTerms::Disj {
+                tterms, neg_preds, ..
+            } => self.scan_tterm_set(tterms).scan_pred_apps(neg_preds),
+            TTerms::Dnf { disj } => disj
+                .iter()
+                .fold(self, |builder, (_, tterms)| builder.scan_tterm_set(tterms)),
+        }
+    }
+
     /// Scans only the lhs terms of a clause.
     pub fn scan_clause_lhs_terms(self, clause: &Clause) -> Self {
         self.scan_terms(clause.lhs_terms())
@@ -169,10 +169,15 @@ fn total<'a>(
                 )
             }
 
-            return Ok(ZipDoTotal::Dwn {
-                nu_term: &fun.def,
-                nu_subst: Some(values.into()),
-            });
+            if fun.is_uninterpreted() {
+                // No definition, nothing is known about the application.
+                val::none(typ.clone())
+            } else {
+                return Ok(ZipDoTotal::Dwn {
+                    nu_term: &fun.def,
+                    nu_subst: Some(values.into()),
+                });
+            }
         }
     };
 
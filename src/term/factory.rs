@@ -1103,6 +1103,28 @@ pub fn gt(lhs: Term, rhs: Term) -> Term {
 /// let t = term::ge( term::int_var(7), term::int(0) );
 /// assert_eq! { &format!("{}", t), "(>= v_7 0)" }
 /// ```
+///
+/// An `ite` with constant branches compared to a constant is lifted:
+///
+/// ```rust
+/// # use hoice::common::*;
+/// let ite = term::ite( term::bool_var(0), term::int(1), term::int(3) );
+/// let t = term::ge( ite.clone(), term::int(2) );
+/// assert_eq! { &format!("{}", t), "(not v_0)" }
+/// let t = term::le( ite, term::int(2) );
+/// assert_eq! { &format!("{}", t), "v_0" }
+/// ```
+///
+/// Negative constants and double negations fold through the lifting:
+///
+/// ```rust
+/// # use hoice::common::*;
+/// let ite = term::ite( term::bool_var(0), term::int(1), term::int(3) );
+/// let t = term::ge( term::u_minus(ite.clone()), term::int(-2) );
+/// assert_eq! { &format!("{}", t), "v_0" }
+/// let t = term::not( term::not( term::ge(ite, term::int(2)) ) );
+/// assert_eq! { &format!("{}", t), "(not v_0)" }
+/// ```
 #[inline]
 pub fn ge(lhs: Term, rhs: Term) -> Term {
     app(Op::Ge, vec![lhs, rhs])
@@ -1117,6 +1139,15 @@ pub fn ge(lhs: Term, rhs: Term) -> Term {
 /// let t = term::eq( term::int_var(7), term::int(2) );
 /// assert_eq! { &format!("{}", t), "(= (+ v_7 (- 2)) 0)" }
 /// ```
+///
+/// An `ite` with constant branches compared to a constant is lifted:
+///
+/// ```rust
+/// # use hoice::common::*;
+/// let ite = term::ite( term::bool_var(0), term::int(1), term::int(3) );
+/// let t = term::eq( term::int(1), ite );
+/// assert_eq! { &format!("{}", t), "v_0" }
+/// ```
 #[inline]
 pub fn eq(lhs: Term, rhs: Term) -> Term {
     app(Op::Eql, vec![lhs, rhs])
@@ -1220,6 +1251,8 @@ pub fn sub2(kid_1: Term, kid_2: Term) -> Term {
 /// assert_eq! { &format!("{}", t), "(- 3)" }
 /// let t = term::u_minus( t );
 /// assert_eq! { &format!("{}", t), "3" }
+/// let t = term::u_minus( term::u_minus( term::int_var(3) ) );
+/// assert_eq! { &format!("{}", t), "v_3" }
 /// let add = term::add2( term::int_var(7), term::int(5) );
 /// let t = term::u_minus( add );
 /// assert_eq! { &format!("{}", t), "(+ (* (- 1) v_7) (- 5))" }
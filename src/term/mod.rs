@@ -289,6 +289,85 @@ impl RTerm {
         }
     }
 
+    /// Degree of a term, seen as a polynomial over its variables.
+    ///
+    /// Variables have degree `1` and constants degree `0`. The degree of a multiplication is the
+    /// sum of the degrees of its kids. Any other term has the maximal degree of its kids: in
+    /// particular, `div`, `mod` and `rem` applications have the degree of their arguments.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::term;
+    /// let (x, y, z) = (term::int_var(0), term::int_var(1), term::int_var(2));
+    /// assert_eq! { term::int(7).degree(), 0 }
+    /// assert_eq! { term::ge(term::cmul(2, x.clone()), y.clone()).degree(), 1 }
+    ///
+    /// let xy = term::mul(vec![x.clone(), y.clone()]);
+    /// assert_eq! { xy.degree(), 2 }
+    /// assert_eq! { term::ge(xy.clone(), term::int(3)).degree(), 2 }
+    /// assert_eq! { term::mul(vec![xy.clone(), z.clone()]).degree(), 3 }
+    /// assert_eq! { term::modulo(xy.clone(), term::int(2)).degree(), 2 }
+    /// assert_eq! { term::modulo(x.clone(), y.clone()).degree(), 1 }
+    /// ```
+    pub fn degree(&self) -> usize {
+        match self {
+            RTerm::Var(_, _) => 1,
+            RTerm::Cst(_) => 0,
+            RTerm::CArray { term, .. } => term.degree(),
+            RTerm::App { op, args, .. } => match op {
+                Op::Mul | Op::CMul => args.iter().map(|arg| arg.degree()).sum(),
+                _ => args.iter().map(|arg| arg.degree()).max().unwrap_or(0),
+            },
+            RTerm::DTypNew { args, .. } | RTerm::Fun { args, .. } => {
+                args.iter().map(|arg| arg.degree()).max().unwrap_or(0)
+            }
+            RTerm::DTypSlc { term, .. } | RTerm::DTypTst { term, .. } => term.degree(),
+        }
+    }
+
+    /// Syntactic complexity of a term, see `--dt_tiebreak`.
+    ///
+    /// Each node counts for `1`, and integer and real constants also count for the number of
+    /// digits of their absolute value (numerator and denominator for reals). So, terms with fewer
+    /// and smaller coefficients are simpler.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::term;
+    /// let (x, y) = (term::int_var(0), term::int_var(1));
+    /// assert_eq! { x.complexity(), 1 }
+    /// assert_eq! { term::int(-7).complexity(), 2 }
+    /// assert_eq! { term::int(1024).complexity(), 5 }
+    ///
+    /// let simple = term::ge(x.clone(), y.clone());
+    /// let with_coef = term::ge(term::cmul(2, x.clone()), y.clone());
+    /// let with_big_coef = term::ge(term::cmul(107, x.clone()), y.clone());
+    /// assert! { simple.complexity() < with_coef.complexity() }
+    /// assert! { with_coef.complexity() < with_big_coef.complexity() }
+    /// ```
+    pub fn complexity(&self) -> usize {
+        fn digits<T: ::std::fmt::Display>(n: T) -> usize {
+            n.to_string().trim_start_matches('-').len()
+        }
+        match self {
+            RTerm::Var(_, _) => 1,
+            RTerm::Cst(val) => match val.get() {
+                val::RVal::I(i) => 1 + digits(i),
+                val::RVal::R(r) if r.is_integer() => 1 + digits(r.numer()),
+                val::RVal::R(r) => 1 + digits(r.numer()) + digits(r.denom()),
+                _ => 1,
+            },
+            RTerm::CArray { term, .. }
+            | RTerm::DTypSlc { term, .. }
+            | RTerm::DTypTst { term, .. } => 1 + term.complexity(),
+            RTerm::App { args, .. } | RTerm::DTypNew { args, .. } | RTerm::Fun { args, .. } => {
+                1 + args.iter().map(|arg| arg.complexity()).sum::<usize>()
+            }
+        }
+    }
+
     /// Type of the term.
     ///
     /// # Examples
@@ -737,6 +816,36 @@ impl RTerm {
         res.is_err()
     }
 
+    /// Returns true if the term mentions an uninterpreted function.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use hoice::{ common::*, fun, info::VarInfo };
+    /// let sig: VarInfos = vec![ VarInfo::new("v_0", typ::int(), 0.into()) ].into();
+    /// let name = "term_has_uf_apps_test_uf";
+    /// fun::new(fun::RFun::uninterpreted(name, sig, typ::int()))
+    ///     .expect("during function registration");
+    ///
+    /// let t_1 = term::ge(term::fun(name, vec![term::int_var(0)]), term::int(0));
+    /// assert! { t_1.has_uf_apps() }
+    /// assert! { t_1.has_fun_apps() }
+    ///
+    /// let t_2 = term::ge(term::int_var(0), term::int(0));
+    /// assert! { !t_2.has_uf_apps() }
+    /// ```
+    pub fn has_uf_apps(&self) -> bool {
+        let mut res = false;
+        self.iter(|term| {
+            if let Some((name, _)) = term.fun_inspect() {
+                if let Some(fun) = fun::get(name) {
+                    res = res || fun.is_uninterpreted()
+                }
+            }
+        });
+        res
+    }
+
     /// Returns true if the term mentions a recursive function.
     ///
     /// # Examples
@@ -2237,6 +2346,110 @@ impl RTerm {
         }
     }
 
+    /// All the ways to turn an equality into a substitution.
+    ///
+    /// Same as [`as_subst`], but yields a substitution for each variable the equality can be
+    /// solved for: arithmetic equalities `(= (+ t_1 ... t_n) 0)` are solved for each variable `v`
+    /// such that some `t_i` is `v` or `(* c v)` and `v` does not appear in the other `t_j`s, with
+    /// `c` either `1` or `-1` on integers and anything but `0` on reals.
+    ///
+    /// [`as_subst`]: #method.as_subst (as_subst method)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::common::*;
+    /// let (x, y, z) = (term::int_var(0), term::int_var(1), term::int_var(2));
+    /// let eq = term::eq(term::add(vec![x.clone(), term::cmul(2, y.clone())]), z.clone());
+    /// let vars: Vec<VarIdx> = eq.as_substs().into_iter().map(|(var, _)| var).collect();
+    /// assert_eq! { vars.len(), 2 }
+    /// assert! { vars.contains(&0.into()) }
+    /// assert! { vars.contains(&2.into()) }
+    ///
+    /// let (x, y) = (term::real_var(0), term::real_var(1));
+    /// let two = Rat::from_integer(2.into());
+    /// let eq = term::eq(term::cmul(two, x.clone()), y.clone());
+    /// let vars: Vec<VarIdx> = eq.as_substs().into_iter().map(|(var, _)| var).collect();
+    /// assert_eq! { vars.len(), 2 }
+    /// ```
+    pub fn as_substs(&self) -> Vec<(VarIdx, Term)> {
+        let mut res = vec![];
+        let kids = match self.eq_inspect() {
+            Some(kids) if kids.len() == 2 => kids,
+            _ => return res,
+        };
+        let (lhs, rhs) = (&kids[0], &kids[1]);
+
+        if let Some(var_idx) = lhs.var_idx() {
+            res.push((var_idx, rhs.clone()))
+        }
+        if let Some(var_idx) = rhs.var_idx() {
+            res.push((var_idx, lhs.clone()))
+        }
+        if !res.is_empty() || !lhs.typ().is_arith() || !rhs.is_zero() {
+            return res;
+        }
+
+        let is_int = lhs.typ().is_int();
+        let lhs = if let Some((_, term)) = lhs.cmul_inspect() {
+            term
+        } else {
+            lhs
+        };
+        let kids = if let Some(kids) = lhs.add_inspect() {
+            kids.clone()
+        } else {
+            vec![lhs.clone()]
+        };
+
+        for (idx, kid) in kids.iter().enumerate() {
+            let (var, coef) = if let Some(var) = kid.var_idx() {
+                (var, Rat::one())
+            } else if let Some((val, term)) = kid.cmul_inspect() {
+                match (term.var_idx(), val.to_real()) {
+                    (Some(var), Ok(Some(coef))) => (var, coef),
+                    _ => continue,
+                }
+            } else {
+                continue;
+            };
+            if coef.is_zero() || (is_int && !coef.is_one() && !(-coef.clone()).is_one()) {
+                continue;
+            }
+
+            let others: Vec<Term> = kids
+                .iter()
+                .enumerate()
+                .filter(|(other_idx, _)| *other_idx != idx)
+                .map(|(_, other)| other.clone())
+                .collect();
+            if others.iter().any(|other| term::vars(other).contains(&var)) {
+                continue;
+            }
+            let sum = if others.is_empty() {
+                if is_int {
+                    term::int_zero()
+                } else {
+                    term::real_zero()
+                }
+            } else {
+                term::add(others)
+            };
+
+            // `coef * var + sum = 0`, so `var = - sum / coef`.
+            let value = if coef.is_one() {
+                term::u_minus(sum)
+            } else if (-coef.clone()).is_one() {
+                sum
+            } else {
+                term::cmul(-Rat::one() / coef, sum)
+            };
+            res.push((var, value))
+        }
+
+        res
+    }
+
     /// Attempts to invert a term from a variable.
     pub fn invert_var(&self, var: VarIdx, typ: Typ) -> Option<(VarIdx, Term)> {
         self.invert(term::var(var, typ))
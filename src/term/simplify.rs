@@ -660,6 +660,145 @@ fn vec_simpl(terms: &mut Vec<Term>, conj: bool) {
     ::std::mem::swap(terms, &mut res)
 }
 
+/// Normalizes the arithmetic bounds of a conjunction.
+///
+/// Bounds over the same arithmetic term are merged and subsumed bounds are dropped. Returns
+/// `false` if the bounds are contradictory, in which case `terms` is left empty.
+///
+/// # Examples
+///
+/// ```
+/// # use hoice::common::*;
+/// use hoice::term::simplify::conj_bounds;
+/// let v_0 = term::int_var(0);
+///
+/// let mut terms = TermSet::new();
+/// terms.insert(term::ge(v_0.clone(), term::int(3)));
+/// terms.insert(term::ge(v_0.clone(), term::int(5)));
+/// assert! { conj_bounds(&mut terms) }
+/// assert_eq! { terms.len(), 1 }
+/// assert! { terms.contains(&term::ge(v_0.clone(), term::int(5))) }
+///
+/// terms.insert(term::le(v_0.clone(), term::int(3)));
+/// assert! { !conj_bounds(&mut terms) }
+/// assert! { terms.is_empty() }
+/// ```
+pub fn conj_bounds(terms: &mut TermSet) -> bool {
+    let mut conj: Vec<Term> = terms.drain().collect();
+    vec_simpl(&mut conj, true);
+    if conj.iter().any(|term| term.bool() == Some(false)) {
+        false
+    } else {
+        terms.extend(conj.into_iter().filter(|term| term.bool() != Some(true)));
+        true
+    }
+}
+
+/// Factors the conjuncts shared by all the disjuncts of the disjunctions of a term.
+///
+/// Dually, factors the disjuncts shared by all the conjuncts of the conjunctions. Also applies
+/// absorption and idempotence. Only looks at the boolean structure of the term: the leaves,
+/// *i.e.* the subterms that are not conjunctions or disjunctions, are left untouched. The result is
+/// equivalent to the input.
+///
+/// # Examples
+///
+/// ```rust
+/// use hoice::{ common::*, term::simplify::factor };
+/// let (a, b, c) = (term::bool_var(0), term::bool_var(1), term::bool_var(2));
+///
+/// let term = term::or(vec![
+///     term::and(vec![a.clone(), b.clone()]),
+///     term::and(vec![a.clone(), c.clone()]),
+/// ]);
+/// let factored = factor(&term);
+/// assert_eq! {
+///     factored, term::and(vec![a.clone(), term::or(vec![b.clone(), c.clone()])])
+/// }
+/// assert_eq! { factor(&factored), factored }
+///
+/// let term = term::and(vec![
+///     term::or(vec![a.clone(), b.clone()]),
+///     term::or(vec![a.clone(), b.clone(), c.clone()]),
+/// ]);
+/// assert_eq! { factor(&term), term::or(vec![a, b]) }
+/// ```
+pub fn factor(term: &Term) -> Term {
+    match term.app_inspect() {
+        Some((op, args)) if op == Op::And || op == Op::Or => {
+            factor_kids(op, args.iter().map(factor).collect())
+        }
+        _ => term.clone(),
+    }
+}
+
+/// Factors a conjunction or a disjunction, see [`factor`].
+///
+/// Each kid is seen as a set of operands of the dual operator. A kid is dropped if its set contains
+/// the set of another kid (absorption), and the operands appearing in all the sets are factored.
+///
+/// [`factor`]: fn.factor.html (factor function)
+fn factor_kids(op: Op, kids: Vec<Term>) -> Term {
+    let (mk, dual): (fn(Vec<Term>) -> Term, Op) = if op == Op::And {
+        (term::and, Op::Or)
+    } else {
+        (term::or, Op::And)
+    };
+    let mk_dual: fn(Vec<Term>) -> Term = if op == Op::And { term::or } else { term::and };
+
+    let mut sets: Vec<Vec<Term>> = kids
+        .into_iter()
+        .map(|kid| {
+            let mut set = match kid.app_inspect() {
+                Some((kid_op, args)) if kid_op == dual => args.clone(),
+                _ => vec![kid.clone()],
+            };
+            set.sort();
+            set.dedup();
+            set
+        })
+        .collect();
+    sets.sort_by(|s_1, s_2| s_1.len().cmp(&s_2.len()).then_with(|| s_1.cmp(s_2)));
+    sets.dedup();
+
+    // Absorption, sets are sorted by length so a set can only be absorbed by a previous one.
+    let mut kept: Vec<Vec<Term>> = Vec::with_capacity(sets.len());
+    for set in sets {
+        if kept
+            .iter()
+            .all(|prev| prev.iter().any(|term| set.binary_search(term).is_err()))
+        {
+            kept.push(set)
+        }
+    }
+
+    if kept.len() > 1 {
+        let common: Vec<Term> = kept[0]
+            .iter()
+            .filter(|term| kept[1..].iter().all(|set| set.binary_search(term).is_ok()))
+            .cloned()
+            .collect();
+        if !common.is_empty() {
+            // No set is equal to `common`, otherwise it would have absorbed all the others.
+            let rest = kept
+                .into_iter()
+                .map(|set| {
+                    mk_dual(
+                        set.into_iter()
+                            .filter(|term| common.binary_search(term).is_err())
+                            .collect(),
+                    )
+                })
+                .collect();
+            let mut res = common;
+            res.push(factor_kids(op, rest));
+            return mk_dual(res);
+        }
+    }
+
+    mk(kept.into_iter().map(mk_dual).collect())
+}
+
 /// Result of deconstructing a sum.
 ///
 /// This is used in `int_deconstruct` below to deconstruct additions to compare relation over
@@ -1077,11 +1216,62 @@ macro_rules! simpl_fun {
   ) ;
 }
 
+/// Lifts an `ite` with constant branches out of a binary relation with a constant.
+///
+/// `(op (ite c t e) k)` becomes `(or (and c (op t k)) (and (not c) (op e k)))`, which the
+/// relations over constants then fold. Same if the `ite` is the second argument.
+fn lift_ite(op: Op, args: &[Term]) -> Option<NormRes> {
+    if args.len() != 2 {
+        return None;
+    }
+    let (ite_idx, (c, t, e)) = if let Some(ite) = args[0].ite_inspect() {
+        (0, ite)
+    } else if let Some(ite) = args[1].ite_inspect() {
+        (1, ite)
+    } else {
+        return None;
+    };
+    let other = &args[1 - ite_idx];
+    if t.val().is_none() || e.val().is_none() || other.val().is_none() {
+        return None;
+    }
+
+    let rel = |branch: &Term| {
+        let mut rel_args = vec![NormRes::Term(branch.clone())];
+        if ite_idx == 0 {
+            rel_args.push(NormRes::Term(other.clone()))
+        } else {
+            rel_args.insert(0, NormRes::Term(other.clone()))
+        }
+        NormRes::App(typ::bool(), op, rel_args)
+    };
+
+    Some(NormRes::App(
+        typ::bool(),
+        Op::Or,
+        vec![
+            NormRes::App(typ::bool(), Op::And, vec![NormRes::Term(c.clone()), rel(t)]),
+            NormRes::App(
+                typ::bool(),
+                Op::And,
+                vec![
+                    NormRes::App(typ::bool(), Op::Not, vec![NormRes::Term(c.clone())]),
+                    rel(e),
+                ],
+            ),
+        ],
+    ))
+}
+
 // Polymorphic operations.
 
 simpl_fun! {
     // Equal.
     fn eql(args) {
+        if let Some(res) = lift_ite(Op::Eql, args) {
+            return Some(res)
+        }
+
         if args.len() == 2 {
 
             if args[0] == args[1] {
@@ -1483,6 +1673,10 @@ simpl_fun! {
   fn gt_ge(op, args) {
     arity!( format!("{}", op) => args, 2 ) ;
 
+    if let Some(res) = lift_ite(* op, args) {
+      return Some(res)
+    }
+
     if args[0] == args[1] {
       return Some(
         NormRes::Term( term::bool( * op == Op::Ge ) )
@@ -1821,6 +2015,11 @@ simpl_fun! {
           Some(
             NormRes::Term( term::real( -r ) )
           )
+        } else if let Some(kid) = first.cmul_inspect().and_then(
+          |(cst, kid)| if cst.is_minus_one() { Some( kid.clone() ) } else { None }
+        ) {
+          // Double negation.
+          Some( NormRes::Term(kid) )
         } else {
           let minus_one = if first.typ() == typ::int() {
             term::int( - Int::one() )
@@ -342,6 +342,32 @@ impl TTermSet {
         &mut self.preds
     }
 
+    /// Evaluates the conjunction of the top terms, their disjunction if `disj`.
+    ///
+    /// `pred` evaluates the predicate applications. `None` if the result is unknown.
+    pub fn eval<F>(&self, vals: &VarVals, disj: bool, pred: &mut F) -> Res<Option<bool>>
+    where
+        F: FnMut(PrdIdx, &VarVals) -> Res<Option<bool>>,
+    {
+        let terms = eval_bool(
+            self.terms
+                .iter()
+                .map(|term| -> Res<Option<bool>> { term.eval(&**vals)?.to_bool() }),
+            disj,
+        )?;
+        if terms == Some(disj) {
+            return Ok(terms);
+        }
+        let preds = eval_bool(
+            self.preds
+                .iter()
+                .flat_map(|(p, argss)| argss.iter().map(move |args| (*p, args)))
+                .map(|(p, args)| eval_args(args, vals).and_then(|args| pred(p, &args))),
+            disj,
+        )?;
+        eval_bool(vec![Ok(terms), Ok(preds)].into_iter(), disj)
+    }
+
     /// True if `self` is a subset of `that`.
     #[inline]
     pub fn is_subset_of(&self, that: &Self) -> bool {
@@ -490,6 +516,26 @@ impl TTermSet {
         write_var: WriteVar,
         write_pred: WritePrd,
     ) -> IoRes<()>
+    where
+        W: Write,
+        WriteVar: Fn(&mut W, VarIdx) -> IoRes<()>,
+        WritePrd: Fn(&mut W, PrdIdx, &VarTerms) -> IoRes<()>,
+    {
+        self.write_with(w, sep, write_var, write_pred, None)
+    }
+
+    /// Writes all top terms with some separator and optional let-bindings.
+    ///
+    /// The bindings only apply to the terms, `write_pred` is responsible for using them in the
+    /// arguments of the predicate applications.
+    pub fn write_with<W, WriteVar, WritePrd>(
+        &self,
+        w: &mut W,
+        sep: &str,
+        write_var: WriteVar,
+        write_pred: WritePrd,
+        bindings: Option<&Bindings>,
+    ) -> IoRes<()>
     where
         W: Write,
         WriteVar: Fn(&mut W, VarIdx) -> IoRes<()>,
@@ -509,7 +555,7 @@ impl TTermSet {
 
         for term in &self.terms {
             write_sep!();
-            term.write(w, &write_var)?
+            term.write_with(w, &write_var, bindings)?
         }
 
         for (pred, argss) in &self.preds {
@@ -607,6 +653,33 @@ fn remove_vars_from_pred_apps(apps: &mut PrdHMap<VarTermsSet>, to_keep: &PrdHMap
     }
 }
 
+/// Evaluates the arguments of a predicate application.
+fn eval_args(args: &VarTerms, vals: &VarVals) -> Res<VarVals> {
+    let mut res = VarMap::with_capacity(args.len());
+    for arg in args.iter() {
+        res.push(arg.eval(&**vals)?)
+    }
+    Ok(var_to::vals::new(res))
+}
+
+/// Three-valued conjunction of some booleans, disjunction if `disj`.
+///
+/// Stops at the first value deciding the result.
+fn eval_bool<I>(vals: I, disj: bool) -> Res<Option<bool>>
+where
+    I: Iterator<Item = Res<Option<bool>>>,
+{
+    let mut res = Some(!disj);
+    for val in vals {
+        match val? {
+            Some(b) if b == disj => return Ok(Some(disj)),
+            Some(_) => (),
+            None => res = None,
+        }
+    }
+    Ok(res)
+}
+
 /// A formula composed of top terms.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TTerms {
@@ -739,6 +812,91 @@ impl TTerms {
         }
     }
 
+    /// Evaluates some top terms.
+    ///
+    /// `pred` evaluates the predicate applications. `None` if the result is unknown. Fails on
+    /// quantified top terms.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// #[macro_use]
+    /// extern crate hoice;
+    /// use hoice::{ common::*, var_vals };
+    ///
+    /// # fn main() {
+    /// let (v_0, v_1) = (term::int_var(0), term::int_var(1));
+    /// let mut conj = TTermSet::new();
+    /// conj.insert_term(term::ge(v_0.clone(), term::int(0)));
+    /// conj.insert_pred_app(0.into(), var_to::terms::new(vec![v_1].into()));
+    /// let tterms = TTerms::dnf(vec![
+    ///     (None, conj),
+    ///     (None, TTermSet::of_term(term::ge(v_0, term::int(7)))),
+    /// ]);
+    ///
+    /// // The predicate is only true on `1`.
+    /// let mut pred = |_, args: &VarVals| Ok(Some(*args == var_vals!( (int 1) )));
+    /// let mut eval = |vals: VarVals| tterms.eval(&vals, &mut pred).unwrap();
+    /// assert_eq! { eval(var_vals!( (int 3) (int 1) )), Some(true) }
+    /// assert_eq! { eval(var_vals!( (int 3) (int 0) )), Some(false) }
+    /// assert_eq! { eval(var_vals!( (int 9) (int 0) )), Some(true) }
+    /// assert_eq! { eval(var_vals!( (int (-1)) (int 1) )), Some(false) }
+    /// # }
+    /// ```
+    pub fn eval<F>(&self, vals: &VarVals, pred: &mut F) -> Res<Option<bool>>
+    where
+        F: FnMut(PrdIdx, &VarVals) -> Res<Option<bool>>,
+    {
+        match *self {
+            TTerms::True => Ok(Some(true)),
+            TTerms::False => Ok(Some(false)),
+
+            TTerms::Conj { ref quant, .. } | TTerms::Disj { ref quant, .. } if quant.is_some() => {
+                bail!("cannot evaluate quantified top terms")
+            }
+
+            TTerms::Conj { ref tterms, .. } => tterms.eval(vals, false, pred),
+
+            TTerms::Disj {
+                ref tterms,
+                ref neg_preds,
+                ..
+            } => {
+                let pos = tterms.eval(vals, true, pred)?;
+                if pos == Some(true) {
+                    return Ok(pos);
+                }
+                let neg = eval_bool(
+                    neg_preds
+                        .iter()
+                        .flat_map(|(p, argss)| argss.iter().map(move |args| (*p, args)))
+                        .map(|(p, args)| {
+                            eval_args(args, vals)
+                                .and_then(|args| pred(p, &args))
+                                .map(|val| val.map(|b| !b))
+                        }),
+                    true,
+                )?;
+                eval_bool(vec![Ok(pos), Ok(neg)].into_iter(), true)
+            }
+
+            TTerms::Dnf { ref disj } => {
+                let mut res = Some(false);
+                for (quant, conj) in disj {
+                    if quant.is_some() {
+                        bail!("cannot evaluate quantified top terms")
+                    }
+                    match conj.eval(vals, false, pred)? {
+                        Some(true) => return Ok(Some(true)),
+                        Some(false) => (),
+                        None => res = None,
+                    }
+                }
+                Ok(res)
+            }
+        }
+    }
+
     /// Collects all the functions mentioned in some TTerms.
     pub fn collect_funs(&self, set: &mut BTreeSet<String>) {
         match self {
@@ -891,6 +1049,15 @@ impl TTerms {
         TTerms::Dnf { disj }.simplify()
     }
 
+    /// True if the top terms mention a quantifier.
+    pub fn is_quantified(&self) -> bool {
+        match self {
+            TTerms::True | TTerms::False => false,
+            TTerms::Conj { quant, .. } | TTerms::Disj { quant, .. } => quant.is_some(),
+            TTerms::Dnf { disj } => disj.iter().any(|(quant, _)| quant.is_some()),
+        }
+    }
+
     /// The predicate applications appearing in the top term.
     pub fn pred_apps(&self) -> Vec<&PredApps> {
         let mut res = vec![];
@@ -1083,6 +1250,11 @@ impl TTerms {
                     }
                 }
 
+                // Merge arithmetic bounds.
+                if tterms.terms.len() > 1 && !term::simplify::conj_bounds(&mut tterms.terms) {
+                    return TTerms::fls();
+                }
+
                 // Only keep active quantified variables.
                 let quant = quant.and_then(|quant| {
                     let mut active = VarSet::with_capacity(quant.vars().len() * 2);
@@ -1360,6 +1532,26 @@ impl TTerms {
         write_var: WriteVar,
         write_prd: WritePrd,
     ) -> IoRes<()>
+    where
+        W: Write,
+        WriteVar: Fn(&mut W, VarIdx) -> IoRes<()>,
+        WritePrd: Fn(&mut W, PrdIdx, &VarTerms) -> IoRes<()>,
+    {
+        self.write_with(w, write_var, write_prd, None)
+    }
+
+    /// Writes some top terms using special functions for writing predicates and
+    /// variables, and optional let-bindings.
+    ///
+    /// The bindings only apply to the terms, `write_prd` is responsible for using them in the
+    /// arguments of the predicate applications.
+    pub fn write_with<W, WriteVar, WritePrd>(
+        &self,
+        w: &mut W,
+        write_var: WriteVar,
+        write_prd: WritePrd,
+        bindings: Option<&Bindings>,
+    ) -> IoRes<()>
     where
         W: Write,
         WriteVar: Fn(&mut W, VarIdx) -> IoRes<()>,
@@ -1383,7 +1575,7 @@ impl TTerms {
                     false
                 };
 
-                $tterms.write(w, " ", &write_var, &write_prd)?;
+                $tterms.write_with(w, " ", &write_var, &write_prd, bindings)?;
 
                 if close_and {
                     write!(w, ")")?
@@ -1427,7 +1619,7 @@ impl TTerms {
                     false
                 };
 
-                tterms.write(w, " ", &write_var, &write_prd)?;
+                tterms.write_with(w, " ", &write_var, &write_prd, bindings)?;
 
                 let mut sep = !tterms.is_empty() && !neg_preds.is_empty();
 
@@ -0,0 +1,376 @@
+//! Resolution-style certificates for unsat results.
+//!
+//! A certificate is a sequence of ground Horn resolution steps over the original instance. Each
+//! step instantiates a clause with a substitution of its variables such that its LHS terms
+//! evaluate to `true` and each of its LHS predicate applications is the atom derived by a previous
+//! step, its *premises*. The step derives the ground atom the RHS of the clause evaluates to, or
+//! `false` for the last step. The leaves of the derivation are the entry points, which are
+//! instances of positive clauses and have no premises.
+//!
+//! Checking a certificate only requires evaluating ground terms.
+
+use crate::{common::*, data::sample::Sample, unsat_core::entry_points::Entry};
+
+/// A ground resolution step.
+#[derive(Debug, Clone)]
+pub struct Step {
+    /// Clause of the original instance this step instantiates.
+    pub clause: ClsIdx,
+    /// Values of the variables of the clause.
+    pub subst: Cex,
+    /// Steps deriving the LHS predicate applications of the clause.
+    pub premises: Vec<usize>,
+    /// Ground atom derived, `None` if the clause is negative.
+    pub atom: Option<Sample>,
+}
+
+/// A resolution certificate: steps leading from the entry points to the empty clause.
+#[derive(Debug, Clone)]
+pub struct Certificate {
+    /// Steps, the last one derives `false`.
+    pub steps: Vec<Step>,
+}
+
+impl Certificate {
+    /// Builds a certificate from some entry points, expressed on the original instance.
+    ///
+    /// Starts from the entry points and saturates them using the non-positive clauses, negative
+    /// clauses first, for at most `conf.proof_recon_depth` rounds.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[macro_use]
+    /// extern crate hoice;
+    /// use hoice::{
+    ///     common::*, data::sample::Sample, parse,
+    ///     unsat_core::{certificate::Certificate, entry_points::{Entry, SampleSet}},
+    ///     var_vals
+    /// };
+    ///
+    /// # fn main() {
+    /// let instance = parse::instance("
+    ///   (declare-fun p ( Int ) Bool)
+    ///   (assert (forall ( (n Int) ) (=> (= n 0) (p n))))
+    ///   (assert (forall ( (n Int) ) (=> (p n) (p (+ n 1)))))
+    ///   (assert (forall ( (n Int) ) (=> (and (p n) (= n 2)) false)))
+    /// ");
+    ///
+    /// let mut samples = SampleSet::new();
+    /// samples.insert(Sample::new(0.into(), var_vals!( (int 0) )));
+    /// let cert = Certificate::new(&instance, &Entry::new(samples)).unwrap();
+    ///
+    /// // `(p 0)`, `(p 1)`, `(p 2)`, `false`.
+    /// assert_eq! { cert.steps.len(), 4 }
+    /// assert! { cert.steps[0].premises.is_empty() }
+    /// assert_eq! { cert.steps[3].premises, vec![2] }
+    /// assert! { cert.steps[3].atom.is_none() }
+    ///
+    /// // Entry points can be partial samples.
+    /// let instance = parse::instance("
+    ///   (declare-fun q ( Int Int ) Bool)
+    ///   (assert (forall ( (n Int) (m Int) ) (=> (= n 0) (q n m))))
+    ///   (assert (forall ( (n Int) (m Int) ) (=> (q n m) (q (+ n 1) m))))
+    ///   (assert (forall ( (n Int) (m Int) ) (=> (and (q n m) (= n 1)) false)))
+    /// ");
+    ///
+    /// let mut samples = SampleSet::new();
+    /// samples.insert(Sample::new(0.into(), var_vals!( (int 0) (val::none(typ::int())) )));
+    /// let cert = Certificate::new(&instance, &Entry::new(samples)).unwrap();
+    ///
+    /// // `(q 0 _)`, `(q 1 _)`, `false`.
+    /// assert_eq! { cert.steps.len(), 3 }
+    /// assert_eq! { cert.steps[2].premises, vec![1] }
+    /// # }
+    /// ```
+    pub fn new(original: &Instance, entry: &Entry) -> Res<Self> {
+        let mut solver = conf
+            .solver
+            .spawn("certificate", smt::FullParser, original)?;
+        let mut builder = Builder {
+            original,
+            solver: &mut solver,
+            steps: vec![],
+            facts: PrdHMap::new(),
+        };
+        let res = builder.work(entry);
+        solver.kill()?;
+        res
+    }
+
+    /// Writes the certificate.
+    ///
+    /// Writes the clauses used first, then one `(step ...)` per step. Clauses are identified by
+    /// the name of their assertion if it is `:named` and yields only this clause, by their index
+    /// otherwise.
+    pub fn write<W: Write>(&self, w: &mut W, original: &Instance) -> Res<()> {
+        let clauses: ClsSet = self.steps.iter().map(|step| step.clause).collect();
+        let mut clauses: Vec<_> = clauses.into_iter().collect();
+        clauses.sort();
+
+        // Number of clauses of each name, only names identifying a single clause are used.
+        let mut name_counts: BTreeMap<&String, usize> = BTreeMap::new();
+        for clause in original.clauses().iter() {
+            if let Some(name) = original.name_of_old_clause(clause.from()) {
+                *name_counts.entry(name).or_insert(0) += 1
+            }
+        }
+        let label = |clause: ClsIdx| match original.name_of_old_clause(original[clause].from()) {
+            Some(name) if name_counts.get(name) == Some(&1) => name.clone(),
+            _ => clause.to_string(),
+        };
+
+        writeln!(w, "(certificate")?;
+        for clause in clauses {
+            writeln!(w, "  (clause {}", label(clause))?;
+            for line in original[clause].to_string_info(original.preds())?.lines() {
+                writeln!(w, "    {}", line)?
+            }
+            writeln!(w, "  )")?
+        }
+        for (idx, step) in self.steps.iter().enumerate() {
+            write!(w, "  (step {} (clause {}) (subst", idx, label(step.clause))?;
+            for var in original[step.clause].vars() {
+                if var.active {
+                    write!(w, " ({} {})", var.name, step.subst[var.idx])?
+                }
+            }
+            write!(w, ") (premises")?;
+            for premise in &step.premises {
+                write!(w, " {}", premise)?
+            }
+            write!(w, ") ")?;
+            if let Some(atom) = step.atom.as_ref() {
+                if atom.args.is_empty() {
+                    write!(w, "{}", original[atom.pred])?
+                } else {
+                    write!(w, "({} {})", original[atom.pred], atom.args)?
+                }
+            } else {
+                write!(w, "false")?
+            }
+            writeln!(w, ")")?
+        }
+        writeln!(w, ")")?;
+        Ok(())
+    }
+}
+
+/// Certificate builder.
+struct Builder<'a> {
+    /// Original instance.
+    original: &'a Instance,
+    /// Solver.
+    solver: &'a mut Solver<smt::FullParser>,
+    /// Steps so far.
+    steps: Vec<Step>,
+    /// Atoms derived so far, and the step deriving them.
+    facts: PrdHMap<Vec<(VarVals, usize)>>,
+}
+
+impl<'a> Builder<'a> {
+    /// Builds the certificate.
+    fn work(&mut self, entry: &Entry) -> Res<Certificate> {
+        if let Some(clause) = entry.clause {
+            if let Some(step) = self.step(clause)? {
+                self.register(step);
+                return Ok(Certificate {
+                    steps: ::std::mem::replace(&mut self.steps, vec![]),
+                });
+            }
+        }
+
+        for sample in &entry.samples {
+            self.leaf(sample)?
+        }
+
+        let mut clauses: Vec<ClsIdx> = self.original.neg_clauses().iter().cloned().collect();
+        clauses.sort();
+        let mut imp_clauses: Vec<ClsIdx> = self.original.imp_clauses().iter().cloned().collect();
+        imp_clauses.sort();
+        clauses.extend(imp_clauses);
+
+        for _ in 0..conf.proof_recon_depth {
+            let mut progress = false;
+            for clause in &clauses {
+                if let Some(step) = self.step(*clause)? {
+                    let is_neg = step.atom.is_none();
+                    self.register(step);
+                    if is_neg {
+                        return Ok(Certificate {
+                            steps: ::std::mem::replace(&mut self.steps, vec![]),
+                        });
+                    }
+                    progress = true
+                }
+            }
+            if !progress {
+                bail!("could not derive the empty clause from the entry points")
+            }
+        }
+
+        bail!(
+            "certificate depth exceeded, see `{}`",
+            conf.emph("--proof_recon_depth")
+        )
+    }
+
+    /// Registers a step, and the atom it derives.
+    fn register(&mut self, step: Step) {
+        let idx = self.steps.len();
+        if let Some(atom) = step.atom.as_ref() {
+            self.facts
+                .entry(atom.pred)
+                .or_insert_with(Vec::new)
+                .push((atom.args.clone(), idx))
+        }
+        self.steps.push(step)
+    }
+
+    /// Justifies an entry point with a positive clause.
+    fn leaf(&mut self, sample: &Sample) -> Res<()> {
+        let mut clauses: Vec<ClsIdx> = self
+            .original
+            .rhs_clauses_of(sample.pred)
+            .iter()
+            .filter(|clause| self.original[**clause].lhs_preds().is_empty())
+            .cloned()
+            .collect();
+        clauses.sort();
+
+        for clause in clauses {
+            let (_, args) = self.original[clause]
+                .rhs()
+                .expect("positive clauses necessarily have a RHS");
+            let constraint = Self::matches(args, &sample.args);
+            if let Some(step) = self.check(clause, constraint)? {
+                self.register(step);
+                return Ok(());
+            }
+        }
+
+        bail!(
+            "entry point ({} {}) is not an instance of a positive clause",
+            self.original[sample.pred],
+            sample.args
+        )
+    }
+
+    /// Tries to instantiate a non-positive clause using the atoms derived so far.
+    ///
+    /// The RHS atom, if any, must be new.
+    fn step(&mut self, clause: ClsIdx) -> Res<Option<Step>> {
+        let mut constraints = vec![];
+        for (pred, argss) in self.original[clause].lhs_preds() {
+            let facts = if let Some(facts) = self.facts.get(pred) {
+                facts
+            } else {
+                return Ok(None);
+            };
+            for args in argss {
+                constraints.push(term::or(
+                    facts
+                        .iter()
+                        .map(|(vals, _)| Self::matches(args, vals))
+                        .collect(),
+                ))
+            }
+        }
+        if let Some((pred, args)) = self.original[clause].rhs() {
+            if let Some(facts) = self.facts.get(&pred) {
+                for (vals, _) in facts {
+                    constraints.push(term::not(Self::matches(args, vals)))
+                }
+            }
+        }
+        self.check(clause, term::and(constraints))
+    }
+
+    /// Term stating that some arguments are equal to some values.
+    ///
+    /// Unknown values are ignored.
+    fn matches(args: &VarTerms, vals: &VarVals) -> Term {
+        term::and(
+            args.iter()
+                .zip(vals.iter())
+                .filter_map(|(arg, val)| val.to_term().map(|val| term::eq(arg.clone(), val)))
+                .collect(),
+        )
+    }
+
+    /// Checks a clause under some constraint, yields a step if sat.
+    fn check(&mut self, clause: ClsIdx, constraint: Term) -> Res<Option<Step>> {
+        self.solver.push(1)?;
+        self.original[clause].declare(self.solver)?;
+        for term in self.original[clause].lhs_terms() {
+            self.solver.assert(&smt::SmtTerm::new(term))?;
+        }
+        self.solver.assert(&smt::SmtTerm::new(&constraint))?;
+
+        let original = self.original;
+        let sat = self
+            .solver
+            .check_sat()
+            .map_err(|e| smt::solver_failure(e.into(), "certificate", Some(clause), original))?;
+        let model = if sat {
+            let model = self.solver.get_model()?;
+            Some(smt::FullParser.fix_model(model)?)
+        } else {
+            None
+        };
+        self.solver.pop(1)?;
+
+        let model = if let Some(model) = model {
+            Cex::of_model(self.original[clause].vars(), model, false)?
+        } else {
+            return Ok(None);
+        };
+
+        // Facts can be partial, a fact is a premise if it subsumes the application.
+        let mut premises = vec![];
+        for (pred, argss) in self.original[clause].lhs_preds() {
+            for args in argss {
+                let vals = Self::eval(args, &model)?;
+                let premise = self
+                    .facts
+                    .get(pred)
+                    .and_then(|facts| facts.iter().find(|(fact, _)| fact.subsumes(&vals)))
+                    .map(|(_, idx)| *idx);
+                if let Some(premise) = premise {
+                    premises.push(premise)
+                } else {
+                    bail!(
+                        "certificate: no premise for ({} {}) in clause #{}",
+                        self.original[*pred],
+                        vals,
+                        clause
+                    )
+                }
+            }
+        }
+        premises.sort();
+        premises.dedup();
+
+        let atom = if let Some((pred, args)) = self.original[clause].rhs() {
+            Some(Sample::new(pred, Self::eval(args, &model)?))
+        } else {
+            None
+        };
+
+        Ok(Some(Step {
+            clause,
+            subst: model,
+            premises,
+            atom,
+        }))
+    }
+
+    /// Evaluates some arguments.
+    fn eval(args: &VarTerms, model: &Cex) -> Res<VarVals> {
+        let mut vals = VarMap::with_capacity(args.len());
+        for arg in args.iter() {
+            vals.push(arg.eval(model)?)
+        }
+        Ok(var_to::vals::new(vals))
+    }
+}
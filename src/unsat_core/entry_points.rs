@@ -24,6 +24,92 @@ pub type SampleMap<T> = BTreeMap<Sample, T>;
 /// Type of the solver used for reconstruction.
 type Slvr = Solver<smt::FullParser>;
 
+/// Compares two samples by predicate name, and then by argument values.
+///
+/// Unlike the `Ord` implementation of [`Sample`], this ordering does not depend on the index of
+/// the predicates, which preprocessing can change. It is the order in which entry points are
+/// printed.
+///
+/// Arithmetic values are compared numerically, unknown values come first.
+///
+/// [`Sample`]: ../../data/sample/struct.Sample.html (Sample struct)
+///
+/// # Examples
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate hoice;
+/// use std::cmp::Ordering::*;
+/// use hoice::{
+///     common::*, data::sample::Sample, parse, unsat_core::entry_points::cmp_samples,
+///     var_vals
+/// };
+///
+/// # fn main() {
+/// let instance = parse::instance("
+///   (declare-fun q ( Int ) Bool)
+///   (declare-fun p ( Int ) Bool)
+/// ");
+/// let (q, p): (PrdIdx, PrdIdx) = (0.into(), 1.into());
+///
+/// let q_1 = Sample::new(q, var_vals!( (int 1) ));
+/// let p_2 = Sample::new(p, var_vals!( (int 2) ));
+/// let p_10 = Sample::new(p, var_vals!( (int 10) ));
+/// let p_none = Sample::new(p, var_vals!( (val::none(typ::int())) ));
+///
+/// assert_eq! { cmp_samples(&instance, &q_1, &p_2), Greater }
+/// assert_eq! { cmp_samples(&instance, &p_2, &p_10), Less }
+/// assert_eq! { cmp_samples(&instance, &p_none, &p_2), Less }
+/// assert_eq! { cmp_samples(&instance, &p_10, &p_10), Equal }
+/// # }
+/// ```
+pub fn cmp_samples(instance: &Instance, s_1: &Sample, s_2: &Sample) -> ::std::cmp::Ordering {
+    use std::cmp::Ordering::*;
+
+    /// Compares two values.
+    fn cmp_vals(v_1: &Val, v_2: &Val) -> ::std::cmp::Ordering {
+        match (v_1.get(), v_2.get()) {
+            (val::RVal::N(_), val::RVal::N(_)) => Equal,
+            (val::RVal::N(_), _) => Less,
+            (_, val::RVal::N(_)) => Greater,
+            (val::RVal::B(b_1), val::RVal::B(b_2)) => b_1.cmp(b_2),
+            (val::RVal::I(i_1), val::RVal::I(i_2)) => i_1.cmp(i_2),
+            (val::RVal::I(_), val::RVal::R(_))
+            | (val::RVal::R(_), val::RVal::I(_))
+            | (val::RVal::R(_), val::RVal::R(_)) => match (v_1.to_real(), v_2.to_real()) {
+                (Ok(Some(r_1)), Ok(Some(r_2))) => r_1.cmp(&r_2),
+                _ => v_1.to_string().cmp(&v_2.to_string()),
+            },
+            _ => v_1.to_string().cmp(&v_2.to_string()),
+        }
+    }
+
+    instance[s_1.pred]
+        .name
+        .cmp(&instance[s_2.pred].name)
+        .then_with(|| {
+            for (v_1, v_2) in s_1.args.iter().zip(s_2.args.iter()) {
+                match cmp_vals(v_1, v_2) {
+                    Equal => (),
+                    res => return res,
+                }
+            }
+            s_1.args.len().cmp(&s_2.args.len())
+        })
+}
+
+/// Sorts some samples with [`cmp_samples`].
+///
+/// [`cmp_samples`]: fn.cmp_samples.html (cmp_samples function)
+pub fn sorted_samples<'a, Samples>(instance: &Instance, samples: Samples) -> Vec<&'a Sample>
+where
+    Samples: IntoIterator<Item = &'a Sample>,
+{
+    let mut res: Vec<_> = samples.into_iter().collect();
+    res.sort_by(|s_1, s_2| cmp_samples(instance, s_1, s_2));
+    res
+}
+
 /// Entry point extraction structure.
 ///
 /// This type distinguishes two kinds of positive samples: *real* ones and the rest. A *real*
@@ -37,12 +123,18 @@ type Slvr = Solver<smt::FullParser>;
 ///
 /// This structures remembers the real positive samples, and has a map associating a non-real
 /// positive sample `P(s)` to the real positive samples that led to classifying `P(s)` as positive.
+///
+/// In lenient mode (`conf.proof_lenient`), dependencies to unknown positive samples are recorded:
+/// the unknown samples are treated as *unresolved* real positive samples, and entries mentioning
+/// them are partial.
 #[derive(Debug, Clone, Default)]
 pub struct EntryPoints {
     /// Real positive samples.
     real_pos_samples: SampleSet,
     /// Maps positive samples that are not real to the real positive samples to infer them.
     pos_sample_map: SampleMap<SampleSet>,
+    /// Unknown samples some dependencies were registered to, in lenient mode.
+    unresolved: SampleSet,
 }
 
 impl EntryPoints {
@@ -51,6 +143,7 @@ impl EntryPoints {
         EntryPoints {
             real_pos_samples: SampleSet::new(),
             pos_sample_map: SampleMap::new(),
+            unresolved: SampleSet::new(),
         }
     }
 
@@ -62,17 +155,36 @@ impl EntryPoints {
     pub fn map(&self) -> &SampleMap<SampleSet> {
         &self.pos_sample_map
     }
+    /// Accessor for the unresolved samples.
+    pub fn unresolved(&self) -> &SampleSet {
+        &self.unresolved
+    }
+
+    /// Entry for some real positive samples, marks the unresolved ones.
+    fn entry_of(&self, samples: SampleSet) -> Entry {
+        let unresolved = samples.intersection(&self.unresolved).cloned().collect();
+        Entry {
+            samples,
+            unresolved,
+            clause: None,
+        }
+    }
 
     /// String representation.
+    ///
+    /// Samples are ordered by [`cmp_samples`], so that the result does not depend on predicate
+    /// indices.
+    ///
+    /// [`cmp_samples`]: fn.cmp_samples.html (cmp_samples function)
     pub fn to_string(&self, instance: &Instance) -> String {
         let mut s = "real_pos_samples:".to_string();
-        for sample in &self.real_pos_samples {
+        for sample in sorted_samples(instance, &self.real_pos_samples) {
             s += &format!("\n  ({} {})", instance[sample.pred], sample.args)
         }
         s += "\npos_sample_map:";
-        for (sample, set) in &self.pos_sample_map {
+        for sample in sorted_samples(instance, self.pos_sample_map.keys()) {
             s += &format!("\n  ({} {})", instance[sample.pred], sample.args);
-            for sample in set {
+            for sample in sorted_samples(instance, &self.pos_sample_map[sample]) {
                 s += &format!("\n  -> ({} {})", instance[sample.pred], sample.args)
             }
         }
@@ -109,7 +221,10 @@ impl EntryPoints {
 
     /// Registers a dependency between the RHS of an implication constraint and a positive sample.
     ///
-    /// Fails if the dependency sample (second argument) is unknown (see below).
+    /// Fails if the dependency sample (second argument) is unknown (see below), unless
+    /// `conf.proof_lenient`. See [`register_dep_with`].
+    ///
+    /// [`register_dep_with`]: #method.register_dep_with (register_dep_with function)
     ///
     /// # Examples
     ///
@@ -174,6 +289,50 @@ impl EntryPoints {
     /// # }
     /// ```
     pub fn register_dep(&mut self, sample: Sample, dep: &Sample) -> Res<()> {
+        self.register_dep_with(sample, dep, conf.proof_lenient)
+    }
+
+    /// Registers a dependency, records dependencies to unknown samples if `lenient`.
+    ///
+    /// Same as [`register_dep`] when not `lenient`. Otherwise, an unknown dependency sample is
+    /// recorded as an unresolved sample, and acts as a real positive sample from then on.
+    ///
+    /// [`register_dep`]: #method.register_dep (register_dep function)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[macro_use]
+    /// extern crate hoice;
+    /// use hoice::{
+    ///     common::*, unsat_core::entry_points::EntryPoints, data::sample::Sample,
+    ///     var_vals
+    /// } ;
+    ///
+    /// # fn main() {
+    /// let mut entry = EntryPoints::new();
+    /// let pred: PrdIdx = 0.into();
+    /// let s_1 = &Sample::new(pred, var_vals!( (int 1) (bool true) ));
+    /// entry.register(s_1.clone());
+    ///
+    /// // `s_2` is unknown.
+    /// let s_2 = &Sample::new(pred, var_vals!( (int 2) (bool true) ));
+    /// let s_3 = &Sample::new(pred, var_vals!( (int 3) (bool true) ));
+    /// entry.register_dep_with(s_3.clone(), s_1, true).unwrap();
+    /// entry.register_dep_with(s_3.clone(), s_2, true).unwrap();
+    /// assert! { entry.unresolved().contains(s_2) }
+    ///
+    /// let s_3_dep = entry.entry_points_of(s_3).unwrap();
+    /// assert! { s_3_dep.is_partial() }
+    /// assert_eq! { s_3_dep.samples.len(), 2 }
+    /// assert_eq! { s_3_dep.unresolved.len(), 1 }
+    /// assert! { s_3_dep.unresolved.contains(s_2) }
+    ///
+    /// let s_1_dep = entry.entry_points_of(s_1).unwrap();
+    /// assert! { !s_1_dep.is_partial() }
+    /// # }
+    /// ```
+    pub fn register_dep_with(&mut self, sample: Sample, dep: &Sample, lenient: bool) -> Res<()> {
         let mut set = self
             .pos_sample_map
             .remove(&sample)
@@ -189,19 +348,16 @@ impl EntryPoints {
 
         if let Some(res) = real_dep {
             set.insert(res);
+        } else if let Some(deps) = self.pos_sample_map.get(dep) {
+            set.extend(deps.iter().cloned())
+        } else if lenient {
+            log! { @3 "unresolved dependency to unknown positive sample ({})", dep }
+            self.unresolved.insert(dep.clone());
+            set.insert(dep.clone());
         } else {
-            set.extend(
-                self.pos_sample_map
-                    .get(dep)
-                    .ok_or_else::<Error, _>(|| {
-                        format!(
-                            "trying to register dependency to unknown positive sample ({})",
-                            dep
-                        )
-                        .into()
-                    })?
-                    .iter()
-                    .cloned(),
+            bail!(
+                "trying to register dependency to unknown positive sample ({})",
+                dep
             )
         }
 
@@ -275,6 +431,7 @@ impl EntryPoints {
         for (sample, set) in other.pos_sample_map {
             self.pos_sample_map.entry(sample).or_insert(set);
         }
+        self.unresolved.extend(other.unresolved)
     }
 
     /// Retrieves the real positive samples corresponding to a sample.
@@ -315,13 +472,13 @@ impl EntryPoints {
     /// # }
     /// ```
     pub fn entry_points_of(&self, sample: &Sample) -> Res<Entry> {
-        if self.real_pos_samples.contains(sample) {
+        if self.real_pos_samples.contains(sample) || self.unresolved.contains(sample) {
             let samples: SampleSet = vec![sample.clone()].into_iter().collect();
-            return Ok(samples.into());
+            return Ok(self.entry_of(samples));
         }
         self.pos_sample_map
             .get(sample)
-            .map(|entry| entry.clone().into())
+            .map(|entry| self.entry_of(entry.clone()))
             .ok_or_else::<Error, _>(|| {
                 format!(
                     "trying to recover entry points for unknown sample ({})",
@@ -330,6 +487,118 @@ impl EntryPoints {
                 .into()
             })
     }
+
+    /// Partitions the samples into independent groups of entry points.
+    ///
+    /// Two samples are in the same group if they (transitively) share some real positive sample
+    /// in their ancestry. Each group is represented by a *root*: the sample of the group
+    /// depending on the most real positive samples, *i.e.* the one closest to a contradiction.
+    /// Real positive samples no other sample depends on are their own root.
+    ///
+    /// Groups are sorted by root with [`cmp_samples`], which also breaks ties between root
+    /// candidates, so that the result does not depend on predicate indices.
+    ///
+    /// [`cmp_samples`]: fn.cmp_samples.html (cmp_samples function)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[macro_use]
+    /// extern crate hoice;
+    /// use hoice::{
+    ///     common::*, unsat_core::entry_points::EntryPoints, data::sample::Sample,
+    ///     parse, var_vals
+    /// } ;
+    ///
+    /// # fn main() {
+    /// let instance = parse::instance("
+    ///   (declare-fun q ( Int Bool ) Bool)
+    ///   (declare-fun p ( Int Bool ) Bool)
+    /// ");
+    /// let mut entry = EntryPoints::new();
+    /// let (q, pred): (PrdIdx, PrdIdx) = (0.into(), 1.into());
+    /// let s_0 = &Sample::new(q, var_vals!( (int 0) (bool true) ));
+    /// entry.register(s_0.clone());
+    /// let s_1 = &Sample::new(pred, var_vals!( (int 1) (bool true) ));
+    /// entry.register(s_1.clone());
+    /// let s_2 = &Sample::new(pred, var_vals!( (int 2) (bool true) ));
+    /// entry.register(s_2.clone());
+    /// let s_3 = &Sample::new(pred, var_vals!( (int 3) (bool true) ));
+    /// entry.register(s_3.clone());
+    ///
+    /// let s_4 = &Sample::new(pred, var_vals!( (int 4) (bool true) ));
+    /// entry.register_dep(s_4.clone(), s_1).unwrap();
+    /// let s_5 = &Sample::new(pred, var_vals!( (int 5) (bool true) ));
+    /// entry.register_dep(s_5.clone(), s_2).unwrap();
+    /// // s_1 and s_2 are both ancestors of s_6
+    /// let s_6 = &Sample::new(pred, var_vals!( (int 6) (bool true) ));
+    /// entry.register_dep(s_6.clone(), s_4).unwrap();
+    /// entry.register_dep(s_6.clone(), s_5).unwrap();
+    ///
+    /// let entries = entry.entries_by_root(&instance);
+    /// assert_eq! { entries.len(), 3 }
+    /// // s_3 is independent from the rest
+    /// assert_eq! { &entries[0].0, s_3 }
+    /// assert_eq! { entries[0].1.samples.len(), 1 }
+    /// assert! { entries[0].1.samples.contains(s_3) }
+    /// assert_eq! { &entries[1].0, s_6 }
+    /// assert_eq! { entries[1].1.samples.len(), 2 }
+    /// assert! { entries[1].1.samples.contains(s_1) }
+    /// assert! { entries[1].1.samples.contains(s_2) }
+    /// // `q` comes after `p` even though its index is lower
+    /// assert_eq! { &entries[2].0, s_0 }
+    /// # }
+    /// ```
+    pub fn entries_by_root(&self, instance: &Instance) -> Vec<(Sample, Entry)> {
+        // Real positive samples of each group, and the samples of the group.
+        let mut groups: Vec<(SampleSet, Vec<&Sample>)> = vec![];
+
+        for (sample, deps) in &self.pos_sample_map {
+            let mut real = deps.clone();
+            let mut samples = vec![sample];
+            let mut cnt = 0;
+            while cnt < groups.len() {
+                if groups[cnt].0.is_disjoint(&real) {
+                    cnt += 1
+                } else {
+                    let (other_real, other_samples) = groups.swap_remove(cnt);
+                    real.extend(other_real);
+                    samples.extend(other_samples)
+                }
+            }
+            groups.push((real, samples))
+        }
+
+        for sample in &self.real_pos_samples {
+            if groups.iter().all(|(real, _)| !real.contains(sample)) {
+                let real: SampleSet = vec![sample.clone()].into_iter().collect();
+                groups.push((real, vec![sample]))
+            }
+        }
+
+        let mut res: Vec<_> = groups
+            .into_iter()
+            .map(|(real, samples)| {
+                let dep_count = |sample: &Sample| {
+                    self.pos_sample_map
+                        .get(sample)
+                        .map(|deps| deps.len())
+                        .unwrap_or(1)
+                };
+                let root = samples
+                    .into_iter()
+                    .max_by(|s_1, s_2| {
+                        dep_count(s_1)
+                            .cmp(&dep_count(s_2))
+                            .then_with(|| cmp_samples(instance, s_1, s_2))
+                    })
+                    .expect("groups always have at least one sample");
+                (root.clone(), self.entry_of(real))
+            })
+            .collect();
+        res.sort_by(|(s_1, _), (s_2, _)| cmp_samples(instance, s_1, s_2));
+        res
+    }
 }
 
 /// Entry points leading to a contradiction.
@@ -337,6 +606,14 @@ impl EntryPoints {
 pub struct Entry {
     /// Positive samples leading to a contradiction.
     pub samples: SampleSet,
+    /// Samples of `samples` that are not known to be positive, see `conf.proof_lenient`.
+    ///
+    /// The entry points are a partial witness if this is not empty.
+    pub unresolved: SampleSet,
+    /// Clause of the original instance that is falsifiable on its own, if any.
+    ///
+    /// Such a clause has no predicate applications, the entry points are empty.
+    pub clause: Option<ClsIdx>,
 }
 
 impl From<SampleSet> for Entry {
@@ -348,14 +625,32 @@ impl From<SampleSet> for Entry {
 impl Entry {
     /// Constructor.
     pub fn new(samples: SampleSet) -> Self {
-        Entry { samples }
+        Entry {
+            samples,
+            unresolved: SampleSet::new(),
+            clause: None,
+        }
+    }
+
+    /// Entry for a clause of the original instance that is falsifiable on its own.
+    pub fn of_clause(clause: ClsIdx) -> Self {
+        Entry {
+            samples: SampleSet::new(),
+            unresolved: SampleSet::new(),
+            clause: Some(clause),
+        }
+    }
+
+    /// True if some samples are unresolved, *i.e.* the entry points are a partial witness.
+    pub fn is_partial(&self) -> bool {
+        !self.unresolved.is_empty()
     }
 
-    /// Rewrites the entry points in terms of the original signatures.
-    fn rewrite(&self, instance: &Instance) -> Vec<Sample> {
-        let mut samples = vec![];
+    /// Rewrites some samples in terms of the original signatures.
+    fn rewrite(instance: &Instance, samples: &SampleSet) -> Vec<Sample> {
+        let mut res = vec![];
 
-        for Sample { pred, args } in &self.samples {
+        for Sample { pred, args, .. } in samples {
             let pred = *pred;
             let original_sig = instance[pred].original_sig();
             let mut nu_args = VarMap::with_capacity(original_sig.len());
@@ -367,21 +662,183 @@ impl Entry {
                 nu_args[old_var] = val.clone()
             }
             let args = var_to::vals::new(nu_args);
-            samples.push(Sample { pred, args })
+            res.push(Sample::new(pred, args))
         }
 
-        samples
+        res
+    }
+
+    /// JSON representation.
+    ///
+    /// Produces a list of objects, one per sample, with the name of the predicate and its
+    /// arguments. Each argument mentions the name and the type of the variable of the signature
+    /// it corresponds to, and its value in SMT-LIB 2 format. Values that are not known (any value
+    /// works) are serialized as `null`. Unresolved samples have an additional `"unresolved": true`
+    /// field. Samples are ordered by [`cmp_samples`].
+    ///
+    /// The instance should be the one the samples are expressed in, *i.e.* the original instance
+    /// if this entry is the result of [`reconstruct`].
+    ///
+    /// [`reconstruct`]: #method.reconstruct (reconstruct function)
+    /// [`cmp_samples`]: fn.cmp_samples.html (cmp_samples function)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[macro_use]
+    /// extern crate hoice;
+    /// use hoice::{
+    ///     common::*, data::sample::Sample, parse, unsat_core::entry_points::{Entry, SampleSet},
+    ///     var_vals
+    /// };
+    ///
+    /// # fn main() {
+    /// let instance = parse::instance("
+    ///   (declare-fun p_0 ( Int Bool ) Bool)
+    ///   (declare-fun p_1 ( Int ) Bool)
+    ///   (assert (forall ( (n Int) ) (=> (> n 0) (p_0 n true))))
+    ///   (assert (forall ( (n Int) (b Bool) ) (=> (p_0 n b) (p_1 n))))
+    ///   (assert (forall ( (n Int) ) (=> (p_1 n) false)))
+    /// ");
+    ///
+    /// let mut samples = SampleSet::new();
+    /// samples.insert(Sample::new(0.into(), var_vals!( (int 7) (bool true) )));
+    /// samples.insert(Sample::new(1.into(), var_vals!( (val::none(typ::int())) )));
+    /// let entry = Entry::new(samples);
+    ///
+    /// assert_eq! {
+    ///     entry.to_json(&instance), "\
+    /// [
+    ///   { \"pred\": \"p_0\", \"args\": [ \
+    /// { \"var\": \"v_0\", \"typ\": \"Int\", \"val\": \"7\" }, \
+    /// { \"var\": \"v_1\", \"typ\": \"Bool\", \"val\": \"true\" } \
+    /// ] },
+    ///   { \"pred\": \"p_1\", \"args\": [ \
+    /// { \"var\": \"v_0\", \"typ\": \"Int\", \"val\": null } \
+    /// ] }
+    /// ]\
+    ///     "
+    /// }
+    /// # }
+    /// ```
+    pub fn to_json(&self, instance: &Instance) -> String {
+        use crate::data::json::escape;
+
+        let mut s = "[".to_string();
+        let mut first = true;
+        for sample in sorted_samples(instance, &self.samples) {
+            let Sample { pred, args, .. } = sample;
+            s += if first { "\n" } else { ",\n" };
+            first = false;
+            s += &format!(
+                "  {{ \"pred\": {}, \"args\": [",
+                escape(&instance[*pred].name)
+            );
+            let mut first_arg = true;
+            for (var, val) in args.index_iter() {
+                s += if first_arg { " " } else { ", " };
+                first_arg = false;
+                let typ = escape(&val.typ().to_string());
+                let val = if val.is_known() {
+                    escape(&val.to_string())
+                } else {
+                    "null".into()
+                };
+                s += &format!(
+                    "{{ \"var\": {}, \"typ\": {}, \"val\": {} }}",
+                    escape(&var.default_str()),
+                    typ,
+                    val
+                )
+            }
+            s += if first_arg { "]" } else { " ]" };
+            s += if self.unresolved.contains(sample) {
+                ", \"unresolved\": true }"
+            } else {
+                " }"
+            }
+        }
+        s += if first { "]" } else { "\n]" };
+        s
     }
 
     /// Reconstructs some entry points given the original instance.
+    ///
+    /// Minimizes the entry points if `conf.proof_minimize`, see [`reconstruct_with`].
+    ///
+    /// [`reconstruct_with`]: #method.reconstruct_with (reconstruct_with function)
     pub fn reconstruct(&self, instance: &Instance, original: &Instance) -> Res<Self> {
-        let samples = self.rewrite(instance);
+        self.reconstruct_with(instance, original, conf.proof_minimize)
+    }
+
+    /// Reconstructs some entry points given the original instance, minimizes them if asked to.
+    ///
+    /// Minimization greedily drops the samples that can be derived from the remaining ones by a
+    /// non-positive clause of the original instance. The result is subset-minimal in that sense.
+    ///
+    /// Unresolved samples are not reconstructed, they are only rewritten in terms of the original
+    /// signatures and stay unresolved in the result.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// #[macro_use]
+    /// extern crate hoice;
+    /// use hoice::{
+    ///     common::*, data::sample::Sample, parse, unsat_core::entry_points::{Entry, SampleSet},
+    ///     var_vals
+    /// };
+    ///
+    /// # fn main() {
+    /// // The second positive clause is redundant: `(p 1)` follows from `(p 0)`.
+    /// let instance = parse::instance("
+    ///   (declare-fun p ( Int ) Bool)
+    ///   (assert (forall ( (n Int) ) (=> (= n 0) (p n))))
+    ///   (assert (forall ( (n Int) ) (=> (= n 1) (p n))))
+    ///   (assert (forall ( (n Int) ) (=> (and (p n) (>= n 0)) (p (+ n 1)))))
+    ///   (assert (forall ( (n Int) ) (=> (and (p n) (= n 1)) false)))
+    /// ");
+    ///
+    /// let mut samples = SampleSet::new();
+    /// let s_0 = Sample::new(0.into(), var_vals!( (int 0) ));
+    /// samples.insert(s_0.clone());
+    /// samples.insert(Sample::new(0.into(), var_vals!( (int 1) )));
+    /// let entry = Entry::new(samples);
+    ///
+    /// let full = entry.reconstruct_with(&instance, &instance, false).unwrap();
+    /// assert_eq! { full.samples.len(), 2 }
+    ///
+    /// let minimized = entry.reconstruct_with(&instance, &instance, true).unwrap();
+    /// assert_eq! { minimized.samples.len(), 1 }
+    /// assert! { minimized.samples.contains(&s_0) }
+    /// # }
+    /// ```
+    pub fn reconstruct_with(
+        &self,
+        instance: &Instance,
+        original: &Instance,
+        minimize: bool,
+    ) -> Res<Self> {
+        // The clause is already a clause of the original instance.
+        if self.clause.is_some() {
+            return Ok(self.clone());
+        }
+        let resolved: SampleSet = self.samples.difference(&self.unresolved).cloned().collect();
+        let samples = Self::rewrite(instance, &resolved);
         log! { @2 | "reconstructing {} sample(s)", samples.len() }
         let mut solver = conf
             .solver
             .spawn("proof_reconstruction", smt::FullParser, original)?;
-        let samples = Reconstr::new(original, instance, samples, &mut solver).work()?;
-        Ok(Self::new(samples))
+        let profiler = Profiler::new();
+        let samples =
+            Reconstr::new(original, instance, samples, &mut solver, &profiler).work(minimize)?;
+        print_stats("proof reconstruction", profiler);
+        let mut res = Self::new(samples);
+        for sample in Self::rewrite(instance, &self.unresolved) {
+            res.samples.insert(sample.clone());
+            res.unresolved.insert(sample);
+        }
+        Ok(res)
     }
 }
 
@@ -404,11 +861,22 @@ struct Reconstr<'a> {
     /// Each element of the vector is a path toward what we're reconstructing. If we're trying to
     /// reconstruct `(p vals)`, then several path are created for this sample if there are more
     /// than one clauses that lead to this sample.
-    to_do: Vec<Vec<Sample>>,
+    ///
+    /// The integer is the depth of the path, *i.e.* the number of expansions that led to it. The
+    /// set contains the samples already expanded on this path, which prevents re-expanding
+    /// structurally identical samples on recursive definitions. It is specific to each path, as
+    /// a sample expanded in a failed branch can still be expanded in another one.
+    to_do: Vec<(usize, Vec<Sample>, HashSet<(PrdIdx, VarVals)>)>,
     /// Positive samples for the original instance.
     samples: SampleSet,
+    /// Last successful model for each predicate, and the clause it was obtained for.
+    ///
+    /// Used to avoid solver calls when a previous model already explains a sample.
+    models: PrdHMap<(ClsIdx, Cex)>,
     /// Solver.
     solver: &'a mut Slvr,
+    /// Profiler.
+    _profiler: &'a Profiler,
 }
 
 impl<'a> Reconstr<'a> {
@@ -418,8 +886,9 @@ impl<'a> Reconstr<'a> {
         instance: &'a Instance,
         to_do: Vec<Sample>,
         solver: &'a mut Slvr,
+        profiler: &'a Profiler,
     ) -> Self {
-        let to_do = vec![to_do];
+        let to_do = vec![(0, to_do, HashSet::new())];
         let pos_preds: PrdSet = original
             .pos_clauses()
             .iter()
@@ -463,8 +932,10 @@ impl<'a> Reconstr<'a> {
             original,
             instance,
             to_do,
+            models: PrdHMap::new(),
             solver,
             samples: SampleSet::new(),
+            _profiler: profiler,
         }
     }
 
@@ -500,6 +971,44 @@ impl<'a> Reconstr<'a> {
         (pos, others)
     }
 
+    /// Checks whether the last model for a predicate explains a sample for some clause.
+    ///
+    /// Only legal if the clause does not mention predicates defined in the instance, as their
+    /// definitions constrain the model.
+    fn cached_model(&self, pred: PrdIdx, sample: &VarVals, clause: ClsIdx) -> Res<Option<Cex>> {
+        let model = match self.models.get(&pred) {
+            Some((cls, model)) if *cls == clause => model,
+            _ => return Ok(None),
+        };
+
+        let clause = &self.original[clause];
+        if clause
+            .lhs_preds()
+            .keys()
+            .any(|pred| self.instance[*pred].is_defined())
+        {
+            return Ok(None);
+        }
+
+        for term in clause.lhs_terms() {
+            if term.eval(model)?.to_bool()? != Some(true) {
+                return Ok(None);
+            }
+        }
+
+        if let Some((_, args)) = clause.rhs() {
+            for (arg, val) in args.iter().zip(sample.iter()) {
+                if val.is_known() && arg.eval(model)? != *val {
+                    return Ok(None);
+                }
+            }
+        } else {
+            return Ok(None);
+        }
+
+        Ok(Some(model.clone()))
+    }
+
     /// Tries to reconstruct a positive sample from a clause.
     ///
     /// Returns `true` if the reconstruction was positive. If it was, (potentially) new positive
@@ -511,6 +1020,13 @@ impl<'a> Reconstr<'a> {
         clause: ClsIdx,
     ) -> Res<Option<Vec<Vec<Sample>>>> {
         debug_assert! { self.original[clause].rhs().map(|(p, _)| p == pred).unwrap_or(false) }
+
+        if let Some(model) = self.cached_model(pred, sample, clause)? {
+            log! { @4 | "sample explained by previous model" }
+            profile! { self "solver calls saved" => add 1 }
+            return self.samples_of_clause_model(clause, &model).map(Some);
+        }
+
         self.solver.push(1)?;
         // Declare clause variables.
         self.original[clause].declare(self.solver)?;
@@ -535,7 +1051,10 @@ impl<'a> Reconstr<'a> {
             bail!("proof reconstruction: illegal clause-level call (no rhs)")
         }
 
-        let sat = self.solver.check_sat()?;
+        let original = self.original;
+        let sat = self.solver.check_sat().map_err(|e| {
+            smt::solver_failure(e.into(), "proof_reconstruction", Some(clause), original)
+        })?;
 
         let model = if sat {
             let model = self.solver.get_model()?;
@@ -545,43 +1064,51 @@ impl<'a> Reconstr<'a> {
         };
 
         self.solver.pop(1)?;
+        profile! { self "solver calls" => add 1 }
 
         if let Some(model) = model {
             let model = Cex::of_model(self.original[clause].vars(), model, true)?;
-            let mut res = vec![];
-            // Reconstruct all LHS applications.
-            for (pred, argss) in self.original[clause].lhs_preds() {
-                let mut samples = vec![];
-                for args in argss {
-                    let mut sample = VarMap::with_capacity(args.len());
-                    for arg in args.iter() {
-                        let val = arg.eval(&model)?;
-                        sample.push(val)
-                    }
-                    samples.push(Sample::new(*pred, var_to::vals::new(sample)))
+            let res = self.samples_of_clause_model(clause, &model)?;
+            self.models.insert(pred, (clause, model));
+            Ok(Some(res))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Generates the samples for the LHS applications of a clause given a model.
+    fn samples_of_clause_model(&self, clause: ClsIdx, model: &Cex) -> Res<Vec<Vec<Sample>>> {
+        let mut res = vec![];
+        // Reconstruct all LHS applications.
+        for (pred, argss) in self.original[clause].lhs_preds() {
+            let mut samples = vec![];
+            for args in argss {
+                let mut sample = VarMap::with_capacity(args.len());
+                for arg in args.iter() {
+                    let val = arg.eval(model)?;
+                    sample.push(val)
                 }
-                if self.pos_preds.contains(pred) {
-                    if_log! { @5
-                        log! { @5 |=> "generated positive samples:" }
-                        for sample in &samples {
-                            log! { @5 |=> "  ({} {})", self.original[sample.pred], sample.args }
-                        }
+                samples.push(Sample::new(*pred, var_to::vals::new(sample)))
+            }
+            if self.pos_preds.contains(pred) {
+                if_log! { @5
+                    log! { @5 |=> "generated positive samples:" }
+                    for sample in &samples {
+                        log! { @5 |=> "  ({} {})", self.original[sample.pred], sample.args }
                     }
-                    res.push(samples)
-                } else {
-                    if_log! { @5
-                        log! { @5 |=> "generated new samples:" }
-                        for sample in &samples {
-                            log! { @5 |=> "  ({} {})", self.original[sample.pred], sample.args }
-                        }
+                }
+                res.push(samples)
+            } else {
+                if_log! { @5
+                    log! { @5 |=> "generated new samples:" }
+                    for sample in &samples {
+                        log! { @5 |=> "  ({} {})", self.original[sample.pred], sample.args }
                     }
-                    res.push(samples)
                 }
+                res.push(samples)
             }
-            Ok(Some(res))
-        } else {
-            Ok(None)
         }
+        Ok(res)
     }
 
     /// Reconstructs a sample using the definitions of the positive predicates.
@@ -665,7 +1192,13 @@ impl<'a> Reconstr<'a> {
     ///
     /// Returns `true` if the sample was found to be a legal positive sample for the original
     /// instance.
-    fn work_on_sample(&mut self, Sample { pred, args }: Sample, path: &Vec<Sample>) -> Res<bool> {
+    fn work_on_sample(
+        &mut self,
+        Sample { pred, args, .. }: Sample,
+        depth: usize,
+        path: &Vec<Sample>,
+        visited: &HashSet<(PrdIdx, VarVals)>,
+    ) -> Res<bool> {
         log! { @3 | "working on ({} {})", self.instance[pred], args }
 
         // Already an entry point for the original instance?
@@ -682,6 +1215,7 @@ impl<'a> Reconstr<'a> {
         }
 
         let (pos, others) = self.clauses_for(pred);
+
         log! { @4 | "{} positive clause(s), {} usable clause(s)", pos.len(), others.len() }
         if_log! { @5
             if ! pos.is_empty() {
@@ -707,6 +1241,14 @@ impl<'a> Reconstr<'a> {
                 }
             }
         }
+
+        if visited.contains(&(pred, args.clone())) {
+            log! { @3 | "({} {}) already expanded, dropping branch", self.instance[pred], args }
+            return Ok(false);
+        }
+        let mut visited = visited.clone();
+        visited.insert((pred, args.clone()));
+
         let mut clause_count = 0;
         for clause in others {
             if let Some(steps) = self.work_on_clause(pred, &args, clause)? {
@@ -718,7 +1260,7 @@ impl<'a> Reconstr<'a> {
                 for step in steps {
                     let mut path = path.clone();
                     path.extend(step.into_iter());
-                    self.to_do.push(path)
+                    self.to_do.push((depth + 1, path, visited.clone()))
                 }
             }
         }
@@ -780,15 +1322,97 @@ impl<'a> Reconstr<'a> {
         Ok(())
     }
 
+    /// Checks whether a sample can be derived in one step from the other samples.
+    ///
+    /// Only considers the non-positive clauses of the original instance such that all the
+    /// predicates in their LHS have samples.
+    fn derivable(&mut self, sample: &Sample) -> Res<bool> {
+        for clause in self.original.rhs_clauses_of(sample.pred) {
+            let clause = *clause;
+            let lhs_preds = self.original[clause].lhs_preds();
+            if lhs_preds.is_empty() {
+                continue;
+            }
+
+            let mut lhs_vals = PrdHMap::new();
+            for pred in lhs_preds.keys() {
+                let mut vals = var_to::vals::VarValsSet::new();
+                for other in &self.samples {
+                    if other.pred == *pred {
+                        vals.insert(other.args.clone());
+                    }
+                }
+                if vals.is_empty() {
+                    break;
+                }
+                lhs_vals.insert(*pred, vals);
+            }
+            if lhs_vals.len() != lhs_preds.len() {
+                continue;
+            }
+
+            self.solver.push(1)?;
+            self.original[clause].declare(self.solver)?;
+            for term in self.original[clause].lhs_terms() {
+                self.solver.assert(&smt::SmtTerm::new(term))?;
+            }
+            for (pred, argss) in lhs_preds {
+                for args in argss {
+                    if !args.is_empty() {
+                        self.solver
+                            .assert(&smt::DisjArgs::new(args, &lhs_vals[pred])?)?
+                    }
+                }
+            }
+            if let Some((_, args)) = self.original[clause].rhs() {
+                self.solver.assert(&smt::EqConj::new(args, &sample.args))?
+            }
+            let sat = self.solver.check_sat()?;
+            self.solver.pop(1)?;
+            profile! { self "minimization solver calls" => add 1 }
+
+            if sat {
+                log! { @4 | "derivable using clause #{}", clause }
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Minimizes the positive samples.
+    ///
+    /// Greedily drops the samples that are [derivable] from the remaining ones.
+    ///
+    /// [derivable]: #method.derivable (derivable function)
+    fn minimize(&mut self) -> Res<()> {
+        let samples: Vec<_> = self.samples.iter().cloned().collect();
+        for sample in samples {
+            self.samples.remove(&sample);
+            if self.derivable(&sample)? {
+                log! { @3 |
+                    "dropping redundant sample ({} {})", self.original[sample.pred], sample.args
+                }
+                profile! { self "minimization: samples dropped" => add 1 }
+            } else {
+                self.samples.insert(sample);
+            }
+        }
+        Ok(())
+    }
+
     /// Reconstructs the positive samples.
-    pub fn work(mut self) -> Res<SampleSet> {
-        if self.to_do.iter().all(|to_do| to_do.is_empty()) {
+    ///
+    /// Minimizes them if `minimize` is true.
+    pub fn work(mut self, minimize: bool) -> Res<SampleSet> {
+        if self.to_do.iter().all(|(_, to_do, _)| to_do.is_empty()) {
             log! { @4 | "no samples to reconstruct, generating samples from positive clauses" }
             self.samples_of_pos_clauses()?;
             log! { @4 | "done, generated {} sample(s)", self.samples.len() }
             return Ok(self.samples);
         }
 
+        // Predicate definitions are asserted once at top-level, all clause-level checks only
+        // push/pop their own constraints on top of them.
         if !self.safe_preds.is_empty() {
             for pred in self.instance.preds() {
                 if !pred.is_defined() {
@@ -811,7 +1435,7 @@ impl<'a> Reconstr<'a> {
             }
         }
 
-        'all_branches: while let Some(mut to_do) = self.to_do.pop() {
+        'all_branches: while let Some((depth, mut to_do, visited)) = self.to_do.pop() {
             if_log! { @3
                 log! { @3 |=> "to_do {} other branch(es):", self.to_do.len() }
                 for sample in &to_do {
@@ -819,7 +1443,15 @@ impl<'a> Reconstr<'a> {
                 }
             }
             while let Some(sample) = to_do.pop() {
-                match self.work_on_sample(sample.clone(), &to_do) {
+                conf.check_timeout()?;
+                if depth > conf.proof_recon_depth {
+                    bail!(
+                        "reconstruction depth exceeded for ({} {})",
+                        self.instance[sample.pred],
+                        sample.args
+                    )
+                }
+                match self.work_on_sample(sample.clone(), depth, &to_do, &visited) {
                     Err(e) => {
                         print_err(&e);
                         self.samples.insert(sample);
@@ -845,6 +1477,10 @@ impl<'a> Reconstr<'a> {
             break 'all_branches;
         }
 
+        if minimize {
+            self.minimize()?
+        }
+
         self.solver.reset()?;
 
         if self.samples.is_empty() {
@@ -1,13 +1,19 @@
 //! Unsat core and proof extraction.
 //!
-//! Right now, only unsat proof in the form of [`entry_points`] is active.
+//! Right now, only unsat proof in the form of [`entry_points`] is active. They can be turned into
+//! a [`certificate`] with `--proof_certificate`, and explained by a derivation [`trace`] with
+//! `--proof_trace`.
 //!
 //! [`entry_points`]: entry_points/index.html (entry_points module)
+//! [`certificate`]: certificate/index.html (certificate module)
+//! [`trace`]: trace/index.html (trace module)
 
 use crate::common::*;
 
+pub mod certificate;
 pub mod entry_points;
 mod sample_graph;
+pub mod trace;
 
 pub use self::entry_points::Entry;
 
@@ -33,6 +39,22 @@ impl UnsatRes {
         UnsatRes::Entry(Entry::new(entry_points::SampleSet::new()))
     }
 
+    /// Unsat result for an unsat error raised while working on an instance.
+    ///
+    /// If the error comes from a clause of the instance (see [`Error::unsat_cause`]) and the
+    /// instance produces proofs, the entry point is the original clause it comes from.
+    ///
+    /// [`Error::unsat_cause`]: ../errors/struct.Error.html#method.unsat_cause
+    /// (unsat_cause method)
+    pub fn of_error(e: &Error, instance: &Instance) -> Self {
+        match e.unsat_cause() {
+            Some(clause) if instance.proofs() && clause < instance.clauses().len() => {
+                UnsatRes::Entry(Entry::of_clause(instance[clause].from()))
+            }
+            _ => UnsatRes::None,
+        }
+    }
+
     /// True if none.
     pub fn is_none(&self) -> bool {
         match self {
@@ -50,6 +72,12 @@ impl UnsatRes {
     }
 
     /// Tries to write the unsat proof.
+    ///
+    /// Writes a [`Certificate`] if `conf.proof_certificate`, the entry points otherwise. Both are
+    /// preceded by a [`Trace`], in comments, if `conf.proof_trace`.
+    ///
+    /// [`Certificate`]: certificate/struct.Certificate.html (Certificate struct)
+    /// [`Trace`]: trace/struct.Trace.html (Trace struct)
     pub fn write_proof<W: Write>(
         &self,
         w: &mut W,
@@ -57,9 +85,55 @@ impl UnsatRes {
         original: &Instance,
     ) -> Res<()> {
         if let Some(entry) = self.get_proof(instance, original)? {
+            if conf.proof_certificate || conf.proof_trace {
+                if entry.is_partial() {
+                    bail!(
+                        "cannot build an unsat certificate for a partial proof ({} unresolved \
+                         sample(s), see `{}`)",
+                        entry.unresolved.len(),
+                        conf.emph("--proof_lenient")
+                    )
+                }
+                let certificate = certificate::Certificate::new(original, &entry)
+                    .chain_err(|| "while building unsat certificate")?;
+                if conf.proof_trace {
+                    writeln!(w, "; derivation trace")?;
+                    for line in trace::Trace::new(original, &certificate)
+                        .to_string()
+                        .lines()
+                    {
+                        writeln!(w, ";   {}", line)?
+                    }
+                }
+                if conf.proof_certificate {
+                    return certificate.write(w, original);
+                }
+            }
+            if entry.is_partial() {
+                writeln!(
+                    w,
+                    "; partial proof, {} sample(s) could not be resolved",
+                    entry.unresolved.len()
+                )?
+            }
+            if let Some(clause) = entry.clause {
+                write!(w, "; falsifiable clause #{}", clause)?;
+                if let Some(name) = original.name_of_old_clause(clause) {
+                    write!(w, " ({})", name)?
+                }
+                writeln!(w)?
+            }
             writeln!(w, "(")?;
-            for sample in &entry.samples {
-                writeln!(w, "  ({} {})", instance[sample.pred], sample.args)?
+            for sample in entry_points::sorted_samples(instance, &entry.samples) {
+                if entry.unresolved.contains(sample) {
+                    writeln!(
+                        w,
+                        "  ({} {}) ; unresolved",
+                        instance[sample.pred], sample.args
+                    )?
+                } else {
+                    writeln!(w, "  ({} {})", instance[sample.pred], sample.args)?
+                }
             }
             writeln!(w, ")")?;
         } else {
@@ -0,0 +1,156 @@
+//! Human-readable derivation traces for unsat results.
+//!
+//! A trace is the part of a [`Certificate`] leading to the contradiction: the entry points it
+//! starts from, and the clause applications deriving `false` from them, facts first. Each step
+//! gives the clause it applies, the values of its variables and the steps deriving its premises.
+//! Clauses are cited by name too when the assertion they come from is `:named`.
+//!
+//! [`Certificate`]: ../certificate/struct.Certificate.html (Certificate struct)
+
+use crate::{common::*, unsat_core::certificate::Certificate};
+
+/// A step of a trace.
+#[derive(Debug, Clone)]
+pub struct TraceStep {
+    /// Clause of the original instance this step applies.
+    pub clause: ClsIdx,
+    /// Name of the clause, if it is named.
+    pub name: Option<String>,
+    /// Values of the active variables of the clause, by name.
+    pub subst: Vec<(String, Val)>,
+    /// Steps deriving the LHS predicate applications of the clause, empty for facts.
+    pub premises: Vec<usize>,
+    /// Atom derived, `None` for the contradiction.
+    pub atom: Option<String>,
+}
+
+/// A derivation trace, from the entry points to the contradiction.
+///
+/// # Examples
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate hoice;
+/// use hoice::{
+///     common::*, data::sample::Sample, parse,
+///     unsat_core::{
+///         certificate::Certificate, entry_points::{Entry, SampleSet}, trace::Trace
+///     },
+///     var_vals
+/// };
+///
+/// # fn main() {
+/// let instance = parse::instance("
+///   (declare-fun p ( Int ) Bool)
+///   (assert (forall ( (n Int) ) (=> (= n 0) (p n))))
+///   (assert (forall ( (n Int) ) (=> (p n) (p (+ n 1)))))
+///   (assert (forall ( (n Int) ) (=> (and (p n) (= n 2)) false)))
+/// ");
+///
+/// let mut samples = SampleSet::new();
+/// samples.insert(Sample::new(0.into(), var_vals!( (int 0) )));
+/// let cert = Certificate::new(&instance, &Entry::new(samples)).unwrap();
+/// let trace = Trace::new(&instance, &cert);
+///
+/// assert_eq! { trace.steps.len(), 4 }
+/// assert! { trace.steps[0].premises.is_empty() }
+/// assert_eq! { trace.steps[0].atom, Some("(p 0)".to_string()) }
+/// assert_eq! { trace.steps[3].premises, vec![2] }
+/// assert! { trace.steps[3].atom.is_none() }
+/// assert! { trace.to_string().contains("[3] false") }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Trace {
+    /// Steps, the premises of a step always come before it, the last one derives `false`.
+    pub steps: Vec<TraceStep>,
+}
+
+impl Trace {
+    /// Extracts the trace of a certificate.
+    ///
+    /// Only keeps the steps the contradiction depends on.
+    pub fn new(original: &Instance, certificate: &Certificate) -> Self {
+        let mut needed = vec![false; certificate.steps.len()];
+        let mut to_do: Vec<usize> = certificate.steps.len().checked_sub(1).into_iter().collect();
+        while let Some(idx) = to_do.pop() {
+            if !needed[idx] {
+                needed[idx] = true;
+                to_do.extend(certificate.steps[idx].premises.iter().cloned())
+            }
+        }
+
+        // Index of the certificate steps in the trace.
+        let mut index = vec![0; certificate.steps.len()];
+        let mut steps = vec![];
+        for (idx, step) in certificate.steps.iter().enumerate() {
+            if !needed[idx] {
+                continue;
+            }
+            index[idx] = steps.len();
+
+            let mut subst = vec![];
+            for var in original[step.clause].vars() {
+                if var.active {
+                    subst.push((var.name.clone(), step.subst[var.idx].clone()))
+                }
+            }
+            let premises = step
+                .premises
+                .iter()
+                .map(|premise| index[*premise])
+                .collect();
+            let atom = step.atom.as_ref().map(|atom| {
+                if atom.args.is_empty() {
+                    format!("{}", original[atom.pred])
+                } else {
+                    format!("({} {})", original[atom.pred], atom.args)
+                }
+            });
+
+            steps.push(TraceStep {
+                clause: step.clause,
+                name: original
+                    .name_of_old_clause(original[step.clause].from())
+                    .cloned(),
+                subst,
+                premises,
+                atom,
+            })
+        }
+
+        Trace { steps }
+    }
+}
+
+mylib::impl_fmt! {
+    Trace(self, fmt) {
+        for (idx, step) in self.steps.iter().enumerate() {
+            let clause = if let Some(name) = step.name.as_ref() {
+                format!("`{}` (#{})", name, step.clause)
+            } else {
+                format!("#{}", step.clause)
+            };
+            if let Some(atom) = step.atom.as_ref() {
+                writeln!(fmt, "[{}] {}", idx, atom)?
+            } else {
+                writeln!(fmt, "[{}] false", idx)?
+            }
+            if step.premises.is_empty() {
+                write!(fmt, "    fact, by clause {}", clause)?
+            } else {
+                write!(fmt, "    from")?;
+                for premise in &step.premises {
+                    write!(fmt, " [{}]", premise)?
+                }
+                write!(fmt, ", by clause {}", clause)?
+            }
+            for (count, (var, val)) in step.subst.iter().enumerate() {
+                let sep = if count == 0 { " with" } else { "," };
+                write!(fmt, "{} {} = {}", sep, var, val)?
+            }
+            writeln!(fmt)?
+        }
+        Ok(())
+    }
+}
@@ -107,6 +107,40 @@ impl RVarVals {
         true
     }
 
+    /// L1 distance between the arithmetic values of two samples.
+    ///
+    /// Only considers the arithmetic values that are known in both samples. Returns `None` if
+    /// there are none.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use hoice::common::*;
+    /// use hoice::var_to::vals::RVarVals;
+    /// let lft: RVarVals = vec![val::int(1), val::int(7), val::bool(true)].into();
+    /// let rgt: RVarVals = vec![val::int(-2), val::none(typ::int()), val::bool(false)].into();
+    /// assert_eq! { lft.l1_distance(&rgt), Some(Rat::new(3.into(), 1.into())) }
+    ///
+    /// let rgt: RVarVals = vec![val::none(typ::int()), val::int(0), val::bool(false)].into();
+    /// assert_eq! { lft.l1_distance(&rgt), Some(Rat::new(7.into(), 1.into())) }
+    ///
+    /// let rgt: RVarVals = vec![val::none(typ::int()), val::none(typ::int()), val::bool(true)].into();
+    /// assert_eq! { lft.l1_distance(&rgt), None }
+    /// ```
+    pub fn l1_distance(&self, other: &Self) -> Option<Rat> {
+        let mut res: Option<Rat> = None;
+        for (v_1, v_2) in self.map.iter().zip(other.map.iter()) {
+            if !v_1.typ().is_arith() || !v_1.is_known() || !v_2.is_known() {
+                continue;
+            }
+            if let (Ok(Some(r_1)), Ok(Some(r_2))) = (v_1.to_real(), v_2.to_real()) {
+                let diff = (r_1 - r_2).abs();
+                res = Some(res.map(|res| res + &diff).unwrap_or(diff))
+            }
+        }
+        res
+    }
+
     /// Constructor from a model.
     pub fn of_model<T>(
         info: &VarMap<crate::info::VarInfo>,
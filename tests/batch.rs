@@ -0,0 +1,41 @@
+//! Batch mode tests.
+//!
+//! Batch mode clears the functions and datatypes declared between instances, which are global.
+//! These tests have their own binary so that they do not interfere with the other ones.
+
+extern crate hoice;
+
+use std::fs::OpenOptions;
+
+use hoice::common::*;
+use hoice::read_and_work_batch;
+
+#[test]
+fn batch_push_pop() {
+    if let Err(e) = run_batch(
+        "rsc/batch/push_pop.smt2",
+        &[Some(true), Some(false), Some(true), Some(true)],
+    ) {
+        println!("Error:");
+        for e in e.iter() {
+            println!("> {}", e)
+        }
+        panic!("failure")
+    }
+}
+
+fn run_batch(file_name: &str, expected: &[Option<bool>]) -> Res<()> {
+    let file = OpenOptions::new()
+        .read(true)
+        .open(file_name)
+        .chain_err(|| format!("while opening file {}", file_name))?;
+    let results = read_and_work_batch(file)?;
+    if results != expected {
+        return Err(format!(
+            "unexpected results on `{}`: expected {:?}, got {:?}",
+            file_name, expected, results
+        )
+        .into());
+    }
+    Ok(())
+}
@@ -49,16 +49,133 @@ fn sat_rec_simpl() {
     run!(run_sat_on("rsc/sat/long/recursive_simplifications.smt2"))
 }
 
+#[test]
+fn sat_factor_clauses() {
+    run!(with_args(&["--factor_clauses", "on"], || {
+        run_sat_on("rsc/sat/factor_clauses.smt2")
+    }))
+}
+
+#[test]
+fn sat_arg_order() {
+    run!(with_args(&["--arg_order", "on"], || {
+        run_sat_on("rsc/sat/arg_order.smt2")
+    }))
+}
+
+#[test]
+fn sat_inline_funs() {
+    run!(with_args(&["--inline_funs", "on"], || {
+        run_sat_on("rsc/sat/macros.smt2")
+    }))
+}
+
+#[test]
+fn sat_stream_parse() {
+    run!(with_args(&["--stream_parse", "on"], || {
+        run_sat_on("rsc/sat/stream/forward_refs.smt2")
+    }))
+}
+
+#[test]
+fn sat_split_pos() {
+    run!(with_args(&["--split_pos", "on"], || {
+        run_sat_on("rsc/sat/sum_base_cases.smt2")
+    }))
+}
+
+#[test]
+fn sat_batch_cexs() {
+    run!(with_args(&["--batch_cexs", "on"], || {
+        run_sat_on("rsc/sat/sum_base_cases.smt2")
+    }))
+}
+
+#[test]
+fn sat_incremental_teacher() {
+    run!(with_args(&["--incremental_teacher", "on"], || {
+        run_sat_on("rsc/sat/long/Ackermann00.smt2")
+    }))
+}
+
+#[test]
+fn sat_memo_checks() {
+    run!(with_args(&["--memo_checks", "on"], || {
+        run_sat_on("rsc/sat/long/Ackermann00.smt2")
+    }))
+}
+
+#[test]
+fn sat_cex_yield() {
+    let args = &["--cex_yield", "on", "--cex_yield_decay", "50"];
+    run!(with_args(args, || {
+        run_sat_on("rsc/sat/long/Ackermann00.smt2")
+    }))
+}
+
+#[test]
+fn sat_one_rhs_max_blowup() {
+    run!(with_args(&["--one_rhs_max_blowup", "1"], || {
+        run_sat_on("rsc/sat/one_rhs.smt2")
+    }))
+}
+
+#[test]
+fn sat_qual_max_modulo() {
+    run!(with_args(&["--qual_max_modulo", "2"], || {
+        run_sat_on("rsc/sat/parity.smt2")
+    }))
+}
+
+#[test]
+fn sat_qual_octagon() {
+    run!(with_args(&["--qual_octagon", "on"], || {
+        run_sat_on("rsc/sat/octagon.smt2")
+    }))
+}
+
+#[test]
+fn sat_cfg_red_max_qvars() {
+    run!(with_args(&["--cfg_red_max_qvars", "1"], || {
+        run_sat_on("rsc/sat/cfg_red.smt2")
+    }))
+}
+
+#[test]
+fn sat_dump_preproc() {
+    run!(run_dump_preproc())
+}
+
 #[test]
 fn unsat() {
     run!(run_unsat())
 }
 
+#[test]
+fn unsat_trivial_clause() {
+    run!(with_args(&["--no_preproc", "simplify"], run_trivial_clause))
+}
+
+#[test]
+fn unsat_soft_clauses() {
+    run!(with_args(&["--soft_clauses", "on"], run_soft_clauses))
+}
+
 #[test]
 fn err() {
     run!(run_err())
 }
 
+#[test]
+fn engine() {
+    run!(run_engine())
+}
+
+#[test]
+fn solve() {
+    run!(run_solve())
+}
+
 macro_rules! map_err {
   ($e:expr, $msg:expr) => (
     $e.map_err( |e| format!("{}:\n{}", $msg, e) ) ?
@@ -68,6 +185,12 @@ macro_rules! map_err {
   ) ;
 }
 
+/// Runs something under the configuration given by some command-line arguments.
+fn with_args<F: FnOnce() -> Res<()>>(args: &[&str], f: F) -> Res<()> {
+    let config: &'static Config = Box::leak(Box::new(Config::of_args(args)?));
+    with_conf(config, f)
+}
+
 fn run_err() -> Res<()> {
     let files = map_err!(
         read_dir(err_files_dir),
@@ -150,6 +273,16 @@ fn run_sat_on<P: AsRef<::std::path::Path> + ?Sized>(path: &P) -> Res<()> {
     }
 }
 
+fn run_dump_preproc() -> Res<()> {
+    let dump = ::std::env::temp_dir().join("hoice_dump_preproc.smt2");
+    let dump_str = dump.to_string_lossy().to_string();
+    with_args(&["--dump_preproc", &dump_str], || {
+        run_sat_on("rsc/sat/sum_base_cases.smt2")
+    })?;
+    // The dump must be a legal sat instance itself.
+    run_sat_on(&dump)
+}
+
 fn run_unsat() -> Res<()> {
     let files = map_err!(
         read_dir(unsat_files_dir),
@@ -184,3 +317,127 @@ fn run_unsat() -> Res<()> {
 
     Ok(())
 }
+
+fn run_trivial_clause() -> Res<()> {
+    use hoice::{
+        engine::{solve_str, SolveOutcome},
+        unsat_core::UnsatRes,
+    };
+    let file_name = "rsc/unsat/trivial_clause.smt2";
+    let mut input = String::new();
+    OpenOptions::new()
+        .read(true)
+        .open(file_name)
+        .and_then(|mut file| file.read_to_string(&mut input))
+        .chain_err(|| format!("while reading file {}", file_name))?;
+    match solve_str(&input)? {
+        SolveOutcome::Unsat(UnsatRes::Entry(entry)) => {
+            if entry.clause != Some(2.into()) {
+                return Err("expected clause #2 as the entry point of the proof".into());
+            }
+        }
+        SolveOutcome::Unsat(_) => return Err("expected an unsat proof".into()),
+        _ => return Err("expected unsat".into()),
+    }
+    Ok(())
+}
+
+fn run_soft_clauses() -> Res<()> {
+    let file_name = "rsc/unsat/soft_weights.smt2";
+    let mut input = String::new();
+    OpenOptions::new()
+        .read(true)
+        .open(file_name)
+        .and_then(|mut file| file.read_to_string(&mut input))
+        .chain_err(|| format!("while reading file {}", file_name))?;
+
+    let (model, _) = read_and_work(input.as_bytes(), true, true, true)?;
+    if model.is_some() {
+        return Err(format!("got sat on `{}`, expected unsat", file_name).into());
+    }
+
+    let instance = hoice::parse::instance(&input);
+    let relaxed = if let Some(relaxed) = hoice::soft::relax(&instance, &Profiler::new())? {
+        relaxed
+    } else {
+        return Err("the hard clauses should be sat".into());
+    };
+    let names: Vec<_> = relaxed
+        .violated
+        .iter()
+        .map(|(clause, _)| {
+            instance
+                .name_of_old_clause(*clause)
+                .map(|name| name.as_str())
+        })
+        .collect();
+    if names != vec![Some("bound")] {
+        return Err("expected exactly one violated clause, named `bound`".into());
+    }
+    Ok(())
+}
+
+fn run_engine() -> Res<()> {
+    let mut engine = ::hoice::engine::Engine::new();
+    engine.add_clauses(
+        "\
+        (declare-fun inv (Int) Bool)
+        (assert (forall ((n Int)) (=> (= n 0) (inv n))))
+        (assert (forall ((n Int)) (=> (and (inv n) (< n 10)) (inv (+ n 1)))))
+        (assert (forall ((n Int)) (=> (and (inv n) (> n 10)) false)))
+    ",
+    )?;
+    if engine.resolve()? != Some(true) {
+        return Err("expected sat on the base instance".into());
+    }
+    engine.add_clauses(
+        "\
+        (assert (forall ((n Int)) (=> (and (inv n) (< n 0)) false)))
+    ",
+    )?;
+    if engine.model().is_some() {
+        return Err("model should be invalidated by new clauses".into());
+    }
+    if engine.resolve()? != Some(true) {
+        return Err("expected sat after adding a clause".into());
+    }
+    engine.add_clauses(
+        "\
+        (assert (forall ((n Int)) (=> (and (inv n) (= n 10)) false)))
+    ",
+    )?;
+    if engine.resolve()? != Some(false) {
+        return Err("expected unsat after adding a second clause".into());
+    }
+    Ok(())
+}
+
+fn run_solve() -> Res<()> {
+    use hoice::engine::{solve_str, SolveOutcome};
+    let base = "\
+        (declare-fun inv (Int) Bool)
+        (assert (forall ((n Int)) (=> (= n 0) (inv n))))
+        (assert (forall ((n Int)) (=> (and (inv n) (< n 10)) (inv (+ n 1)))))
+    ";
+    match solve_str(&format!(
+        "{}(assert (forall ((n Int)) (=> (and (inv n) (> n 10)) false)))",
+        base
+    ))? {
+        SolveOutcome::Sat(model, solved) => {
+            let mut buff: Vec<u8> = vec![];
+            solved.write_model(&model, &mut buff)?;
+            if !String::from_utf8_lossy(&buff).contains("inv") {
+                return Err("model does not define `inv`".into());
+            }
+        }
+        _ => return Err("expected sat".into()),
+    }
+    match solve_str(&format!(
+        "{}(assert (forall ((n Int)) (=> (and (inv n) (= n 10)) false))) (check-sat)",
+        base
+    ))? {
+        SolveOutcome::Unsat(_) => (),
+        _ => return Err("expected unsat".into()),
+    }
+    Ok(())
+}